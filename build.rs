@@ -0,0 +1,4 @@
+// Rebuild when migrations change so `sqlx::migrate!` picks up new files
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
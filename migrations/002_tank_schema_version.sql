@@ -0,0 +1,5 @@
+-- Tank schema versioning
+-- Migration: 002_tank_schema_version
+
+-- Rows written before versioning are marked 0 and upgraded on read
+ALTER TABLE tanks ADD COLUMN schema_version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- Bead execution provenance
+-- Migration: 003_bead_provenance
+
+-- JSON-encoded Provenance (provider, model, prompt hash, executor, timestamp)
+ALTER TABLE beads ADD COLUMN provenance TEXT;
@@ -0,0 +1,5 @@
+-- Per-bead execution context
+-- Migration: 004_bead_context
+
+-- JSON-encoded BeadContext (working directory and context files)
+ALTER TABLE beads ADD COLUMN context TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,5 @@
+-- Per-bead retry budget
+-- Migration: 005_bead_retries
+
+ALTER TABLE beads ADD COLUMN retry_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE beads ADD COLUMN max_retries INTEGER NOT NULL DEFAULT 3;
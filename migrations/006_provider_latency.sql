@@ -0,0 +1,9 @@
+-- Observed per-provider execution latency
+-- Migration: 006_provider_latency
+
+CREATE TABLE IF NOT EXISTS provider_latency (
+    provider TEXT PRIMARY KEY,
+    avg_ms REAL NOT NULL,
+    samples INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,5 @@
+-- Provider switching after repeated failures
+-- Migration: 007_bead_provider_failures
+
+ALTER TABLE beads ADD COLUMN provider_failures INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE beads ADD COLUMN excluded_providers TEXT NOT NULL DEFAULT '[]';
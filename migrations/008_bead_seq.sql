@@ -0,0 +1,13 @@
+-- Per-workspace bead sequence numbers (#42)
+-- Migration: 008_bead_seq
+
+ALTER TABLE beads ADD COLUMN seq INTEGER;
+
+-- Number existing beads in creation order
+UPDATE beads SET seq = (
+    SELECT COUNT(*) FROM beads AS earlier
+    WHERE earlier.created_at < beads.created_at
+       OR (earlier.created_at = beads.created_at AND earlier.rowid <= beads.rowid)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_beads_seq ON beads(seq);
@@ -0,0 +1,4 @@
+-- Convoys that must finish before a convoy's beads may depend on theirs
+-- Migration: 009_convoy_dependencies
+
+ALTER TABLE convoys ADD COLUMN depends_on TEXT NOT NULL DEFAULT '[]';
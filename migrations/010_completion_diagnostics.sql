@@ -0,0 +1,7 @@
+-- Process diagnostics for each execution attempt (`rigs bead logs`)
+-- Migration: 010_completion_diagnostics
+
+ALTER TABLE completions ADD COLUMN command TEXT;
+ALTER TABLE completions ADD COLUMN exit_code INTEGER;
+ALTER TABLE completions ADD COLUMN stdout TEXT;
+ALTER TABLE completions ADD COLUMN stderr TEXT;
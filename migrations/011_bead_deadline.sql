@@ -0,0 +1,4 @@
+-- Bead deadlines (`rigs bead reschedule`)
+-- Migration: 011_bead_deadline
+
+ALTER TABLE beads ADD COLUMN deadline TEXT;
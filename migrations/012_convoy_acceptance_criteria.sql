@@ -0,0 +1,4 @@
+-- Criteria the combined output of a convoy's beads must meet
+-- Migration: 012_convoy_acceptance_criteria
+
+ALTER TABLE convoys ADD COLUMN acceptance_criteria TEXT NOT NULL DEFAULT '[]';
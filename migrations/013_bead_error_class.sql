@@ -0,0 +1,4 @@
+-- How the failure behind a bead's error should be retried
+-- Migration: 013_bead_error_class
+
+ALTER TABLE beads ADD COLUMN error_class TEXT;
@@ -0,0 +1,6 @@
+-- Tank limits tracked beyond the window (`TankRepository`)
+-- Migration: 014_tank_state
+
+-- JSON of the remaining tank fields (weekly and daily caps, rolling
+-- consumption, request rate); the other columns take precedence
+ALTER TABLE tanks ADD COLUMN state TEXT;
@@ -0,0 +1,4 @@
+-- A convoy's beads in execution order
+-- Migration: 015_convoy_beads
+
+ALTER TABLE convoys ADD COLUMN beads TEXT NOT NULL DEFAULT '[]';
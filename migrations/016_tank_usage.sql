@@ -0,0 +1,12 @@
+-- Token consumption history (`rigs tank history`)
+-- Migration: 016_tank_usage
+
+CREATE TABLE IF NOT EXISTS tank_usage (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    provider TEXT NOT NULL,
+    tokens INTEGER NOT NULL,
+    remaining INTEGER NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_tank_usage_recorded ON tank_usage(recorded_at, provider);
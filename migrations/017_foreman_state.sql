@@ -0,0 +1,11 @@
+-- Durable foreman settings shared between the daemon and the CLI
+-- Migration: 017_foreman_state
+
+CREATE TABLE IF NOT EXISTS foreman_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    paused INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL
+);
+
+INSERT OR IGNORE INTO foreman_state (id, paused, updated_at)
+VALUES (1, 0, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'));
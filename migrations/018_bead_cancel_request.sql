@@ -0,0 +1,4 @@
+-- Cancellation requested for a bead a running foreman is executing (`rigs bead cancel`)
+-- Migration: 018_bead_cancel_request
+
+ALTER TABLE beads ADD COLUMN cancel_requested INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,17 @@
+-- Keep the last bead number handed out, so deleting or pruning the newest
+-- bead doesn't free its number for reuse
+-- Migration: 019_bead_seq_counter
+
+CREATE TABLE IF NOT EXISTS bead_seq (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    last INTEGER NOT NULL
+);
+
+INSERT INTO bead_seq (id, last) SELECT 1, COALESCE(MAX(seq), 0) FROM beads;
+
+-- Advances in the same statement as the INSERT that numbered the bead
+CREATE TRIGGER IF NOT EXISTS beads_advance_seq AFTER INSERT ON beads
+WHEN NEW.seq IS NOT NULL
+BEGIN
+    UPDATE bead_seq SET last = MAX(last, NEW.seq) WHERE id = 1;
+END;
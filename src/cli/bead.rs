@@ -1,7 +1,26 @@
 //! Bead (task) management commands
 
+use chrono::{DateTime, Duration, Utc};
 use clap::Subcommand;
-use crate::core::{BeadStatus, Priority, Provider, Result, TaskType};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use super::goal::parse_deadline;
+use super::prune::parse_age;
+use super::OutputFormat;
+use crate::config::{Config, DisplayTimezone};
+use crate::core::assayer::{infer_task_type, Estimator};
+use crate::core::daemon::DaemonState;
+use crate::core::pricing::PricingTable;
+use crate::core::{
+    Bead, BeadContext, BeadId, BeadRef, BeadStatus, ExecutionRecord, Priority, Provider,
+    Reschedule, Result, RetryClass, RigsError, TaskType,
+};
+use crate::db::open_workspace;
+use crate::db::repository::{
+    BeadFilter, BeadRepository, BeadSort, CompletionRepository, SqliteRepository,
+};
 
 #[derive(Subcommand)]
 pub enum BeadCommands {
@@ -9,15 +28,36 @@ pub enum BeadCommands {
     Create {
         /// Task description
         description: String,
-        /// Task type
+        /// Short title (defaults to the description's first line)
+        #[arg(long)]
+        title: Option<String>,
+        /// Task type (inferred from the description if omitted)
         #[arg(short, long)]
-        task_type: TaskType,
+        task_type: Option<TaskType>,
         /// Priority
         #[arg(short, long, default_value = "normal")]
         priority: Priority,
         /// Preferred provider
         #[arg(long)]
         provider: Option<Provider>,
+        /// Working directory the executor runs in
+        #[arg(long)]
+        cwd: Option<PathBuf>,
+        /// File to include in the prompt (repeatable, relative to --cwd)
+        #[arg(long = "file")]
+        files: Vec<PathBuf>,
+        /// Finish by this time (RFC 3339, or a duration from now like 2d)
+        #[arg(long, value_parser = parse_deadline)]
+        deadline: Option<DateTime<Utc>>,
+        /// Acceptance criterion for the quality gate (repeatable)
+        #[arg(long = "criteria")]
+        criteria: Vec<String>,
+        /// Bead that must complete first, by ID or #seq (repeatable)
+        #[arg(long = "depends-on")]
+        depends_on: Vec<BeadRef>,
+        /// Skip the estimator and keep the default estimate
+        #[arg(long)]
+        no_estimate: bool,
     },
 
     /// List beads
@@ -31,70 +71,243 @@ pub enum BeadCommands {
         /// Maximum results
         #[arg(long, default_value = "20")]
         limit: u32,
+        /// Skip this many matching beads first
+        #[arg(long, default_value = "0")]
+        offset: u32,
+        /// Order of the listing
+        #[arg(long, value_enum, default_value_t = BeadSort::Created)]
+        sort: BeadSort,
     },
 
     /// Show bead details
     Show {
-        /// Bead ID
-        id: String,
+        /// Bead ID or #seq
+        id: BeadRef,
+        /// Show what the last execution ran with (model, prompt hash, executor)
+        #[arg(long)]
+        provenance: bool,
+        /// Compare the original description with the optimized prompt
+        #[arg(long)]
+        prompt: bool,
+        /// Print the whole effective prompt instead of its beginning
+        #[arg(long)]
+        full: bool,
     },
 
     /// Edit a bead
     Edit {
-        /// Bead ID
-        id: String,
+        /// Bead ID or #seq
+        id: BeadRef,
     },
 
     /// Cancel a bead
     Cancel {
-        /// Bead ID
-        id: String,
+        /// Bead ID or #seq
+        id: BeadRef,
+        /// Cancel an in-progress bead now instead of when the foreman's
+        /// execution returns
+        #[arg(long)]
+        force: bool,
     },
 
     /// Retry a failed bead
     Retry {
-        /// Bead ID
-        id: String,
+        /// Bead ID or #seq
+        #[arg(
+            required_unless_present = "all_failed",
+            conflicts_with_all = ["all_failed", "only_recoverable"]
+        )]
+        id: Option<BeadRef>,
+        /// Retry every failed bead that has retry budget left
+        #[arg(long)]
+        all_failed: bool,
+        /// Only retry beads whose last error was transient or a rate limit
+        #[arg(long, requires = "all_failed")]
+        only_recoverable: bool,
+    },
+
+    /// Re-estimate a bead's token usage from its current description
+    Estimate {
+        /// Bead ID or #seq
+        id: BeadRef,
+    },
+
+    /// Preview what running a bead would cost on each provider
+    Cost {
+        /// Bead ID or #seq
+        id: BeadRef,
+        /// Compare these providers instead of the enabled ones (repeatable)
+        #[arg(long)]
+        provider: Vec<Provider>,
+    },
+
+    /// Show each execution attempt: command, exit status, stderr and stdout
+    Logs {
+        /// Bead ID or #seq
+        id: BeadRef,
+    },
+
+    /// Show everything a bead transitively depends on
+    Tree {
+        /// Bead ID or #seq
+        id: BeadRef,
+        /// Show what is waiting on the bead instead
+        #[arg(long)]
+        dependents: bool,
+    },
+
+    /// Move the deadlines of a convoy's unfinished beads
+    Reschedule {
+        /// Convoy whose beads to reschedule
+        #[arg(long)]
+        convoy: String,
+        /// Shift deadlines and deferred times by this much (e.g. 2d, -6h)
+        #[arg(
+            long,
+            value_parser = parse_shift,
+            allow_hyphen_values = true,
+            required_unless_present = "deadline",
+            conflicts_with = "deadline"
+        )]
+        shift: Option<Duration>,
+        /// Set every deadline to this time (RFC 3339, or a duration from now)
+        #[arg(long, value_parser = parse_deadline)]
+        deadline: Option<DateTime<Utc>>,
+    },
+
+    /// Inspect and recover permanently failed beads
+    DeadLetter {
+        #[command(subcommand)]
+        action: DeadLetterCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DeadLetterCommands {
+    /// List failed beads that have exhausted their retries
+    List,
+
+    /// Requeue a dead-lettered bead with a fresh retry budget
+    Requeue {
+        /// Bead ID or #seq
+        id: BeadRef,
     },
 }
 
-pub async fn run(cmd: BeadCommands) -> Result<()> {
+pub async fn run(cmd: BeadCommands, config: &Config, format: OutputFormat) -> Result<()> {
     match cmd {
-        BeadCommands::Create { description, task_type, priority, provider } => {
-            let id = "gt-abc12"; // TODO: Generate real ID
-            println!("Created bead: {}", id);
-            println!("  Type:     {}", task_type);
+        BeadCommands::Create {
+            description,
+            title,
+            task_type,
+            priority,
+            provider,
+            cwd,
+            files,
+            deadline,
+            criteria,
+            depends_on,
+            no_estimate,
+        } => {
+            let context = BeadContext {
+                working_dir: cwd
+                    .map(|dir| std::env::current_dir().map(|cur| cur.join(dir)))
+                    .transpose()?,
+                files,
+            };
+            let mut bead = Bead::new(
+                title.unwrap_or_else(|| title_from(&description)),
+                description.clone(),
+                task_type.unwrap_or_else(|| infer_task_type(&description)),
+            )
+            .with_priority(priority)
+            .with_context(context.clone())
+            .with_criteria(criteria);
+            bead.preferred_provider = provider;
+            bead.deadline = deadline;
+            bead.max_retries = config.foreman.bead_retries;
+            if !no_estimate {
+                bead.estimated_tokens = Estimator::from_config(config)?.estimate(&bead).await?;
+            }
+
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let seq = create_bead(&repo, &mut bead, &depends_on).await?;
+            println!("Created bead: {} (#{})", bead.id, seq);
+            println!("  Title:    {}", bead.title);
+            match task_type {
+                Some(task_type) => println!("  Type:     {}", task_type),
+                None => println!(
+                    "  Type:     {} (inferred; pass --task-type to override)",
+                    bead.task_type
+                ),
+            }
             println!("  Priority: {}", priority);
+            println!("  Estimate: {} tokens", bead.estimated_tokens);
             if let Some(p) = provider {
                 println!("  Provider: {}", p);
             }
+            if let Some(dir) = &context.working_dir {
+                println!("  Working dir: {}", dir.display());
+            }
+            for file in &context.files {
+                println!("  Context file: {}", file.display());
+            }
+            if let Some(deadline) = deadline {
+                println!(
+                    "  Deadline: {}",
+                    config.display_timezone()?.format_datetime(&deadline)
+                );
+            }
+            for dependency in &bead.dependencies {
+                println!("  Depends on: {}", dependency);
+            }
+            for criterion in &bead.acceptance_criteria {
+                println!("  Criterion: {}", criterion);
+            }
             println!("  Description: {}", description);
             Ok(())
         }
-        BeadCommands::List { status, convoy: _, limit } => {
-            println!("Beads (showing {} of {}):", limit, 42);
-            println!();
-            println!("  ID         Status      Type           Priority  Provider");
-            println!("  ─────────────────────────────────────────────────────────");
-            println!("  gt-abc12   completed   implementation high      Claude");
-            println!("  gt-def34   in_progress review         normal    Codex");
-            println!("  gt-ghi56   pending     research       normal    -");
-            if let Some(s) = status {
-                println!("\n  (filtered by status: {})", s);
-            }
+        BeadCommands::List {
+            status,
+            convoy,
+            limit,
+            offset,
+            sort,
+        } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let filter = BeadFilter {
+                status,
+                convoy_id: convoy,
+                limit,
+                offset,
+                sort,
+            };
+            let beads = repo.list(&filter).await?;
+            print!("{}", format.render(beads.as_slice(), render_list)?);
+            Ok(())
+        }
+        BeadCommands::Show { id, .. } if format == OutputFormat::Json => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let bead = repo.resolve(&id).await?;
+            print!("{}", format.render(&bead, |_| String::new())?);
             Ok(())
         }
-        BeadCommands::Show { id } => {
-            println!("Bead: {}", id);
-            println!("  Title:       Implement user authentication");
-            println!("  Description: Add OAuth2 authentication flow...");
-            println!("  Type:        implementation");
-            println!("  Priority:    high");
-            println!("  Status:      in_progress");
-            println!("  Provider:    Claude");
-            println!("  Est. Tokens: 5,000");
-            println!("  Created:     2026-01-18 10:00 UTC");
-            println!("  Started:     2026-01-18 10:05 UTC");
+        BeadCommands::Show {
+            id,
+            provenance,
+            prompt,
+            full,
+        } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let bead = repo.resolve(&id).await?;
+            print!(
+                "{}",
+                render_show(&bead, provenance, full, &config.display_timezone()?)
+            );
+            if prompt {
+                println!();
+                print!("{}", render_prompts(&bead));
+            }
             Ok(())
         }
         BeadCommands::Edit { id } => {
@@ -102,13 +315,1290 @@ pub async fn run(cmd: BeadCommands) -> Result<()> {
             // TODO: Open editor
             Ok(())
         }
-        BeadCommands::Cancel { id } => {
-            println!("Cancelled bead: {}", id);
+        BeadCommands::Cancel { id, force } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let foreman_running =
+                DaemonState::running(&DaemonState::path(&config.workspace_dir()))?.is_some();
+            let (id, cancellation) = cancel_bead(&repo, &id, force, foreman_running).await?;
+            match cancellation {
+                Cancellation::Cancelled => println!("Cancelled bead: {}", id),
+                Cancellation::Requested => println!(
+                    "Bead {} is in progress; the foreman will cancel it when its execution returns (--force cancels now)",
+                    id
+                ),
+            }
             Ok(())
         }
-        BeadCommands::Retry { id } => {
+        BeadCommands::Retry {
+            id,
+            all_failed: false,
+            ..
+        } => {
+            let id = id.expect("clap requires an id without --all-failed");
             println!("Retrying bead: {}", id);
             Ok(())
         }
+        BeadCommands::Retry {
+            only_recoverable, ..
+        } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let report = retry_all_failed(&repo, only_recoverable).await?;
+            print!("{}", render_retry_report(&report));
+            Ok(())
+        }
+        BeadCommands::Estimate { id } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let id = repo.resolve(&id).await?.id;
+            let (before, after) = re_estimate(&repo, &Estimator::from_config(config)?, &id).await?;
+            println!("Estimated bead: {}", id);
+            println!("  Previous: {} tokens", before);
+            println!("  Current:  {} tokens", after);
+            Ok(())
+        }
+        BeadCommands::Cost { id, provider } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let bead = repo.resolve(&id).await?;
+            let candidates = if !provider.is_empty() {
+                provider
+            } else if config.general.local_only {
+                vec![Provider::Ollama]
+            } else {
+                Provider::execution()
+                    .filter(|&p| config.is_provider_enabled(p))
+                    .collect()
+            };
+            let pricing = PricingTable::with_overrides(&config.pricing);
+            print!("{}", render_cost(&bead, &candidates, &pricing));
+            Ok(())
+        }
+        BeadCommands::Logs { id } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let bead = repo.resolve(&id).await?;
+            let attempts = repo.list_completions(&bead.id).await?;
+            print!(
+                "{}",
+                render_logs(&bead, &attempts, &config.display_timezone()?)
+            );
+            Ok(())
+        }
+        BeadCommands::Tree { id, dependents } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            // A missing root is still drawn, so only `#seq` has to exist
+            let id = match id {
+                BeadRef::Id(id) => id,
+                seq => repo.resolve(&seq).await?.id,
+            };
+            let graph = load_graph(&repo, &id, dependents).await?;
+            print!("{}", render_tree(&id, &graph));
+            Ok(())
+        }
+        BeadCommands::Reschedule {
+            convoy,
+            shift,
+            deadline,
+        } => {
+            let change = match (shift, deadline) {
+                (Some(by), _) => Reschedule::Shift(by),
+                (None, Some(at)) => Reschedule::Deadline(at),
+                (None, None) => unreachable!("clap requires --shift or --deadline"),
+            };
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let changed = repo.reschedule_convoy(&convoy, change, Utc::now()).await?;
+            if changed.is_empty() {
+                println!(
+                    "No unfinished beads with deadlines to move in convoy {}",
+                    convoy
+                );
+                return Ok(());
+            }
+            let tz = config.display_timezone()?;
+            println!(
+                "Rescheduled {} bead(s) in convoy {}:",
+                changed.len(),
+                convoy
+            );
+            for bead in &changed {
+                let deadline = bead
+                    .deadline
+                    .map_or_else(|| "-".to_string(), |at| tz.format_datetime(&at));
+                println!("  {:<10} due {}", bead.id, deadline);
+            }
+            Ok(())
+        }
+        BeadCommands::DeadLetter { action } => match action {
+            DeadLetterCommands::List => {
+                let repo = SqliteRepository::new(open_workspace(config).await?);
+                let beads = repo.list_dead_letter().await?;
+                print!(
+                    "{}",
+                    render_dead_letter(&beads, &config.display_timezone()?)
+                );
+                Ok(())
+            }
+            DeadLetterCommands::Requeue { id } => {
+                let repo = SqliteRepository::new(open_workspace(config).await?);
+                let bead = requeue_dead_letter(&repo, &id).await?;
+                println!("✓ {} re-queued with a fresh retry budget", bead.id);
+                Ok(())
+            }
+        },
+    }
+}
+
+/// Render the `bead list` table
+fn render_list(beads: &[Bead]) -> String {
+    let mut out = String::new();
+    if beads.is_empty() {
+        let _ = writeln!(out, "No beads found");
+        return out;
+    }
+
+    let header = ["ID", "Status", "Type", "Priority", "Provider"];
+    let rows: Vec<[String; 5]> = beads
+        .iter()
+        .map(|bead| {
+            [
+                bead.id.to_string(),
+                bead.status.to_string(),
+                bead.task_type.to_string(),
+                bead.priority.to_string(),
+                bead.assigned_provider
+                    .map_or_else(|| "-".to_string(), |p| p.display_name().to_string()),
+            ]
+        })
+        .collect();
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: [&str; 5]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        format!("  {}", padded.join("  ").trim_end())
+    };
+
+    let _ = writeln!(out, "Beads ({}):", beads.len());
+    let _ = writeln!(out);
+    let _ = writeln!(out, "{}", line(header));
+    let rule = widths.iter().sum::<usize>() + 2 * (widths.len() - 1);
+    let _ = writeln!(out, "  {}", "─".repeat(rule));
+    for row in &rows {
+        let _ = writeln!(out, "{}", line(row.each_ref().map(String::as_str)));
+    }
+    out
+}
+
+/// Persist a new bead after resolving its dependencies
+///
+/// Fails with [`RigsError::BeadNotFound`] (or `BeadSeqNotFound`) if a
+/// dependency doesn't exist, storing nothing. Returns the bead's `#seq`.
+async fn create_bead(
+    repo: &dyn BeadRepository,
+    bead: &mut Bead,
+    depends_on: &[BeadRef],
+) -> Result<u64> {
+    for dependency in depends_on {
+        let id = repo.resolve(dependency).await?.id;
+        if !bead.dependencies.contains(&id) {
+            bead.dependencies.push(id);
+        }
+    }
+    repo.create(bead).await
+}
+
+/// What `bead cancel` did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cancellation {
+    /// The bead is now `Cancelled`
+    Cancelled,
+    /// A running foreman is executing the bead and will cancel it
+    Requested,
+}
+
+/// Cancel a bead that hasn't finished
+///
+/// An in-progress bead a running foreman owns is only flagged, unless
+/// `force`; the foreman cancels it at its next checkpoint. Fails with
+/// [`RigsError::BeadAlreadyFinished`] for terminal beads.
+async fn cancel_bead(
+    repo: &dyn BeadRepository,
+    bead: &BeadRef,
+    force: bool,
+    foreman_running: bool,
+) -> Result<(BeadId, Cancellation)> {
+    let mut bead = repo.resolve(bead).await?;
+    if bead.status.is_terminal() {
+        return Err(RigsError::BeadAlreadyFinished {
+            bead_id: bead.id,
+            status: bead.status,
+        });
+    }
+    if bead.status == BeadStatus::InProgress && foreman_running {
+        // Even when forced, so the foreman drops the result instead of
+        // saving it over the cancellation
+        repo.request_cancel(&bead.id).await?;
+        if !force {
+            return Ok((bead.id, Cancellation::Requested));
+        }
+    }
+    bead.transition(BeadStatus::Cancelled)?;
+    repo.update(&bead).await?;
+    Ok((bead.id, Cancellation::Cancelled))
+}
+
+/// Give a dead-lettered bead a fresh retry budget and put it back in the queue
+///
+/// Fails for beads that still have retries left or didn't fail.
+async fn requeue_dead_letter(repo: &dyn BeadRepository, bead: &BeadRef) -> Result<Bead> {
+    let mut bead = repo.resolve(bead).await?;
+    if !bead.requeue_dead_letter() {
+        return Err(RigsError::Other(format!(
+            "Bead {} is not in the dead-letter queue",
+            bead.id
+        )));
+    }
+    repo.update(&bead).await?;
+    Ok(bead)
+}
+
+/// A title for a bead created from the command line: the description's
+/// first line, cut to 60 characters
+fn title_from(description: &str) -> String {
+    let line = description.lines().next().unwrap_or_default().trim();
+    match line.char_indices().nth(60) {
+        Some((cut, _)) => format!("{}…", line[..cut].trim_end()),
+        None => line.to_string(),
+    }
+}
+
+/// Re-run the estimator on a stored bead and persist the new estimate
+///
+/// Returns the previous and new estimates. Nothing is dispatched.
+async fn re_estimate(
+    repo: &dyn BeadRepository,
+    estimator: &Estimator,
+    id: &BeadId,
+) -> Result<(u64, u64)> {
+    let mut bead = repo
+        .get(id)
+        .await?
+        .ok_or_else(|| RigsError::BeadNotFound(id.clone()))?;
+    let before = bead.estimated_tokens;
+    bead.estimated_tokens = estimator.estimate(&bead).await?;
+    repo.update(&bead).await?;
+    Ok((before, bead.estimated_tokens))
+}
+
+/// Outcome of `bead retry --all-failed`, keyed by the last error's retry class
+///
+/// Beads that failed before errors were classified are counted under `None`.
+#[derive(Debug, Default)]
+struct RetryReport {
+    /// Beads put back in the queue
+    requeued: BTreeMap<Option<RetryClass>, usize>,
+    /// Beads left failed because their error isn't recoverable
+    skipped: BTreeMap<Option<RetryClass>, usize>,
+    /// Beads left failed because their retry budget is spent
+    exhausted: usize,
+}
+
+/// Requeue every failed bead that has retry budget left
+///
+/// With `only_recoverable`, beads whose last error was fatal (or was never
+/// classified) stay failed, since another attempt would fail the same way.
+async fn retry_all_failed(
+    repo: &dyn BeadRepository,
+    only_recoverable: bool,
+) -> Result<RetryReport> {
+    let mut report = RetryReport::default();
+    for mut bead in repo.list_by_status(BeadStatus::Failed).await? {
+        let class = bead.error_class;
+        if only_recoverable && !class.is_some_and(|c| c.is_recoverable()) {
+            *report.skipped.entry(class).or_default() += 1;
+        } else if bead.requeue_for_retry() {
+            repo.update(&bead).await?;
+            *report.requeued.entry(class).or_default() += 1;
+        } else {
+            report.exhausted += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// Render the `bead retry --all-failed` summary
+fn render_retry_report(report: &RetryReport) -> String {
+    let label = |class: &Option<RetryClass>| {
+        class.map_or_else(|| "unclassified".to_string(), |c| c.to_string())
+    };
+
+    let mut out = String::new();
+    let requeued: usize = report.requeued.values().sum();
+    let _ = writeln!(out, "✓ Re-queued {} failed bead(s)", requeued);
+    for (class, count) in &report.requeued {
+        let _ = writeln!(out, "    {:<13} {}", label(class), count);
+    }
+    if !report.skipped.is_empty() {
+        let skipped: usize = report.skipped.values().sum();
+        let _ = writeln!(out, "✗ Left {} unrecoverable bead(s) failed", skipped);
+        for (class, count) in &report.skipped {
+            let _ = writeln!(out, "    {:<13} {}", label(class), count);
+        }
+    }
+    if report.exhausted > 0 {
+        let _ = writeln!(
+            out,
+            "✗ {} bead(s) out of retries (see `rigs bead dead-letter list`)",
+            report.exhausted
+        );
+    }
+    out
+}
+
+/// Render the `bead cost` table, cheapest first
+///
+/// The cheapest provider whose context window fits the bead is starred;
+/// providers it doesn't fit are listed but never starred.
+fn render_cost(bead: &Bead, providers: &[Provider], pricing: &PricingTable) -> String {
+    let tokens = bead.estimated_tokens;
+    let fits = |p: Provider| p.capabilities().fits_context(tokens);
+    let cheapest = pricing
+        .rank(providers, tokens)
+        .into_iter()
+        .find(|(p, _)| fits(*p))
+        .map(|(p, _)| p);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Estimated cost of {} (~{} tokens):", bead.id, tokens);
+    let _ = writeln!(out);
+    for (provider, cost) in pricing.rank(providers, tokens) {
+        let marker = if Some(provider) == cheapest {
+            "★"
+        } else {
+            " "
+        };
+        let note = if fits(provider) {
+            ""
+        } else {
+            "  (context window too small)"
+        };
+        let _ = writeln!(
+            out,
+            "  {} {:<10} ${:.4}{}",
+            marker,
+            provider.display_name(),
+            cost,
+            note
+        );
+    }
+    out
+}
+
+/// Glyph for a bead's status in trees and convoy views
+pub(crate) fn status_glyph(status: BeadStatus) -> &'static str {
+    match status {
+        BeadStatus::Completed => "✓",
+        BeadStatus::Failed => "✗",
+        BeadStatus::InProgress | BeadStatus::Assigned | BeadStatus::Reviewing => "▶",
+        BeadStatus::Deferred => "⏸",
+        _ => "○",
+    }
+}
+
+/// Beads reachable from a root, and the edges followed to reach them
+#[derive(Debug, Default)]
+struct DependencyGraph {
+    beads: HashMap<BeadId, Bead>,
+    /// Dependencies (or dependents) of each loaded bead, in display order
+    edges: HashMap<BeadId, Vec<BeadId>>,
+}
+
+/// Walk the dependency closure of `root` (or, with `dependents`, the beads waiting on it)
+///
+/// Ids that don't resolve to a bead are kept as edges but have no entry in
+/// `beads`.
+async fn load_graph(
+    repo: &dyn BeadRepository,
+    root: &BeadId,
+    dependents: bool,
+) -> Result<DependencyGraph> {
+    let mut graph = DependencyGraph::default();
+    let mut queue = vec![root.clone()];
+    while let Some(id) = queue.pop() {
+        if graph.edges.contains_key(&id) {
+            continue;
+        }
+        let Some(bead) = repo.get(&id).await? else {
+            if id == *root {
+                return Err(RigsError::BeadNotFound(id));
+            }
+            graph.edges.insert(id, Vec::new());
+            continue;
+        };
+        let next: Vec<BeadId> = if dependents {
+            repo.list_dependents(&id)
+                .await?
+                .into_iter()
+                .map(|b| b.id)
+                .collect()
+        } else {
+            bead.dependencies.clone()
+        };
+        queue.extend(next.iter().cloned());
+        graph.edges.insert(id.clone(), next);
+        graph.beads.insert(id, bead);
+    }
+    Ok(graph)
+}
+
+/// Render the `bead tree` view, one indented line per node
+///
+/// A node that reappears under one of its own descendants is marked as a
+/// cycle instead of being expanded again.
+fn render_tree(root: &BeadId, graph: &DependencyGraph) -> String {
+    fn label(id: &BeadId, graph: &DependencyGraph) -> String {
+        match graph.beads.get(id) {
+            Some(bead) => format!("{} {} {}", id, status_glyph(bead.status), bead.title),
+            None => format!("{} ? (missing)", id),
+        }
+    }
+
+    fn walk(
+        out: &mut String,
+        id: &BeadId,
+        graph: &DependencyGraph,
+        prefix: &str,
+        path: &mut Vec<BeadId>,
+    ) {
+        let children = graph.edges.get(id).map(Vec::as_slice).unwrap_or_default();
+        path.push(id.clone());
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let branch = if last { "└── " } else { "├── " };
+            if path.contains(child) {
+                let _ = writeln!(out, "{}{}{} (cycle)", prefix, branch, label(child, graph));
+                continue;
+            }
+            let _ = writeln!(out, "{}{}{}", prefix, branch, label(child, graph));
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            walk(out, child, graph, &prefix, path);
+        }
+        path.pop();
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", label(root, graph));
+    walk(&mut out, root, graph, "", &mut Vec::new());
+    out
+}
+
+/// Characters of the effective prompt `bead show` prints without `--full`
+const PROMPT_PREVIEW_CHARS: usize = 200;
+
+/// Render `bead show`: every populated field of the bead
+///
+/// The effective prompt is cut after [`PROMPT_PREVIEW_CHARS`] unless `full`.
+fn render_show(bead: &Bead, provenance: bool, full: bool, tz: &DisplayTimezone) -> String {
+    let mut out = String::new();
+    match bead.seq {
+        Some(seq) => {
+            let _ = writeln!(out, "Bead: {} (#{})", bead.id, seq);
+        }
+        None => {
+            let _ = writeln!(out, "Bead: {}", bead.id);
+        }
+    }
+    let _ = writeln!(out, "  Title:       {}", bead.title);
+    let _ = writeln!(out, "  Type:        {}", bead.task_type);
+    let _ = writeln!(out, "  Priority:    {}", bead.priority);
+    let _ = writeln!(out, "  Status:      {}", bead.status);
+    if let Some(convoy) = &bead.convoy_id {
+        let _ = writeln!(out, "  Convoy:      {}", convoy);
+    }
+    let provider = |p: Option<Provider>| p.map_or("-", |p| p.display_name());
+    let _ = writeln!(
+        out,
+        "  Provider:    {} (preferred: {})",
+        provider(bead.assigned_provider),
+        provider(bead.preferred_provider)
+    );
+    let _ = writeln!(
+        out,
+        "  Tokens:      {} estimated, {} actual",
+        bead.estimated_tokens,
+        bead.actual_tokens
+            .map_or_else(|| "-".to_string(), |t| t.to_string())
+    );
+    if bead.retry_count > 0 {
+        let _ = writeln!(
+            out,
+            "  Retries:     {}/{}",
+            bead.retry_count, bead.max_retries
+        );
+    }
+    if let Some(error) = &bead.error {
+        let _ = writeln!(out, "  Error:       {}", error);
+    }
+    for (label, ts) in [
+        ("Created:", Some(bead.created_at)),
+        ("Started:", bead.started_at),
+        ("Completed:", bead.completed_at),
+        ("Deferred to:", bead.deferred_until),
+        ("Deadline:", bead.deadline),
+    ] {
+        if let Some(ts) = ts {
+            let _ = writeln!(out, "  {:<12} {}", label, tz.format_datetime(&ts));
+        }
+    }
+    if !bead.dependencies.is_empty() {
+        let _ = writeln!(out, "  Depends on:");
+        for dep in &bead.dependencies {
+            let _ = writeln!(out, "    - {}", dep);
+        }
+    }
+    if !bead.acceptance_criteria.is_empty() {
+        let _ = writeln!(out, "  Criteria:");
+        for criterion in &bead.acceptance_criteria {
+            let _ = writeln!(out, "    - {}", criterion);
+        }
+    }
+
+    let _ = writeln!(out, "  Prompt:");
+    let prompt = bead.effective_prompt();
+    match prompt.char_indices().nth(PROMPT_PREVIEW_CHARS) {
+        Some((cut, _)) if !full => {
+            write_indented(&mut out, &format!("{}…", prompt[..cut].trim_end()));
+            let _ = writeln!(
+                out,
+                "    (--full shows all {} chars)",
+                prompt.chars().count()
+            );
+        }
+        _ => write_indented(&mut out, prompt),
+    }
+
+    if provenance {
+        let _ = writeln!(out);
+        match &bead.provenance {
+            Some(p) => {
+                let _ = writeln!(out, "  Provenance:");
+                let _ = writeln!(out, "    Provider:    {}", p.provider.display_name());
+                let _ = writeln!(out, "    Model:       {}", p.model);
+                let _ = writeln!(out, "    Prompt Hash: {}", p.prompt_hash);
+                let _ = writeln!(out, "    Executor:    {}", p.executor);
+                let _ = writeln!(
+                    out,
+                    "    Executed:    {}",
+                    tz.format_datetime(&p.executed_at)
+                );
+            }
+            None => {
+                let _ = writeln!(out, "  Provenance:  (not executed yet)");
+            }
+        }
+    }
+    out
+}
+
+/// Render the original and optimized prompts, flagging the one that will be sent
+fn render_prompts(bead: &Bead) -> String {
+    const EFFECTIVE: &str = "  ← effective";
+
+    let mut out = String::new();
+    let optimized = bead.optimized_prompt.as_deref();
+    let _ = writeln!(
+        out,
+        "  Original:{}",
+        if optimized.is_none() { EFFECTIVE } else { "" }
+    );
+    write_indented(&mut out, &bead.description);
+    match optimized {
+        Some(prompt) => {
+            let _ = writeln!(out, "  Optimized:{}", EFFECTIVE);
+            write_indented(&mut out, prompt);
+        }
+        None => {
+            let _ = writeln!(out, "  Optimized:   (not optimized yet)");
+        }
+    }
+    out
+}
+
+fn write_indented(out: &mut String, text: &str) {
+    for line in text.lines() {
+        let _ = writeln!(out, "    {}", line);
+    }
+}
+
+/// Parse `--shift` as a duration that may be negative ("-6h")
+fn parse_shift(s: &str) -> std::result::Result<Duration, String> {
+    match s.trim().strip_prefix('-') {
+        Some(rest) => parse_age(rest).map(|by| -by),
+        None => parse_age(s),
+    }
+}
+
+/// Render `bead logs`: every attempt with what its process printed
+fn render_logs(bead: &Bead, attempts: &[ExecutionRecord], tz: &DisplayTimezone) -> String {
+    let mut out = String::new();
+    if attempts.is_empty() {
+        let _ = writeln!(out, "Bead {} has not been executed yet.", bead.id);
+        return out;
+    }
+    let _ = writeln!(out, "Execution log for {}: {}", bead.id, bead.title);
+    for (n, attempt) in attempts.iter().enumerate() {
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "Attempt {} · {} · {} · {:.1}s · {}",
+            n + 1,
+            attempt.provider,
+            tz.format(&attempt.completed_at, "%Y-%m-%d %H:%M:%S"),
+            attempt.duration.as_secs_f64(),
+            if attempt.succeeded() { "ok" } else { "failed" }
+        );
+        let Some(diagnostics) = &attempt.diagnostics else {
+            if let Some(error) = &attempt.error {
+                let _ = writeln!(out, "  Error:   {}", error);
+            }
+            continue;
+        };
+        let _ = writeln!(out, "  Command: {}", diagnostics.command);
+        let _ = writeln!(
+            out,
+            "  Exit:    {}",
+            diagnostics
+                .exit_code
+                .map_or_else(|| "killed by signal".to_string(), |c| c.to_string())
+        );
+        for (name, stream) in [
+            ("stderr", &diagnostics.stderr),
+            ("stdout", &diagnostics.stdout),
+        ] {
+            if stream.trim().is_empty() {
+                let _ = writeln!(out, "  {}:  (empty)", name);
+            } else {
+                let _ = writeln!(out, "  {}:", name);
+                write_indented(&mut out, stream);
+            }
+        }
+    }
+    out
+}
+
+/// Render the `bead dead-letter list` table
+fn render_dead_letter(beads: &[Bead], tz: &DisplayTimezone) -> String {
+    const ERROR_WIDTH: usize = 48;
+
+    let mut out = String::new();
+    if beads.is_empty() {
+        let _ = writeln!(out, "No dead-lettered beads.");
+        return out;
+    }
+
+    let _ = writeln!(out, "Dead-lettered beads ({}):", beads.len());
+    let _ = writeln!(out);
+    let _ = writeln!(out, "  ID         Retries  Failed             Last Error");
+    let _ = writeln!(
+        out,
+        "  ──────────────────────────────────────────────────────────"
+    );
+    for bead in beads {
+        let failed = bead
+            .completed_at
+            .map(|t| tz.format(&t, "%Y-%m-%d %H:%M"))
+            .unwrap_or_else(|| "-".to_string());
+        let error = bead.error.as_deref().unwrap_or("-");
+        let error = match error.char_indices().nth(ERROR_WIDTH) {
+            Some((i, _)) => format!("{}…", &error[..i]),
+            None => error.to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "  {:<10} {}/{:<5}  {:<18} {}",
+            bead.id, bead.retry_count, bead.max_retries, failed, error
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(subcommand)]
+        command: BeadCommands,
+    }
+
+    #[test]
+    fn test_malformed_bead_ids_rejected_at_parse() {
+        for command in ["show", "cancel", "retry"] {
+            let err = TestCli::try_parse_from(["rigs", command, "xyz"])
+                .err()
+                .unwrap_or_else(|| panic!("`bead {} xyz` should be rejected", command));
+            let message = err.to_string();
+            assert!(message.contains("Invalid bead ID 'xyz'"), "{}", message);
+            assert!(
+                message.contains("'gt-' followed by 5 alphanumeric"),
+                "{}",
+                message
+            );
+        }
+
+        let cli = TestCli::try_parse_from(["rigs", "show", "gt-AB12C"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            BeadCommands::Show { id: BeadRef::Id(id), .. } if id.as_str() == "gt-ab12c"
+        ));
+
+        let cli = TestCli::try_parse_from(["rigs", "show", "#42"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            BeadCommands::Show {
+                id: BeadRef::Seq(42),
+                ..
+            }
+        ));
+        assert!(TestCli::try_parse_from(["rigs", "show", "#4x"]).is_err());
+    }
+
+    #[test]
+    fn test_render_prompts_flags_effective() {
+        let mut bead = Bead::new(
+            "Parser",
+            "Write a parser\nfor TOML",
+            TaskType::Implementation,
+        );
+        let original = render_prompts(&bead);
+        assert!(original.contains("  Original:  ← effective\n    Write a parser\n    for TOML\n"));
+        assert!(original.contains("(not optimized yet)"));
+
+        bead.optimized_prompt = Some("Write a recursive-descent TOML parser".into());
+        let out = render_prompts(&bead);
+        assert!(out.contains("  Original:\n    Write a parser\n"));
+        assert!(
+            out.contains("  Optimized:  ← effective\n    Write a recursive-descent TOML parser\n")
+        );
+        assert!(out.contains(bead.effective_prompt()));
+    }
+
+    use crate::db::init_memory_pool;
+
+    #[tokio::test]
+    async fn test_estimate_updates_after_description_change() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let estimator = Estimator::new();
+        let mut bead = Bead::new("Parser", "Write a parser", TaskType::Implementation);
+        BeadRepository::create(&repo, &bead).await.unwrap();
+
+        let (before, first) = re_estimate(&repo, &estimator, &bead.id).await.unwrap();
+        assert_eq!(before, 0);
+        assert!(first > 0);
+
+        bead.description = "Write a parser for the full config grammar ".repeat(40);
+        BeadRepository::update(&repo, &bead).await.unwrap();
+
+        let (before, second) = re_estimate(&repo, &estimator, &bead.id).await.unwrap();
+        assert_eq!(before, 0, "update above reset the stored estimate");
+        assert!(second > first);
+        let stored = BeadRepository::get(&repo, &bead.id).await.unwrap().unwrap();
+        assert_eq!(stored.estimated_tokens, second);
+
+        let missing = BeadId::new();
+        assert!(matches!(
+            re_estimate(&repo, &estimator, &missing).await,
+            Err(RigsError::BeadNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_created_bead_reads_back_with_dependencies() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut first = Bead::new("Schema", "Design the schema", TaskType::Design);
+        let first_seq = create_bead(&repo, &mut first, &[]).await.unwrap();
+
+        let mut second = Bead::new("Parser", "Write a parser", TaskType::Implementation)
+            .with_criteria(vec!["Handles nested tables".into()]);
+        create_bead(
+            &repo,
+            &mut second,
+            &[BeadRef::Seq(first_seq), BeadRef::Id(first.id.clone())],
+        )
+        .await
+        .unwrap();
+
+        let stored = BeadRepository::get(&repo, &second.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.title, "Parser");
+        assert_eq!(stored.dependencies, vec![first.id.clone()]);
+        assert_eq!(stored.acceptance_criteria, vec!["Handles nested tables"]);
+
+        let missing = BeadId::new();
+        let mut orphan = Bead::new("Orphan", "o", TaskType::Review);
+        assert!(matches!(
+            create_bead(&repo, &mut orphan, &[BeadRef::Id(missing)]).await,
+            Err(RigsError::BeadNotFound(_))
+        ));
+        assert!(BeadRepository::get(&repo, &orphan.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_create_args() {
+        let cli = TestCli::try_parse_from([
+            "bead",
+            "create",
+            "Write a parser",
+            "--title",
+            "Parser",
+            "--criteria",
+            "fast",
+            "--criteria",
+            "correct",
+            "--depends-on",
+            "#3",
+        ])
+        .unwrap();
+        let BeadCommands::Create {
+            title,
+            criteria,
+            depends_on,
+            ..
+        } = cli.command
+        else {
+            panic!("expected create");
+        };
+        assert_eq!(title.as_deref(), Some("Parser"));
+        assert_eq!(criteria, vec!["fast", "correct"]);
+        assert_eq!(depends_on, vec![BeadRef::Seq(3)]);
+
+        assert!(TestCli::try_parse_from(["bead", "create", "x", "--depends-on", "nope"]).is_err());
+    }
+
+    #[test]
+    fn test_title_from_description() {
+        assert_eq!(title_from("Fix typo\nin the README"), "Fix typo");
+        let long = "word ".repeat(20);
+        let title = title_from(&long);
+        assert_eq!(title.chars().count(), 60);
+        assert!(title.ends_with("word…"));
+    }
+
+    #[tokio::test]
+    async fn test_list_and_show_as_json() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut parser = Bead::new("Parser", "Write a parser", TaskType::Implementation);
+        parser.convoy_id = Some("toml".into());
+        let mut docs = Bead::new("Docs", "Document it", TaskType::Documentation);
+        docs.status = BeadStatus::Completed;
+        docs.created_at += chrono::Duration::milliseconds(1);
+        for bead in [&parser, &docs] {
+            BeadRepository::create(&repo, bead).await.unwrap();
+        }
+
+        let beads = repo.list(&BeadFilter::default()).await.unwrap();
+        let out = OutputFormat::Json
+            .render(beads.as_slice(), render_list)
+            .unwrap();
+        let listed: Vec<Bead> = serde_json::from_str(&out).unwrap();
+        let ids: Vec<BeadId> = listed.into_iter().map(|b| b.id).collect();
+        assert_eq!(ids, vec![docs.id.clone(), parser.id.clone()]);
+
+        let text = render_list(&beads);
+        assert!(
+            text.contains(&format!("{}  completed  documentation", docs.id)),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains(&format!("{}  pending    implementation", parser.id)),
+            "{}",
+            text
+        );
+        assert_eq!(render_list(&[]), "No beads found\n");
+
+        let bead = repo.resolve(&BeadRef::Seq(1)).await.unwrap();
+        let out = OutputFormat::Json.render(&bead, |_| String::new()).unwrap();
+        let shown: Bead = serde_json::from_str(&out).unwrap();
+        assert_eq!(shown.id, parser.id);
+        assert_eq!(shown.convoy_id.as_deref(), Some("toml"));
+    }
+
+    #[tokio::test]
+    async fn test_show_renders_stored_bead_or_not_found() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let dep = Bead::new("Lexer", "Write a lexer", TaskType::Implementation);
+        let mut bead = Bead::new("Parser", "x".repeat(300), TaskType::Implementation)
+            .with_priority(Priority::High)
+            .with_provider(Provider::Codex)
+            .with_criteria(vec!["Handles nesting".into()])
+            .with_dependencies(vec![dep.id.clone()])
+            .with_estimate(4_000);
+        bead.assigned_provider = Some(Provider::Claude);
+        bead.actual_tokens = Some(3_500);
+        bead.created_at = "2026-01-18T10:00:00Z".parse().unwrap();
+        BeadRepository::create(&repo, &bead).await.unwrap();
+
+        let missing = BeadId::new();
+        match repo.resolve(&BeadRef::Id(missing.clone())).await {
+            Err(RigsError::BeadNotFound(id)) => assert_eq!(id, missing),
+            other => panic!("expected BeadNotFound, got {:?}", other),
+        }
+
+        let stored = repo.resolve(&BeadRef::Id(bead.id.clone())).await.unwrap();
+        let tz = DisplayTimezone::default();
+        let out = render_show(&stored, false, false, &tz);
+        for expected in [
+            format!("Bead: {} (#1)", bead.id),
+            "Priority:    high".to_string(),
+            "Provider:    Claude (preferred: Codex)".to_string(),
+            "Tokens:      4000 estimated, 3500 actual".to_string(),
+            "Created:     2026-01-18 10:00 UTC".to_string(),
+            format!("Depends on:\n    - {}", dep.id),
+            "Criteria:\n    - Handles nesting".to_string(),
+            format!("    {}…\n    (--full shows all 300 chars)", "x".repeat(200)),
+        ] {
+            assert!(
+                out.contains(&expected),
+                "missing {:?} in\n{}",
+                expected,
+                out
+            );
+        }
+        assert!(!out.contains("Started:"));
+
+        let full = render_show(&stored, true, true, &tz);
+        assert!(full.contains(&format!("    {}\n", "x".repeat(300))));
+        assert!(full.contains("Provenance:  (not executed yet)"));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_guards_status() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let pending = Bead::new("Pending", "p", TaskType::Review);
+        let mut done = Bead::new("Done", "d", TaskType::Review);
+        done.status = BeadStatus::Completed;
+        let mut running = Bead::new("Running", "r", TaskType::Review);
+        running.status = BeadStatus::InProgress;
+        for bead in [&pending, &done, &running] {
+            BeadRepository::create(&repo, bead).await.unwrap();
+        }
+        let status = |bead: Option<Bead>| bead.unwrap().status;
+
+        let (id, outcome) = cancel_bead(&repo, &BeadRef::Id(pending.id.clone()), false, false)
+            .await
+            .unwrap();
+        assert_eq!((id, outcome), (pending.id.clone(), Cancellation::Cancelled));
+        let cancelled = repo.get(&pending.id).await.unwrap().unwrap();
+        assert_eq!(cancelled.status, BeadStatus::Cancelled);
+        assert!(cancelled.completed_at.is_some());
+
+        for finished in [&done.id, &pending.id] {
+            let err = cancel_bead(&repo, &BeadRef::Id(finished.clone()), true, false)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, RigsError::BeadAlreadyFinished { .. }));
+        }
+        assert_eq!(
+            status(repo.get(&done.id).await.unwrap()),
+            BeadStatus::Completed
+        );
+
+        // A running foreman owns the bead: only ask it to cancel
+        let running_ref = BeadRef::Id(running.id.clone());
+        let (_, outcome) = cancel_bead(&repo, &running_ref, false, true).await.unwrap();
+        assert_eq!(outcome, Cancellation::Requested);
+        assert!(repo.cancel_requested(&running.id).await.unwrap());
+        assert_eq!(
+            status(repo.get(&running.id).await.unwrap()),
+            BeadStatus::InProgress
+        );
+
+        let (_, outcome) = cancel_bead(&repo, &running_ref, true, true).await.unwrap();
+        assert_eq!(outcome, Cancellation::Cancelled);
+        assert_eq!(
+            status(repo.get(&running.id).await.unwrap()),
+            BeadStatus::Cancelled
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tree_shows_transitive_dependencies() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut research = Bead::new("Research", "r", TaskType::Research);
+        research.status = BeadStatus::Completed;
+        let design =
+            Bead::new("Design", "d", TaskType::Design).with_dependencies(vec![research.id.clone()]);
+        let missing = BeadId::new();
+        let build = Bead::new("Build", "b", TaskType::Implementation)
+            .with_dependencies(vec![design.id.clone(), missing.clone()]);
+        for (i, bead) in [&research, &design, &build].into_iter().enumerate() {
+            let mut bead = bead.clone();
+            bead.created_at += chrono::Duration::milliseconds(i as i64);
+            BeadRepository::create(&repo, &bead).await.unwrap();
+        }
+
+        let graph = load_graph(&repo, &build.id, false).await.unwrap();
+        let tree = render_tree(&build.id, &graph);
+        assert_eq!(
+            tree,
+            format!(
+                "{} ○ Build\n├── {} ○ Design\n│   └── {} ✓ Research\n└── {} ? (missing)\n",
+                build.id, design.id, research.id, missing
+            )
+        );
+
+        let graph = load_graph(&repo, &research.id, true).await.unwrap();
+        let tree = render_tree(&research.id, &graph);
+        assert!(tree.ends_with(&format!(
+            "└── {} ○ Design\n    └── {} ○ Build\n",
+            design.id, build.id
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_retry_all_failed_only_recoverable() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let errors = [
+            RigsError::ProviderOverloaded(Provider::Claude, "overloaded".into()),
+            RigsError::ProviderRateLimited {
+                provider: Provider::Codex,
+                reset: None,
+            },
+            RigsError::ProviderNotConfigured(Provider::Gemini),
+        ];
+        let mut beads = Vec::new();
+        for error in &errors {
+            let mut bead = Bead::new("Failed", "f", TaskType::Implementation);
+            bead.status = BeadStatus::Failed;
+            bead.record_error(error);
+            beads.push(bead);
+        }
+        let mut legacy = Bead::new("Legacy", "l", TaskType::Research);
+        legacy.status = BeadStatus::Failed;
+        legacy.error = Some("failed before errors were classified".into());
+        beads.push(legacy);
+        let mut done = Bead::new("Done", "d", TaskType::Test);
+        done.status = BeadStatus::Completed;
+        beads.push(done);
+        for bead in &beads {
+            BeadRepository::create(&repo, bead).await.unwrap();
+        }
+
+        let report = retry_all_failed(&repo, true).await.unwrap();
+        assert_eq!(
+            report.requeued,
+            BTreeMap::from([
+                (Some(RetryClass::Transient), 1),
+                (Some(RetryClass::RateLimited), 1)
+            ])
+        );
+        assert_eq!(
+            report.skipped,
+            BTreeMap::from([(None, 1), (Some(RetryClass::Fatal), 1)])
+        );
+        assert_eq!(report.exhausted, 0);
+
+        let mut stored = Vec::new();
+        for bead in &beads {
+            stored.push(BeadRepository::get(&repo, &bead.id).await.unwrap().unwrap());
+        }
+        for bead in &stored[..2] {
+            assert_eq!(bead.status, BeadStatus::Queued);
+            assert_eq!(bead.retry_count, 1);
+            assert!(bead.error.is_none() && bead.error_class.is_none());
+        }
+        assert_eq!(stored[2].status, BeadStatus::Failed);
+        assert_eq!(stored[2].error_class, Some(RetryClass::Fatal));
+        assert_eq!(stored[3].status, BeadStatus::Failed);
+        assert_eq!(stored[4].status, BeadStatus::Completed);
+
+        let out = render_retry_report(&report);
+        assert!(out.contains("Re-queued 2 failed bead(s)"), "{}", out);
+        assert!(out.contains("rate limited  1"), "{}", out);
+        assert!(
+            out.contains("Left 2 unrecoverable bead(s) failed"),
+            "{}",
+            out
+        );
+        assert!(out.contains("unclassified  1"), "{}", out);
+
+        // Without the filter the rest are retried too
+        let report = retry_all_failed(&repo, false).await.unwrap();
+        assert_eq!(report.requeued.values().sum::<usize>(), 2);
+        assert!(report.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_retry_needs_an_id_or_all_failed() {
+        assert!(TestCli::try_parse_from(["rigs", "retry"]).is_err());
+        assert!(TestCli::try_parse_from(["rigs", "retry", "#1", "--all-failed"]).is_err());
+        assert!(TestCli::try_parse_from(["rigs", "retry", "#1", "--only-recoverable"]).is_err());
+        let cli = TestCli::try_parse_from(["rigs", "retry", "--all-failed", "--only-recoverable"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            BeadCommands::Retry {
+                id: None,
+                all_failed: true,
+                only_recoverable: true
+            }
+        ));
+    }
+
+    #[test]
+    fn test_tree_marks_cycles() {
+        let mut a = Bead::new("A", "a", TaskType::Design);
+        let b = Bead::new("B", "b", TaskType::Design).with_dependencies(vec![a.id.clone()]);
+        a.dependencies = vec![b.id.clone()];
+        let graph = DependencyGraph {
+            edges: HashMap::from([
+                (a.id.clone(), a.dependencies.clone()),
+                (b.id.clone(), b.dependencies.clone()),
+            ]),
+            beads: HashMap::from([(a.id.clone(), a.clone()), (b.id.clone(), b.clone())]),
+        };
+
+        let tree = render_tree(&a.id, &graph);
+        assert!(tree.ends_with(&format!("    └── {} ○ A (cycle)\n", a.id)));
+        assert_eq!(tree.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_cost_ranks_providers_and_stars_cheapest_viable() {
+        let pricing = PricingTable::with_overrides(&HashMap::from([
+            (Provider::Claude, 1.0),
+            (Provider::Gemini, 20.0),
+        ]));
+        let bead = Bead::new("Audit", "audit", TaskType::Review).with_estimate(300_000);
+        let out = render_cost(
+            &bead,
+            &[Provider::Codex, Provider::Claude, Provider::Gemini],
+            &pricing,
+        );
+        let lines: Vec<&str> = out.lines().skip(2).collect();
+        // Claude is cheapest but its 200k window can't hold the bead
+        assert_eq!(
+            lines,
+            vec![
+                "    Claude     $0.3000  (context window too small)",
+                "    Codex      $3.0000  (context window too small)",
+                "  ★ Gemini     $6.0000",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_dead_letter() {
+        assert!(
+            render_dead_letter(&[], &DisplayTimezone::default()).contains("No dead-lettered beads")
+        );
+
+        let mut bead = Bead::new("Flaky", "flaky", TaskType::Test);
+        bead.status = BeadStatus::Failed;
+        bead.retry_count = 3;
+        bead.error = Some("x".repeat(100));
+
+        let out = render_dead_letter(&[bead.clone()], &DisplayTimezone::default());
+        assert!(out.contains(&bead.id.to_string()));
+        assert!(out.contains("3/3"));
+        assert!(out.contains(&format!("{}…", "x".repeat(48))));
+        assert!(!out.contains(&"x".repeat(49)));
+    }
+
+    #[tokio::test]
+    async fn test_requeue_dead_letter_saves_the_bead() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut dead = Bead::new("Flaky", "flaky", TaskType::Test);
+        dead.status = BeadStatus::Failed;
+        dead.retry_count = dead.max_retries;
+        let mut retryable = Bead::new("Retryable", "r", TaskType::Test);
+        retryable.status = BeadStatus::Failed;
+        for bead in [&dead, &retryable] {
+            BeadRepository::create(&repo, bead).await.unwrap();
+        }
+        assert_eq!(repo.list_dead_letter().await.unwrap().len(), 1);
+
+        requeue_dead_letter(&repo, &BeadRef::Id(dead.id.clone()))
+            .await
+            .unwrap();
+        let stored = repo.get(&dead.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, BeadStatus::Queued);
+        assert_eq!(stored.retry_count, 0);
+        assert!(repo.list_dead_letter().await.unwrap().is_empty());
+
+        assert!(
+            requeue_dead_letter(&repo, &BeadRef::Id(retryable.id.clone()))
+                .await
+                .is_err()
+        );
+        assert!(matches!(
+            requeue_dead_letter(&repo, &BeadRef::Id(BeadId::new())).await,
+            Err(RigsError::BeadNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_reschedule_takes_a_shift_or_a_deadline() {
+        let cli =
+            TestCli::try_parse_from(["rigs", "reschedule", "--convoy", "cv-1", "--shift", "-6h"])
+                .unwrap();
+        assert!(matches!(
+            cli.command,
+            BeadCommands::Reschedule { shift: Some(by), deadline: None, .. } if by == Duration::hours(-6)
+        ));
+
+        assert!(TestCli::try_parse_from(["rigs", "reschedule", "--convoy", "cv-1"]).is_err());
+        assert!(TestCli::try_parse_from([
+            "rigs",
+            "reschedule",
+            "--convoy",
+            "cv-1",
+            "--shift",
+            "2d",
+            "--deadline",
+            "2026-03-01T12:00:00Z",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_render_logs() {
+        let bead = Bead::new("Parser", "parse", TaskType::Implementation);
+        assert!(render_logs(&bead, &[], &DisplayTimezone::default()).contains("not been executed"));
+
+        let attempt = ExecutionRecord {
+            bead_id: bead.id.clone(),
+            provider: Provider::Codex,
+            estimated_tokens: 100,
+            actual_tokens: 0,
+            duration: std::time::Duration::from_millis(1500),
+            error: Some("Provider Codex API error: `codex exec` exited with 1".into()),
+            diagnostics: Some(crate::core::ExecutionDiagnostics {
+                command: "codex exec --model gpt-5 -".into(),
+                exit_code: Some(1),
+                stdout: String::new(),
+                stderr: "error: not logged in\n".into(),
+            }),
+            completed_at: chrono::Utc::now(),
+        };
+        let out = render_logs(&bead, &[attempt], &DisplayTimezone::default());
+        assert!(out.contains("Attempt 1 · Codex"));
+        assert!(out.contains("1.5s · failed"));
+        assert!(out.contains("  Command: codex exec --model gpt-5 -"));
+        assert!(out.contains("  Exit:    1"));
+        assert!(out.contains("  stderr:\n    error: not logged in"));
+        assert!(out.contains("  stdout:  (empty)"));
     }
 }
@@ -0,0 +1,107 @@
+//! Configuration file commands
+
+use clap::Subcommand;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::core::{Result, RigsError};
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Write a commented default config file
+    Init {
+        /// Overwrite an existing config file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check the config file for settings that parse but can't work
+    Validate {
+        /// Config file to check instead of the `--config` one
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+}
+
+/// `path` is the `--config` file if one was given, else the default location
+pub async fn run(cmd: ConfigCommands, path: Option<&Path>) -> Result<()> {
+    match cmd {
+        ConfigCommands::Init { force } => {
+            let path = match path {
+                Some(path) => path.to_path_buf(),
+                None => Config::default_config_path()?,
+            };
+            write_default(&path, force)?;
+            println!("✓ Wrote default configuration to {}", path.display());
+            Ok(())
+        }
+        ConfigCommands::Validate { path: file } => {
+            let path = match file.as_deref().or(path) {
+                Some(path) => path.to_path_buf(),
+                None => Config::default_config_path()?,
+            };
+            if !path.exists() {
+                println!(
+                    "No config file at {}; checking the defaults",
+                    path.display()
+                );
+            }
+            match Config::load(Some(&path))?.validate() {
+                Ok(()) => {
+                    println!("✓ {} is valid", path.display());
+                    Ok(())
+                }
+                Err(problems) => {
+                    for problem in &problems {
+                        println!("✗ {}", problem);
+                    }
+                    Err(RigsError::InvalidConfig(format!(
+                        "{}: {} problem(s) found",
+                        path.display(),
+                        problems.len()
+                    )))
+                }
+            }
+        }
+    }
+}
+
+/// Write the default config to `path`, refusing to replace a file unless `force`
+fn write_default(path: &PathBuf, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        return Err(RigsError::ConfigError(format!(
+            "{} already exists. Re-run with --force to overwrite it.",
+            path.display()
+        )));
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, Config::default_toml()?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_default_refuses_to_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rigs").join("config.toml");
+
+        write_default(&path, false).unwrap();
+        assert!(Config::load(Some(&path)).is_ok());
+
+        std::fs::write(&path, "# mine").unwrap();
+        assert!(matches!(
+            write_default(&path, false),
+            Err(RigsError::ConfigError(_))
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "# mine");
+
+        write_default(&path, true).unwrap();
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("# Rigs configuration"));
+    }
+}
@@ -1,7 +1,25 @@
 //! Convoy (batch) management commands
 
+use chrono::{DateTime, Utc};
 use clap::Subcommand;
-use crate::core::Result;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::OutputFormat;
+use crate::cli::bead::status_glyph;
+use crate::cli::tank::{format_duration, load_tanks};
+use crate::config::Config;
+use crate::core::bundle::ImportReport;
+use crate::core::schedule::{forecast_completion, CompletionForecast};
+use crate::core::{
+    Bead, BeadId, BeadRef, BeadStatus, Convoy, ConvoyBundle, ConvoyStatus, Provider, Result,
+    RetrySummary, RigsError, Tank,
+};
+use crate::db::open_workspace;
+use crate::db::repository::{BeadRepository, ConvoyRepository, SqliteRepository};
 
 #[derive(Subcommand)]
 pub enum ConvoyCommands {
@@ -9,6 +27,10 @@ pub enum ConvoyCommands {
     Create {
         /// Convoy name
         name: String,
+        /// Criterion for the convoy as a whole, checked once every bead has
+        /// finished (repeatable)
+        #[arg(long = "criterion")]
+        criteria: Vec<String>,
     },
 
     /// List convoys
@@ -18,22 +40,28 @@ pub enum ConvoyCommands {
     Show {
         /// Convoy ID
         id: String,
+        /// Keep refreshing until the convoy finishes (Ctrl+C to quit)
+        #[arg(long)]
+        watch: bool,
+        /// Seconds between refreshes with --watch
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
     },
 
     /// Add bead to convoy
     Add {
         /// Convoy ID
         convoy_id: String,
-        /// Bead ID
-        bead_id: String,
+        /// Bead ID or #seq
+        bead_id: BeadRef,
     },
 
     /// Remove bead from convoy
     Remove {
         /// Convoy ID
         convoy_id: String,
-        /// Bead ID
-        bead_id: String,
+        /// Bead ID or #seq
+        bead_id: BeadRef,
     },
 
     /// Pause a convoy
@@ -47,38 +75,98 @@ pub enum ConvoyCommands {
         /// Convoy ID
         id: String,
     },
+
+    /// Re-queue only the failed beads of a convoy
+    RetryFailed {
+        /// Convoy ID
+        id: String,
+    },
+
+    /// Move every bead of one convoy into another and archive the first
+    Merge {
+        /// Convoy to merge (archived afterwards)
+        src: String,
+        /// Convoy receiving the beads
+        dst: String,
+    },
+
+    /// Copy a convoy and its beads as a new, unstarted convoy
+    Clone {
+        /// Convoy ID
+        id: String,
+        /// Name of the copy (defaults to "<id>-copy")
+        #[arg(long)]
+        name: Option<String>,
+    },
+
+    /// Export a convoy and its beads to a checksummed (optionally signed) bundle
+    Export {
+        /// Convoy ID
+        id: String,
+        /// Output file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Import a convoy bundle, verifying its checksum and signature
+    Import {
+        /// Bundle file
+        path: PathBuf,
+    },
 }
 
-pub async fn run(cmd: ConvoyCommands) -> Result<()> {
+pub async fn run(cmd: ConvoyCommands, config: &Config, format: OutputFormat) -> Result<()> {
     match cmd {
-        ConvoyCommands::Create { name } => {
-            println!("Created convoy: {}", name);
+        ConvoyCommands::Create { name, criteria } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let mut convoy = Convoy::new(name);
+            convoy.acceptance_criteria = criteria;
+            ConvoyRepository::create(&repo, &convoy).await?;
+            println!("Created convoy: {} ({})", convoy.name, convoy.id);
+            for criterion in &convoy.acceptance_criteria {
+                println!("  Acceptance: {}", criterion);
+            }
             Ok(())
         }
         ConvoyCommands::List => {
-            println!("Convoys:");
-            println!();
-            println!("  ID                                   Name              Progress  Status");
-            println!("  ─────────────────────────────────────────────────────────────────────────");
-            println!("  abc-123-def-456                      OAuth Feature     [████░░░░] 50%   in_progress");
-            println!("  ghi-789-jkl-012                      Bug Fixes         [████████] 100%  completed");
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let tanks = load_tanks(config, &repo, &Provider::all().collect::<Vec<_>>()).await?;
+            let now = Utc::now();
+            let mut rows = Vec::new();
+            for id in repo.convoy_ids().await? {
+                let (convoy, beads) = snapshot(&repo, &id).await?;
+                rows.push(ConvoyRow::new(&convoy, &beads, &tanks, now));
+            }
+
+            print!(
+                "{}",
+                format.render(rows.as_slice(), |rows| render_list(rows, now))?
+            );
             Ok(())
         }
-        ConvoyCommands::Show { id } => {
-            println!("Convoy: {}", id);
-            println!("  Name:     OAuth Feature");
-            println!("  Goal:     Add OAuth2 authentication with Google and GitHub");
-            println!("  Status:   in_progress");
-            println!("  Progress: 50% (3/6 beads complete)");
-            println!();
-            println!("  Beads:");
-            println!("    gt-abc12  ✓ Research OAuth2 flows");
-            println!("    gt-def34  ✓ Design auth endpoints");
-            println!("    gt-ghi56  ✓ Implement OAuth client");
-            println!("    gt-jkl78  ▶ Add Google provider");
-            println!("    gt-mno90  ○ Add GitHub provider");
-            println!("    gt-pqr12  ○ Write tests");
-            Ok(())
+        ConvoyCommands::Show {
+            id,
+            watch,
+            interval,
+        } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            if !watch {
+                let (convoy, beads) = snapshot(&repo, &id).await?;
+                print!("{}", render_show(format, &convoy, &beads)?);
+                return Ok(());
+            }
+
+            let interval = Duration::from_secs(interval.max(1));
+            let watching = watch_convoy(&repo, &id, format, interval, |frame| match format {
+                // Clear the screen so each frame replaces the last
+                OutputFormat::Text => print!("\x1b[2J\x1b[H{}", frame),
+                OutputFormat::Json => print!("{}", frame),
+            });
+            let convoy = tokio::select! {
+                convoy = watching => convoy?,
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+            };
+            finish_watch(&convoy, format)
         }
         ConvoyCommands::Add { convoy_id, bead_id } => {
             println!("Added {} to convoy {}", bead_id, convoy_id);
@@ -96,5 +184,695 @@ pub async fn run(cmd: ConvoyCommands) -> Result<()> {
             println!("Resumed convoy: {}", id);
             Ok(())
         }
+        ConvoyCommands::RetryFailed { id } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let (convoy, beads, summary) = retry_failed(&repo, &id).await?;
+            print!("{}", render_retry(&convoy, &beads, &summary));
+            Ok(())
+        }
+        ConvoyCommands::Merge { src, dst } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let (source, target, moved) = merge_convoys(&repo, &src, &dst).await?;
+            println!("Merged convoy {} into {}", source.name, target.name);
+            println!("  {} beads moved, {} archived", moved, source.id);
+            println!(
+                "  {} now has {} beads ({:?})",
+                target.id,
+                target.beads.len(),
+                target.status
+            );
+            Ok(())
+        }
+        ConvoyCommands::Clone { id, name } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let (copy, cloned) = clone_convoy(&repo, &id, name).await?;
+            println!("Cloned convoy {} as {} ({})", id, copy.name, copy.id);
+            println!("  {} beads queued", cloned.len());
+            Ok(())
+        }
+        ConvoyCommands::Export { id, output } => {
+            let signing_key = config.bundle_signing_key()?;
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let bundle = export_convoy(&repo, &id, signing_key.as_deref()).await?;
+            std::fs::write(&output, bundle.to_json()?)?;
+            println!(
+                "Exported convoy {} ({} beads) to {} ({})",
+                bundle.convoy.name,
+                bundle.beads.len(),
+                output.display(),
+                if signing_key.is_some() {
+                    "signed"
+                } else {
+                    "checksummed"
+                }
+            );
+            Ok(())
+        }
+        ConvoyCommands::Import { path } => {
+            let signing_key = config.bundle_signing_key()?;
+            let json = std::fs::read_to_string(&path)?;
+            let bundle = ConvoyBundle::from_json(&json, signing_key.as_deref())?;
+
+            let verification = if bundle.signature.is_some() {
+                "signature verified"
+            } else if bundle.checksum.is_some() {
+                "checksum verified"
+            } else {
+                "unverified (no checksum)"
+            };
+
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let (convoy, beads, report) = import_bundle(&repo, bundle).await?;
+
+            if format == OutputFormat::Json {
+                let summary = serde_json::json!({
+                    "convoy_id": convoy.id,
+                    "name": convoy.name,
+                    "beads": beads.len(),
+                    "verification": verification,
+                    "report": report,
+                });
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+            } else {
+                println!(
+                    "Imported convoy {} ({} beads, {})",
+                    convoy.name,
+                    beads.len(),
+                    verification
+                );
+                print!("{}", render_import_report(&report));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Requeue a convoy's failed beads and save the beads and convoy that changed
+async fn retry_failed(
+    repo: &SqliteRepository,
+    id: &str,
+) -> Result<(Convoy, Vec<Bead>, RetrySummary)> {
+    let (mut convoy, mut beads) = snapshot(repo, id).await?;
+    let summary = convoy.retry_failed(&mut beads);
+    for bead in beads.iter().filter(|b| summary.requeued.contains(&b.id)) {
+        BeadRepository::update(repo, bead).await?;
+    }
+    ConvoyRepository::update(repo, &convoy).await?;
+    Ok((convoy, beads, summary))
+}
+
+/// Merge convoy `src` into `dst` and save the moved beads and both convoys
+/// in one transaction
+///
+/// Returns the archived source, the merged target and how many beads moved.
+async fn merge_convoys(
+    repo: &SqliteRepository,
+    src: &str,
+    dst: &str,
+) -> Result<(Convoy, Convoy, usize)> {
+    let (mut source, source_beads) = snapshot(repo, src).await?;
+    let (mut target, target_beads) = snapshot(repo, dst).await?;
+    let moving: HashSet<BeadId> = source_beads.iter().map(|b| b.id.clone()).collect();
+    let mut beads = source_beads;
+    beads.extend(target_beads);
+
+    let moved = target.merge_from(&mut source, &mut beads)?;
+    beads.retain(|b| moving.contains(&b.id));
+    repo.save_convoy_merge(&source, &target, &beads).await?;
+    Ok((source, target, moved))
+}
+
+/// Copy convoy `id` and its beads, saving the copies in one transaction
+///
+/// The copy is named `name`, or "<original>-copy" when none is given.
+async fn clone_convoy(
+    repo: &SqliteRepository,
+    id: &str,
+    name: Option<String>,
+) -> Result<(Convoy, Vec<Bead>)> {
+    let (convoy, beads) = snapshot(repo, id).await?;
+    let name = name.unwrap_or_else(|| format!("{}-copy", convoy.name));
+    let (copy, cloned) = convoy.clone_as(name, &beads);
+    repo.create_convoy_with_beads(&copy, &cloned).await?;
+    Ok((copy, cloned))
+}
+
+/// Render what `convoy retry-failed` did to each failed bead
+fn render_retry(convoy: &Convoy, beads: &[Bead], summary: &RetrySummary) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Retrying failed beads in convoy: {}", convoy.name);
+    if summary.requeued.is_empty() && summary.exhausted.is_empty() {
+        let _ = writeln!(out, "  No failed beads");
+    }
+    for bead in beads {
+        let retries = format!("{}/{}", bead.retry_count, bead.max_retries);
+        if summary.requeued.contains(&bead.id) {
+            let _ = writeln!(out, "  ✓ {} re-queued (retry {})", bead.id, retries);
+        } else if summary.exhausted.contains(&bead.id) {
+            let _ = writeln!(out, "  ✗ {} out of retries ({})", bead.id, retries);
+        }
+    }
+    let _ = writeln!(out, "Convoy {} is {:?}", convoy.id, convoy.status);
+    out
+}
+
+/// Bundle a stored convoy and its beads, sealed with `signing_key` if given
+async fn export_convoy(
+    repo: &SqliteRepository,
+    id: &str,
+    signing_key: Option<&[u8]>,
+) -> Result<ConvoyBundle> {
+    let (convoy, beads) = snapshot(repo, id).await?;
+    let mut bundle = ConvoyBundle::new(convoy, beads);
+    bundle.seal(signing_key)?;
+    Ok(bundle)
+}
+
+/// Store a verified bundle's convoy and beads, renaming ids already in use
+async fn import_bundle(
+    repo: &SqliteRepository,
+    bundle: ConvoyBundle,
+) -> Result<(Convoy, Vec<Bead>, ImportReport)> {
+    let mut existing_beads = HashSet::new();
+    for bead in &bundle.beads {
+        if BeadRepository::get(repo, &bead.id).await?.is_some() {
+            existing_beads.insert(bead.id.clone());
+        }
+    }
+    let mut existing_convoys = HashSet::new();
+    if ConvoyRepository::get(repo, &bundle.convoy.id)
+        .await?
+        .is_some()
+    {
+        existing_convoys.insert(bundle.convoy.id.clone());
+    }
+
+    let (convoy, beads, report) = bundle.into_import(&existing_beads, &existing_convoys);
+    repo.create_convoy_with_beads(&convoy, &beads).await?;
+    Ok((convoy, beads, report))
+}
+
+/// List the ids an import reassigned and the dependencies it dropped
+fn render_import_report(report: &ImportReport) -> String {
+    let mut out = String::new();
+    if let Some(convoy) = &report.convoy {
+        let _ = writeln!(
+            out,
+            "  Convoy id {} already exists; imported as {}",
+            convoy.old, convoy.new
+        );
+    }
+    if !report.beads.is_empty() {
+        let _ = writeln!(out, "  Renamed beads (id already in use):");
+        for remap in &report.beads {
+            let _ = writeln!(out, "    {} → {}", remap.old, remap.new);
+        }
+    }
+    if !report.dropped_dependencies.is_empty() {
+        let _ = writeln!(out, "  Dropped dependencies on beads outside the bundle:");
+        for dropped in &report.dropped_dependencies {
+            let _ = writeln!(
+                out,
+                "    {} no longer waits on {}",
+                dropped.bead_id, dropped.dependency
+            );
+        }
+    }
+    out
+}
+
+/// One line of `convoy list`
+#[derive(Debug, Serialize)]
+struct ConvoyRow {
+    id: String,
+    status: ConvoyStatus,
+    completed: usize,
+    total: usize,
+    /// Earliest time the tanks let every remaining bead run
+    eta: Option<DateTime<Utc>>,
+    /// Why no ETA can be given, when a bead can never run
+    blocked: Option<String>,
+}
+
+impl ConvoyRow {
+    fn new(convoy: &Convoy, beads: &[Bead], tanks: &[Tank], now: DateTime<Utc>) -> Self {
+        let counts = convoy.status_counts(&statuses(beads));
+        let completed: HashSet<BeadId> = beads
+            .iter()
+            .filter(|b| b.status == BeadStatus::Completed)
+            .map(|b| b.id.clone())
+            .collect();
+        let remaining: Vec<Bead> = beads
+            .iter()
+            .filter(|b| !b.status.is_terminal())
+            .cloned()
+            .collect();
+        let forecast = forecast_completion(&remaining, &completed, tanks, now);
+
+        Self {
+            id: convoy.id.clone(),
+            status: convoy.status,
+            completed: counts.completed,
+            total: counts.total(),
+            eta: forecast.eta(),
+            blocked: match forecast {
+                CompletionForecast::Blocked(reason) => Some(reason),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Render the `convoy list` table
+fn render_list(rows: &[ConvoyRow], now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Convoys:");
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "  ID                                    Progress         Status               ETA"
+    );
+    let _ = writeln!(out, "  {}", "─".repeat(90));
+    for row in rows {
+        let filled = (row.completed * 8).checked_div(row.total).unwrap_or(0);
+        let progress = format!(
+            "[{}{}] {}%",
+            "█".repeat(filled),
+            "░".repeat(8 - filled),
+            row.completed * 100 / row.total.max(1)
+        );
+        let eta = match (row.eta, &row.blocked) {
+            (_, Some(_)) => "blocked".to_string(),
+            (Some(eta), None) if eta > now => format!("in {}", format_duration(eta - now)),
+            (Some(_), None) => "now".to_string(),
+            (None, None) => "-".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "  {:<36}  {:<15}  {:<19}  {}",
+            row.id,
+            progress,
+            format!("{:?}", row.status),
+            eta
+        );
+    }
+    out
+}
+
+/// Load a convoy from the `convoys` table along with its beads
+///
+/// The stored status only catches up with the beads when the foreman
+/// reconciles, so an active convoy's status is refreshed from them here;
+/// finished and archived convoys keep theirs.
+async fn snapshot(repo: &SqliteRepository, id: &str) -> Result<(Convoy, Vec<Bead>)> {
+    let mut convoy = ConvoyRepository::get(repo, id)
+        .await?
+        .ok_or_else(|| RigsError::ConvoyNotFound(id.to_string()))?;
+    let members = repo.list_by_convoy(id).await?;
+    for bead in &members {
+        convoy.add_bead(bead.id.clone());
+    }
+    if !convoy.status.is_terminal() {
+        convoy.refresh_status(&statuses(&members));
+    }
+    Ok((convoy, members))
+}
+
+fn statuses(beads: &[Bead]) -> HashMap<BeadId, BeadStatus> {
+    beads.iter().map(|b| (b.id.clone(), b.status)).collect()
+}
+
+/// What `convoy show --format json` prints
+#[derive(Serialize)]
+struct ConvoyDetail<'a> {
+    convoy: &'a Convoy,
+    beads: &'a [Bead],
+}
+
+/// Render `convoy show` in the chosen format
+fn render_show(format: OutputFormat, convoy: &Convoy, beads: &[Bead]) -> Result<String> {
+    format.render(&ConvoyDetail { convoy, beads }, |detail| {
+        render_convoy(detail.convoy, detail.beads)
+    })
+}
+
+/// Render the `convoy show` view: progress followed by every bead
+fn render_convoy(convoy: &Convoy, beads: &[Bead]) -> String {
+    let statuses = statuses(beads);
+    let counts = convoy.status_counts(&statuses);
+    let mut out = String::new();
+    let _ = writeln!(out, "Convoy: {}", convoy.id);
+    let _ = writeln!(out, "  Name:     {}", convoy.name);
+    if let Some(goal) = &convoy.goal {
+        let _ = writeln!(out, "  Goal:     {}", goal);
+    }
+    let _ = writeln!(out, "  Status:   {:?}", convoy.status);
+    let _ = writeln!(
+        out,
+        "  Progress: {:.0}% ({}/{} beads complete)",
+        convoy.progress(&statuses) * 100.0,
+        counts.completed,
+        counts.total()
+    );
+    let _ = writeln!(out);
+    let _ = writeln!(out, "  Beads:");
+    for bead in beads {
+        let _ = writeln!(
+            out,
+            "    {}  {} {}",
+            bead.id,
+            status_glyph(bead.status),
+            bead.title
+        );
+    }
+    out
+}
+
+/// Render a frame every `interval` until the convoy finishes
+///
+/// Each frame is re-read from the repository; in JSON each frame is a
+/// separate document. Returns the convoy in its terminal state.
+async fn watch_convoy(
+    repo: &SqliteRepository,
+    id: &str,
+    format: OutputFormat,
+    interval: Duration,
+    mut emit: impl FnMut(&str),
+) -> Result<Convoy> {
+    loop {
+        let (convoy, members) = snapshot(repo, id).await?;
+        emit(&render_show(format, &convoy, &members)?);
+        if convoy.status.is_terminal() {
+            return Ok(convoy);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Print the closing summary; a convoy with failures is an error
+fn finish_watch(convoy: &Convoy, format: OutputFormat) -> Result<()> {
+    match convoy.status {
+        ConvoyStatus::Completed => {
+            if format == OutputFormat::Text {
+                println!("\nConvoy {} completed", convoy.id);
+            }
+            Ok(())
+        }
+        _ => Err(RigsError::ConvoyFailed(convoy.id.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TaskType;
+    use crate::db::init_memory_pool;
+    use std::sync::Arc;
+
+    async fn create_convoy(repo: &SqliteRepository, convoy_id: &str) {
+        let mut convoy = Convoy::new(format!("{} name", convoy_id));
+        convoy.id = convoy_id.to_string();
+        convoy.status = ConvoyStatus::Queued;
+        ConvoyRepository::create(repo, &convoy).await.unwrap();
+    }
+
+    async fn convoy_beads(repo: &SqliteRepository, convoy_id: &str, n: usize) -> Vec<Bead> {
+        create_convoy(repo, convoy_id).await;
+        let mut beads = Vec::new();
+        for i in 0..n {
+            let mut bead = Bead::new(format!("Step {}", i), "step", TaskType::Implementation);
+            bead.convoy_id = Some(convoy_id.to_string());
+            bead.created_at += chrono::Duration::milliseconds(i as i64);
+            BeadRepository::create(repo, &bead).await.unwrap();
+            beads.push(bead);
+        }
+        beads
+    }
+
+    #[tokio::test]
+    async fn test_watch_renders_successive_frames() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let mut beads = convoy_beads(&repo, "c1", 2).await;
+
+        let (convoy, members) = snapshot(repo.as_ref(), "c1").await.unwrap();
+        let first = render_convoy(&convoy, &members);
+        assert!(first.contains("Name:     c1 name"));
+        assert!(first.contains("Progress: 0% (0/2 beads complete)"));
+        let json = render_show(OutputFormat::Json, &convoy, &members).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["convoy"]["id"], "c1");
+        assert_eq!(json["beads"].as_array().unwrap().len(), 2);
+        assert_eq!(json["beads"][0]["title"], "Step 0");
+
+        beads[0].status = BeadStatus::Completed;
+        BeadRepository::update(repo.as_ref(), &beads[0])
+            .await
+            .unwrap();
+
+        let advancing = {
+            let repo = repo.clone();
+            let mut last = beads.pop().unwrap();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                last.status = BeadStatus::Completed;
+                BeadRepository::update(repo.as_ref(), &last).await.unwrap();
+            })
+        };
+
+        let mut frames = Vec::new();
+        let convoy = watch_convoy(
+            repo.as_ref(),
+            "c1",
+            OutputFormat::Text,
+            Duration::from_millis(10),
+            |f| frames.push(f.to_string()),
+        )
+        .await
+        .unwrap();
+        advancing.await.unwrap();
+
+        assert_eq!(convoy.status, ConvoyStatus::Completed);
+        assert!(frames.len() >= 2);
+        assert!(frames[0].contains("Progress: 50% (1/2 beads complete)"));
+        assert!(frames[0].contains("Status:   InProgress"));
+        assert!(frames
+            .last()
+            .unwrap()
+            .contains("Progress: 100% (2/2 beads complete)"));
+        assert!(finish_watch(&convoy, OutputFormat::Text).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_list_reports_eta_unless_blocked() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut tank = Tank::new(Provider::Claude, 1_000, 5);
+        tank.remaining = 100;
+        let now = tank.window_start;
+
+        create_convoy(&repo, "ok").await;
+        let mut waiting = Bead::new("Needs a reset", "w", TaskType::Implementation)
+            .with_provider(Provider::Claude)
+            .with_estimate(500);
+        waiting.convoy_id = Some("ok".into());
+        BeadRepository::create(&repo, &waiting).await.unwrap();
+
+        let mut failed = convoy_beads(&repo, "stuck", 1).await.remove(0);
+        failed.status = BeadStatus::Failed;
+        BeadRepository::update(&repo, &failed).await.unwrap();
+        let mut dependent = Bead::new("Dependent", "d", TaskType::Review)
+            .with_dependencies(vec![failed.id.clone()]);
+        dependent.convoy_id = Some("stuck".into());
+        BeadRepository::create(&repo, &dependent).await.unwrap();
+
+        let mut rows = Vec::new();
+        for id in repo.convoy_ids().await.unwrap() {
+            let (convoy, beads) = snapshot(&repo, &id).await.unwrap();
+            rows.push(ConvoyRow::new(&convoy, &beads, &[tank.clone()], now));
+        }
+        let out = OutputFormat::Json
+            .render(rows.as_slice(), |rows| render_list(rows, now))
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json[0]["id"], "ok");
+        assert_eq!(
+            json[0]["eta"],
+            serde_json::to_value(tank.window_end).unwrap()
+        );
+        assert_eq!(json[1]["id"], "stuck");
+        assert!(json[1]["eta"].is_null());
+        assert!(json[1]["blocked"].as_str().unwrap().contains("waiting on"));
+
+        let text = render_list(&rows, now);
+        assert!(text.contains("in 5h 0m"));
+        assert!(text.contains("blocked"));
+    }
+
+    #[tokio::test]
+    async fn test_merge_saves_moved_beads_and_archives_source() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let moving = convoy_beads(&repo, "src", 2).await;
+        convoy_beads(&repo, "dst", 1).await;
+
+        let (source, target, moved) = merge_convoys(&repo, "src", "dst").await.unwrap();
+        assert_eq!(moved, 2);
+        assert_eq!(source.status, ConvoyStatus::Archived);
+        assert_eq!(target.beads.len(), 3);
+
+        for bead in &moving {
+            let stored = BeadRepository::get(&repo, &bead.id).await.unwrap().unwrap();
+            assert_eq!(stored.convoy_id.as_deref(), Some("dst"));
+        }
+        assert_eq!(repo.list_by_convoy("dst").await.unwrap().len(), 3);
+        assert!(repo.list_by_convoy("src").await.unwrap().is_empty());
+        let stored = ConvoyRepository::get(&repo, "src").await.unwrap().unwrap();
+        assert_eq!(stored.status, ConvoyStatus::Archived);
+        assert_eq!(
+            stored.metadata.get("merged_into").map(String::as_str),
+            Some("dst")
+        );
+
+        assert!(matches!(
+            merge_convoys(&repo, "dst", "dst").await,
+            Err(RigsError::InvalidMerge(_))
+        ));
+        assert!(matches!(
+            merge_convoys(&repo, "missing", "dst").await,
+            Err(RigsError::ConvoyNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_clone_saves_convoy_with_its_beads() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        convoy_beads(&repo, "c4", 2).await;
+
+        let (copy, cloned) = clone_convoy(&repo, "c4", None).await.unwrap();
+        assert_eq!(copy.name, "c4 name-copy");
+        assert_eq!(cloned.len(), 2);
+
+        let (stored, members) = snapshot(&repo, &copy.id).await.unwrap();
+        assert_eq!(stored.status, ConvoyStatus::Queued);
+        assert_eq!(
+            stored.metadata.get("cloned_from").map(String::as_str),
+            Some("c4")
+        );
+        assert_eq!(members.len(), 2);
+        assert!(members.iter().all(|b| b.status == BeadStatus::Pending));
+        assert_eq!(repo.list_by_convoy("c4").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_failed_saves_requeued_beads() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut beads = convoy_beads(&repo, "c3", 3).await;
+        beads[0].status = BeadStatus::Completed;
+        beads[1].status = BeadStatus::Failed;
+        beads[2].status = BeadStatus::Failed;
+        beads[2].retry_count = beads[2].max_retries;
+        for bead in &beads {
+            BeadRepository::update(&repo, bead).await.unwrap();
+        }
+
+        let (convoy, members, summary) = retry_failed(&repo, "c3").await.unwrap();
+        assert_eq!(summary.requeued, vec![beads[1].id.clone()]);
+        assert_eq!(summary.exhausted, vec![beads[2].id.clone()]);
+
+        let stored = BeadRepository::get(&repo, &beads[1].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, BeadStatus::Queued);
+        assert_eq!(stored.retry_count, 1);
+        let stored = ConvoyRepository::get(&repo, "c3").await.unwrap().unwrap();
+        assert_eq!(stored.status, ConvoyStatus::InProgress);
+
+        let out = render_retry(&convoy, &members, &summary);
+        assert!(out.contains(&format!("✓ {} re-queued (retry 1/", beads[1].id)));
+        assert!(out.contains(&format!("✗ {} out of retries", beads[2].id)));
+        assert!(matches!(
+            retry_failed(&repo, "missing").await,
+            Err(RigsError::ConvoyNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_export_then_import_into_same_workspace() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let beads = convoy_beads(&repo, "c4", 2).await;
+
+        let bundle = export_convoy(&repo, "c4", Some(b"key")).await.unwrap();
+        assert_eq!(bundle.beads.len(), 2);
+        let json = bundle.to_json().unwrap();
+        let bundle = ConvoyBundle::from_json(&json, Some(b"key")).unwrap();
+
+        // Everything already exists here, so every id is replaced
+        let (convoy, imported, report) = import_bundle(&repo, bundle).await.unwrap();
+        assert_ne!(convoy.id, "c4");
+        assert_eq!(report.convoy.unwrap().old, "c4");
+        assert_eq!(report.beads.len(), 2);
+
+        let stored = ConvoyRepository::get(&repo, &convoy.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.name, "c4 name");
+        let members = repo.list_by_convoy(&convoy.id).await.unwrap();
+        assert_eq!(members.len(), 2);
+        assert!(members.iter().all(|b| !beads.iter().any(|o| o.id == b.id)));
+        assert_eq!(members[0].id, imported[0].id);
+    }
+
+    #[test]
+    fn test_import_report_lists_dropped_dependencies() {
+        let mut bundle = ConvoyBundle::new(
+            Convoy::new("Imported"),
+            vec![Bead::new("Child", "child", TaskType::Review)],
+        );
+        let external = BeadId::new();
+        bundle.beads[0].dependencies = vec![external.clone()];
+
+        let (_, beads, report) = bundle.into_import(&HashSet::new(), &HashSet::new());
+        let text = render_import_report(&report);
+        assert!(text.contains("Dropped dependencies"));
+        assert!(text.contains(&format!("{} no longer waits on {}", beads[0].id, external)));
+        assert!(!text.contains("Renamed"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json["dropped_dependencies"][0]["dependency"],
+            external.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_fails_on_convoy_with_errors() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut beads = convoy_beads(&repo, "c2", 2).await;
+        for (bead, status) in beads
+            .iter_mut()
+            .zip([BeadStatus::Completed, BeadStatus::Failed])
+        {
+            bead.status = status;
+            BeadRepository::update(&repo, bead).await.unwrap();
+        }
+
+        let convoy = watch_convoy(
+            &repo,
+            "c2",
+            OutputFormat::Text,
+            Duration::from_millis(10),
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(convoy.status, ConvoyStatus::CompletedWithErrors);
+        assert!(matches!(
+            finish_watch(&convoy, OutputFormat::Text),
+            Err(RigsError::ConvoyFailed(_))
+        ));
+        assert!(matches!(
+            snapshot(&repo, "missing").await,
+            Err(RigsError::ConvoyNotFound(_))
+        ));
     }
 }
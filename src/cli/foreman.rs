@@ -1,7 +1,24 @@
 //! Foreman (orchestrator) commands
 
+use chrono::Utc;
 use clap::Subcommand;
-use crate::core::Result;
+use std::fmt::Write;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use super::tank::{format_duration, load_tanks};
+use crate::config::Config;
+use crate::core::assayer::{OllamaGate, Optimizer};
+use crate::core::daemon::{self, DaemonState};
+use crate::core::executor::CliExecutor;
+use crate::core::foreman::{DrainReport, Foreman};
+use crate::core::schedule::DispatchPlan;
+use crate::core::{Provider, QualityGate, Result, RigsError};
+use crate::db::open_workspace;
+use crate::db::repository::{ForemanStateRepository, SqliteRepository};
 
 #[derive(Subcommand)]
 pub enum ForemanCommands {
@@ -18,6 +35,9 @@ pub enum ForemanCommands {
     /// Show foreman status
     Status,
 
+    /// Preview the order pending beads would run in (executes nothing)
+    Plan,
+
     /// Attach to running foreman (interactive)
     Attach,
 
@@ -28,48 +48,78 @@ pub enum ForemanCommands {
     Resume,
 }
 
-pub async fn run(cmd: ForemanCommands) -> Result<()> {
+pub async fn run(cmd: ForemanCommands, config: &Config) -> Result<()> {
     match cmd {
         ForemanCommands::Start { foreground } => {
-            if foreground {
-                println!("Starting foreman in foreground...");
-                println!("Press Ctrl+C to stop");
-                println!();
-                println!("[14:32:01] Foreman started");
-                println!("[14:32:01] Loaded 3 providers: Claude, Codex, Gemini");
-                println!("[14:32:01] Queue: 5 pending, 0 in progress");
-                println!("[14:32:02] Processing bead gt-abc12 with Claude...");
-                // TODO: Actual event loop
-            } else {
+            let state_path = DaemonState::path(&config.workspace_dir());
+            if let Some(running) = DaemonState::running(&state_path)? {
+                return Err(RigsError::ForemanAlreadyRunning(running.pid));
+            }
+            if !foreground {
                 println!("Starting foreman daemon...");
-                println!("✓ Foreman started (PID: 12345)");
+                let pid = spawn_daemon(&config.workspace_dir())?;
+                println!("✓ Foreman started (PID: {})", pid);
                 println!("  Use `rigs foreman attach` to view progress");
+                return Ok(());
             }
+
+            println!("Starting foreman in foreground...");
+            println!("Press Ctrl+C to stop");
+            println!();
+            DaemonState::current().write(&state_path)?;
+            let outcome = run_foreground(config).await;
+            DaemonState::remove(&state_path)?;
+            print!("{}", render_drain(&outcome?));
             Ok(())
         }
         ForemanCommands::Stop => {
-            println!("Stopping foreman daemon...");
+            let state_path = DaemonState::path(&config.workspace_dir());
+            let Some(running) = DaemonState::running(&state_path)? else {
+                println!("Foreman is not running");
+                return Ok(());
+            };
+            let grace = config.foreman.shutdown_grace_secs;
+            println!("Stopping foreman daemon (PID: {})...", running.pid);
+            println!("  Waiting up to {}s for in-flight beads to finish", grace);
+            daemon::terminate(running.pid)?;
+
+            // The daemon prints its own drain report and removes the state file
+            let deadline = Instant::now() + Duration::from_secs(grace + STOP_MARGIN_SECS);
+            while daemon::is_alive(running.pid) {
+                if Instant::now() >= deadline {
+                    return Err(RigsError::Other(format!(
+                        "Foreman (PID {}) is still running after {}s",
+                        running.pid,
+                        grace + STOP_MARGIN_SECS
+                    )));
+                }
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+            DaemonState::remove(&state_path)?;
             println!("✓ Foreman stopped");
             Ok(())
         }
         ForemanCommands::Status => {
-            println!("Foreman Status: Running (PID: 12345)");
-            println!();
-            println!("  Uptime:          2h 34m");
-            println!("  State:           Processing");
-            println!("  Current Bead:    gt-abc12");
-            println!("  Current Provider: Claude");
-            println!();
-            println!("  Queue Status:");
-            println!("    Pending:     5");
-            println!("    In Progress: 1");
-            println!("    Deferred:    2");
-            println!("    Completed:   42");
-            println!();
-            println!("  Session Stats:");
-            println!("    Beads Completed: 42");
-            println!("    Tokens Used:     156,789");
-            println!("    Avg Time/Bead:   3m 24s");
+            let state_path = DaemonState::path(&config.workspace_dir());
+            let running = DaemonState::running(&state_path)?;
+            let paused = match open_workspace(config).await {
+                Ok(pool) => SqliteRepository::new(pool).is_paused().await?,
+                Err(RigsError::WorkspaceNotInitialized) => false,
+                Err(e) => return Err(e),
+            };
+            print!("{}", render_status(running.as_ref(), paused));
+            Ok(())
+        }
+        ForemanCommands::Plan => {
+            let repo = Arc::new(SqliteRepository::new(open_workspace(config).await?));
+            let foreman = Foreman::new(
+                repo.clone(),
+                Arc::new(CliExecutor::default()),
+                config.foreman.clone(),
+            );
+            let tanks =
+                load_tanks(config, repo.as_ref(), &Provider::all().collect::<Vec<_>>()).await?;
+            print!("{}", render_plan(&foreman.plan(&tanks).await?));
             Ok(())
         }
         ForemanCommands::Attach => {
@@ -80,14 +130,249 @@ pub async fn run(cmd: ForemanCommands) -> Result<()> {
             Ok(())
         }
         ForemanCommands::Pause => {
-            println!("Pausing foreman...");
-            println!("✓ Foreman paused");
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            repo.set_paused(true).await?;
+            println!("✓ Foreman paused; beads already running will finish");
             Ok(())
         }
         ForemanCommands::Resume => {
-            println!("Resuming foreman...");
-            println!("✓ Foreman resumed");
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            repo.set_paused(false).await?;
+            // The daemon notices at its next poll
+            println!(
+                "✓ Foreman resumed (within {}s if running)",
+                config.foreman.poll_interval
+            );
             Ok(())
         }
     }
 }
+
+/// Extra seconds `foreman stop` waits beyond the shutdown grace period
+const STOP_MARGIN_SECS: u64 = 10;
+
+/// The quality gate the config enables, if any
+fn quality_gate(config: &Config) -> Result<Option<Arc<OllamaGate>>> {
+    if config.assayer.use_ollama && config.providers.ollama.enabled {
+        Ok(Some(Arc::new(OllamaGate::from_config(config)?)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// A foreman over the workspace set up as the config describes
+///
+/// Shared by the daemon and by `goal execute`, which runs its own convoy.
+pub(crate) fn configured_foreman(config: &Config, repo: Arc<SqliteRepository>) -> Result<Foreman> {
+    let executor = Arc::new(CliExecutor::default());
+    let mut foreman = Foreman::new(repo.clone(), executor.clone(), config.foreman.clone());
+    if let Some(gate) = quality_gate(config)? {
+        let optimizer = Optimizer::from_config(config, executor)?;
+        foreman = foreman
+            .with_assayer(Arc::new(optimizer), config.assayer.max_concurrent)
+            .with_gate(gate);
+    }
+    Ok(foreman
+        .with_history(repo.clone())
+        .with_convoys(repo.clone())
+        .with_tanks(repo.clone(), repo.clone())
+        .with_state(repo)
+        .with_local_only(config.general.local_only)
+        .with_models(
+            Provider::all()
+                .map(|p| (p, config.provider_config(p)))
+                .collect(),
+        )
+        .with_prompt_template(config.prompts.bead_template()))
+}
+
+/// Run the foreman in this process until Ctrl+C or SIGTERM
+async fn run_foreground(config: &Config) -> Result<DrainReport> {
+    let repo = Arc::new(SqliteRepository::new(open_workspace(config).await?));
+    let gate = quality_gate(config)?;
+    let convoy_gate = gate.as_deref().map(|g| g as &dyn QualityGate);
+    for fix in repo.reconcile_convoys(convoy_gate).await? {
+        warn!(convoy = %fix.convoy_id, from = ?fix.from, to = ?fix.to, "Corrected convoy status");
+    }
+    let foreman = configured_foreman(config, repo.clone())?;
+    let providers: Vec<Provider> = Provider::all().collect();
+    let grace = config.foreman.shutdown_grace_secs;
+    // TODO: Notify the waker when beads are added from another process
+    foreman
+        .run(|| load_tanks(config, repo.as_ref(), &providers), async {
+            shutdown_signal().await;
+            println!();
+            println!("Stopping: waiting up to {}s for in-flight beads...", grace);
+        })
+        .await
+}
+
+/// Re-run this command in the foreground as a detached background process
+///
+/// Its output goes to `foreman.log` in the workspace. Returns the new PID.
+fn spawn_daemon(workspace: &Path) -> Result<u32> {
+    std::fs::create_dir_all(workspace)?;
+    let log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(workspace.join("foreman.log"))?;
+    let child = std::process::Command::new(std::env::current_exe()?)
+        .args(std::env::args_os().skip(1))
+        .arg("--foreground")
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .spawn()?;
+    Ok(child.id())
+}
+
+/// Render `foreman status` for the daemon found, if any
+fn render_status(running: Option<&DaemonState>, paused: bool) -> String {
+    let mut out = String::new();
+    match running {
+        Some(state) => {
+            let _ = writeln!(out, "Foreman Status: Running (PID: {})", state.pid);
+            let _ = writeln!(
+                out,
+                "  Started: {} (up {})",
+                state.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                format_duration(Utc::now() - state.started_at)
+            );
+            let _ = writeln!(
+                out,
+                "  State:   {}",
+                if paused { "Paused" } else { "Processing" }
+            );
+        }
+        None => {
+            let _ = writeln!(out, "Foreman Status: Not running");
+            let _ = writeln!(out, "  Start it with `rigs foreman start`");
+            if paused {
+                let _ = writeln!(
+                    out,
+                    "  Paused: it won't pick up beads until `rigs foreman resume`"
+                );
+            }
+        }
+    }
+    out
+}
+
+/// Resolve on Ctrl+C, or on SIGTERM where there is one
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = term.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Render what happened to in-flight beads at shutdown
+fn render_drain(report: &DrainReport) -> String {
+    let mut out = String::new();
+    if report.finished > 0 {
+        let _ = writeln!(out, "  {} in-flight beads finished", report.finished);
+    }
+    if !report.requeued.is_empty() {
+        let _ = writeln!(
+            out,
+            "  {} beads didn't finish in time and were requeued:",
+            report.requeued.len()
+        );
+        for id in &report.requeued {
+            let _ = writeln!(out, "    {}", id);
+        }
+    }
+    let _ = writeln!(out, "Foreman stopped");
+    out
+}
+
+/// Render the `foreman plan` preview
+fn render_plan(plan: &DispatchPlan) -> String {
+    let mut out = String::new();
+    if plan.dispatched.is_empty() && plan.deferred.is_empty() && plan.blocked.is_empty() {
+        let _ = writeln!(out, "No pending beads.");
+        return out;
+    }
+
+    let _ = writeln!(out, "Dispatch order ({} beads):", plan.dispatched.len());
+    let mut wave = 0;
+    for (i, step) in plan.dispatched.iter().enumerate() {
+        if step.wave != wave {
+            wave = step.wave;
+            let _ = writeln!(out, "  Pass {}", wave);
+        }
+        let _ = writeln!(
+            out,
+            "    {:>3}. {}  {:<30} → {} (~{} tokens)",
+            i + 1,
+            step.bead_id,
+            step.title,
+            step.provider,
+            step.estimated_tokens
+        );
+    }
+
+    for (heading, held) in [
+        ("Projected deferrals", &plan.deferred),
+        ("Blocked (will not run)", &plan.blocked),
+    ] {
+        if held.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{}:", heading);
+        for bead in held {
+            let _ = writeln!(out, "  {}  {}: {}", bead.bead_id, bead.title, bead.reason);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::schedule::plan_dispatch;
+    use crate::core::{Bead, BeadId, TaskType};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_render_plan_shows_passes_and_blocked() {
+        let first = Bead::new("Research", "r", TaskType::Research);
+        let second =
+            Bead::new("Design", "d", TaskType::Design).with_dependencies(vec![first.id.clone()]);
+        let orphan =
+            Bead::new("Orphan", "o", TaskType::Review).with_dependencies(vec![BeadId::new()]);
+
+        let plan = plan_dispatch(&[first, second, orphan.clone()], &HashSet::new(), &[]);
+        let out = render_plan(&plan);
+
+        assert!(out.contains("Dispatch order (2 beads):"));
+        assert!(out.contains("  Pass 2\n      2. "));
+        assert!(out.contains("Blocked (will not run):"));
+        assert!(out.contains(&format!("{}  Orphan: waiting on", orphan.id)));
+    }
+
+    #[test]
+    fn test_render_status() {
+        let state = DaemonState {
+            pid: 4242,
+            started_at: Utc::now() - chrono::Duration::minutes(95),
+        };
+        let out = render_status(Some(&state), false);
+        assert!(out.starts_with("Foreman Status: Running (PID: 4242)\n"));
+        assert!(out.contains("(up 1h 35m)"));
+        assert!(out.contains("State:   Processing"));
+        assert!(render_status(Some(&state), true).contains("State:   Paused"));
+
+        assert!(render_status(None, false).contains("Not running"));
+        assert!(render_status(None, true).contains("rigs foreman resume"));
+    }
+}
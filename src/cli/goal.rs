@@ -1,7 +1,24 @@
 //! Goal commands (decomposition and execution)
 
+use chrono::{DateTime, Utc};
 use clap::Subcommand;
-use crate::core::{Priority, Result};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::Arc;
+use tracing::warn;
+
+use super::foreman::configured_foreman;
+use super::read_confirmation;
+use super::tank::load_tanks;
+use crate::config::{Config, ProviderReadiness};
+use crate::core::budget::{Budget, BudgetTracker};
+use crate::core::foreman::Foreman;
+use crate::core::planner::{GoalPlan, Planner};
+use crate::core::pricing::PricingTable;
+use crate::core::{Bead, BeadId, BeadStatus, Convoy, Priority, Provider, Result, RigsError, Tank};
+use crate::db::open_workspace;
+use crate::db::repository::{BeadRepository, ConvoyRepository, SqliteRepository};
 
 #[derive(Subcommand)]
 pub enum GoalCommands {
@@ -24,75 +41,566 @@ pub enum GoalCommands {
         /// Auto-approve (no confirmation)
         #[arg(long)]
         yes: bool,
+        /// Convoy name (generated from the goal if omitted)
+        #[arg(long)]
+        name: Option<String>,
+        /// Stop dispatching once the estimated cost (USD) would exceed this
+        #[arg(long)]
+        max_cost: Option<f64>,
+        /// Stop dispatching once total tokens would exceed this
+        #[arg(long)]
+        max_tokens: Option<u64>,
+        /// Stop dispatching after this time (RFC 3339, or a duration like 2h)
+        #[arg(long, value_parser = parse_deadline)]
+        deadline: Option<DateTime<Utc>>,
+        /// Try providers in this order for every bead (e.g. claude,gemini,codex)
+        #[arg(long, value_delimiter = ',')]
+        provider_order: Option<Vec<Provider>>,
     },
 }
 
-pub async fn run(cmd: GoalCommands) -> Result<()> {
+pub async fn run(cmd: GoalCommands, config: &Config) -> Result<()> {
     match cmd {
         GoalCommands::Plan { goal, refine } => {
+            preflight(config, false)?;
+
             println!("Planning goal: {}", goal);
             println!();
-            
+
             if refine {
                 println!("Using iterative refinement...");
             }
-            
-            println!("Decomposing with Planner Assayer (DeepSeek R1)...");
-            println!();
-            println!("Generated 5 beads:");
-            println!();
-            println!("  1. [research]       Research OAuth2 authentication flows");
-            println!("     Est. tokens: 2,000 | Provider: Gemini");
-            println!();
-            println!("  2. [design]         Design authentication API endpoints");
-            println!("     Est. tokens: 3,000 | Provider: Claude");
-            println!("     Depends on: #1");
-            println!();
-            println!("  3. [implementation] Implement OAuth2 client library");
-            println!("     Est. tokens: 5,000 | Provider: Claude");
-            println!("     Depends on: #2");
-            println!();
-            println!("  4. [implementation] Add Google OAuth provider");
-            println!("     Est. tokens: 3,000 | Provider: Claude");
-            println!("     Depends on: #3");
-            println!();
-            println!("  5. [test]           Write authentication tests");
-            println!("     Est. tokens: 2,000 | Provider: Codex");
-            println!("     Depends on: #3, #4");
-            println!();
-            println!("Total estimated tokens: 15,000");
-            println!("Estimated cost: ~$0.50 (if using API)");
+
+            let plan = Planner::from_config(config)?.plan(&goal).await?;
+            let pricing = PricingTable::with_overrides(&config.pricing);
+            print!("{}", render_goal_plan(&plan, &pricing));
             println!();
             println!("Run `rigs goal execute \"{}\"` to execute this plan", goal);
             Ok(())
         }
-        GoalCommands::Execute { goal, priority, yes } => {
+        GoalCommands::Execute {
+            goal,
+            priority,
+            yes,
+            name,
+            max_cost,
+            max_tokens,
+            deadline,
+            provider_order,
+        } => {
+            let budget = Budget {
+                max_tokens,
+                max_cost,
+                deadline,
+            };
+
+            if let Some(warning) = preflight(config, true)? {
+                warn!("{}", warning);
+            }
+            let repo = Arc::new(SqliteRepository::new(open_workspace(config).await?));
+
             println!("Executing goal: {}", goal);
             println!("Priority: {}", priority);
+            if !budget.is_unlimited() {
+                println!("Budget: {}", describe_budget(&budget));
+            }
+            let mut order = None;
+            if config.general.local_only {
+                println!("Local only: every bead runs on Ollama");
+            } else if let Some(requested) = &provider_order {
+                let all: Vec<Provider> = Provider::all().collect();
+                let tanks = load_tanks(config, repo.as_ref(), &all).await?;
+                let usable = usable_order(requested, config, &tanks)?;
+                let names: Vec<&str> = usable.iter().map(|p| p.as_str()).collect();
+                println!("Provider order: {}", names.join(" → "));
+                order = Some(usable);
+            }
             println!();
-            
+
             // Show plan first
-            println!("Generated plan with 5 beads...");
-            
-            if !yes {
-                println!();
-                println!("Proceed? [y/N] ");
-                // TODO: Read input
-            }
-            
+            let plan = Planner::from_config(config)?.plan(&goal).await?;
+            println!(
+                "Generated plan with {} beads ({})...",
+                plan.beads.len(),
+                plan.source
+            );
+
+            let stdin = io::stdin();
+            let interactive = stdin.is_terminal();
+            let Some(mut convoy) = confirm_and_create(
+                repo.as_ref(),
+                &goal,
+                name,
+                yes,
+                interactive,
+                &mut stdin.lock(),
+            )
+            .await?
+            else {
+                println!("Aborted.");
+                return Ok(());
+            };
+
             println!();
             println!("Creating convoy...");
-            println!("✓ Convoy created: oauth-feature-xyz123");
+            convoy.add_beads(&plan.beads)?;
+            ConvoyRepository::update(repo.as_ref(), &convoy).await?;
+            println!("✓ Convoy created: {}", convoy.name);
             println!();
             println!("Queuing beads...");
-            println!("  ✓ gt-abc12 queued (research)");
-            println!("  ✓ gt-def34 queued (design)");
-            println!("  ✓ gt-ghi56 queued (implementation)");
-            println!("  ✓ gt-jkl78 queued (implementation)");
-            println!("  ✓ gt-mno90 queued (test)");
+            for mut bead in plan.beads {
+                bead.priority = priority;
+                bead.convoy_id = Some(convoy.id.clone());
+                BeadRepository::create(repo.as_ref(), &bead).await?;
+                println!("  ✓ {} queued ({})", bead.id, bead.task_type);
+            }
             println!();
-            println!("Convoy started. Use `rigs convoy show oauth-feature-xyz123` to track progress.");
+
+            let mut foreman = configured_foreman(config, repo.clone())?
+                .with_convoy(convoy.id.clone())
+                .with_budget(BudgetTracker::new(
+                    budget,
+                    PricingTable::with_overrides(&config.pricing),
+                ));
+            if let Some(order) = order {
+                foreman = foreman.with_provider_order(order);
+            }
+            println!("Running convoy {}...", convoy.name);
+            let beads = run_convoy(&foreman, config, repo.as_ref(), &convoy.id).await?;
+            print!("{}", render_outcome(&beads, foreman.budget_spent()));
+            println!(
+                "Use `rigs convoy show {}` for the details of each bead.",
+                convoy.id
+            );
+            if beads.iter().any(|b| b.status == BeadStatus::Failed) {
+                return Err(RigsError::ConvoyFailed(convoy.id));
+            }
             Ok(())
         }
     }
 }
+
+/// Dispatch a convoy's beads until nothing more can run
+///
+/// Stops once a pass dispatches nothing: every bead has finished, or the
+/// rest wait on the budget, a tank or a failed dependency. Returns the
+/// convoy's beads as they were left.
+async fn run_convoy(
+    foreman: &Foreman,
+    config: &Config,
+    repo: &SqliteRepository,
+    convoy_id: &str,
+) -> Result<Vec<Bead>> {
+    let providers: Vec<Provider> = Provider::all().collect();
+    loop {
+        let tanks = load_tanks(config, repo, &providers).await?;
+        if foreman.run_once_within(&tanks).await? == 0 {
+            break;
+        }
+    }
+    let beads = repo.list_by_convoy(convoy_id).await?;
+    // A convoy halted for failing keeps its Failed status
+    let stored = ConvoyRepository::get(repo, convoy_id).await?;
+    if let Some(mut convoy) = stored.filter(|c| !c.status.is_terminal()) {
+        for bead in &beads {
+            convoy.add_bead(bead.id.clone());
+        }
+        convoy.refresh_status(&beads.iter().map(|b| (b.id.clone(), b.status)).collect());
+        ConvoyRepository::update(repo, &convoy).await?;
+    }
+    Ok(beads)
+}
+
+/// Summarize how a convoy run left its beads, listing those deferred
+fn render_outcome(beads: &[Bead], spent: Option<(u64, f64)>) -> String {
+    let count = |status| beads.iter().filter(|b| b.status == status).count();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{} completed, {} failed, {} deferred, {} not run",
+        count(BeadStatus::Completed),
+        count(BeadStatus::Failed),
+        count(BeadStatus::Deferred),
+        beads.iter().filter(|b| !b.status.is_terminal()).count() - count(BeadStatus::Deferred)
+    );
+    if let Some((tokens, cost)) = spent {
+        let _ = writeln!(out, "Spent: {} tokens (~${:.2})", tokens, cost);
+    }
+    let deferred: Vec<&Bead> = beads
+        .iter()
+        .filter(|b| b.status == BeadStatus::Deferred)
+        .collect();
+    if !deferred.is_empty() {
+        let _ = writeln!(out, "Deferred beads:");
+        for bead in deferred {
+            let _ = writeln!(
+                out,
+                "  {} {}: {}",
+                bead.id,
+                bead.title,
+                bead.error.as_deref().unwrap_or("Deferred")
+            );
+        }
+    }
+    out
+}
+
+/// Render a plan for `goal plan`, numbering beads from 1
+fn render_goal_plan(plan: &GoalPlan, pricing: &PricingTable) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Decomposed by: {}", plan.source);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Generated {} beads:", plan.beads.len());
+    let number = |id: &BeadId| plan.beads.iter().position(|b| &b.id == id).map(|i| i + 1);
+    let mut cost = 0.0;
+    for (i, bead) in plan.beads.iter().enumerate() {
+        let provider = bead.dispatch_provider();
+        cost += pricing.cost(provider, bead.estimated_tokens);
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "  {}. {:<16} {}",
+            i + 1,
+            format!("[{}]", bead.task_type),
+            bead.title
+        );
+        let _ = writeln!(
+            out,
+            "     Est. tokens: {} | Provider: {}",
+            bead.estimated_tokens,
+            provider.display_name()
+        );
+        let deps: Vec<String> = bead
+            .dependencies
+            .iter()
+            .map(|id| number(id).map_or_else(|| id.to_string(), |n| format!("#{}", n)))
+            .collect();
+        if !deps.is_empty() {
+            let _ = writeln!(out, "     Depends on: {}", deps.join(", "));
+        }
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Total estimated tokens: {}", plan.total_tokens());
+    let _ = writeln!(out, "Estimated cost: ~${:.2} (if using API)", cost);
+    out
+}
+
+/// Parse `--deadline` as an RFC 3339 timestamp or a duration from now
+pub fn parse_deadline(s: &str) -> std::result::Result<DateTime<Utc>, String> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    super::prune::parse_age(s)
+        .map(|age| Utc::now() + age)
+        .map_err(|_| format!("'{}' is neither an RFC 3339 time nor a duration like 2h", s))
+}
+
+/// Drop providers from `--provider-order` that are disabled or out of tokens
+///
+/// Errors if that leaves nothing to run on.
+fn usable_order(order: &[Provider], config: &Config, tanks: &[Tank]) -> Result<Vec<Provider>> {
+    let exhausted = |p: &Provider| {
+        tanks
+            .iter()
+            .any(|t| t.provider == *p && !t.is_unlimited() && t.remaining == 0)
+    };
+    let usable: Vec<Provider> = order
+        .iter()
+        .copied()
+        .filter(|p| config.is_provider_enabled(*p) && !exhausted(p))
+        .collect();
+    if usable.is_empty() {
+        return Err(RigsError::Other(
+            "None of the providers in --provider-order are enabled with tokens left".into(),
+        ));
+    }
+    Ok(usable)
+}
+
+/// One-line summary of the limits in a budget
+fn describe_budget(budget: &Budget) -> String {
+    let mut limits = Vec::new();
+    if let Some(tokens) = budget.max_tokens {
+        limits.push(format!("{} tokens", tokens));
+    }
+    if let Some(cost) = budget.max_cost {
+        limits.push(format!("${:.2}", cost));
+    }
+    if let Some(deadline) = budget.deadline {
+        limits.push(format!("until {}", deadline.to_rfc3339()));
+    }
+    limits.join(", ")
+}
+
+/// Ask for confirmation (unless `--yes`) and create the convoy if approved
+///
+/// Returns `None` if the user declines. Without `--yes`, a non-interactive
+/// stdin is refused outright rather than blocking on a prompt nobody sees.
+async fn confirm_and_create(
+    repo: &dyn ConvoyRepository,
+    goal: &str,
+    name: Option<String>,
+    yes: bool,
+    interactive: bool,
+    input: &mut dyn BufRead,
+) -> Result<Option<Convoy>> {
+    if !yes {
+        if !interactive {
+            return Err(RigsError::ConfirmationRequired("--yes"));
+        }
+        println!();
+        print!("Proceed? [y/N] ");
+        io::stdout().flush()?;
+        if !read_confirmation(input)? {
+            return Ok(None);
+        }
+    }
+
+    let existing: HashSet<String> = repo.list_all().await?.into_iter().map(|c| c.name).collect();
+    let name = name.unwrap_or_else(|| Convoy::name_from_goal(goal, &existing));
+    let convoy = Convoy::from_goal(name, goal, Vec::new());
+    repo.create(&convoy).await?;
+    Ok(Some(convoy))
+}
+
+/// Check providers before planning or executing
+///
+/// Fails if nothing is usable at all; returns a warning when executing with
+/// only assayer providers, since planned beads would never be dispatched.
+fn preflight(config: &Config, executing: bool) -> Result<Option<String>> {
+    match config.check_providers()? {
+        ProviderReadiness::Ready(_) => Ok(None),
+        ProviderReadiness::AssayerOnly if executing => Ok(Some(
+            "Only assayer providers are enabled: beads will be planned but not dispatched. \
+             Run `rigs provider add <claude|codex|gemini>` to enable execution."
+                .to_string(),
+        )),
+        ProviderReadiness::AssayerOnly => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::convoy::slugify_goal;
+    use crate::core::{ExecutionOutput, ExecutionRequest, Executor, TaskType};
+    use crate::db::init_memory_pool;
+    use std::io::Cursor;
+
+    fn assayer_only_config() -> Config {
+        let mut config = Config::default();
+        config.providers.claude.enabled = false;
+        config.providers.codex.enabled = false;
+        config.providers.gemini.enabled = false;
+        config
+    }
+
+    /// Executor that answers every prompt with its uppercase form
+    struct UpperExecutor;
+
+    #[async_trait::async_trait]
+    impl Executor for UpperExecutor {
+        fn kind(&self) -> &'static str {
+            "upper"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            Ok(ExecutionOutput::estimated(
+                request,
+                request.prompt.to_uppercase(),
+                std::time::Duration::ZERO,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_convoy_reports_beads_deferred_by_budget() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let convoy = Convoy::from_goal("docs", "Write docs", Vec::new());
+        ConvoyRepository::create(repo.as_ref(), &convoy)
+            .await
+            .unwrap();
+        let prompt = "x".repeat(2_000);
+        for i in 0..3 {
+            let mut bead = Bead::new(format!("Step {}", i), &prompt, TaskType::Documentation)
+                .with_estimate(1_000);
+            bead.convoy_id = Some(convoy.id.clone());
+            bead.created_at += chrono::Duration::milliseconds(i);
+            BeadRepository::create(repo.as_ref(), &bead).await.unwrap();
+        }
+        let outsider = Bead::new("Outsider", "other", TaskType::Documentation);
+        BeadRepository::create(repo.as_ref(), &outsider)
+            .await
+            .unwrap();
+
+        let budget = Budget {
+            max_tokens: Some(2_500),
+            ..Budget::default()
+        };
+        let foreman = Foreman::new(
+            repo.clone(),
+            Arc::new(UpperExecutor),
+            crate::config::ForemanConfig::default(),
+        )
+        .with_convoy(convoy.id.clone())
+        .with_budget(BudgetTracker::new(budget, PricingTable::default()));
+        let beads = run_convoy(&foreman, &Config::default(), repo.as_ref(), &convoy.id)
+            .await
+            .unwrap();
+
+        let out = render_outcome(&beads, foreman.budget_spent());
+        assert!(out.contains("2 completed, 0 failed, 1 deferred, 0 not run"));
+        assert!(out.contains("Step 2: Deferred: "));
+        let outsider = BeadRepository::get(repo.as_ref(), &outsider.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(outsider.status, BeadStatus::Pending);
+        let stored = ConvoyRepository::get(repo.as_ref(), &convoy.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, crate::core::ConvoyStatus::InProgress);
+    }
+
+    #[test]
+    fn test_parse_deadline() {
+        let ts = parse_deadline("2026-03-01T12:00:00Z").unwrap();
+        assert_eq!(ts.to_rfc3339(), "2026-03-01T12:00:00+00:00");
+
+        let relative = parse_deadline("2h").unwrap();
+        assert!(relative > Utc::now() + chrono::Duration::minutes(119));
+        assert!(parse_deadline("tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_provider_order_parsing_and_filtering() {
+        #[derive(clap::Parser)]
+        struct Args {
+            #[command(subcommand)]
+            cmd: GoalCommands,
+        }
+        let parse = |order: &str| {
+            <Args as clap::Parser>::try_parse_from([
+                "rigs",
+                "execute",
+                "g",
+                "--provider-order",
+                order,
+            ])
+        };
+        match parse("claude,gemini,codex").unwrap().cmd {
+            GoalCommands::Execute { provider_order, .. } => assert_eq!(
+                provider_order,
+                Some(vec![Provider::Claude, Provider::Gemini, Provider::Codex])
+            ),
+            _ => unreachable!(),
+        }
+        assert!(parse("claude,gpt").is_err());
+
+        let mut config = Config::default();
+        config.providers.codex.enabled = false;
+        let mut claude = Tank::new(Provider::Claude, 1_000, 5);
+        claude.remaining = 0;
+        let order = [Provider::Claude, Provider::Gemini, Provider::Codex];
+        assert_eq!(
+            usable_order(&order, &config, &[claude]).unwrap(),
+            vec![Provider::Gemini]
+        );
+        assert!(usable_order(&[Provider::Codex], &config, &[]).is_err());
+    }
+
+    #[test]
+    fn test_render_heuristic_plan() {
+        let plan = crate::core::planner::heuristic_plan("Add OAuth login");
+        let out = render_goal_plan(&plan, &PricingTable::default());
+        assert!(out.starts_with("Decomposed by: heuristic (no LLM available)\n"));
+        assert!(out.contains("  2. [design]         Design: Add OAuth login\n"));
+        assert!(out.contains("     Depends on: #1\n"));
+        assert!(out.contains(&format!("Total estimated tokens: {}", plan.total_tokens())));
+    }
+
+    #[test]
+    fn test_preflight_ready() {
+        let config = Config::default();
+        assert!(preflight(&config, true).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_preflight_assayer_only_warns_on_execute() {
+        let config = assayer_only_config();
+        assert!(preflight(&config, false).unwrap().is_none());
+
+        let warning = preflight(&config, true).unwrap().unwrap();
+        assert!(warning.contains("rigs provider add"));
+    }
+
+    #[test]
+    fn test_preflight_no_providers_errors() {
+        let mut config = assayer_only_config();
+        config.providers.deepseek.enabled = false;
+        config.providers.ollama.enabled = false;
+
+        let err = preflight(&config, true).unwrap_err();
+        assert!(matches!(err, RigsError::NoExecutionProviders));
+        assert!(matches!(
+            preflight(&config, false),
+            Err(RigsError::NoExecutionProviders)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_declined_confirmation_creates_no_convoy() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        for answer in ["n\n", "\n", "", "nope\n"] {
+            let mut input = Cursor::new(answer);
+            let convoy = confirm_and_create(&repo, "Add OAuth", None, false, true, &mut input)
+                .await
+                .unwrap();
+            assert!(convoy.is_none(), "answer {:?} should decline", answer);
+        }
+        assert!(repo.list_all().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_accepted_confirmation_creates_convoy() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut names = HashSet::new();
+        for answer in ["y\n", "YES\n"] {
+            let mut input = Cursor::new(answer);
+            let convoy = confirm_and_create(&repo, "Add OAuth", None, false, true, &mut input)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(convoy.name.starts_with(&slugify_goal("Add OAuth")));
+            assert_eq!(convoy.goal.as_deref(), Some("Add OAuth"));
+            names.insert(convoy.name);
+        }
+        let stored = repo.list_all().await.unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(names.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_non_interactive_requires_yes() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut input = Cursor::new("y\n");
+        let err = confirm_and_create(&repo, "Add OAuth", None, false, false, &mut input)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RigsError::ConfirmationRequired(_)));
+
+        let convoy = confirm_and_create(
+            &repo,
+            "Add OAuth",
+            Some("oauth".into()),
+            true,
+            false,
+            &mut input,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(convoy.name, "oauth");
+    }
+}
@@ -1,34 +1,47 @@
 //! Workspace initialization
 
-use std::path::PathBuf;
+use crate::config::Config;
 use crate::core::Result;
+use crate::db::init_pool;
+use std::path::{Path, PathBuf};
+
+pub async fn run(path: PathBuf, git: bool, minimal: bool, db: Option<PathBuf>) -> Result<()> {
+    if minimal {
+        let db_path = match db {
+            Some(db) => db,
+            None => minimal_db_path(&path),
+        };
+        init_minimal(&db_path).await?;
+        println!("✓ Database ready at {}", db_path.display());
+        println!("  (minimal: no config, logs or git; pass the same --db or --workspace to later commands)");
+        return Ok(());
+    }
 
-pub async fn run(path: PathBuf, git: bool) -> Result<()> {
     let path_str = path.display();
-    
+
     println!("Initializing Rigs workspace at {}...", path_str);
-    
+
     // TODO: Create directories
     println!("  Creating directories...");
     println!("    ✓ {}/", path_str);
     println!("    ✓ {}/db/", path_str);
     println!("    ✓ {}/logs/", path_str);
-    
+
     // TODO: Create config
     println!("  Creating configuration...");
     println!("    ✓ {}/config.toml", path_str);
-    
+
     // TODO: Initialize database
     println!("  Initializing database...");
     println!("    ✓ {}/db/rigs.db", path_str);
     println!("    ✓ Running migrations...");
-    
+
     if git {
         println!("  Initializing git repository...");
         println!("    ✓ git init");
         println!("    ✓ .gitignore created");
     }
-    
+
     println!();
     println!("✓ Workspace initialized!");
     println!();
@@ -37,6 +50,50 @@ pub async fn run(path: PathBuf, git: bool) -> Result<()> {
     println!("  2. Check tank status:   rigs tank list");
     println!("  3. Create your first bead or goal:");
     println!("     rigs goal plan \"Add user authentication\"");
-    
+
     Ok(())
 }
+
+/// `<workspace>/db/rigs.db`, with `~/` expanded
+fn minimal_db_path(workspace: &Path) -> PathBuf {
+    Config::default()
+        .expand_path(&workspace.to_string_lossy())
+        .join("db")
+        .join("rigs.db")
+}
+
+/// Create and migrate just the database at `db_path` (`init --minimal`)
+///
+/// Nothing else is written: no config file, log directory or git
+/// repository, which keeps CI and throwaway workspaces fast.
+pub async fn init_minimal(db_path: &Path) -> Result<()> {
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    init_pool(db_path).await?.close().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Bead, TaskType};
+    use crate::db::repository::{BeadRepository, SqliteRepository};
+
+    #[tokio::test]
+    async fn test_minimal_init_creates_only_a_working_db() {
+        let dir = tempfile::tempdir().unwrap();
+        run(dir.path().to_path_buf(), false, true, None)
+            .await
+            .unwrap();
+
+        let db_path = dir.path().join("db").join("rigs.db");
+        assert!(db_path.exists());
+        assert!(!dir.path().join("config.toml").exists());
+        assert!(!dir.path().join("logs").exists());
+
+        let repo = SqliteRepository::new(init_pool(&db_path).await.unwrap());
+        let bead = Bead::new("CI", "smoke test", TaskType::Test);
+        assert_eq!(repo.create(&bead).await.unwrap(), 1);
+    }
+}
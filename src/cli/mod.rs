@@ -1,10 +1,54 @@
 //! CLI commands for Rigs
 
 pub mod bead;
+pub mod bench;
+pub mod config;
 pub mod convoy;
 pub mod foreman;
 pub mod goal;
 pub mod init;
 pub mod provider;
+pub mod prune;
+pub mod stats;
 pub mod status;
 pub mod tank;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::BufRead;
+
+use crate::core::Result;
+
+/// How commands print their results (`--format`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Tables and summaries for people
+    #[default]
+    Text,
+    /// Pretty-printed JSON for scripts
+    Json,
+}
+
+impl OutputFormat {
+    /// Render `value` as JSON, or with `text` in text mode
+    pub fn render<T: Serialize + ?Sized>(
+        self,
+        value: &T,
+        text: impl FnOnce(&T) -> String,
+    ) -> Result<String> {
+        match self {
+            OutputFormat::Text => Ok(text(value)),
+            OutputFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(value)?)),
+        }
+    }
+}
+
+/// Read a yes/no answer; anything but "y" or "yes" (including EOF) is no
+pub(crate) fn read_confirmation(input: &mut dyn BufRead) -> Result<bool> {
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    Ok(matches!(
+        line.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
@@ -1,7 +1,11 @@
 //! Provider management commands
 
 use clap::Subcommand;
-use crate::core::{Provider, Result};
+
+use crate::config::Config;
+use crate::core::assayer::OllamaClient;
+use crate::core::executor::CliExecutor;
+use crate::core::{ExecutionRequest, Executor, Provider, Result};
 
 #[derive(Subcommand)]
 pub enum ProviderCommands {
@@ -39,7 +43,7 @@ pub enum ProviderCommands {
     },
 }
 
-pub async fn run(cmd: ProviderCommands) -> Result<()> {
+pub async fn run(cmd: ProviderCommands, config: &Config) -> Result<()> {
     match cmd {
         ProviderCommands::Add { provider } => {
             println!("Adding provider: {}", provider);
@@ -61,10 +65,58 @@ pub async fn run(cmd: ProviderCommands) -> Result<()> {
             Ok(())
         }
         ProviderCommands::Test { provider } => {
-            println!("Testing provider: {}", provider);
-            // TODO: Send test request
-            println!("✓ {} is responding", provider);
-            Ok(())
+            let model = config.resolve_model(provider);
+            println!("Testing provider: {} (model: {})", provider, model);
+            for warning in config.model_warnings(provider) {
+                println!("  ⚠ {}", warning);
+            }
+            if let Err(e) = config.provider_config(provider).resolve_api_key() {
+                println!("✗ {}", e);
+                return Err(e);
+            }
+
+            match provider {
+                Provider::Ollama => {
+                    let client = OllamaClient::from_config(&config.providers.ollama)?;
+                    return match client.health().await {
+                        Ok(()) => {
+                            println!("✓ {} is responding", provider);
+                            Ok(())
+                        }
+                        Err(e) => {
+                            println!("✗ {} is not responding", provider);
+                            Err(e)
+                        }
+                    };
+                }
+                Provider::DeepSeek => {
+                    // There is no DeepSeek API client to send a request with
+                    println!(
+                        "- {} untested: only its configuration was checked",
+                        provider
+                    );
+                    return Ok(());
+                }
+                Provider::Claude | Provider::Codex | Provider::Gemini => {}
+            }
+
+            // A tiny prompt is enough for the CLI to reject an unknown model
+            let request = ExecutionRequest {
+                provider,
+                model: model.to_string(),
+                prompt: "Reply with OK.".to_string(),
+                working_dir: None,
+            };
+            match CliExecutor::default().execute(&request).await {
+                Ok(_) => {
+                    println!("✓ {} is responding and accepted model {}", provider, model);
+                    Ok(())
+                }
+                Err(e) => {
+                    println!("✗ {} did not accept model {}", provider, model);
+                    Err(e)
+                }
+            }
         }
         ProviderCommands::Enable { provider } => {
             println!("Enabled provider: {}", provider);
@@ -0,0 +1,71 @@
+//! Retention cleanup (`rigs prune`)
+
+use chrono::{Duration, Utc};
+
+use crate::config::Config;
+use crate::core::Result;
+use crate::db::open_workspace;
+use crate::db::repository::SqliteRepository;
+
+pub async fn run(older_than: Duration, dry_run: bool, config: &Config) -> Result<()> {
+    let repo = SqliteRepository::new(open_workspace(config).await?);
+
+    let cutoff = Utc::now() - older_than;
+    let report = repo.prune(cutoff, dry_run).await?;
+
+    let verb = if dry_run { "Would delete" } else { "Deleted" };
+    println!(
+        "{} data finished before {}:",
+        verb,
+        config.display_timezone()?.format_datetime(&cutoff)
+    );
+    println!("  Beads:             {}", report.beads);
+    println!("  Execution history: {}", report.completions);
+    if report.protected > 0 {
+        println!(
+            "  Kept {} bead(s) belonging to convoys that are still running",
+            report.protected
+        );
+    }
+    Ok(())
+}
+
+/// Parse an age or period such as "30d", "12h", "90m" or "2w"
+pub fn parse_age(s: &str) -> std::result::Result<Duration, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in '{}' (use s, m, h, d or w)", s))?;
+    let (amount, unit) = s.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| format!("invalid age '{}'", s))?;
+
+    match unit {
+        "s" => Ok(Duration::seconds(amount)),
+        "m" => Ok(Duration::minutes(amount)),
+        "h" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        "w" => Ok(Duration::weeks(amount)),
+        _ => Err(format!(
+            "unknown unit '{}' in '{}' (use s, m, h, d or w)",
+            unit, s
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d").unwrap(), Duration::days(30));
+        assert_eq!(parse_age("12h").unwrap(), Duration::hours(12));
+        assert_eq!(parse_age("2w").unwrap(), Duration::weeks(2));
+        assert_eq!(parse_age("24h").unwrap(), Duration::days(1));
+        assert_eq!(parse_age("7d").unwrap(), Duration::weeks(1));
+        assert_eq!(parse_age("90m").unwrap(), Duration::minutes(90));
+        assert!(parse_age("30").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("3y").is_err());
+    }
+}
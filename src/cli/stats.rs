@@ -0,0 +1,74 @@
+//! Workspace metrics (`rigs stats`)
+
+use std::collections::BTreeMap;
+
+use super::OutputFormat;
+use crate::config::Config;
+use crate::core::Result;
+use crate::db::open_workspace;
+use crate::db::repository::{SqliteRepository, WorkspaceStats};
+
+pub async fn run(format: OutputFormat, config: &Config) -> Result<()> {
+    let repo = SqliteRepository::new(open_workspace(config).await?);
+    let stats = repo.stats().await?;
+    print!("{}", format.render(&stats, render_stats)?);
+    Ok(())
+}
+
+fn render_stats(stats: &WorkspaceStats) -> String {
+    let mut out = String::new();
+    section(&mut out, "Beads by status", &stats.beads_by_status, |n| {
+        n.to_string()
+    });
+    section(&mut out, "Beads by task type", &stats.beads_by_type, |n| {
+        n.to_string()
+    });
+    section(
+        &mut out,
+        "Convoys by status",
+        &stats.convoys_by_status,
+        |n| n.to_string(),
+    );
+    section(
+        &mut out,
+        "Average tokens per task type",
+        &stats.avg_tokens_by_type,
+        |avg| format!("{:.0}", avg),
+    );
+    section(
+        &mut out,
+        "Provider usage share",
+        &stats.provider_share,
+        |share| percent(Some(*share)),
+    );
+
+    out.push_str(&format!(
+        "Completion rate:     {}\n",
+        percent(stats.completion_rate)
+    ));
+    out.push_str(&format!(
+        "Estimation accuracy: {}\n",
+        percent(stats.estimation_accuracy)
+    ));
+    out
+}
+
+fn section<T>(
+    out: &mut String,
+    title: &str,
+    rows: &BTreeMap<String, T>,
+    fmt: impl Fn(&T) -> String,
+) {
+    out.push_str(&format!("{}:\n", title));
+    if rows.is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for (key, value) in rows {
+        out.push_str(&format!("  {:<16} {}\n", key, fmt(value)));
+    }
+    out.push('\n');
+}
+
+fn percent(ratio: Option<f64>) -> String {
+    ratio.map_or_else(|| "n/a".to_string(), |r| format!("{:.1}%", r * 100.0))
+}
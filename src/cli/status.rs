@@ -42,6 +42,6 @@ pub async fn run() -> Result<()> {
     println!("║  Est. Cost:       ~$2.45                                       ║");
     println!("║                                                                 ║");
     println!("╚═══════════════════════════════════════════════════════════════╝");
-    
+
     Ok(())
 }
@@ -1,17 +1,32 @@
 //! Tank (rate limit) management commands
 
+use chrono::{Duration, DurationRound, Utc};
 use clap::Subcommand;
-use crate::core::{Provider, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::io::{self, BufRead, IsTerminal, Write};
+use tracing::info;
+
+use super::{read_confirmation, OutputFormat};
+use crate::config::{Config, DisplayTimezone};
+use crate::core::{Provider, Result, RigsError, Tank, UsageEvent};
+use crate::db::open_workspace;
+use crate::db::repository::{SqliteRepository, TankRepository, UsageRepository};
 
 #[derive(Subcommand)]
 pub enum TankCommands {
     /// List all tanks with status
-    List,
+    List {
+        /// Only show these providers (repeatable, default: all)
+        #[arg(long = "provider")]
+        providers: Vec<Provider>,
+    },
 
-    /// Show detailed status for a provider
+    /// Show detailed status for providers
     Status {
-        /// Provider to show
-        provider: Provider,
+        /// Only show these providers (repeatable, default: all)
+        #[arg(long = "provider")]
+        providers: Vec<Provider>,
     },
 
     /// Force refresh all tank data
@@ -25,40 +40,54 @@ pub enum TankCommands {
         tokens: u64,
     },
 
+    /// Add purchased capacity to the current window (raises capacity and remaining)
+    Topup {
+        /// Provider to top up
+        provider: Provider,
+        /// Tokens granted
+        tokens: u64,
+    },
+
+    /// Restart a provider's window with full capacity (recovery for drifted accounting)
+    Reset {
+        /// Provider to reset
+        provider: Provider,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        confirm: bool,
+    },
+
     /// Show usage history
     History {
-        /// Provider (optional, shows all if omitted)
-        provider: Option<Provider>,
-        /// Time period
-        #[arg(long, default_value = "24h")]
-        period: String,
+        /// Only show these providers (repeatable, default: all)
+        #[arg(long = "provider")]
+        providers: Vec<Provider>,
+        /// Time period (e.g. 90m, 24h, 7d)
+        #[arg(long, default_value = "24h", value_parser = super::prune::parse_age)]
+        period: Duration,
     },
 }
 
-pub async fn run(cmd: TankCommands) -> Result<()> {
+pub async fn run(cmd: TankCommands, config: &Config, format: OutputFormat) -> Result<()> {
     match cmd {
-        TankCommands::List => {
-            println!("Tank Status:");
-            println!();
-            println!("  Provider   Health   Remaining     Reset In");
-            println!("  ─────────────────────────────────────────────");
-            println!("  Claude     🟢       [████████░░]  78%    2h 34m");
-            println!("  Codex      🟡       [██████░░░░]  45%    1h 12m");
-            println!("  Gemini     🟢       [█████████░]  92%    18h 45m");
-            println!("  DeepSeek   🟢       [██████████] 100%    (API)");
-            println!("  Ollama     🟢       [██████████] ∞       (local)");
+        TankCommands::List { providers } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let tanks = load_tanks(config, &repo, &select_providers(&providers)).await?;
+            print!("{}", format.render(tanks.as_slice(), render_list)?);
             Ok(())
         }
-        TankCommands::Status { provider } => {
-            println!("Tank: {}", provider);
-            println!("  Capacity:     100,000 tokens");
-            println!("  Remaining:    78,000 tokens (78%)");
-            println!("  Health:       🟢 Green");
-            println!("  Window Start: 2026-01-18 12:00 UTC");
-            println!("  Window End:   2026-01-18 17:00 UTC");
-            println!("  Reset In:     2h 34m");
-            println!("  Requests:     45");
-            println!("  Tokens Used:  22,000");
+        TankCommands::Status { providers } => {
+            let tz = config.display_timezone()?;
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let tanks = load_tanks(config, &repo, &select_providers(&providers)).await?;
+            let render = |tanks: &[Tank]| {
+                tanks
+                    .iter()
+                    .map(|tank| render_status(tank, &tz))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            print!("{}", format.render(tanks.as_slice(), render)?);
             Ok(())
         }
         TankCommands::Refresh => {
@@ -73,11 +102,561 @@ pub async fn run(cmd: TankCommands) -> Result<()> {
             println!("Setting {} remaining tokens to {}", provider, tokens);
             Ok(())
         }
-        TankCommands::History { provider, period } => {
-            let prov = provider.map(|p| p.to_string()).unwrap_or("all".to_string());
-            println!("Usage history for {} (last {})", prov, period);
-            // TODO: Show graph
+        TankCommands::Topup { provider, tokens } => {
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let Some(tank) = top_up_saved_tank(config, &repo, provider, tokens).await? else {
+                println!("{} has no token limit; nothing to top up", provider);
+                return Ok(());
+            };
+            println!(
+                "✓ {} tank topped up by {}: {} / {} tokens {}",
+                provider,
+                tokens,
+                tank.remaining,
+                tank.capacity,
+                tank.health.emoji()
+            );
+            Ok(())
+        }
+        TankCommands::Reset { provider, confirm } => {
+            let stdin = io::stdin();
+            if !confirm_reset(provider, confirm, stdin.is_terminal(), &mut stdin.lock())? {
+                println!("Aborted.");
+                return Ok(());
+            }
+
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let tank = reset_saved_tank(config, &repo, provider).await?;
+            println!(
+                "✓ {} tank reset: {} tokens until {}",
+                provider,
+                tank.remaining,
+                config.display_timezone()?.format_datetime(&tank.window_end)
+            );
+            Ok(())
+        }
+        TankCommands::History { providers, period } => {
+            let tz = config.display_timezone()?;
+            let repo = SqliteRepository::new(open_workspace(config).await?);
+            let since = Utc::now() - period;
+            let mut events = Vec::new();
+            if providers.is_empty() {
+                events = repo.list_usage(None, since).await?;
+            } else {
+                for provider in select_providers(&providers) {
+                    events.extend(repo.list_usage(Some(provider), since).await?);
+                }
+                events.sort_by_key(|e| e.at);
+            }
+            let render = |events: &[UsageEvent]| render_history(events, since, &tz);
+            print!("{}", format.render(events.as_slice(), render)?);
             Ok(())
         }
     }
 }
+
+/// Ask before resetting a tank (unless `--confirm`)
+///
+/// A non-interactive stdin without `--confirm` is refused rather than
+/// silently resetting.
+fn confirm_reset(
+    provider: Provider,
+    confirm: bool,
+    interactive: bool,
+    input: &mut dyn BufRead,
+) -> Result<bool> {
+    if confirm {
+        return Ok(true);
+    }
+    if !interactive {
+        return Err(RigsError::ConfirmationRequired("--confirm"));
+    }
+    print!(
+        "Reset the {} tank to full capacity and start a new window? [y/N] ",
+        provider
+    );
+    io::stdout().flush()?;
+    read_confirmation(input)
+}
+
+/// Top up `provider`'s saved tank (or its default one) and save the result
+///
+/// Returns `None` for a tank without a token limit, which is left alone.
+async fn top_up_saved_tank(
+    config: &Config,
+    repo: &dyn TankRepository,
+    provider: Provider,
+    tokens: u64,
+) -> Result<Option<Tank>> {
+    let mut tank = load_tanks(config, repo, &[provider]).await?.remove(0);
+    if tank.is_unlimited() {
+        return Ok(None);
+    }
+    let before = tank.capacity;
+    tank.top_up(tokens);
+    repo.upsert(&tank).await?;
+    info!(
+        provider = %provider,
+        tokens,
+        capacity_before = before,
+        capacity = tank.capacity,
+        "Tank topped up"
+    );
+    Ok(Some(tank))
+}
+
+/// Reset `provider`'s saved tank (or its default one) and save the result
+async fn reset_saved_tank(
+    config: &Config,
+    repo: &dyn TankRepository,
+    provider: Provider,
+) -> Result<Tank> {
+    let mut tank = load_tanks(config, repo, &[provider]).await?.remove(0);
+    reset_tank(
+        &mut tank,
+        config.provider_config(provider).limits.window_hours,
+    );
+    repo.upsert(&tank).await?;
+    Ok(tank)
+}
+
+/// Start a fresh window with full capacity, logging the manual override
+fn reset_tank(tank: &mut Tank, window_hours: u32) {
+    let before = tank.remaining;
+    // A manual reset forgets rolling consumption too, not just what aged out
+    tank.consumption.clear();
+    tank.reset_window(window_hours);
+    info!(
+        provider = %tank.provider,
+        remaining_before = before,
+        window_hours,
+        "Tank window manually reset"
+    );
+}
+
+/// Resolve a `--provider` selection; an empty selection means all providers
+fn select_providers(selected: &[Provider]) -> Vec<Provider> {
+    Provider::all()
+        .filter(|p| selected.is_empty() || selected.contains(p))
+        .collect()
+}
+
+/// Load the current tanks for the given providers
+///
+/// Saved tanks keep their usage but take their limits and thresholds from
+/// the config, and start a new window if theirs has ended; providers
+/// without a saved row get a [`default_tank`].
+pub(crate) async fn load_tanks(
+    config: &Config,
+    repo: &dyn TankRepository,
+    providers: &[Provider],
+) -> Result<Vec<Tank>> {
+    let mut saved: HashMap<Provider, Tank> = repo
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|t| (t.provider, t))
+        .collect();
+    Ok(providers
+        .iter()
+        .map(|&p| match saved.remove(&p) {
+            Some(tank) => {
+                let settings = config.provider_config(p);
+                let mut tank = tank
+                    .with_limits(&settings.limits)
+                    .with_thresholds(settings.threshold_yellow, settings.threshold_red);
+                if tank.needs_refresh() {
+                    tank.reset_window(settings.limits.window_hours);
+                }
+                tank
+            }
+            None => default_tank(config, p),
+        })
+        .collect())
+}
+
+/// A full tank for `provider`, with its configured limits and thresholds
+pub(crate) fn default_tank(config: &Config, provider: Provider) -> Tank {
+    let settings = config.provider_config(provider);
+    let limits = settings.limits;
+    Tank::new(provider, limits.tokens_per_window, limits.window_hours)
+        .with_limits(&limits)
+        .with_thresholds(settings.threshold_yellow, settings.threshold_red)
+}
+
+/// Render the `tank list` table
+fn render_list(tanks: &[Tank]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Tank Status:");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "  Provider   Health   Remaining          Reset In");
+    let _ = writeln!(out, "  ──────────────────────────────────────────────────");
+    for tank in tanks {
+        let (bar, reset) = if tank.is_unlimited() {
+            ("[██████████]    ∞".to_string(), "(local)".to_string())
+        } else {
+            (
+                tank.progress_bar(10),
+                format_duration(tank.time_until_reset()),
+            )
+        };
+        let _ = writeln!(
+            out,
+            "  {:<10} {}       {}  {}",
+            tank.provider.display_name(),
+            tank.health.emoji(),
+            bar,
+            reset
+        );
+    }
+    out
+}
+
+/// Render `tank history`: tokens per provider in each hour since `since`
+fn render_history(
+    events: &[UsageEvent],
+    since: chrono::DateTime<Utc>,
+    tz: &DisplayTimezone,
+) -> String {
+    let mut hours: BTreeMap<chrono::DateTime<Utc>, HashMap<Provider, u64>> = BTreeMap::new();
+    for event in events {
+        let hour = event
+            .at
+            .duration_trunc(Duration::hours(1))
+            .unwrap_or(event.at);
+        *hours
+            .entry(hour)
+            .or_default()
+            .entry(event.provider)
+            .or_default() += event.tokens;
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Usage since {}:", tz.format_datetime(&since));
+    let _ = writeln!(out);
+    if hours.is_empty() {
+        let _ = writeln!(out, "  No usage recorded.");
+        return out;
+    }
+    let _ = writeln!(out, "  Hour                   Provider   Tokens");
+    let _ = writeln!(out, "  ──────────────────────────────────────────");
+    for (hour, tokens) in &hours {
+        for provider in Provider::all().filter(|p| tokens.contains_key(p)) {
+            let _ = writeln!(
+                out,
+                "  {:<22} {:<10} {}",
+                tz.format_datetime(hour),
+                provider.to_string(),
+                tokens[&provider]
+            );
+        }
+    }
+    let total: u64 = events.iter().map(|e| e.tokens).sum();
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "  Total: {} tokens in {} requests",
+        total,
+        events.len()
+    );
+    out
+}
+
+/// Render the detailed `tank status` block for one provider
+fn render_status(tank: &Tank, tz: &DisplayTimezone) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Tank: {}", tank.provider);
+    if tank.is_unlimited() {
+        let _ = writeln!(out, "  Capacity:     unlimited (local)");
+    } else {
+        let _ = writeln!(out, "  Capacity:     {} tokens", tank.capacity);
+        let _ = writeln!(
+            out,
+            "  Remaining:    {} tokens ({:.0}%)",
+            tank.remaining,
+            tank.capacity_ratio() * 100.0
+        );
+    }
+    let _ = writeln!(
+        out,
+        "  Health:       {} {:?}",
+        tank.health.emoji(),
+        tank.health
+    );
+    let _ = writeln!(
+        out,
+        "  Window Start: {}",
+        tz.format_datetime(&tank.window_start)
+    );
+    let _ = writeln!(
+        out,
+        "  Window End:   {}",
+        tz.format_datetime(&tank.window_end)
+    );
+    let _ = writeln!(
+        out,
+        "  Reset In:     {}",
+        format_duration(tank.time_until_reset())
+    );
+    let now = chrono::Utc::now();
+    if let Some(empty) = tank.projected_exhaustion_at(now) {
+        if empty < tank.window_end {
+            let _ = writeln!(
+                out,
+                "  Projected empty in: {}",
+                format_duration(empty - now)
+            );
+        }
+    }
+    let _ = writeln!(out, "  Requests:     {}", tank.requests_this_window);
+    let _ = writeln!(out, "  Tokens Used:  {}", tank.tokens_this_window);
+    out
+}
+
+/// Format a duration as a compact countdown (e.g. "2h 34m")
+pub(crate) fn format_duration(d: Duration) -> String {
+    let minutes = d.num_minutes().max(0);
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_memory_pool;
+
+    #[test]
+    fn test_select_providers() {
+        assert_eq!(select_providers(&[]).len(), Provider::all().count());
+        assert_eq!(
+            select_providers(&[Provider::Codex, Provider::Claude]),
+            vec![Provider::Claude, Provider::Codex]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_respects_provider_filter() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let tanks = load_tanks(
+            &Config::default(),
+            &repo,
+            &select_providers(&[Provider::Claude, Provider::Codex]),
+        )
+        .await
+        .unwrap();
+        let out = render_list(&tanks);
+
+        assert!(out.contains("Claude"));
+        assert!(out.contains("Codex"));
+        assert!(!out.contains("Gemini"));
+        assert!(!out.contains("DeepSeek"));
+        assert!(!out.contains("Ollama"));
+    }
+
+    #[tokio::test]
+    async fn test_list_as_json() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let tanks = load_tanks(
+            &Config::default(),
+            &repo,
+            &select_providers(&[Provider::Claude, Provider::Gemini]),
+        )
+        .await
+        .unwrap();
+        let out = OutputFormat::Json
+            .render(tanks.as_slice(), render_list)
+            .unwrap();
+        let parsed: Vec<Tank> = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].provider, Provider::Gemini);
+        assert_eq!(parsed[0].remaining, tanks[0].remaining);
+
+        let text = OutputFormat::Text
+            .render(tanks.as_slice(), render_list)
+            .unwrap();
+        assert!(text.starts_with("Tank Status:"));
+    }
+
+    #[test]
+    fn test_status_uses_display_timezone() {
+        let mut tank = Tank::new(Provider::Claude, 1_000, 5);
+        tank.window_start = "2026-01-18T10:00:00Z".parse().unwrap();
+        tank.window_end = "2026-01-18T15:00:00Z".parse().unwrap();
+
+        let utc = render_status(&tank, &DisplayTimezone::default());
+        assert!(utc.contains("Window Start: 2026-01-18 10:00 UTC"));
+
+        let tz = DisplayTimezone::parse("America/New_York").unwrap();
+        let out = render_status(&tank, &tz);
+        assert!(out.contains("Window Start: 2026-01-18 05:00 EST"));
+        assert!(out.contains("Window End:   2026-01-18 10:00 EST"));
+    }
+
+    #[test]
+    fn test_status_projects_exhaustion_before_reset() {
+        let now = chrono::Utc::now();
+        let mut tank = Tank::new(Provider::Claude, 1_000, 5);
+        tank.window_start = now - Duration::hours(1);
+        tank.consume(500).unwrap();
+
+        let out = render_status(&tank, &DisplayTimezone::default());
+        // About an hour, give or take the clock moving during the test
+        assert!(
+            out.contains("Projected empty in: 59m") || out.contains("Projected empty in: 1h 0m")
+        );
+
+        // Running dry after the window resets isn't worth mentioning
+        tank.window_end = now + Duration::minutes(30);
+        let out = render_status(&tank, &DisplayTimezone::default());
+        assert!(!out.contains("Projected empty"));
+    }
+
+    #[test]
+    fn test_history_buckets_tokens_by_hour() {
+        let start: chrono::DateTime<Utc> = "2026-01-14T08:00:00Z".parse().unwrap();
+        let event = |provider, minutes, tokens| UsageEvent {
+            provider,
+            tokens,
+            at: start + Duration::minutes(minutes),
+            remaining: 0,
+        };
+        let events = [
+            event(Provider::Claude, 5, 1_000),
+            event(Provider::Gemini, 20, 300),
+            event(Provider::Claude, 55, 2_000),
+            event(Provider::Claude, 61, 700),
+        ];
+
+        let out = render_history(&events, start, &DisplayTimezone::default());
+        assert!(out.contains("2026-01-14 08:00 UTC   Claude     3000"));
+        assert!(out.contains("2026-01-14 08:00 UTC   Gemini     300"));
+        assert!(out.contains("2026-01-14 09:00 UTC   Claude     700"));
+        assert!(out.contains("Total: 4000 tokens in 4 requests"));
+
+        let json = OutputFormat::Json
+            .render(&events[..], |_| String::new())
+            .unwrap();
+        let parsed: Vec<UsageEvent> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.len(), 4);
+        assert_eq!(parsed[3], events[3]);
+    }
+
+    #[tokio::test]
+    async fn test_load_tanks_prefers_saved_state() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut saved = Tank::new(Provider::Claude, 100_000, 5);
+        saved.consume(60_000).unwrap();
+        repo.upsert(&saved).await.unwrap();
+
+        let mut config = Config::default();
+        config.providers.claude.threshold_yellow = 0.3;
+        let tanks = load_tanks(&config, &repo, &[Provider::Claude, Provider::Codex])
+            .await
+            .unwrap();
+
+        assert_eq!(tanks[0].remaining, 40_000);
+        // 40% left is yellow by default, but green against the configured 0.3
+        assert_eq!(tanks[0].health, crate::core::TankHealth::Green);
+        assert_eq!(tanks[1].provider, Provider::Codex);
+        assert_eq!(tanks[1].remaining, tanks[1].capacity);
+    }
+
+    #[tokio::test]
+    async fn test_load_tanks_rolls_over_an_expired_window() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut saved = Tank::new(Provider::Claude, 100_000, 5);
+        saved.consume(100_000).unwrap();
+        saved.window_start = chrono::Utc::now() - chrono::Duration::hours(10);
+        saved.window_end = chrono::Utc::now() - chrono::Duration::hours(5);
+        repo.upsert(&saved).await.unwrap();
+
+        let tanks = load_tanks(&Config::default(), &repo, &[Provider::Claude])
+            .await
+            .unwrap();
+        assert_eq!(tanks[0].remaining, tanks[0].capacity);
+        assert!(tanks[0].window_end > chrono::Utc::now());
+    }
+
+    #[test]
+    fn test_reset_restores_capacity_and_window() {
+        let mut tank = Tank::new(Provider::Codex, 1_000, 5);
+        tank.consume(900).unwrap();
+        tank.window_start = "2026-01-18T10:00:00Z".parse().unwrap();
+        tank.window_end = "2026-01-18T15:00:00Z".parse().unwrap();
+
+        let before = chrono::Utc::now();
+        reset_tank(&mut tank, 5);
+
+        assert_eq!(tank.remaining, tank.capacity);
+        assert_eq!(tank.tokens_this_window, 0);
+        assert_eq!(tank.requests_this_window, 0);
+        assert!(tank.window_start >= before);
+        assert_eq!(tank.window_end - tank.window_start, Duration::hours(5));
+    }
+
+    #[tokio::test]
+    async fn test_top_up_saves_the_larger_tank() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut tank = Tank::new(Provider::Claude, 1_000, 5);
+        tank.consume(900).unwrap();
+        repo.upsert(&tank).await.unwrap();
+
+        let config = Config::default();
+        top_up_saved_tank(&config, &repo, Provider::Claude, 500)
+            .await
+            .unwrap()
+            .unwrap();
+        let saved = TankRepository::get(&repo, Provider::Claude)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((saved.remaining, saved.capacity), (600, 1_500));
+
+        let ollama = top_up_saved_tank(&config, &repo, Provider::Ollama, 500)
+            .await
+            .unwrap();
+        assert!(ollama.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reset_saves_the_reset_tank() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut tank = Tank::new(Provider::Codex, 1_000, 5);
+        tank.consume(900).unwrap();
+        repo.upsert(&tank).await.unwrap();
+
+        let config = Config::default();
+        let reset = reset_saved_tank(&config, &repo, Provider::Codex)
+            .await
+            .unwrap();
+        let saved = TankRepository::get(&repo, Provider::Codex)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.remaining, saved.capacity);
+        assert_eq!(saved.tokens_this_window, 0);
+        assert_eq!(saved.window_end.timestamp(), reset.window_end.timestamp());
+    }
+
+    #[test]
+    fn test_reset_requires_confirmation() {
+        let mut yes = io::Cursor::new("y\n");
+        assert!(confirm_reset(Provider::Claude, false, true, &mut yes).unwrap());
+
+        let mut no = io::Cursor::new("\n");
+        assert!(!confirm_reset(Provider::Claude, false, true, &mut no).unwrap());
+
+        let err = confirm_reset(Provider::Claude, false, false, &mut io::empty()).unwrap_err();
+        assert!(err.to_string().contains("--confirm"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::minutes(154)), "2h 34m");
+        assert_eq!(format_duration(Duration::minutes(12)), "12m");
+        assert_eq!(format_duration(Duration::minutes(-5)), "0m");
+    }
+}
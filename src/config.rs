@@ -1,13 +1,21 @@
 //! Configuration loading and management
 
+use chrono::{DateTime, Local, Utc};
+use chrono_tz::Tz;
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use crate::core::{Provider, Result, RigsError};
+use crate::core::bead::DEFAULT_MAX_RETRIES;
+use crate::core::postprocess::PostProcessor;
+use crate::core::prompt::{PromptTemplate, DEFAULT_BEAD_TEMPLATE};
+use crate::core::{
+    Provider, ProviderConfig, ReconcilePolicy, Result, RigsError, TaskType, WeeklyReset,
+};
 
 /// Main configuration structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub general: GeneralConfig,
@@ -21,6 +29,13 @@ pub struct Config {
     pub foreman: ForemanConfig,
     #[serde(default)]
     pub database: DatabaseConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub prompts: PromptsConfig,
+    /// Per-provider price overrides in USD per million tokens
+    #[serde(default)]
+    pub pricing: HashMap<Provider, f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +44,12 @@ pub struct GeneralConfig {
     pub workspace: String,
     #[serde(default = "default_log_level")]
     pub log_level: String,
+    /// Zone for displaying absolute times: an IANA name or "local" (storage stays UTC)
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Never call remote APIs: execution and assaying go to Ollama only
+    #[serde(default)]
+    pub local_only: bool,
 }
 
 fn default_workspace() -> String {
@@ -39,11 +60,17 @@ fn default_log_level() -> String {
     "info".to_string()
 }
 
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
 impl Default for GeneralConfig {
     fn default() -> Self {
         Self {
             workspace: default_workspace(),
             log_level: default_log_level(),
+            timezone: default_timezone(),
+            local_only: false,
         }
     }
 }
@@ -74,8 +101,22 @@ pub struct ProviderEntry {
     pub threshold_red: f32,
     #[serde(default)]
     pub fallback_model: Option<String>,
+    /// Run on `fallback_model` while the provider's tank is yellow or red
+    #[serde(default)]
+    pub fallback_under_pressure: bool,
     #[serde(default)]
     pub api_key_env: Option<String>,
+    /// Models to validate against instead of the built-in list (empty disables the check)
+    #[serde(default)]
+    pub known_models: Option<Vec<String>>,
+    /// When the provider resets its weekly cap, e.g. "mon 00:00" (UTC)
+    #[serde(default)]
+    pub weekly_reset: Option<WeeklyReset>,
+    /// How provider-reported capacity is reconciled with local accounting
+    #[serde(default)]
+    pub reconcile: ReconcilePolicy,
+    #[serde(flatten)]
+    pub tls: TlsOptions,
 }
 
 fn default_true() -> bool {
@@ -98,27 +139,59 @@ impl Default for ProviderEntry {
             threshold_yellow: default_threshold_yellow(),
             threshold_red: default_threshold_red(),
             fallback_model: None,
+            fallback_under_pressure: false,
             api_key_env: None,
+            known_models: None,
+            weekly_reset: None,
+            reconcile: ReconcilePolicy::default(),
+            tls: TlsOptions::default(),
         }
     }
 }
 
+/// TLS settings for a provider's HTTP endpoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TlsOptions {
+    /// PEM file of an extra root CA to trust (e.g. a corporate proxy's)
+    #[serde(default)]
+    pub ca_cert: Option<PathBuf>,
+    /// Skip certificate verification entirely. Insecure: only for endpoints
+    /// whose certificate can't be validated any other way
+    #[serde(default)]
+    pub danger_accept_invalid_certs: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OllamaEntry {
     #[serde(default = "default_true")]
     pub enabled: bool,
     #[serde(default = "default_ollama_url")]
     pub base_url: String,
+    /// Additional nodes to load-balance across; when set, replaces `base_url`
+    #[serde(default)]
+    pub base_urls: Vec<String>,
     #[serde(default = "default_ollama_model")]
     pub model: String,
     #[serde(default)]
     pub fallback_model: Option<String>,
+    /// Models to validate against (Ollama has no built-in list)
+    #[serde(default)]
+    pub known_models: Option<Vec<String>>,
+    /// How long a single request may take, in seconds
+    #[serde(default = "default_ollama_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(flatten)]
+    pub tls: TlsOptions,
 }
 
 fn default_ollama_url() -> String {
     "http://localhost:11434".to_string()
 }
 
+fn default_ollama_timeout_secs() -> u64 {
+    crate::core::assayer::OLLAMA_TIMEOUT.as_secs()
+}
+
 fn default_ollama_model() -> String {
     "deepseek-r1:7b".to_string()
 }
@@ -128,8 +201,23 @@ impl Default for OllamaEntry {
         Self {
             enabled: true,
             base_url: default_ollama_url(),
+            base_urls: Vec::new(),
             model: default_ollama_model(),
             fallback_model: Some("llama3.2:3b".to_string()),
+            known_models: None,
+            timeout_secs: default_ollama_timeout_secs(),
+            tls: TlsOptions::default(),
+        }
+    }
+}
+
+impl OllamaEntry {
+    /// Base URLs of every configured Ollama node
+    pub fn nodes(&self) -> Vec<String> {
+        if self.base_urls.is_empty() {
+            vec![self.base_url.clone()]
+        } else {
+            self.base_urls.clone()
         }
     }
 }
@@ -148,6 +236,13 @@ pub struct AssayerConfig {
     pub quality_model: String,
     #[serde(default = "default_true")]
     pub fallback_to_api: bool,
+    /// Beads optimized or estimated at once, independent of execution concurrency
+    #[serde(default = "default_assayer_max_concurrent")]
+    pub max_concurrent: u32,
+}
+
+fn default_assayer_max_concurrent() -> u32 {
+    1
 }
 
 fn default_planner_model() -> String {
@@ -175,6 +270,7 @@ impl Default for AssayerConfig {
             estimator_model: default_estimator_model(),
             quality_model: default_quality_model(),
             fallback_to_api: true,
+            max_concurrent: default_assayer_max_concurrent(),
         }
     }
 }
@@ -185,17 +281,74 @@ pub struct RoutingConfig {
     pub strategy: String,
     #[serde(default)]
     pub affinity: HashMap<String, HashMap<String, f32>>,
+    /// Providers to prefer, in order, when affinity weights tie
+    #[serde(default)]
+    pub tie_breaker: Vec<Provider>,
 }
 
 fn default_strategy() -> String {
     "balanced".to_string()
 }
 
+impl RoutingConfig {
+    /// Parse `routing.affinity` into per-task-type provider rankings
+    ///
+    /// Keys are lowercase task type and provider names; each ranking is
+    /// sorted by weight, highest first, with equal weights in
+    /// [`tie_break_order`](Self::tie_break_order). Unknown names are an error.
+    pub fn affinities(&self) -> Result<HashMap<TaskType, Vec<(Provider, f32)>>> {
+        let order = self.tie_break_order();
+        let tie_rank = |p: Provider| order.iter().position(|&o| o == p);
+        self.affinity
+            .iter()
+            .map(|(task_key, weights)| {
+                let task_type = TaskType::value_variants()
+                    .iter()
+                    .copied()
+                    .find(|t| t.to_string() == *task_key)
+                    .ok_or_else(|| {
+                        RigsError::ConfigError(format!(
+                            "routing.affinity: unknown task type '{}'",
+                            task_key
+                        ))
+                    })?;
+                let mut ranking = weights
+                    .iter()
+                    .map(|(name, &weight)| {
+                        let provider = name.parse::<Provider>().map_err(|e| {
+                            RigsError::ConfigError(format!("routing.affinity.{}: {}", task_key, e))
+                        })?;
+                        Ok((provider, weight))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                ranking.sort_by(|a, b| {
+                    b.1.total_cmp(&a.1)
+                        .then_with(|| tie_rank(a.0).cmp(&tie_rank(b.0)))
+                });
+                Ok((task_type, ranking))
+            })
+            .collect()
+    }
+
+    /// Every provider, `tie_breaker` ones first and the rest in
+    /// [`Provider::all`] order (Claude, Codex, Gemini, ...)
+    pub fn tie_break_order(&self) -> Vec<Provider> {
+        let mut order = self.tie_breaker.clone();
+        for provider in Provider::all() {
+            if !order.contains(&provider) {
+                order.push(provider);
+            }
+        }
+        order
+    }
+}
+
 impl Default for RoutingConfig {
     fn default() -> Self {
         Self {
             strategy: default_strategy(),
             affinity: HashMap::new(),
+            tie_breaker: Vec::new(),
         }
     }
 }
@@ -204,10 +357,76 @@ impl Default for RoutingConfig {
 pub struct ForemanConfig {
     #[serde(default = "default_poll_interval")]
     pub poll_interval: u64,
+    /// Randomly lengthen or shorten each poll by up to this percentage, so
+    /// daemons sharing a host don't wake in step (0 = no jitter)
+    #[serde(default)]
+    pub poll_jitter_pct: u8,
     #[serde(default = "default_max_concurrent")]
     pub max_concurrent: u32,
     #[serde(default)]
     pub auto_start: bool,
+    /// Ask for more when a provider stops at its output limit
+    #[serde(default)]
+    pub auto_continue: bool,
+    /// Follow-up requests allowed per bead when `auto_continue` is on
+    #[serde(default = "default_max_continuations")]
+    pub max_continuations: u32,
+    /// In-flight limit per task type; types not listed share only the global cap
+    #[serde(default)]
+    pub type_concurrency: HashMap<TaskType, u32>,
+    /// Fail a convoy once more than this fraction of its beads failed (unset = never)
+    #[serde(default)]
+    pub convoy_failure_ratio: Option<f64>,
+    /// Consecutive failures on one provider before a bead moves to the next (0 = never)
+    #[serde(default = "default_switch_provider_after")]
+    pub switch_provider_after: u32,
+    /// Filters applied to output before it is stored, per task type
+    #[serde(default)]
+    pub postprocess: HashMap<TaskType, Vec<PostProcessor>>,
+    /// Resends of a request after a transient failure (network error, 5xx);
+    /// these don't use up the bead's retries
+    #[serde(default = "default_request_retries")]
+    pub request_retries: u32,
+    /// Delay before the first resend in milliseconds, doubling each time
+    #[serde(default = "default_request_backoff_ms")]
+    pub request_backoff_ms: u64,
+    /// Resends of a request after a recoverable failure (rate limit,
+    /// exhausted provider, Ollama down); also not counted as bead retries
+    #[serde(default = "default_recover_retries")]
+    pub recover_retries: u32,
+    /// Longest wait between those resends, in seconds
+    #[serde(default = "default_recover_backoff_cap_secs")]
+    pub recover_backoff_cap_secs: u64,
+    /// Retry budget (`max_retries`) for new beads, used up by failed attempts
+    #[serde(default = "default_bead_retries")]
+    pub bead_retries: u32,
+    /// Seconds in-flight beads get to finish at shutdown before they are requeued
+    #[serde(default = "default_shutdown_grace_secs")]
+    pub shutdown_grace_secs: u64,
+    /// Reject (instead of warn about) beads depending on beads outside their
+    /// convoy and its prerequisite convoys
+    #[serde(default)]
+    pub strict_dependencies: bool,
+    /// Add the output of a bead's dependencies to its prompt
+    #[serde(default)]
+    pub inject_dependency_output: bool,
+    /// Heading above each injected output; `{title}` and `{id}` name the dependency
+    #[serde(default = "default_dependency_output_header")]
+    pub dependency_output_header: String,
+    /// Most tokens of each dependency's output to inject (~4 characters each)
+    #[serde(default = "default_dependency_output_tokens")]
+    pub dependency_output_tokens: u64,
+}
+
+impl ForemanConfig {
+    /// Continuations to allow per bead (0 when `auto_continue` is off)
+    pub fn continuation_limit(&self) -> u32 {
+        if self.auto_continue {
+            self.max_continuations
+        } else {
+            0
+        }
+    }
 }
 
 fn default_poll_interval() -> u64 {
@@ -218,22 +437,95 @@ fn default_max_concurrent() -> u32 {
     1
 }
 
+fn default_max_continuations() -> u32 {
+    3
+}
+
+fn default_switch_provider_after() -> u32 {
+    2
+}
+
+fn default_request_retries() -> u32 {
+    2
+}
+
+fn default_request_backoff_ms() -> u64 {
+    500
+}
+
+fn default_recover_retries() -> u32 {
+    2
+}
+
+fn default_recover_backoff_cap_secs() -> u64 {
+    60
+}
+
+fn default_bead_retries() -> u32 {
+    DEFAULT_MAX_RETRIES
+}
+
+fn default_shutdown_grace_secs() -> u64 {
+    30
+}
+
+fn default_dependency_output_header() -> String {
+    "## Output of {title} ({id})".into()
+}
+
+fn default_dependency_output_tokens() -> u64 {
+    2_000
+}
+
 impl Default for ForemanConfig {
     fn default() -> Self {
         Self {
             poll_interval: default_poll_interval(),
+            poll_jitter_pct: 0,
             max_concurrent: default_max_concurrent(),
             auto_start: false,
+            auto_continue: false,
+            max_continuations: default_max_continuations(),
+            type_concurrency: HashMap::new(),
+            convoy_failure_ratio: None,
+            switch_provider_after: default_switch_provider_after(),
+            postprocess: HashMap::new(),
+            request_retries: default_request_retries(),
+            request_backoff_ms: default_request_backoff_ms(),
+            recover_retries: default_recover_retries(),
+            recover_backoff_cap_secs: default_recover_backoff_cap_secs(),
+            bead_retries: default_bead_retries(),
+            shutdown_grace_secs: default_shutdown_grace_secs(),
+            strict_dependencies: false,
+            inject_dependency_output: false,
+            dependency_output_header: default_dependency_output_header(),
+            dependency_output_tokens: default_dependency_output_tokens(),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
+    #[serde(default)]
+    pub backend: DatabaseBackend,
     #[serde(default = "default_db_path")]
     pub path: String,
     #[serde(default = "default_true")]
     pub wal_mode: bool,
+    /// Connection URL for server backends, e.g. "postgres://rigs@localhost/rigs"
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// Where the repositories are stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    /// A local file at `database.path`
+    #[default]
+    Sqlite,
+    /// A server at `database.url` (needs the `postgres` feature)
+    Postgres,
 }
 
 fn default_db_path() -> String {
@@ -243,26 +535,111 @@ fn default_db_path() -> String {
 impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
+            backend: DatabaseBackend::default(),
             path: default_db_path(),
             wal_mode: true,
+            url: None,
         }
     }
 }
 
-impl Default for Config {
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportConfig {
+    /// Environment variable holding the HMAC key used to sign and verify bundles
+    #[serde(default)]
+    pub signing_key_env: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptsConfig {
+    /// Template rendering each bead's prompt (see [`PromptTemplate`])
+    #[serde(default = "default_bead_template")]
+    pub bead_template: String,
+}
+
+fn default_bead_template() -> String {
+    DEFAULT_BEAD_TEMPLATE.to_string()
+}
+
+impl Default for PromptsConfig {
     fn default() -> Self {
         Self {
-            general: GeneralConfig::default(),
-            providers: ProvidersConfig::default(),
-            assayer: AssayerConfig::default(),
-            routing: RoutingConfig::default(),
-            foreman: ForemanConfig::default(),
-            database: DatabaseConfig::default(),
+            bead_template: default_bead_template(),
         }
     }
 }
 
+impl PromptsConfig {
+    pub fn bead_template(&self) -> PromptTemplate {
+        PromptTemplate::new(self.bead_template.clone())
+    }
+}
+
+/// Comments written into the default config, keyed by dotted path
+///
+/// A path naming a table comments the table header; any other path
+/// comments that key.
+const DEFAULT_CONFIG_COMMENTS: &[(&str, &str)] = &[
+    ("general", "General settings"),
+    ("general.workspace", "Directory holding the database, logs and tank state"),
+    ("general.timezone", "Zone for displaying times: an IANA name (e.g. \"Europe/Copenhagen\") or \"local\""),
+    ("general.local_only", "Forbid remote API calls; everything runs on Ollama (also --local-only)"),
+    ("providers.claude", "Execution providers. Per provider you can also set:\n  fallback_model, fallback_under_pressure = true, api_key_env,\n  known_models, weekly_reset = \"mon 00:00\",\n  ca_cert = \"/path/to/ca.pem\""),
+    ("providers.claude.model", "Empty uses the provider's default model"),
+    ("providers.claude.threshold_yellow", "Tank health turns yellow/red below these fractions of capacity"),
+    ("providers.claude.reconcile", "Reported vs. tracked capacity: min, trust_remote or trust_local"),
+    ("providers.claude.danger_accept_invalid_certs", "Disables TLS certificate verification. Insecure; prefer ca_cert"),
+    ("providers.ollama", "Local models used by the assayers"),
+    ("providers.ollama.base_urls", "Several nodes to load-balance across; replaces base_url when set"),
+    ("providers.ollama.timeout_secs", "Give up on a node after this many seconds (a slow model may need more)"),
+    ("assayer", "Pre-execution analysis (planning, prompt optimization, estimation)"),
+    ("assayer.max_concurrent", "Beads assayed at once, separate from foreman.max_concurrent, so a local GPU isn't overwhelmed"),
+    ("routing", "Provider selection"),
+    ("routing.strategy", "balanced, cheapest or fastest"),
+    ("routing.affinity", "Task type affinity overrides, e.g. review = { codex = 0.9, claude = 0.7 }"),
+    ("routing.tie_breaker", "Providers to prefer when affinity weights tie, e.g. [\"codex\", \"claude\"] (default: claude, codex, gemini)"),
+    ("foreman", "Scheduler"),
+    ("foreman.poll_interval", "Longest idle sleep between queue checks, in seconds"),
+    ("foreman.poll_jitter_pct", "Vary each poll by up to this percentage so daemons on one host don't wake together"),
+    ("foreman.auto_continue", "Ask for more when a provider stops at its output limit"),
+    ("foreman.switch_provider_after", "Consecutive failures on one provider before a bead moves on (0 = never)"),
+    ("foreman.type_concurrency", "In-flight limit per task type, e.g. implementation = 2"),
+    ("foreman.request_retries", "Resends after a network error or 5xx, with backoff; not counted as bead retries"),
+    ("foreman.recover_retries", "Resends after a rate limit or unavailable Ollama, waiting up to\n  recover_backoff_cap_secs; not counted as bead retries"),
+    ("foreman.bead_retries", "Retries each new bead gets for failed attempts"),
+    ("foreman.shutdown_grace_secs", "On shutdown, how long in-flight beads may finish before they are requeued"),
+    ("foreman.strict_dependencies", "Reject beads that depend on beads outside their convoy or its prerequisite convoys (default: warn)"),
+    ("foreman.inject_dependency_output", "Prepend the output of each finished dependency to a bead's prompt"),
+    ("foreman.dependency_output_header", "Heading above each injected output; {title} and {id} name the dependency"),
+    ("foreman.dependency_output_tokens", "Injected output is truncated to this many tokens per dependency"),
+    ("foreman.postprocess", "Output filters per task type, e.g. implementation = [\"strip_think_tags\", \"trim\"]"),
+    ("database", "Storage"),
+    ("database.backend", "sqlite, or postgres with `url` set (requires building with --features postgres)"),
+    ("prompts", "Prompt sent for each bead. Placeholders: {title}, {task_type},\n  {description} (the optimized prompt if any) and {criteria}"),
+    ("export", "Convoy bundles. signing_key_env names the variable holding the HMAC key"),
+    ("pricing", "Price overrides in USD per million tokens, e.g. deepseek = 1.10"),
+];
+
 impl Config {
+    /// The default configuration as commented TOML, as written by
+    /// `rigs config init`
+    pub fn default_toml() -> Result<String> {
+        let plain = toml::to_string(&Config::default())
+            .map_err(|e| RigsError::ConfigError(format!("serializing defaults: {}", e)))?;
+        let mut doc: toml_edit::DocumentMut = plain
+            .parse()
+            .map_err(|e| RigsError::ConfigError(format!("re-reading defaults: {}", e)))?;
+
+        tidy_floats(doc.as_table_mut());
+        for (path, comment) in DEFAULT_CONFIG_COMMENTS {
+            annotate(doc.as_table_mut(), path, comment);
+        }
+
+        let mut out = String::from("# Rigs configuration (generated by `rigs config init`)\n");
+        out.push_str(&doc.to_string());
+        Ok(out)
+    }
+
     /// Load configuration from file, with fallback to defaults
     pub fn load(path: Option<&Path>) -> Result<Self> {
         let config_path = match path {
@@ -272,8 +649,15 @@ impl Config {
 
         if config_path.exists() {
             let content = std::fs::read_to_string(&config_path)?;
-            let config: Config = toml::from_str(&content)
-                .map_err(|e| RigsError::InvalidConfig(format!("{}: {}", config_path.display(), e)))?;
+            let config: Config = toml::from_str(&content).map_err(|e| {
+                RigsError::InvalidConfig(format!("{}: {}", config_path.display(), e))
+            })?;
+            config
+                .display_timezone()
+                .and(config.routing.affinities())
+                .map_err(|e| {
+                    RigsError::InvalidConfig(format!("{}: {}", config_path.display(), e))
+                })?;
             Ok(config)
         } else {
             // Return defaults if no config file
@@ -290,9 +674,9 @@ impl Config {
 
     /// Expand ~ in paths to actual home directory
     pub fn expand_path(&self, path: &str) -> PathBuf {
-        if path.starts_with("~/") {
+        if let Some(rest) = path.strip_prefix("~/") {
             if let Some(home) = directories::BaseDirs::new() {
-                return home.home_dir().join(&path[2..]);
+                return home.home_dir().join(rest);
             }
         }
         PathBuf::from(path)
@@ -303,13 +687,31 @@ impl Config {
         self.expand_path(&self.general.workspace)
     }
 
+    /// Use `dir` as the workspace (`--workspace`), keeping the database in it
+    ///
+    /// Replaces `general.workspace` and points `database.path` at
+    /// `<dir>/db/rigs.db`; a `--db` given alongside is applied after this.
+    pub fn set_workspace(&mut self, dir: &Path) {
+        self.general.workspace = dir.to_string_lossy().into_owned();
+        self.database.path = dir
+            .join("db")
+            .join("rigs.db")
+            .to_string_lossy()
+            .into_owned();
+    }
+
     /// Get the database path
     pub fn database_path(&self) -> PathBuf {
         self.expand_path(&self.database.path)
     }
 
     /// Check if a provider is enabled
+    ///
+    /// With `general.local_only` every remote provider counts as disabled.
     pub fn is_provider_enabled(&self, provider: Provider) -> bool {
+        if self.general.local_only && provider != Provider::Ollama {
+            return false;
+        }
         match provider {
             Provider::Claude => self.providers.claude.enabled,
             Provider::Codex => self.providers.codex.enabled,
@@ -319,6 +721,273 @@ impl Config {
         }
     }
 
+    /// Check whether a provider's credentials are available
+    ///
+    /// Providers without an `api_key_env` authenticate through their CLI and
+    /// are assumed to be logged in.
+    pub fn is_provider_authenticated(&self, provider: Provider) -> bool {
+        let api_key_env = match provider {
+            Provider::Claude => &self.providers.claude.api_key_env,
+            Provider::Codex => &self.providers.codex.api_key_env,
+            Provider::Gemini => &self.providers.gemini.api_key_env,
+            Provider::DeepSeek => &self.providers.deepseek.api_key_env,
+            Provider::Ollama => return true,
+        };
+        match api_key_env {
+            Some(var) => std::env::var(var).map(|v| !v.is_empty()).unwrap_or(false),
+            None => true,
+        }
+    }
+
+    /// Check that there is something to execute beads with
+    ///
+    /// Returns the usable execution providers, or [`ProviderReadiness::AssayerOnly`]
+    /// when only assayer providers are available (enough for planning).
+    /// Fails with [`RigsError::NoExecutionProviders`] when nothing is usable.
+    /// With `general.local_only`, Ollama is the only execution provider.
+    pub fn check_providers(&self) -> Result<ProviderReadiness> {
+        let usable =
+            |p: &Provider| self.is_provider_enabled(*p) && self.is_provider_authenticated(*p);
+
+        if self.general.local_only {
+            return if usable(&Provider::Ollama) {
+                Ok(ProviderReadiness::Ready(vec![Provider::Ollama]))
+            } else {
+                Err(RigsError::NoLocalProvider)
+            };
+        }
+
+        let execution: Vec<Provider> = Provider::execution().filter(usable).collect();
+        if !execution.is_empty() {
+            return Ok(ProviderReadiness::Ready(execution));
+        }
+        if Provider::assayer().any(|p| usable(&p)) {
+            return Ok(ProviderReadiness::AssayerOnly);
+        }
+        Err(RigsError::NoExecutionProviders)
+    }
+
+    /// Resolve the bundle signing key, if one is configured
+    ///
+    /// Errors if `export.signing_key_env` names a variable that is unset or
+    /// empty, rather than silently producing unsigned bundles.
+    pub fn bundle_signing_key(&self) -> Result<Option<Vec<u8>>> {
+        match &self.export.signing_key_env {
+            Some(var) => match std::env::var(var) {
+                Ok(key) if !key.is_empty() => Ok(Some(key.into_bytes())),
+                _ => Err(RigsError::ConfigError(format!(
+                    "export.signing_key_env is set to {} but that variable is unset or empty",
+                    var
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Check the configuration for likely mistakes
+    ///
+    /// Returns human-readable warnings rather than failing, since provider
+    /// model lists change faster than this crate does.
+    pub fn warnings(&self) -> Vec<String> {
+        Provider::all()
+            .filter(|&p| self.is_provider_enabled(p))
+            .flat_map(|p| self.model_warnings(p))
+            .collect()
+    }
+
+    /// Check settings that parse but can't work, for `rigs config validate`
+    ///
+    /// Health thresholds must satisfy `0 <= threshold_red <= threshold_yellow
+    /// <= 1`, an enabled provider's `api_key_env` must be set in the
+    /// environment, `foreman.poll_interval` must be positive, and at least
+    /// one execution provider (Ollama with `general.local_only`) must be
+    /// enabled.
+    pub fn validate(&self) -> std::result::Result<(), Vec<ConfigProblem>> {
+        let mut problems = Vec::new();
+        let entries = [
+            (Provider::Claude, &self.providers.claude),
+            (Provider::Codex, &self.providers.codex),
+            (Provider::Gemini, &self.providers.gemini),
+            (Provider::DeepSeek, &self.providers.deepseek),
+        ];
+        for (provider, entry) in entries {
+            let key = format!("providers.{}", provider.as_str());
+            let (yellow, red) = (entry.threshold_yellow, entry.threshold_red);
+            if !(0.0..=1.0).contains(&yellow) {
+                problems.push(ConfigProblem::new(
+                    format!("{}.threshold_yellow", key),
+                    format!("{} is outside 0 to 1", yellow),
+                ));
+            }
+            if !(0.0..=1.0).contains(&red) {
+                problems.push(ConfigProblem::new(
+                    format!("{}.threshold_red", key),
+                    format!("{} is outside 0 to 1", red),
+                ));
+            } else if red > yellow {
+                problems.push(ConfigProblem::new(
+                    format!("{}.threshold_red", key),
+                    format!("{} is above threshold_yellow ({})", red, yellow),
+                ));
+            }
+            if let Some(var) = &entry.api_key_env {
+                if self.is_provider_enabled(provider) && !self.is_provider_authenticated(provider) {
+                    problems.push(ConfigProblem::new(
+                        format!("{}.api_key_env", key),
+                        format!("{} is not set (or empty) in the environment", var),
+                    ));
+                }
+            }
+        }
+
+        if self.foreman.poll_interval == 0 {
+            problems.push(ConfigProblem::new(
+                "foreman.poll_interval",
+                "must be greater than 0",
+            ));
+        }
+
+        if self.general.local_only {
+            if !self.is_provider_enabled(Provider::Ollama) {
+                problems.push(ConfigProblem::new(
+                    "providers.ollama.enabled",
+                    "general.local_only is set but Ollama is disabled",
+                ));
+            }
+        } else if !Provider::execution().any(|p| self.is_provider_enabled(p)) {
+            problems.push(ConfigProblem::new(
+                "providers",
+                "no execution provider (claude, codex or gemini) is enabled",
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Warn about configured or fallback models a provider isn't known to accept
+    pub fn model_warnings(&self, provider: Provider) -> Vec<String> {
+        let (fallback, known) = match provider {
+            Provider::Claude => (
+                &self.providers.claude.fallback_model,
+                &self.providers.claude.known_models,
+            ),
+            Provider::Codex => (
+                &self.providers.codex.fallback_model,
+                &self.providers.codex.known_models,
+            ),
+            Provider::Gemini => (
+                &self.providers.gemini.fallback_model,
+                &self.providers.gemini.known_models,
+            ),
+            Provider::DeepSeek => (
+                &self.providers.deepseek.fallback_model,
+                &self.providers.deepseek.known_models,
+            ),
+            Provider::Ollama => (
+                &self.providers.ollama.fallback_model,
+                &self.providers.ollama.known_models,
+            ),
+        };
+        let known: Vec<&str> = match known {
+            Some(models) => models.iter().map(String::as_str).collect(),
+            None => provider.known_models().to_vec(),
+        };
+        if known.is_empty() {
+            return Vec::new();
+        }
+
+        [
+            ("model", Some(self.resolve_model(provider))),
+            ("fallback_model", fallback.as_deref()),
+        ]
+        .into_iter()
+        .filter_map(|(key, model)| Some((key, model?)))
+        .filter(|(_, model)| !known.contains(model))
+        .map(|(key, model)| {
+            format!(
+                "providers.{}.{} = \"{}\" is not a known {} model (known: {})",
+                provider.to_string().to_lowercase(),
+                key,
+                model,
+                provider,
+                known.join(", ")
+            )
+        })
+        .collect()
+    }
+
+    /// Configured model for a provider, falling back to its default
+    pub fn resolve_model(&self, provider: Provider) -> &str {
+        match self.get_model(provider) {
+            "" => provider.default_model(),
+            model => model,
+        }
+    }
+
+    /// A provider's built-in config with the `[providers]` settings applied
+    ///
+    /// Ollama only takes its models from here; its tank never runs low.
+    pub fn provider_config(&self, provider: Provider) -> ProviderConfig {
+        let mut config = ProviderConfig::default_for(provider);
+        config.enabled = self.is_provider_enabled(provider);
+        config.model = self.resolve_model(provider).to_string();
+        let entry = match provider {
+            Provider::Claude => &self.providers.claude,
+            Provider::Codex => &self.providers.codex,
+            Provider::Gemini => &self.providers.gemini,
+            Provider::DeepSeek => &self.providers.deepseek,
+            Provider::Ollama => {
+                if let Some(fallback) = &self.providers.ollama.fallback_model {
+                    config.fallback_model = Some(fallback.clone());
+                }
+                return config;
+            }
+        };
+        if let Some(fallback) = &entry.fallback_model {
+            config.fallback_model = Some(fallback.clone());
+        }
+        config.fallback_under_pressure = entry.fallback_under_pressure;
+        config.threshold_yellow = entry.threshold_yellow;
+        config.threshold_red = entry.threshold_red;
+        // Unset means CLI auth, as in `is_provider_authenticated`
+        config.api_key_env = entry.api_key_env.clone();
+        config.limits.weekly_reset = self.weekly_reset(provider);
+        config
+    }
+
+    /// Resolve `general.timezone` for displaying timestamps
+    pub fn display_timezone(&self) -> Result<DisplayTimezone> {
+        DisplayTimezone::parse(&self.general.timezone)
+    }
+
+    /// Weekly cap reset point for a provider (configured, else the built-in default)
+    pub fn weekly_reset(&self, provider: Provider) -> WeeklyReset {
+        let configured = match provider {
+            Provider::Claude => self.providers.claude.weekly_reset,
+            Provider::Codex => self.providers.codex.weekly_reset,
+            Provider::Gemini => self.providers.gemini.weekly_reset,
+            Provider::DeepSeek => self.providers.deepseek.weekly_reset,
+            Provider::Ollama => None,
+        };
+        configured.unwrap_or_else(|| ProviderConfig::default_for(provider).limits.weekly_reset)
+    }
+
+    /// Reconciliation policy for a provider's reported capacity
+    pub fn reconcile_policy(&self, provider: Provider) -> ReconcilePolicy {
+        match provider {
+            Provider::Claude => self.providers.claude.reconcile,
+            Provider::Codex => self.providers.codex.reconcile,
+            Provider::Gemini => self.providers.gemini.reconcile,
+            Provider::DeepSeek => self.providers.deepseek.reconcile,
+            // Local and unlimited; nothing to reconcile
+            Provider::Ollama => ReconcilePolicy::TrustLocal,
+        }
+    }
+
     /// Get model for a provider
     pub fn get_model(&self, provider: Provider) -> &str {
         match provider {
@@ -331,6 +1000,119 @@ impl Config {
     }
 }
 
+/// Time zone used to display absolute timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayTimezone {
+    /// The system's local zone
+    Local,
+    /// A named IANA zone (including "UTC")
+    Zone(Tz),
+}
+
+impl Default for DisplayTimezone {
+    fn default() -> Self {
+        DisplayTimezone::Zone(Tz::UTC)
+    }
+}
+
+impl DisplayTimezone {
+    /// Parse an IANA zone name or "local"
+    pub fn parse(name: &str) -> Result<Self> {
+        if name.eq_ignore_ascii_case("local") {
+            return Ok(DisplayTimezone::Local);
+        }
+        name.parse::<Tz>().map(DisplayTimezone::Zone).map_err(|_| {
+            RigsError::ConfigError(format!(
+                "general.timezone: unknown time zone '{}' (use an IANA name like \"Europe/Copenhagen\" or \"local\")",
+                name
+            ))
+        })
+    }
+
+    /// Format a UTC timestamp in this zone using a chrono format string
+    pub fn format(&self, ts: &DateTime<Utc>, fmt: &str) -> String {
+        match self {
+            DisplayTimezone::Local => ts.with_timezone(&Local).format(fmt).to_string(),
+            DisplayTimezone::Zone(tz) => ts.with_timezone(tz).format(fmt).to_string(),
+        }
+    }
+
+    /// Format as date, minutes and zone abbreviation (e.g. "2026-01-18 11:00 CET")
+    pub fn format_datetime(&self, ts: &DateTime<Utc>) -> String {
+        self.format(ts, "%Y-%m-%d %H:%M %Z")
+    }
+}
+
+/// A setting [`Config::validate`] rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// Dotted path of the offending key, e.g. `providers.claude.threshold_red`
+    pub key: String,
+    pub message: String,
+}
+
+impl ConfigProblem {
+    fn new(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// Which kinds of providers a configuration can actually use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderReadiness {
+    /// At least one execution provider is enabled and authenticated
+    Ready(Vec<Provider>),
+    /// Only assayer providers (e.g. Ollama) are usable: planning works,
+    /// but nothing can execute beads
+    AssayerOnly,
+}
+
+/// Write `comment` above the table or key at `path`
+fn annotate(table: &mut toml_edit::Table, path: &str, comment: &str) {
+    let prefix: String = comment.lines().map(|l| format!("# {}\n", l)).collect();
+    let (parent, key) = match path.rsplit_once('.') {
+        Some((parent, key)) => (parent.split('.').collect::<Vec<_>>(), key),
+        None => (Vec::new(), path),
+    };
+    let mut table = table;
+    for segment in parent {
+        match table.get_mut(segment).and_then(|item| item.as_table_mut()) {
+            Some(child) => table = child,
+            None => return,
+        }
+    }
+
+    if let Some(child) = table.get_mut(key).and_then(|item| item.as_table_mut()) {
+        child.decor_mut().set_prefix(format!("\n{}", prefix));
+    } else if let Some(mut key) = table.key_mut(key) {
+        key.leaf_decor_mut().set_prefix(prefix);
+    }
+}
+
+/// Print floats at `f32` precision
+///
+/// Every float in the defaults is an `f32`, which serializes with noise
+/// digits (0.2 becomes 0.20000000298023224).
+fn tidy_floats(table: &mut toml_edit::Table) {
+    for (_, item) in table.iter_mut() {
+        if let Some(child) = item.as_table_mut() {
+            tidy_floats(child);
+        } else if let Some(value) = item.as_float() {
+            let tidy: f64 = (value as f32).to_string().parse().unwrap_or(value);
+            *item = toml_edit::value(tidy);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -344,6 +1126,17 @@ mod tests {
         assert_eq!(config.routing.strategy, "balanced");
     }
 
+    #[test]
+    fn test_set_workspace_moves_the_database_with_it() {
+        let mut config = Config::default();
+        config.set_workspace(Path::new("/tmp/ci-rigs"));
+        assert_eq!(config.workspace_dir(), PathBuf::from("/tmp/ci-rigs"));
+        assert_eq!(
+            config.database_path(),
+            PathBuf::from("/tmp/ci-rigs/db/rigs.db")
+        );
+    }
+
     #[test]
     fn test_parse_config() {
         let toml = r#"
@@ -354,15 +1147,388 @@ mod tests {
             [providers.claude]
             enabled = false
             model = "claude-opus-4"
+
+            [providers.codex]
+            weekly_reset = "thu 09:00"
+
+            [pricing]
+            deepseek = 0.5
         "#;
 
         let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.pricing.get(&Provider::DeepSeek), Some(&0.5));
+        assert_eq!(
+            config.weekly_reset(Provider::Codex).to_string(),
+            "thu 09:00"
+        );
+        assert_eq!(
+            config.weekly_reset(Provider::Claude),
+            WeeklyReset::default()
+        );
         assert_eq!(config.general.workspace, "/custom/path");
         assert_eq!(config.general.log_level, "debug");
         assert!(!config.providers.claude.enabled);
         assert_eq!(config.providers.claude.model, "claude-opus-4");
     }
 
+    #[test]
+    fn test_check_providers_default() {
+        let config = Config::default();
+        match config.check_providers().unwrap() {
+            ProviderReadiness::Ready(providers) => assert!(providers.contains(&Provider::Claude)),
+            other => panic!("expected Ready, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_providers_assayer_only() {
+        let toml = r#"
+            [providers.claude]
+            enabled = false
+            [providers.codex]
+            enabled = false
+            [providers.gemini]
+            enabled = false
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.check_providers().unwrap(),
+            ProviderReadiness::AssayerOnly
+        );
+    }
+
+    #[test]
+    fn test_check_providers_none_usable() {
+        let toml = r#"
+            [providers.claude]
+            enabled = false
+            [providers.codex]
+            enabled = false
+            [providers.gemini]
+            api_key_env = "RIGS_TEST_UNSET_GEMINI_KEY"
+            [providers.deepseek]
+            enabled = false
+            [providers.ollama]
+            enabled = false
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        let err = config.check_providers().unwrap_err();
+        assert!(matches!(err, RigsError::NoExecutionProviders));
+        assert!(err.to_string().contains("rigs provider add"));
+    }
+
+    #[test]
+    fn test_warnings_default_config_is_clean() {
+        assert!(Config::default().warnings().is_empty());
+    }
+
+    #[test]
+    fn test_warns_on_unknown_model() {
+        let toml = r#"
+            [providers.claude]
+            model = "claude-sonet-4"
+
+            [providers.gemini]
+            fallback_model = "gemini-1.0-ultra"
+
+            [providers.ollama]
+            model = "anything-local:7b"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let warnings = config.warnings();
+
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].contains("providers.claude.model"));
+        assert!(warnings[0].contains("claude-sonet-4"));
+        assert!(warnings[1].contains("providers.gemini.fallback_model"));
+    }
+
+    #[test]
+    fn test_warnings_use_configured_known_models() {
+        let toml = r#"
+            [providers.codex]
+            model = "gpt-6-codex"
+            known_models = ["codex", "gpt-6-codex"]
+
+            [providers.ollama]
+            model = "qwen3:8b"
+            known_models = ["llama3.2:3b"]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let warnings = config.warnings();
+
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains("providers.ollama.model"));
+    }
+
+    #[test]
+    fn test_validate_default_config() {
+        assert_eq!(Config::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_reports_broken_settings() {
+        let toml = r#"
+            [foreman]
+            poll_interval = 0
+
+            [providers.claude]
+            threshold_yellow = 0.2
+            threshold_red = 0.5
+
+            [providers.gemini]
+            threshold_yellow = 1.5
+            api_key_env = "RIGS_TEST_SURELY_UNSET_KEY"
+
+            [providers.deepseek]
+            api_key_env = "PATH"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let keys: Vec<String> = config
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|p| p.key)
+            .collect();
+
+        assert_eq!(
+            keys,
+            vec![
+                "providers.claude.threshold_red",
+                "providers.gemini.threshold_yellow",
+                "providers.gemini.api_key_env",
+                "foreman.poll_interval",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_treats_empty_api_key_as_missing() {
+        std::env::set_var("RIGS_TEST_EMPTY_CLAUDE_KEY", "");
+        let mut config = Config::default();
+        config.providers.claude.api_key_env = Some("RIGS_TEST_EMPTY_CLAUDE_KEY".into());
+
+        let problems = config.validate().unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].key, "providers.claude.api_key_env");
+        assert!(!config.is_provider_authenticated(Provider::Claude));
+    }
+
+    #[test]
+    fn test_validate_requires_an_execution_provider() {
+        let toml = r#"
+            [providers.claude]
+            enabled = false
+            [providers.codex]
+            enabled = false
+            [providers.gemini]
+            enabled = false
+        "#;
+        let mut config: Config = toml::from_str(toml).unwrap();
+        let problems = config.validate().unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].key, "providers");
+
+        config.general.local_only = true;
+        assert_eq!(config.validate(), Ok(()));
+        config.providers.ollama.enabled = false;
+        assert_eq!(
+            config.validate().unwrap_err()[0].to_string(),
+            "providers.ollama.enabled: general.local_only is set but Ollama is disabled"
+        );
+    }
+
+    #[test]
+    fn test_default_toml_round_trips() {
+        let text = Config::default_toml().unwrap();
+        assert!(text.contains("# Zone for displaying times"));
+        assert!(text.contains("\n# Scheduler\n[foreman]"));
+        assert!(text.contains("threshold_red = 0.2\n"));
+
+        let parsed: Config = toml::from_str(&text).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(Config::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reconcile_policy() {
+        let toml = r#"
+            [providers.codex]
+            reconcile = "trust_remote"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.reconcile_policy(Provider::Codex),
+            ReconcilePolicy::TrustRemote
+        );
+        assert_eq!(
+            config.reconcile_policy(Provider::Claude),
+            ReconcilePolicy::Min
+        );
+    }
+
+    #[test]
+    fn test_tls_options() {
+        let toml = r#"
+            [providers.claude]
+            danger_accept_invalid_certs = true
+
+            [providers.ollama]
+            ca_cert = "/etc/ssl/corp-ca.pem"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.providers.claude.tls.danger_accept_invalid_certs);
+        assert_eq!(
+            config.providers.ollama.tls.ca_cert,
+            Some(PathBuf::from("/etc/ssl/corp-ca.pem"))
+        );
+        assert_eq!(config.providers.codex.tls, TlsOptions::default());
+    }
+
+    #[test]
+    fn test_postprocess_per_task_type() {
+        let toml = r#"
+            [foreman.postprocess]
+            implementation = ["strip_think_tags", "extract_code"]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.foreman.postprocess[&TaskType::Implementation],
+            vec![PostProcessor::StripThinkTags, PostProcessor::ExtractCode]
+        );
+        assert!(!config.foreman.postprocess.contains_key(&TaskType::Review));
+    }
+
+    #[test]
+    fn test_display_timezone() {
+        let ts = DateTime::parse_from_rfc3339("2026-01-18T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let utc = Config::default().display_timezone().unwrap();
+        assert_eq!(utc.format_datetime(&ts), "2026-01-18 10:00 UTC");
+
+        let copenhagen = DisplayTimezone::parse("Europe/Copenhagen").unwrap();
+        assert_eq!(copenhagen.format_datetime(&ts), "2026-01-18 11:00 CET");
+
+        let tokyo = DisplayTimezone::parse("Asia/Tokyo").unwrap();
+        assert_eq!(tokyo.format(&ts, "%H:%M"), "19:00");
+
+        assert_eq!(
+            DisplayTimezone::parse("local").unwrap(),
+            DisplayTimezone::Local
+        );
+        assert!(DisplayTimezone::parse("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn test_invalid_timezone_rejected_on_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[general]\ntimezone = \"Not/AZone\"\n").unwrap();
+
+        let err = Config::load(Some(&path)).unwrap_err();
+        assert!(matches!(err, RigsError::InvalidConfig(_)));
+        assert!(err.to_string().contains("Not/AZone"));
+    }
+
+    #[test]
+    fn test_routing_affinity_keys() {
+        let config: Config = toml::from_str(
+            r#"
+            [routing.affinity.review]
+            gemini = 0.9
+            claude = 1.0
+        "#,
+        )
+        .unwrap();
+        let affinities = config.routing.affinities().unwrap();
+        assert_eq!(
+            affinities[&TaskType::Review],
+            vec![(Provider::Claude, 1.0), (Provider::Gemini, 0.9)]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[routing.affinity.review]
+gpt4 = 1.0
+",
+        )
+        .unwrap();
+        let err = Config::load(Some(&path)).unwrap_err();
+        assert!(matches!(err, RigsError::InvalidConfig(_)));
+        assert!(err.to_string().contains("Unknown provider 'gpt4'"));
+    }
+
+    #[test]
+    fn test_tie_breaker_orders_equal_affinities() {
+        let tied = r#"
+            [routing.affinity.review]
+            claude = 0.8
+            gemini = 0.8
+            codex = 1.0
+        "#;
+        for _ in 0..20 {
+            let config: Config = toml::from_str(tied).unwrap();
+            assert_eq!(
+                config.routing.affinities().unwrap()[&TaskType::Review],
+                vec![
+                    (Provider::Codex, 1.0),
+                    (Provider::Claude, 0.8),
+                    (Provider::Gemini, 0.8)
+                ]
+            );
+
+            let config: Config =
+                toml::from_str(&format!("[routing]\ntie_breaker = [\"gemini\"]\n{}", tied))
+                    .unwrap();
+            assert_eq!(
+                config.routing.affinities().unwrap()[&TaskType::Review],
+                vec![
+                    (Provider::Codex, 1.0),
+                    (Provider::Gemini, 0.8),
+                    (Provider::Claude, 0.8)
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_ollama_nodes() {
+        let single: Config = toml::from_str(
+            r#"
+            [providers.ollama]
+            base_url = "http://gpu-1:11434"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(single.providers.ollama.nodes(), vec!["http://gpu-1:11434"]);
+
+        let cluster: Config = toml::from_str(
+            r#"
+            [providers.ollama]
+            base_urls = ["http://gpu-1:11434", "http://gpu-2:11434"]
+        "#,
+        )
+        .unwrap();
+        assert_eq!(cluster.providers.ollama.nodes().len(), 2);
+    }
+
+    #[test]
+    fn test_bundle_signing_key() {
+        let mut config = Config::default();
+        assert!(config.bundle_signing_key().unwrap().is_none());
+
+        config.export.signing_key_env = Some("RIGS_TEST_UNSET_SIGNING_KEY".into());
+        assert!(config.bundle_signing_key().is_err());
+    }
+
     #[test]
     fn test_expand_path() {
         let config = Config::default();
@@ -0,0 +1,927 @@
+//! Assayer (pre-execution analysis)
+//!
+//! Assayers look at a bead before it is dispatched. The Estimator predicts
+//! how many tokens a bead will consume so routing can budget tank capacity,
+//! the [`Optimizer`] rewrites its description into a tighter prompt, and
+//! [`infer_task_type`] picks a type for beads created without one. Before
+//! any of that, the [`OllamaPlanner`] splits goals into beads, and once a
+//! bead has run, the [`OllamaGate`] reviews its output.
+//! Model-backed assayers run on Ollama through [`OllamaClient`]; the foreman
+//! runs an [`Assay`] on each bead before executing it.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+use super::bead::{Bead, BeadId, TaskType};
+use super::error::{Result, RigsError};
+use super::executor::{ExecutionRequest, Executor};
+use super::gate::{QualityGate, Verdict};
+use super::http::{build_client, tls_error};
+use super::planner::Decomposer;
+use super::postprocess::PostProcessor;
+use super::provider::Provider;
+use crate::config::{Config, OllamaEntry, TlsOptions};
+
+/// Prepares a bead for execution, e.g. by optimizing its prompt and
+/// re-estimating it
+///
+/// The foreman calls this for beads without an optimized prompt, with the
+/// bead in [`BeadStatus::Optimizing`](super::bead::BeadStatus::Optimizing).
+#[async_trait]
+pub trait Assay: Send + Sync {
+    async fn assay(&self, bead: &mut Bead) -> Result<()>;
+}
+
+/// How long to wait for a single Ollama request, unless configured
+pub const OLLAMA_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Smallest estimate given to any bead (prompt framing, short answers)
+pub const MIN_ESTIMATE: u64 = 500;
+
+/// Predicts token usage for beads
+///
+/// Without a model only the [heuristic](Estimator::heuristic) is used.
+#[derive(Clone, Default)]
+pub struct Estimator {
+    model: Option<(Arc<OllamaClient>, String)>,
+}
+
+impl Estimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refine estimates by asking `model` on Ollama
+    pub fn with_model(client: Arc<OllamaClient>, model: impl Into<String>) -> Self {
+        Self {
+            model: Some((client, model.into())),
+        }
+    }
+
+    /// Use `assayer.estimator_model` when Ollama is enabled for assayers
+    pub fn from_config(config: &Config) -> Result<Self> {
+        if !(config.assayer.use_ollama && config.providers.ollama.enabled) {
+            return Ok(Self::new());
+        }
+        let client = OllamaClient::from_config(&config.providers.ollama)?;
+        Ok(Self::with_model(
+            Arc::new(client),
+            config.assayer.estimator_model.clone(),
+        ))
+    }
+
+    /// Estimate the total tokens a bead will consume
+    ///
+    /// The model's answer replaces the heuristic when there is one; if the
+    /// model is unreachable or doesn't answer with a number, the heuristic
+    /// stands.
+    pub async fn estimate(&self, bead: &Bead) -> Result<u64> {
+        let heuristic = Self::heuristic(bead);
+        let Some((client, model)) = &self.model else {
+            return Ok(heuristic);
+        };
+        let prompt = format!(
+            "Estimate how many tokens (prompt plus response) an LLM will use to \
+             complete this {} task. A rough guess is {}. Reply with a single number.\n\n{}",
+            bead.task_type, heuristic, bead.description
+        );
+        match client.generate(model, &prompt).await {
+            Ok(reply) => match parse_estimate(&PostProcessor::StripThinkTags.apply(&reply)) {
+                Some(tokens) => Ok(tokens.max(MIN_ESTIMATE)),
+                None => {
+                    warn!(bead = %bead.id, model = %model, "Estimator reply had no number; using heuristic");
+                    Ok(heuristic)
+                }
+            },
+            Err(e) => {
+                warn!(bead = %bead.id, model = %model, error = %e, "Estimator model unavailable; using heuristic");
+                Ok(heuristic)
+            }
+        }
+    }
+
+    /// Fast estimate from the bead's current description and task type
+    ///
+    /// Uses roughly 4/3 tokens per word of description and acceptance
+    /// criteria, scaled by the task type's output multiplier and rounded up
+    /// to the nearest hundred.
+    pub fn heuristic(bead: &Bead) -> u64 {
+        let words = bead.description.split_whitespace().count()
+            + bead
+                .acceptance_criteria
+                .iter()
+                .map(|c| c.split_whitespace().count())
+                .sum::<usize>();
+        let prompt_tokens = (words as u64 * 4).div_ceil(3);
+        let total = prompt_tokens * (1 + bead.task_type.token_multiplier());
+        total.div_ceil(100).saturating_mul(100).max(MIN_ESTIMATE)
+    }
+}
+
+/// Rewrites bead descriptions into structured prompts
+///
+/// Runs `assayer.optimizer_model` on Ollama. If Ollama fails and a fallback
+/// executor is set, the request goes to DeepSeek instead; if that fails too,
+/// or there is no fallback, the description is kept as it is.
+pub struct Optimizer {
+    client: Arc<OllamaClient>,
+    model: String,
+    /// Executor and DeepSeek model used when Ollama fails
+    fallback: Option<(Arc<dyn Executor>, String)>,
+}
+
+impl Optimizer {
+    pub fn new(client: Arc<OllamaClient>, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+            fallback: None,
+        }
+    }
+
+    /// Send prompts to DeepSeek's `model` through `executor` when Ollama fails
+    pub fn with_fallback(mut self, executor: Arc<dyn Executor>, model: impl Into<String>) -> Self {
+        self.fallback = Some((executor, model.into()));
+        self
+    }
+
+    /// Use `assayer.optimizer_model`, falling back through `executor` to the
+    /// configured DeepSeek model when `assayer.fallback_to_api` is set
+    pub fn from_config(config: &Config, executor: Arc<dyn Executor>) -> Result<Self> {
+        let client = OllamaClient::from_config(&config.providers.ollama)?;
+        let optimizer = Self::new(Arc::new(client), config.assayer.optimizer_model.clone());
+        Ok(if config.assayer.fallback_to_api {
+            optimizer.with_fallback(executor, config.resolve_model(Provider::DeepSeek))
+        } else {
+            optimizer
+        })
+    }
+
+    /// An optimized prompt for `bead`, or its description if no model could
+    /// produce one
+    pub async fn optimize(&self, bead: &Bead) -> Result<String> {
+        let mut prompt = format!(
+            "Rewrite this {} task as a clear, structured prompt for a coding assistant. \
+             Keep every requirement, state the expected output, and reply with the \
+             prompt only.\n\nTask:\n{}\n",
+            bead.task_type, bead.description
+        );
+        for criterion in &bead.acceptance_criteria {
+            prompt.push_str(&format!("- Must: {}\n", criterion));
+        }
+
+        let reply = match self.client.generate(&self.model, &prompt).await {
+            Ok(reply) => Some(reply),
+            Err(e) => match &self.fallback {
+                Some((executor, model)) => {
+                    warn!(bead = %bead.id, error = %e, "Optimizer model unavailable; falling back to DeepSeek");
+                    let request = ExecutionRequest {
+                        provider: Provider::DeepSeek,
+                        model: model.clone(),
+                        prompt,
+                        working_dir: None,
+                    };
+                    match executor.execute(&request).await {
+                        Ok(output) => Some(output.text),
+                        Err(e) => {
+                            warn!(bead = %bead.id, error = %e, "Optimizer fallback failed; keeping the description");
+                            None
+                        }
+                    }
+                }
+                None => {
+                    warn!(bead = %bead.id, error = %e, "Optimizer model unavailable; keeping the description");
+                    None
+                }
+            },
+        };
+
+        let cleaned = reply
+            .map(|r| PostProcessor::Trim.apply(&PostProcessor::StripThinkTags.apply(&r)))
+            .unwrap_or_default();
+        if cleaned.is_empty() {
+            Ok(bead.description.clone())
+        } else {
+            Ok(cleaned)
+        }
+    }
+}
+
+#[async_trait]
+impl Assay for Optimizer {
+    async fn assay(&self, bead: &mut Bead) -> Result<()> {
+        let prompt = self.optimize(bead).await?;
+        if prompt != bead.description {
+            bead.optimized_prompt = Some(prompt);
+        }
+        Ok(())
+    }
+}
+
+/// Splits goals into beads with `assayer.planner_model`
+///
+/// The model is asked for a JSON array of subtasks, each naming the
+/// subtasks it depends on by index.
+pub struct OllamaPlanner {
+    client: Arc<OllamaClient>,
+    model: String,
+    name: String,
+}
+
+/// One subtask as the planner model describes it
+#[derive(Deserialize)]
+struct PlannedSubtask {
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    task_type: TaskType,
+    /// Indexes of earlier subtasks
+    #[serde(default)]
+    dependencies: Vec<usize>,
+    #[serde(default)]
+    estimate: Option<u64>,
+}
+
+impl OllamaPlanner {
+    pub fn new(client: Arc<OllamaClient>, model: impl Into<String>) -> Self {
+        let model = model.into();
+        Self {
+            client,
+            name: format!("{} (Ollama)", model),
+            model,
+        }
+    }
+
+    /// Use `assayer.planner_model` on the configured Ollama node(s)
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let client = OllamaClient::from_config(&config.providers.ollama)?;
+        Ok(Self::new(
+            Arc::new(client),
+            config.assayer.planner_model.clone(),
+        ))
+    }
+}
+
+#[async_trait]
+impl Decomposer for OllamaPlanner {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn decompose(&self, goal: &str) -> Result<Vec<Bead>> {
+        let prompt = format!(
+            "Split this software goal into 2 to 8 subtasks. Reply with only a JSON array. \
+             Each element has \"title\", \"description\", \"task_type\" (one of \
+             implementation, review, research, refactor, test, documentation, debug, \
+             design), \"dependencies\" (indexes of earlier subtasks it needs) and \
+             \"estimate\" (tokens).\n\nGoal: {}",
+            goal
+        );
+        let reply = self.client.generate(&self.model, &prompt).await?;
+        parse_plan(goal, &reply)
+    }
+}
+
+/// Beads from a planner model's reply
+///
+/// Tolerates reasoning blocks and prose or code fences around the JSON
+/// array. Dependencies must point at earlier subtasks, so the beads come
+/// out in dependency order; missing estimates come from
+/// [`Estimator::heuristic`].
+fn parse_plan(goal: &str, reply: &str) -> Result<Vec<Bead>> {
+    let reply = PostProcessor::StripThinkTags.apply(reply);
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => {
+            return Err(RigsError::LlmParseError(
+                "planner reply has no JSON array".into(),
+            ))
+        }
+    };
+    let subtasks: Vec<PlannedSubtask> = serde_json::from_str(json)
+        .map_err(|e| RigsError::LlmParseError(format!("planner reply: {}", e)))?;
+    if subtasks.is_empty() {
+        return Err(RigsError::LlmParseError(
+            "planner returned no subtasks".into(),
+        ));
+    }
+
+    let mut ids: Vec<BeadId> = Vec::with_capacity(subtasks.len());
+    let mut beads = Vec::with_capacity(subtasks.len());
+    for (i, subtask) in subtasks.into_iter().enumerate() {
+        let dependencies = subtask
+            .dependencies
+            .iter()
+            .map(|&dep| {
+                ids.get(dep).cloned().ok_or_else(|| {
+                    RigsError::LlmParseError(format!(
+                        "subtask {} depends on {}, which isn't an earlier subtask",
+                        i, dep
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let description = subtask
+            .description
+            .unwrap_or_else(|| format!("Goal: {}\n\n{}", goal, subtask.title));
+        let mut bead = Bead::new(subtask.title, description, subtask.task_type)
+            .with_dependencies(dependencies);
+        bead.estimated_tokens = match subtask.estimate {
+            Some(tokens) if tokens > 0 => tokens.max(MIN_ESTIMATE),
+            _ => Estimator::heuristic(&bead),
+        };
+        ids.push(bead.id.clone());
+        beads.push(bead);
+    }
+    Ok(beads)
+}
+
+/// Quality gate run by `assayer.quality_model` on Ollama
+pub struct OllamaGate {
+    client: Arc<OllamaClient>,
+    model: String,
+}
+
+/// The review a quality model is asked for
+#[derive(Deserialize)]
+struct ModelVerdict {
+    passed: bool,
+    #[serde(default)]
+    reasons: Vec<String>,
+}
+
+impl OllamaGate {
+    pub fn new(client: Arc<OllamaClient>, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+        }
+    }
+
+    /// Use `assayer.quality_model` on the configured Ollama node(s)
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let client = OllamaClient::from_config(&config.providers.ollama)?;
+        Ok(Self::new(
+            Arc::new(client),
+            config.assayer.quality_model.clone(),
+        ))
+    }
+}
+
+#[async_trait]
+impl QualityGate for OllamaGate {
+    async fn review(&self, criteria: &[String], output: &str) -> Result<Verdict> {
+        let criteria: String = criteria.iter().map(|c| format!("- {}\n", c)).collect();
+        let prompt = format!(
+            "Check this output against every acceptance criterion. Reply with only JSON: \
+             {{\"passed\": true or false, \"reasons\": [one entry per unmet criterion]}}.\n\n\
+             Criteria:\n{}\nOutput:\n{}",
+            criteria, output
+        );
+        let reply = self.client.generate(&self.model, &prompt).await?;
+        parse_verdict(&reply)
+    }
+}
+
+/// A verdict from a quality model's reply
+fn parse_verdict(reply: &str) -> Result<Verdict> {
+    let reply = PostProcessor::StripThinkTags.apply(reply);
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => {
+            return Err(RigsError::LlmParseError(
+                "quality review has no JSON object".into(),
+            ))
+        }
+    };
+    let verdict: ModelVerdict = serde_json::from_str(json)
+        .map_err(|e| RigsError::LlmParseError(format!("quality review: {}", e)))?;
+    Ok(Verdict {
+        passed: verdict.passed,
+        reason: verdict.reasons.join("\n"),
+    })
+}
+
+/// The first number in a model's reply, allowing thousands separators
+fn parse_estimate(reply: &str) -> Option<u64> {
+    let start = reply.find(|c: char| c.is_ascii_digit())?;
+    let digits: String = reply[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == ',')
+        .filter(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
+/// Words that mark a description as a particular task type
+const TASK_TYPE_KEYWORDS: &[(TaskType, &[&str])] = &[
+    (TaskType::Test, &["test", "tests", "testing", "coverage"]),
+    (
+        TaskType::Refactor,
+        &[
+            "refactor",
+            "refactoring",
+            "cleanup",
+            "restructure",
+            "simplify",
+            "rename",
+        ],
+    ),
+    (
+        TaskType::Debug,
+        &[
+            "debug", "bug", "fix", "crash", "crashes", "panic", "panics", "broken", "failing",
+        ],
+    ),
+    (
+        TaskType::Documentation,
+        &[
+            "document",
+            "docs",
+            "documentation",
+            "readme",
+            "docstring",
+            "docstrings",
+        ],
+    ),
+    (TaskType::Review, &["review", "audit"]),
+    (
+        TaskType::Research,
+        &["research", "investigate", "compare", "evaluate", "explore"],
+    ),
+    (
+        TaskType::Design,
+        &["design", "architecture", "rfc", "proposal"],
+    ),
+];
+
+/// Guess a bead's task type from its description
+///
+/// Keyword heuristic: the description must point at exactly one type.
+/// Anything with no keyword, or keywords for several types (e.g. "fix the
+/// failing tests"), is treated as implementation.
+pub fn infer_task_type(description: &str) -> TaskType {
+    // TODO: Ask the Ollama classifier model when available
+    let description = description.to_lowercase();
+    let words: Vec<&str> = description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let mut matched = TASK_TYPE_KEYWORDS
+        .iter()
+        .filter(|(_, keywords)| words.iter().any(|w| keywords.contains(w)))
+        .map(|(task_type, _)| *task_type);
+    match (matched.next(), matched.next()) {
+        (Some(task_type), None) => task_type,
+        _ => TaskType::Implementation,
+    }
+}
+
+/// HTTP client for one or more Ollama nodes
+///
+/// Requests are spread round-robin across the nodes. A node that can't be
+/// reached is skipped and the request retried on the next one; HTTP errors
+/// from a reachable node are returned as-is.
+pub struct OllamaClient {
+    http: reqwest::Client,
+    nodes: Vec<String>,
+    next: AtomicUsize,
+}
+
+#[derive(Serialize)]
+struct GenerateRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct GenerateResponse {
+    response: String,
+}
+
+impl OllamaClient {
+    /// Create a client over the given base URLs
+    pub fn new(nodes: Vec<String>) -> Result<Self> {
+        Self::with_tls(nodes, &TlsOptions::default())
+    }
+
+    /// Create a client over the given base URLs with custom TLS settings
+    pub fn with_tls(nodes: Vec<String>, tls: &TlsOptions) -> Result<Self> {
+        Self::with_timeout(nodes, tls, OLLAMA_TIMEOUT)
+    }
+
+    /// Create a client whose requests give up after `timeout`
+    ///
+    /// A node that times out counts as unreachable.
+    pub fn with_timeout(nodes: Vec<String>, tls: &TlsOptions, timeout: Duration) -> Result<Self> {
+        if nodes.is_empty() {
+            return Err(RigsError::ConfigError(
+                "providers.ollama: at least one base URL is required".into(),
+            ));
+        }
+        let http = build_client(tls, timeout)?;
+        Ok(Self {
+            http,
+            nodes: nodes
+                .into_iter()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .collect(),
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Create a client for the configured Ollama node(s)
+    pub fn from_config(entry: &OllamaEntry) -> Result<Self> {
+        Self::with_timeout(
+            entry.nodes(),
+            &entry.tls,
+            Duration::from_secs(entry.timeout_secs),
+        )
+    }
+
+    /// Run a prompt to completion (non-streaming)
+    pub async fn generate(&self, model: &str, prompt: &str) -> Result<String> {
+        let body = GenerateRequest {
+            model,
+            prompt,
+            stream: false,
+        };
+        let response = self
+            .send(|http, base| http.post(format!("{}/api/generate", base)).json(&body))
+            .await?;
+        let parsed: GenerateResponse = response
+            .json()
+            .await
+            .map_err(|e| RigsError::AssayerError(format!("unexpected Ollama response: {}", e)))?;
+        Ok(parsed.response)
+    }
+
+    /// Check that at least one node is up
+    pub async fn health(&self) -> Result<()> {
+        self.send(|http, base| http.get(format!("{}/api/tags", base)))
+            .await
+            .map(|_| ())
+    }
+
+    /// Send a request, starting at the next node and failing over past
+    /// unreachable ones
+    async fn send(
+        &self,
+        build: impl Fn(&reqwest::Client, &str) -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut unreachable = Vec::new();
+
+        for i in 0..self.nodes.len() {
+            let base = &self.nodes[(start + i) % self.nodes.len()];
+            match build(&self.http, base).send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(RigsError::AssayerError(format!(
+                        "Ollama at {} returned {}: {}",
+                        base,
+                        status,
+                        body.trim()
+                    )));
+                }
+                Err(e) => {
+                    // A certificate problem won't go away on another node
+                    if let Some(tls) = tls_error(base, &e) {
+                        return Err(tls);
+                    }
+                    if !(e.is_connect() || e.is_timeout()) {
+                        return Err(e.into());
+                    }
+                    unreachable.push(format!("{} ({})", base, e));
+                }
+            }
+        }
+
+        Err(RigsError::OllamaNotAvailable(unreachable.join("; ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ExecutionOutput, TaskType};
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn ollama_node(reply: &str) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "response": reply })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    /// A URL nothing is listening on
+    fn downed_node() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_requests_distribute_across_nodes() {
+        let a = ollama_node("from a").await;
+        let b = ollama_node("from b").await;
+        let client = OllamaClient::new(vec![a.uri(), b.uri()]).unwrap();
+
+        let mut replies = Vec::new();
+        for _ in 0..4 {
+            replies.push(client.generate("llama3.2:3b", "hi").await.unwrap());
+        }
+
+        assert_eq!(replies, vec!["from a", "from b", "from a", "from b"]);
+        assert_eq!(a.received_requests().await.unwrap().len(), 2);
+        assert_eq!(b.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_downed_node_is_skipped() {
+        let live = ollama_node("ok").await;
+        let client = OllamaClient::new(vec![downed_node(), live.uri()]).unwrap();
+
+        for _ in 0..3 {
+            assert_eq!(client.generate("llama3.2:3b", "hi").await.unwrap(), "ok");
+        }
+        assert_eq!(live.received_requests().await.unwrap().len(), 3);
+
+        let all_down = OllamaClient::new(vec![downed_node(), downed_node()]).unwrap();
+        assert!(matches!(
+            all_down.generate("llama3.2:3b", "hi").await,
+            Err(RigsError::OllamaNotAvailable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_server_error_is_an_assayer_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("model crashed"))
+            .mount(&server)
+            .await;
+        let client = OllamaClient::new(vec![server.uri()]).unwrap();
+
+        let err = client.generate("llama3.2:3b", "hi").await.unwrap_err();
+        assert!(
+            matches!(&err, RigsError::AssayerError(msg) if msg.contains("500") && msg.contains("model crashed"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_and_config() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "models": [] })))
+            .mount(&server)
+            .await;
+        let entry = OllamaEntry {
+            base_url: format!("{}/", server.uri()),
+            ..OllamaEntry::default()
+        };
+        OllamaClient::from_config(&entry)
+            .unwrap()
+            .health()
+            .await
+            .unwrap();
+
+        let refused = OllamaEntry {
+            base_url: downed_node(),
+            ..OllamaEntry::default()
+        };
+        assert!(matches!(
+            OllamaClient::from_config(&refused).unwrap().health().await,
+            Err(RigsError::OllamaNotAvailable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_slow_node_times_out() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "response": "late" }))
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+        let client = OllamaClient::with_timeout(
+            vec![server.uri()],
+            &TlsOptions::default(),
+            Duration::from_millis(200),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            client.generate("llama3.2:3b", "hi").await,
+            Err(RigsError::OllamaNotAvailable(_))
+        ));
+    }
+
+    #[test]
+    fn test_heuristic_scales_with_description_and_type() {
+        let short = Bead::new("Fix", "Fix typo", TaskType::Documentation);
+        assert_eq!(Estimator::heuristic(&short), MIN_ESTIMATE);
+
+        let description = "word ".repeat(300);
+        let docs = Bead::new("Docs", description.clone(), TaskType::Documentation);
+        let code = Bead::new("Code", description, TaskType::Implementation);
+        assert_eq!(Estimator::heuristic(&docs), 1_600);
+        assert_eq!(Estimator::heuristic(&code), 3_600);
+    }
+
+    #[tokio::test]
+    async fn test_model_refines_estimate() {
+        let bead = &Bead::new("Parser", "Write a parser", TaskType::Implementation);
+        let estimate = |node: String| async move {
+            let client = OllamaClient::new(vec![node]).unwrap();
+            Estimator::with_model(Arc::new(client), "llama3.2:3b")
+                .estimate(bead)
+                .await
+                .unwrap()
+        };
+
+        let node = ollama_node("<think>hmm</think>About 4,200 tokens.").await;
+        assert_eq!(estimate(node.uri()).await, 4_200);
+        let vague = ollama_node("Hard to say.").await;
+        assert_eq!(estimate(vague.uri()).await, MIN_ESTIMATE);
+        assert_eq!(estimate(downed_node()).await, MIN_ESTIMATE);
+    }
+
+    /// Stands in for the DeepSeek fallback
+    struct RemoteAssayer;
+
+    #[async_trait]
+    impl Executor for RemoteAssayer {
+        fn kind(&self) -> &'static str {
+            "test"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            assert_eq!(request.provider, Provider::DeepSeek);
+            assert_eq!(request.model, "deepseek-reasoner");
+            Ok(ExecutionOutput::estimated(
+                request,
+                "Remote prompt".into(),
+                Duration::ZERO,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_optimizer_fills_optimized_prompt() {
+        let node = ollama_node("<think>plan</think>\n  Structured prompt\n").await;
+        let client = Arc::new(OllamaClient::new(vec![node.uri()]).unwrap());
+        let mut bead = Bead::new("Parser", "Write a parser", TaskType::Implementation);
+
+        Optimizer::new(client, "qwen3:8b")
+            .assay(&mut bead)
+            .await
+            .unwrap();
+        assert_eq!(bead.optimized_prompt.as_deref(), Some("Structured prompt"));
+        assert_eq!(bead.effective_prompt(), "Structured prompt");
+    }
+
+    #[tokio::test]
+    async fn test_optimizer_without_ollama() {
+        let bead = Bead::new("Parser", "Write a parser", TaskType::Implementation);
+        let client = Arc::new(OllamaClient::new(vec![downed_node()]).unwrap());
+
+        let kept = Optimizer::new(client.clone(), "qwen3:8b")
+            .optimize(&bead)
+            .await
+            .unwrap();
+        assert_eq!(kept, "Write a parser");
+
+        let remote = Optimizer::new(client, "qwen3:8b")
+            .with_fallback(Arc::new(RemoteAssayer), "deepseek-reasoner")
+            .optimize(&bead)
+            .await
+            .unwrap();
+        assert_eq!(remote, "Remote prompt");
+    }
+
+    #[test]
+    fn test_parse_plan() {
+        let reply = r#"<think>OAuth needs a provider first.</think>
+Here is the plan:
+```json
+[
+  {"title": "Compare OAuth providers", "task_type": "research", "estimate": 3000},
+  {"title": "Design the login flow", "description": "Sequence diagram and endpoints",
+   "task_type": "design", "dependencies": [0], "estimate": 4000},
+  {"title": "Implement login", "task_type": "implementation", "dependencies": [1]},
+  {"title": "Test login", "task_type": "test", "dependencies": [1, 2], "estimate": 5000}
+]
+```"#;
+        let beads = parse_plan("Add OAuth login", reply).unwrap();
+
+        assert_eq!(beads.len(), 4);
+        assert_eq!(beads[0].task_type, TaskType::Research);
+        assert_eq!(
+            beads[0].description,
+            "Goal: Add OAuth login\n\nCompare OAuth providers"
+        );
+        assert_eq!(beads[1].description, "Sequence diagram and endpoints");
+        assert_eq!(beads[1].dependencies, vec![beads[0].id.clone()]);
+        assert_eq!(
+            beads[3].dependencies,
+            vec![beads[1].id.clone(), beads[2].id.clone()]
+        );
+        assert_eq!(beads[1].estimated_tokens, 4_000);
+        assert_eq!(beads[2].estimated_tokens, Estimator::heuristic(&beads[2]));
+    }
+
+    #[test]
+    fn test_malformed_plan_is_a_parse_error() {
+        for reply in [
+            "I can't help with that.",
+            "[{\"title\": \"Do it\"}]",
+            "[{\"title\": \"Do it\", \"task_type\": \"cooking\"}]",
+            "[{\"title\": \"A\", \"task_type\": \"test\", \"dependencies\": [0]}]",
+            "[]",
+        ] {
+            assert!(
+                matches!(parse_plan("goal", reply), Err(RigsError::LlmParseError(_))),
+                "{}",
+                reply
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_verdict() {
+        let verdict = parse_verdict(
+            "<think>two gaps</think>{\"passed\": false, \"reasons\": [\"No tests\", \"Unwraps input\"]}",
+        )
+        .unwrap();
+        assert!(!verdict.passed);
+        assert_eq!(verdict.reason, "No tests\nUnwraps input");
+        assert!(parse_verdict("{\"passed\": true}").unwrap().passed);
+        assert!(matches!(
+            parse_verdict("Looks good to me"),
+            Err(RigsError::LlmParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_estimate() {
+        assert_eq!(parse_estimate("1200"), Some(1_200));
+        assert_eq!(
+            parse_estimate("Roughly 12,500 tokens, maybe 14000"),
+            Some(12_500)
+        );
+        assert_eq!(parse_estimate("no idea"), None);
+    }
+
+    #[test]
+    fn test_infer_task_type_from_keywords() {
+        for (description, expected) in [
+            ("Add unit tests for the tokenizer", TaskType::Test),
+            (
+                "Refactor the scheduler into smaller modules",
+                TaskType::Refactor,
+            ),
+            ("Fix crash when the config file is empty", TaskType::Debug),
+            (
+                "Document the pricing table in the README",
+                TaskType::Documentation,
+            ),
+            ("Review the auth middleware", TaskType::Review),
+            ("Investigate why builds are slow", TaskType::Research),
+            ("Design the plugin architecture", TaskType::Design),
+            ("Add a --json flag to bead list", TaskType::Implementation),
+            // Points at both debug and test
+            ("Fix the failing tests", TaskType::Implementation),
+            // Whole words only
+            ("Add a latest-release badge", TaskType::Implementation),
+        ] {
+            assert_eq!(infer_task_type(description), expected, "{}", description);
+        }
+    }
+
+    #[test]
+    fn test_heuristic_counts_acceptance_criteria() {
+        let bead = Bead::new("Parser", "word ".repeat(300), TaskType::Test);
+        let with_criteria = bead
+            .clone()
+            .with_criteria(vec!["handles deeply nested input ".repeat(30)]);
+        assert!(Estimator::heuristic(&with_criteria) > Estimator::heuristic(&bead));
+    }
+}
@@ -6,7 +6,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::path::{Path, PathBuf};
 
+use super::error::{RetryClass, RigsError};
 use super::provider::Provider;
 
 /// Unique identifier for a bead
@@ -34,9 +36,7 @@ impl BeadId {
 
     /// Parse a BeadId from a string
     pub fn parse(s: &str) -> Result<Self, InvalidBeadId> {
-        if s.starts_with("gt-")
-            && s.len() == 8
-            && s[3..].chars().all(|c| c.is_ascii_alphanumeric())
+        if s.starts_with("gt-") && s.len() == 8 && s[3..].chars().all(|c| c.is_ascii_alphanumeric())
         {
             Ok(BeadId(s.to_lowercase()))
         } else {
@@ -62,12 +62,50 @@ impl fmt::Display for BeadId {
     }
 }
 
+impl std::str::FromStr for BeadId {
+    type Err = InvalidBeadId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
 impl AsRef<str> for BeadId {
     fn as_ref(&self) -> &str {
         &self.0
     }
 }
 
+/// A bead named on the command line: its id, or `#<seq>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeadRef {
+    Id(BeadId),
+    Seq(u64),
+}
+
+impl std::str::FromStr for BeadRef {
+    type Err = InvalidBeadId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('#') {
+            Some(seq) => seq
+                .parse()
+                .map(BeadRef::Seq)
+                .map_err(|_| InvalidBeadId(s.to_string())),
+            None => BeadId::parse(s).map(BeadRef::Id),
+        }
+    }
+}
+
+impl fmt::Display for BeadRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BeadRef::Id(id) => write!(f, "{}", id),
+            BeadRef::Seq(seq) => write!(f, "#{}", seq),
+        }
+    }
+}
+
 /// Error for invalid BeadId format
 #[derive(Debug, Clone)]
 pub struct InvalidBeadId(pub String);
@@ -166,6 +204,23 @@ impl TaskType {
             ],
         }
     }
+
+    /// Output tokens expected per prompt token for this kind of work
+    ///
+    /// Used by the heuristic estimator: writing code or designs produces far
+    /// more output than summarizing or documenting.
+    pub fn token_multiplier(&self) -> u64 {
+        match self {
+            TaskType::Implementation => 8,
+            TaskType::Design => 6,
+            TaskType::Refactor => 6,
+            TaskType::Debug => 5,
+            TaskType::Test => 5,
+            TaskType::Research => 4,
+            TaskType::Review => 3,
+            TaskType::Documentation => 3,
+        }
+    }
 }
 
 impl fmt::Display for TaskType {
@@ -186,22 +241,28 @@ impl fmt::Display for TaskType {
 
 /// Priority level for a bead
 #[derive(
-    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, clap::ValueEnum,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
 )]
 #[serde(rename_all = "lowercase")]
 pub enum Priority {
     Low = 0,
+    #[default]
     Normal = 1,
     High = 2,
     Critical = 3,
 }
 
-impl Default for Priority {
-    fn default() -> Self {
-        Priority::Normal
-    }
-}
-
 impl fmt::Display for Priority {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
@@ -259,6 +320,39 @@ impl BeadStatus {
                 | BeadStatus::Reviewing
         )
     }
+
+    /// Check whether a bead may move from this status to `next`
+    ///
+    /// The full path is Pending → Optimizing → Queued → Assigned →
+    /// InProgress → Reviewing → Completed, though optimization, assignment
+    /// and review may be skipped. Any unfinished bead can be deferred, failed
+    /// or cancelled; deferred, interrupted and failed beads go back to the
+    /// queue. Completed and cancelled beads never move again.
+    pub fn can_transition_to(&self, next: BeadStatus) -> bool {
+        match (self, next) {
+            (
+                BeadStatus::Pending,
+                BeadStatus::Optimizing | BeadStatus::Queued | BeadStatus::InProgress,
+            ) => true,
+            (BeadStatus::Optimizing, BeadStatus::Pending | BeadStatus::Queued) => true,
+            (
+                BeadStatus::Queued,
+                BeadStatus::Optimizing | BeadStatus::Assigned | BeadStatus::InProgress,
+            ) => true,
+            (BeadStatus::Assigned, BeadStatus::InProgress | BeadStatus::Queued) => true,
+            (
+                BeadStatus::InProgress,
+                BeadStatus::Reviewing | BeadStatus::Completed | BeadStatus::Queued,
+            ) => true,
+            (BeadStatus::Reviewing, BeadStatus::Completed | BeadStatus::Queued) => true,
+            (BeadStatus::Deferred, BeadStatus::Pending | BeadStatus::Queued) => true,
+            (BeadStatus::Failed, BeadStatus::Queued) => true,
+            (from, BeadStatus::Deferred | BeadStatus::Failed | BeadStatus::Cancelled) => {
+                !from.is_terminal() && *from != next
+            }
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for BeadStatus {
@@ -279,11 +373,117 @@ impl fmt::Display for BeadStatus {
     }
 }
 
+/// Record of exactly what a bead was executed with
+///
+/// Captured at dispatch time so results stay reproducible even after
+/// prompts, models or provider defaults change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Provider the prompt was sent to
+    pub provider: Provider,
+    /// Resolved model string (after any fallback)
+    pub model: String,
+    /// SHA-256 of the exact prompt sent, hex encoded
+    pub prompt_hash: String,
+    /// Executor that ran the request (e.g. "cli", "http")
+    pub executor: String,
+    /// When the request was sent
+    pub executed_at: DateTime<Utc>,
+}
+
+impl Provenance {
+    /// Record provenance for a prompt about to be sent
+    pub fn new(
+        provider: Provider,
+        model: impl Into<String>,
+        prompt: &str,
+        executor: impl Into<String>,
+    ) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            prompt_hash: hash_prompt(prompt),
+            executor: executor.into(),
+            executed_at: Utc::now(),
+        }
+    }
+
+    /// Check whether `prompt` is the one this provenance was recorded for
+    pub fn matches_prompt(&self, prompt: &str) -> bool {
+        self.prompt_hash == hash_prompt(prompt)
+    }
+}
+
+/// Hex-encoded SHA-256 of a prompt
+pub fn hash_prompt(prompt: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(prompt.as_bytes()))
+}
+
+/// Repository context an executor needs to run a bead
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeadContext {
+    /// Directory the executor runs in (e.g. the repository being worked on)
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    /// Files whose contents are included in the prompt
+    #[serde(default)]
+    pub files: Vec<PathBuf>,
+}
+
+impl BeadContext {
+    /// Check if there is no context to pass along
+    pub fn is_empty(&self) -> bool {
+        self.working_dir.is_none() && self.files.is_empty()
+    }
+
+    /// Resolve a context file, relative paths being taken from `working_dir`
+    pub fn resolve(&self, file: &Path) -> PathBuf {
+        match &self.working_dir {
+            Some(dir) if file.is_relative() => dir.join(file),
+            _ => file.to_path_buf(),
+        }
+    }
+
+    /// Append the contents of the context files to a prompt
+    pub fn render_prompt(&self, prompt: &str) -> std::io::Result<String> {
+        let mut rendered = prompt.to_string();
+        for file in &self.files {
+            let contents = std::fs::read_to_string(self.resolve(file))?;
+            rendered.push_str(&format!(
+                "\n\n--- File: {} ---\n{}",
+                file.display(),
+                contents.trim_end()
+            ));
+        }
+        Ok(rendered)
+    }
+}
+
+/// Default retry budget for new beads
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+fn default_max_retries() -> u32 {
+    DEFAULT_MAX_RETRIES
+}
+
+/// A change to a bead's deadline (see [`Bead::reschedule`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reschedule {
+    /// Move the deadline and deferred time by this much (may be negative)
+    Shift(chrono::Duration),
+    /// Set the deadline to this time
+    Deadline(DateTime<Utc>),
+}
+
 /// A work unit in the Rigs system
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bead {
     /// Unique identifier
     pub id: BeadId,
+    /// Workspace sequence number (`#42`), assigned when the bead is stored
+    #[serde(default)]
+    pub seq: Option<u64>,
     /// Short descriptive title
     pub title: String,
     /// Full task description / prompt
@@ -314,12 +514,30 @@ pub struct Bead {
     pub dependencies: Vec<BeadId>,
     /// Parent convoy (if part of a batch)
     pub convoy_id: Option<String>,
+    /// Number of retries already attempted
+    #[serde(default)]
+    pub retry_count: u32,
+    /// Retries allowed before the bead stays failed
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Consecutive failed attempts on the current provider
+    #[serde(default)]
+    pub provider_failures: u32,
+    /// Providers skipped after failing this bead repeatedly
+    #[serde(default)]
+    pub excluded_providers: Vec<Provider>,
+    /// Working directory and files passed to the executor
+    #[serde(default)]
+    pub context: BeadContext,
 
     // Timestamps
     pub created_at: DateTime<Utc>,
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub deferred_until: Option<DateTime<Utc>>,
+    /// When the bead should be finished by
+    #[serde(default)]
+    pub deadline: Option<DateTime<Utc>>,
 
     // Content
     /// Optimized prompt (from Optimizer Assayer)
@@ -328,13 +546,24 @@ pub struct Bead {
     pub output: Option<String>,
     /// Error message (if failed)
     pub error: Option<String>,
+    /// How the failure behind `error` should be retried
+    #[serde(default)]
+    pub error_class: Option<RetryClass>,
+    /// What the last execution actually ran with
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
 }
 
 impl Bead {
     /// Create a new bead with minimal required fields
-    pub fn new(title: impl Into<String>, description: impl Into<String>, task_type: TaskType) -> Self {
+    pub fn new(
+        title: impl Into<String>,
+        description: impl Into<String>,
+        task_type: TaskType,
+    ) -> Self {
         Self {
             id: BeadId::new(),
+            seq: None,
             title: title.into(),
             description: description.into(),
             task_type,
@@ -347,13 +576,21 @@ impl Bead {
             acceptance_criteria: vec![],
             dependencies: vec![],
             convoy_id: None,
+            retry_count: 0,
+            max_retries: DEFAULT_MAX_RETRIES,
+            context: BeadContext::default(),
             created_at: Utc::now(),
             started_at: None,
             completed_at: None,
             deferred_until: None,
+            deadline: None,
             optimized_prompt: None,
             output: None,
             error: None,
+            error_class: None,
+            provenance: None,
+            provider_failures: 0,
+            excluded_providers: Vec::new(),
         }
     }
 
@@ -381,12 +618,55 @@ impl Bead {
         self
     }
 
+    /// Builder: set execution context
+    pub fn with_context(mut self, context: BeadContext) -> Self {
+        self.context = context;
+        self
+    }
+
     /// Builder: set estimated tokens
     pub fn with_estimate(mut self, tokens: u64) -> Self {
         self.estimated_tokens = tokens;
         self
     }
 
+    /// Provider the Foreman sends this bead to
+    ///
+    /// The preferred provider, then task affinity order, skipping excluded
+    /// providers. If every candidate is excluded the first one is used anyway.
+    pub fn dispatch_provider(&self) -> Provider {
+        let first = self
+            .preferred_provider
+            .unwrap_or_else(|| self.task_type.preferred_provider());
+        self.preferred_provider
+            .into_iter()
+            .chain(
+                self.task_type
+                    .provider_affinities()
+                    .into_iter()
+                    .map(|(p, _)| p),
+            )
+            .find(|p| !self.excluded_providers.contains(p))
+            .unwrap_or(first)
+    }
+
+    /// Count a failed attempt on `provider`
+    ///
+    /// After `switch_after` failures in a row the provider is excluded, so
+    /// the next attempt goes to the next choice. Returns `true` if this
+    /// failure caused a switch. A `switch_after` of 0 never switches.
+    pub fn record_provider_failure(&mut self, provider: Provider, switch_after: u32) -> bool {
+        self.provider_failures += 1;
+        if switch_after == 0 || self.provider_failures < switch_after {
+            return false;
+        }
+        self.provider_failures = 0;
+        if !self.excluded_providers.contains(&provider) {
+            self.excluded_providers.push(provider);
+        }
+        true
+    }
+
     /// Get the prompt to use (optimized if available, else original)
     pub fn effective_prompt(&self) -> &str {
         self.optimized_prompt
@@ -394,6 +674,107 @@ impl Bead {
             .unwrap_or(&self.description)
     }
 
+    /// Check if a failed bead still has retry budget left
+    pub fn can_retry(&self) -> bool {
+        self.status == BeadStatus::Failed && self.retry_count < self.max_retries
+    }
+
+    /// Move the bead to `next`, stamping its timestamps
+    ///
+    /// Entering `InProgress` sets `started_at`; entering a terminal status
+    /// sets `completed_at`. Illegal moves (see
+    /// [`BeadStatus::can_transition_to`]) leave the bead untouched.
+    pub fn transition(&mut self, next: BeadStatus) -> Result<(), RigsError> {
+        if !self.status.can_transition_to(next) {
+            return Err(RigsError::InvalidStateTransition {
+                from: self.status,
+                to: next,
+            });
+        }
+        let now = Utc::now();
+        if next == BeadStatus::InProgress {
+            self.started_at = Some(now);
+        }
+        if next.is_terminal() {
+            self.completed_at = Some(now);
+        }
+        self.status = next;
+        Ok(())
+    }
+
+    /// Keep `error` as the reason this attempt failed, with its retry class
+    pub fn record_error(&mut self, error: &RigsError) {
+        self.error = Some(error.to_string());
+        self.error_class = Some(error.retry_class());
+    }
+
+    /// Requeue a failed bead, consuming one retry
+    ///
+    /// Clears the previous attempt's error, output and timestamps. Returns
+    /// `false` (leaving the bead untouched) if it can't be retried.
+    pub fn requeue_for_retry(&mut self) -> bool {
+        if !self.can_retry() {
+            return false;
+        }
+        self.retry_count += 1;
+        self.reset_for_requeue();
+        true
+    }
+
+    /// Check if a bead failed permanently (no retry budget left)
+    ///
+    /// These beads make up the dead-letter queue.
+    pub fn is_dead_letter(&self) -> bool {
+        self.status == BeadStatus::Failed && !self.can_retry()
+    }
+
+    /// Resurrect a dead-lettered bead with a fresh retry budget
+    ///
+    /// Returns `false` (leaving the bead untouched) if it isn't dead-lettered.
+    pub fn requeue_dead_letter(&mut self) -> bool {
+        if !self.is_dead_letter() {
+            return false;
+        }
+        self.retry_count = 0;
+        self.reset_for_requeue();
+        true
+    }
+
+    /// Queue a bead whose attempt was cut short (e.g. by shutdown)
+    ///
+    /// The attempt wasn't the bead's fault, so no retry is used.
+    pub fn requeue_interrupted(&mut self) {
+        self.reset_for_requeue();
+    }
+
+    /// Move the bead's deadline, never to before `now`
+    ///
+    /// A shift also moves the deferred time, so a deferred bead keeps its
+    /// place relative to its deadline; an absolute deadline leaves it alone.
+    /// Returns whether anything changed.
+    pub fn reschedule(&mut self, change: Reschedule, now: DateTime<Utc>) -> bool {
+        let before = (self.deadline, self.deferred_until);
+        match change {
+            Reschedule::Shift(by) => {
+                self.deadline = self.deadline.map(|at| (at + by).max(now));
+                self.deferred_until = self.deferred_until.map(|at| (at + by).max(now));
+            }
+            Reschedule::Deadline(at) => self.deadline = Some(at.max(now)),
+        }
+        (self.deadline, self.deferred_until) != before
+    }
+
+    /// Queue the bead again, clearing the previous attempt
+    fn reset_for_requeue(&mut self) {
+        self.status = BeadStatus::Queued;
+        self.error = None;
+        self.error_class = None;
+        self.output = None;
+        self.actual_tokens = None;
+        self.started_at = None;
+        self.completed_at = None;
+    }
+
     /// Check if all dependencies are complete
     pub fn dependencies_met(&self, completed: &std::collections::HashSet<BeadId>) -> bool {
         self.dependencies.iter().all(|dep| completed.contains(dep))
@@ -404,6 +785,23 @@ impl Bead {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_provider_switch_after_repeated_failures() {
+        let mut bead =
+            Bead::new("Review", "review", TaskType::Review).with_provider(Provider::Gemini);
+        assert_eq!(bead.dispatch_provider(), Provider::Gemini);
+
+        assert!(!bead.record_provider_failure(Provider::Gemini, 2));
+        assert!(bead.record_provider_failure(Provider::Gemini, 2));
+        assert_eq!(bead.provider_failures, 0);
+        // Preferred provider excluded: fall through to Review's affinity order
+        assert_eq!(bead.dispatch_provider(), Provider::Codex);
+
+        bead.excluded_providers = Provider::all().collect();
+        assert_eq!(bead.dispatch_provider(), Provider::Gemini);
+        assert!(!bead.record_provider_failure(Provider::Gemini, 0));
+    }
+
     #[test]
     fn test_bead_id_generation() {
         let id1 = BeadId::new();
@@ -435,6 +833,143 @@ mod tests {
         assert_eq!(bead.estimated_tokens, 5000);
     }
 
+    #[test]
+    fn test_prompt_hash() {
+        let hash = hash_prompt("hello");
+        assert_eq!(
+            hash,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+
+        let provenance = Provenance::new(Provider::Claude, "claude-sonnet-4", "hello", "cli");
+        assert!(provenance.matches_prompt("hello"));
+        assert!(!provenance.matches_prompt("hello "));
+    }
+
+    #[test]
+    fn test_context_render_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "remember this\n").unwrap();
+
+        let context = BeadContext {
+            working_dir: Some(dir.path().to_path_buf()),
+            files: vec![PathBuf::from("notes.txt")],
+        };
+        let prompt = context.render_prompt("Summarize").unwrap();
+        assert_eq!(
+            prompt,
+            "Summarize\n\n--- File: notes.txt ---\nremember this"
+        );
+
+        let missing = BeadContext {
+            working_dir: None,
+            files: vec![dir.path().join("missing.txt")],
+        };
+        assert!(missing.render_prompt("x").is_err());
+        assert!(BeadContext::default().is_empty());
+    }
+
+    #[test]
+    fn test_transition_follows_the_status_graph() {
+        let mut bead = Bead::new("Parser", "Write a parser", TaskType::Implementation);
+        for next in [
+            BeadStatus::Optimizing,
+            BeadStatus::Queued,
+            BeadStatus::Assigned,
+            BeadStatus::InProgress,
+        ] {
+            bead.transition(next).unwrap();
+        }
+        assert!(bead.started_at.is_some());
+        assert!(bead.completed_at.is_none());
+        bead.transition(BeadStatus::Reviewing).unwrap();
+        bead.transition(BeadStatus::Completed).unwrap();
+        assert!(bead.completed_at.is_some());
+
+        let err = bead.transition(BeadStatus::InProgress).unwrap_err();
+        assert!(matches!(
+            err,
+            RigsError::InvalidStateTransition {
+                from: BeadStatus::Completed,
+                to: BeadStatus::InProgress
+            }
+        ));
+        assert_eq!(bead.status, BeadStatus::Completed);
+
+        // Deferral, failure and retry edges
+        let mut bead = Bead::new("Docs", "Write docs", TaskType::Documentation);
+        bead.transition(BeadStatus::Deferred).unwrap();
+        bead.transition(BeadStatus::Queued).unwrap();
+        bead.transition(BeadStatus::InProgress).unwrap();
+        bead.transition(BeadStatus::Failed).unwrap();
+        bead.transition(BeadStatus::Queued).unwrap();
+        bead.transition(BeadStatus::Cancelled).unwrap();
+        assert!(bead.transition(BeadStatus::Queued).is_err());
+    }
+
+    #[test]
+    fn test_illegal_status_transitions() {
+        let illegal = [
+            (BeadStatus::Pending, BeadStatus::Completed),
+            (BeadStatus::Pending, BeadStatus::Reviewing),
+            (BeadStatus::Queued, BeadStatus::Pending),
+            (BeadStatus::Assigned, BeadStatus::Completed),
+            (BeadStatus::Reviewing, BeadStatus::InProgress),
+            (BeadStatus::Deferred, BeadStatus::Deferred),
+            (BeadStatus::Failed, BeadStatus::Completed),
+            (BeadStatus::Failed, BeadStatus::Cancelled),
+            (BeadStatus::Cancelled, BeadStatus::Queued),
+        ];
+        for (from, to) in illegal {
+            assert!(!from.can_transition_to(to), "{} -> {}", from, to);
+        }
+        for to in [
+            BeadStatus::Pending,
+            BeadStatus::Queued,
+            BeadStatus::InProgress,
+            BeadStatus::Failed,
+            BeadStatus::Cancelled,
+        ] {
+            assert!(!BeadStatus::Completed.can_transition_to(to));
+        }
+    }
+
+    #[test]
+    fn test_requeue_for_retry() {
+        let mut bead = Bead::new("Flaky", "flaky", TaskType::Test);
+        assert!(!bead.requeue_for_retry()); // Pending beads aren't retried
+
+        bead.max_retries = 1;
+        bead.status = BeadStatus::Failed;
+        bead.error = Some("timeout".into());
+        assert!(bead.requeue_for_retry());
+        assert_eq!(bead.status, BeadStatus::Queued);
+        assert_eq!(bead.retry_count, 1);
+        assert!(bead.error.is_none());
+
+        bead.status = BeadStatus::Failed;
+        assert!(!bead.can_retry());
+        assert!(!bead.requeue_for_retry());
+        assert_eq!(bead.status, BeadStatus::Failed);
+    }
+
+    #[test]
+    fn test_requeue_dead_letter() {
+        let mut bead = Bead::new("Flaky", "flaky", TaskType::Test);
+        bead.status = BeadStatus::Failed;
+        assert!(!bead.is_dead_letter()); // Still has retries
+        assert!(!bead.requeue_dead_letter());
+
+        bead.retry_count = bead.max_retries;
+        bead.error = Some("timeout".into());
+        assert!(bead.is_dead_letter());
+        assert!(bead.requeue_dead_letter());
+        assert_eq!(bead.status, BeadStatus::Queued);
+        assert_eq!(bead.retry_count, 0);
+        assert!(bead.error.is_none());
+        assert!(!bead.is_dead_letter());
+    }
+
     #[test]
     fn test_task_type_affinities() {
         let affinities = TaskType::Implementation.provider_affinities();
@@ -444,4 +979,23 @@ mod tests {
         let research_affinities = TaskType::Research.provider_affinities();
         assert_eq!(research_affinities[0].0, Provider::Gemini);
     }
+
+    #[test]
+    fn test_reschedule_never_lands_in_the_past() {
+        use chrono::{Duration, TimeZone};
+
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        let mut bead = Bead::new("Ship", "ship", TaskType::Implementation);
+        assert!(!bead.reschedule(Reschedule::Shift(Duration::days(1)), now));
+
+        bead.deadline = Some(now + Duration::days(1));
+        bead.deferred_until = Some(now + Duration::hours(2));
+        assert!(bead.reschedule(Reschedule::Shift(Duration::hours(-3)), now));
+        assert_eq!(bead.deadline, Some(now + Duration::hours(21)));
+        assert_eq!(bead.deferred_until, Some(now));
+
+        assert!(bead.reschedule(Reschedule::Deadline(now - Duration::days(1)), now));
+        assert_eq!(bead.deadline, Some(now));
+        assert_eq!(bead.deferred_until, Some(now));
+    }
 }
@@ -0,0 +1,145 @@
+//! Spending limits for a run
+//!
+//! A budget caps the tokens, estimated cost, or wall-clock time a run may
+//! use. The Foreman reserves each bead's estimate before dispatching it and
+//! defers beads that would push the run over any limit.
+
+use chrono::{DateTime, Utc};
+
+use super::pricing::PricingTable;
+use super::provider::Provider;
+
+/// Limits for a run; `None` means unlimited
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Budget {
+    pub max_tokens: Option<u64>,
+    /// Maximum estimated cost in USD
+    pub max_cost: Option<f64>,
+    /// No new beads are dispatched after this time
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+impl Budget {
+    /// Check if no limit is set
+    pub fn is_unlimited(&self) -> bool {
+        self.max_tokens.is_none() && self.max_cost.is_none() && self.deadline.is_none()
+    }
+}
+
+/// Running total of what a run has committed against its budget
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    budget: Budget,
+    pricing: PricingTable,
+    tokens: u64,
+    cost: f64,
+}
+
+impl BudgetTracker {
+    pub fn new(budget: Budget, pricing: PricingTable) -> Self {
+        Self {
+            budget,
+            pricing,
+            tokens: 0,
+            cost: 0.0,
+        }
+    }
+
+    /// Reserve `tokens` on `provider` if that stays within budget
+    ///
+    /// Returns the reason the reservation was refused, if it was.
+    pub fn reserve(
+        &mut self,
+        provider: Provider,
+        tokens: u64,
+        now: DateTime<Utc>,
+    ) -> Result<(), String> {
+        if let Some(deadline) = self.budget.deadline {
+            if now >= deadline {
+                return Err(format!("deadline {} has passed", deadline.to_rfc3339()));
+            }
+        }
+        if let Some(max) = self.budget.max_tokens {
+            if self.tokens + tokens > max {
+                return Err(format!(
+                    "token budget of {} would be exceeded ({} committed, {} needed)",
+                    max, self.tokens, tokens
+                ));
+            }
+        }
+        let cost = self.pricing.cost(provider, tokens);
+        if let Some(max) = self.budget.max_cost {
+            if self.cost + cost > max {
+                return Err(format!(
+                    "cost budget of ${:.2} would be exceeded (${:.2} committed, ${:.2} needed)",
+                    max, self.cost, cost
+                ));
+            }
+        }
+
+        self.tokens += tokens;
+        self.cost += cost;
+        Ok(())
+    }
+
+    /// Replace a reservation with what was actually used
+    pub fn settle(&mut self, provider: Provider, reserved: u64, actual: u64) {
+        self.tokens = self.tokens - reserved.min(self.tokens) + actual;
+        self.cost = (self.cost - self.pricing.cost(provider, reserved)).max(0.0)
+            + self.pricing.cost(provider, actual);
+    }
+
+    /// Tokens committed so far
+    pub fn tokens(&self) -> u64 {
+        self.tokens
+    }
+
+    /// Estimated USD committed so far
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_reserve_respects_each_limit() {
+        let now = Utc::now();
+        let mut tokens = BudgetTracker::new(
+            Budget {
+                max_tokens: Some(2_000),
+                ..Budget::default()
+            },
+            PricingTable::default(),
+        );
+        assert!(tokens.reserve(Provider::Claude, 1_500, now).is_ok());
+        assert!(tokens.reserve(Provider::Claude, 1_000, now).is_err());
+        tokens.settle(Provider::Claude, 1_500, 800);
+        assert!(tokens.reserve(Provider::Claude, 1_000, now).is_ok());
+        assert_eq!(tokens.tokens(), 1_800);
+
+        let mut cost = BudgetTracker::new(
+            Budget {
+                max_cost: Some(0.02),
+                ..Budget::default()
+            },
+            PricingTable::default(),
+        );
+        assert!(cost.reserve(Provider::Ollama, 1_000_000, now).is_ok());
+        assert!(cost.reserve(Provider::Claude, 1_000, now).is_ok());
+        let reason = cost.reserve(Provider::Claude, 1_000, now).unwrap_err();
+        assert!(reason.contains("cost budget"));
+
+        let mut deadline = BudgetTracker::new(
+            Budget {
+                deadline: Some(now - Duration::minutes(1)),
+                ..Budget::default()
+            },
+            PricingTable::default(),
+        );
+        assert!(deadline.reserve(Provider::Claude, 1, now).is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Convoy export bundles
+//!
+//! A bundle is a portable JSON snapshot of a convoy and its beads. Bundles
+//! carry a SHA-256 checksum of their canonical serialization and, when a
+//! signing key is configured, an HMAC-SHA256 signature, so that truncated or
+//! tampered bundles are rejected on import.
+//!
+//! Importing never silently changes a bundle: ids that collide with the
+//! workspace are reassigned and dependencies on beads outside the bundle are
+//! dropped, and both are listed in an [`ImportReport`].
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+use super::bead::{Bead, BeadId};
+use super::convoy::Convoy;
+use super::error::{Result, RigsError};
+
+/// Current bundle format version
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Portable snapshot of a convoy and its beads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvoyBundle {
+    /// Bundle format version
+    pub format_version: u32,
+    /// When the bundle was exported
+    pub exported_at: DateTime<Utc>,
+    /// The exported convoy
+    pub convoy: Convoy,
+    /// The convoy's beads
+    pub beads: Vec<Bead>,
+    /// Hex SHA-256 of the canonical serialization (without checksum/signature)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// Hex HMAC-SHA256 of the canonical serialization
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl ConvoyBundle {
+    /// Create an unsealed bundle
+    pub fn new(convoy: Convoy, beads: Vec<Bead>) -> Self {
+        Self {
+            format_version: BUNDLE_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            convoy,
+            beads,
+            checksum: None,
+            signature: None,
+        }
+    }
+
+    /// Canonical serialization covered by the checksum and signature
+    ///
+    /// Goes through `serde_json::Value` so object keys (including convoy
+    /// metadata) are emitted in sorted order regardless of map iteration.
+    pub fn canonical_json(&self) -> Result<Vec<u8>> {
+        let unsealed = Self {
+            checksum: None,
+            signature: None,
+            ..self.clone()
+        };
+        let value = serde_json::to_value(&unsealed)?;
+        Ok(serde_json::to_vec(&value)?)
+    }
+
+    /// Compute the checksum and, if a key is given, the signature
+    pub fn seal(&mut self, signing_key: Option<&[u8]>) -> Result<()> {
+        let canonical = self.canonical_json()?;
+        self.checksum = Some(hex::encode(Sha256::digest(&canonical)));
+        self.signature = match signing_key {
+            Some(key) => {
+                let mut mac = new_mac(key)?;
+                mac.update(&canonical);
+                Some(hex::encode(mac.finalize().into_bytes()))
+            }
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// Check the checksum and signature against the bundle contents
+    ///
+    /// With a signing key the bundle must carry a valid signature. Without
+    /// one, a signed bundle is rejected since it can't be verified.
+    pub fn verify(&self, signing_key: Option<&[u8]>) -> Result<()> {
+        let canonical = self.canonical_json()?;
+
+        if let Some(checksum) = &self.checksum {
+            if *checksum != hex::encode(Sha256::digest(&canonical)) {
+                return Err(RigsError::InvalidBundle(
+                    "checksum mismatch: bundle contents were modified".into(),
+                ));
+            }
+        }
+
+        match (signing_key, &self.signature) {
+            (Some(key), Some(signature)) => {
+                let signature = hex::decode(signature)
+                    .map_err(|_| RigsError::InvalidBundle("signature is not valid hex".into()))?;
+                let mut mac = new_mac(key)?;
+                mac.update(&canonical);
+                mac.verify_slice(&signature).map_err(|_| {
+                    RigsError::InvalidBundle("signature does not match signing key".into())
+                })
+            }
+            (Some(_), None) => Err(RigsError::InvalidBundle(
+                "bundle is unsigned but a signing key is configured".into(),
+            )),
+            (None, Some(_)) => Err(RigsError::InvalidBundle(
+                "bundle is signed but no signing key is configured".into(),
+            )),
+            (None, None) => Ok(()),
+        }
+    }
+
+    /// Serialize a sealed bundle for writing to disk
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a bundle and verify it before returning it
+    pub fn from_json(json: &str, signing_key: Option<&[u8]>) -> Result<Self> {
+        let bundle: Self = serde_json::from_str(json).map_err(|e| {
+            RigsError::InvalidBundle(format!("malformed or truncated bundle: {}", e))
+        })?;
+        if bundle.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(RigsError::InvalidBundle(format!(
+                "unsupported format version {} (newest supported is {})",
+                bundle.format_version, BUNDLE_FORMAT_VERSION
+            )));
+        }
+        bundle.verify(signing_key)?;
+        Ok(bundle)
+    }
+}
+
+/// An id reassigned on import because it was already taken
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IdRemap {
+    pub old: String,
+    pub new: String,
+}
+
+/// A dependency removed on import because it points outside the bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DroppedDependency {
+    /// The imported bead (by its new id, if remapped)
+    pub bead_id: BeadId,
+    /// The missing dependency
+    pub dependency: BeadId,
+}
+
+/// What changed while importing a bundle
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportReport {
+    /// Set when the convoy id was already taken
+    pub convoy: Option<IdRemap>,
+    /// Bead ids that were already taken, in bundle order
+    pub beads: Vec<IdRemap>,
+    pub dropped_dependencies: Vec<DroppedDependency>,
+}
+
+impl ImportReport {
+    /// Whether the import changed anything
+    pub fn is_clean(&self) -> bool {
+        self.convoy.is_none() && self.beads.is_empty() && self.dropped_dependencies.is_empty()
+    }
+}
+
+impl ConvoyBundle {
+    /// Prepare a verified bundle's contents for insertion into a workspace
+    ///
+    /// Bead and convoy ids already present are replaced with fresh ones,
+    /// and every reference to them (dependencies, convoy membership) is
+    /// rewritten. Dependencies on beads the bundle doesn't contain can't be
+    /// satisfied reliably in another workspace, so they are removed.
+    pub fn into_import(
+        self,
+        existing_beads: &HashSet<BeadId>,
+        existing_convoys: &HashSet<String>,
+    ) -> (Convoy, Vec<Bead>, ImportReport) {
+        let mut report = ImportReport::default();
+        let mut convoy = self.convoy;
+        let mut beads = self.beads;
+
+        if existing_convoys.contains(&convoy.id) {
+            let new = uuid::Uuid::new_v4().to_string();
+            report.convoy = Some(IdRemap {
+                old: std::mem::replace(&mut convoy.id, new.clone()),
+                new,
+            });
+        }
+
+        let bundled: HashSet<BeadId> = beads.iter().map(|b| b.id.clone()).collect();
+        let mut taken: HashSet<BeadId> = existing_beads.union(&bundled).cloned().collect();
+        let mut remap: HashMap<BeadId, BeadId> = HashMap::new();
+        for bead in &beads {
+            if existing_beads.contains(&bead.id) {
+                let new = std::iter::repeat_with(BeadId::new)
+                    .find(|id| !taken.contains(id))
+                    .expect("id generator is unbounded");
+                taken.insert(new.clone());
+                report.beads.push(IdRemap {
+                    old: bead.id.to_string(),
+                    new: new.to_string(),
+                });
+                remap.insert(bead.id.clone(), new);
+            }
+        }
+        let renamed = |id: &BeadId| remap.get(id).cloned().unwrap_or_else(|| id.clone());
+
+        for bead in &mut beads {
+            bead.id = renamed(&bead.id);
+            bead.convoy_id = Some(convoy.id.clone());
+            let (kept, dropped): (Vec<BeadId>, Vec<BeadId>) = bead
+                .dependencies
+                .drain(..)
+                .partition(|dep| bundled.contains(dep));
+            bead.dependencies = kept.iter().map(renamed).collect();
+            report
+                .dropped_dependencies
+                .extend(dropped.into_iter().map(|dependency| DroppedDependency {
+                    bead_id: bead.id.clone(),
+                    dependency,
+                }));
+        }
+        convoy.beads = convoy.beads.iter().map(renamed).collect();
+
+        (convoy, beads, report)
+    }
+}
+
+fn new_mac(key: &[u8]) -> Result<HmacSha256> {
+    HmacSha256::new_from_slice(key)
+        .map_err(|e| RigsError::InvalidBundle(format!("invalid signing key: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::bead::TaskType;
+
+    fn sample_bundle() -> ConvoyBundle {
+        let beads = vec![
+            Bead::new("Research", "Research flows", TaskType::Research),
+            Bead::new("Implement", "Implement client", TaskType::Implementation),
+        ];
+        let mut convoy = Convoy::new("OAuth");
+        convoy.set_metadata("team", "platform");
+        convoy.set_metadata("ticket", "AUTH-1");
+        for bead in &beads {
+            convoy.add_bead(bead.id.clone());
+        }
+        ConvoyBundle::new(convoy, beads)
+    }
+
+    #[test]
+    fn test_checksummed_bundle_round_trip() {
+        let mut bundle = sample_bundle();
+        bundle.seal(None).unwrap();
+
+        let json = bundle.to_json().unwrap();
+        let imported = ConvoyBundle::from_json(&json, None).unwrap();
+        assert_eq!(imported.convoy.id, bundle.convoy.id);
+        assert_eq!(imported.beads.len(), 2);
+    }
+
+    #[test]
+    fn test_signed_bundle_round_trip() {
+        let mut bundle = sample_bundle();
+        bundle.seal(Some(b"team-secret")).unwrap();
+        assert!(bundle.signature.is_some());
+
+        let json = bundle.to_json().unwrap();
+        assert!(ConvoyBundle::from_json(&json, Some(b"team-secret")).is_ok());
+        assert!(ConvoyBundle::from_json(&json, Some(b"wrong-secret")).is_err());
+        assert!(ConvoyBundle::from_json(&json, None).is_err());
+    }
+
+    #[test]
+    fn test_mutated_bundle_rejected() {
+        let mut bundle = sample_bundle();
+        bundle.seal(Some(b"team-secret")).unwrap();
+        let json = bundle.to_json().unwrap();
+
+        let tampered = json.replace("Implement client", "Exfiltrate secrets");
+        let err = ConvoyBundle::from_json(&tampered, Some(b"team-secret")).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+
+        let truncated = &json[..json.len() / 2];
+        let err = ConvoyBundle::from_json(truncated, Some(b"team-secret")).unwrap_err();
+        assert!(err.to_string().contains("truncated"));
+    }
+
+    #[test]
+    fn test_import_remaps_collisions_and_drops_external_dependencies() {
+        let mut bundle = sample_bundle();
+        let external = BeadId::new();
+        let research = bundle.beads[0].id.clone();
+        bundle.beads[1].dependencies = vec![research.clone(), external.clone()];
+        let convoy_id = bundle.convoy.id.clone();
+
+        let existing_beads = HashSet::from([research.clone()]);
+        let existing_convoys = HashSet::from([convoy_id.clone()]);
+        let (convoy, beads, report) = bundle.into_import(&existing_beads, &existing_convoys);
+
+        let renamed = &report.convoy.as_ref().unwrap();
+        assert_eq!(renamed.old, convoy_id);
+        assert_eq!(renamed.new, convoy.id);
+        assert_eq!(report.beads.len(), 1);
+        assert_eq!(report.beads[0].old, research.to_string());
+        let new_research = beads[0].id.clone();
+        assert_ne!(new_research, research);
+        assert_eq!(report.beads[0].new, new_research.to_string());
+
+        // The internal dependency follows the rename; the external one is gone
+        assert_eq!(beads[1].dependencies, vec![new_research.clone()]);
+        assert_eq!(
+            report.dropped_dependencies,
+            vec![DroppedDependency {
+                bead_id: beads[1].id.clone(),
+                dependency: external,
+            }]
+        );
+        assert_eq!(convoy.beads[0], new_research);
+        assert!(beads
+            .iter()
+            .all(|b| b.convoy_id.as_deref() == Some(convoy.id.as_str())));
+    }
+
+    #[test]
+    fn test_clean_import() {
+        let bundle = sample_bundle();
+        let ids: Vec<BeadId> = bundle.beads.iter().map(|b| b.id.clone()).collect();
+        let (_, beads, report) = bundle.into_import(&HashSet::new(), &HashSet::new());
+        assert!(report.is_clean());
+        assert_eq!(beads.iter().map(|b| b.id.clone()).collect::<Vec<_>>(), ids);
+    }
+
+    #[test]
+    fn test_resigned_tampering_detected_by_signature() {
+        let mut bundle = sample_bundle();
+        bundle.seal(Some(b"team-secret")).unwrap();
+
+        // An attacker without the key can recompute the checksum, not the signature
+        bundle.beads[0].description = "Changed".into();
+        let signature = bundle.signature.clone();
+        bundle.seal(None).unwrap();
+        bundle.signature = signature;
+
+        let err = bundle.verify(Some(b"team-secret")).unwrap_err();
+        assert!(err.to_string().contains("signature"));
+    }
+}
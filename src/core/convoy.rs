@@ -4,10 +4,13 @@
 //! decomposing a high-level goal.
 
 use chrono::{DateTime, Utc};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use super::bead::{BeadId, BeadStatus};
+use super::bead::{Bead, BeadId, BeadStatus};
+use super::error::{Result, RigsError};
+use super::graph::detect_cycle;
 
 /// Unique identifier for a convoy
 pub type ConvoyId = String;
@@ -26,14 +29,24 @@ pub enum ConvoyStatus {
     Paused,
     /// All beads completed successfully
     Completed,
+    /// All beads finished, but some of them failed
+    CompletedWithErrors,
     /// One or more beads failed
     Failed,
+    /// Retired without running (e.g. merged into another convoy)
+    Archived,
 }
 
 impl ConvoyStatus {
     /// Check if this is a terminal status
     pub fn is_terminal(&self) -> bool {
-        matches!(self, ConvoyStatus::Completed | ConvoyStatus::Failed)
+        matches!(
+            self,
+            ConvoyStatus::Completed
+                | ConvoyStatus::CompletedWithErrors
+                | ConvoyStatus::Failed
+                | ConvoyStatus::Archived
+        )
     }
 }
 
@@ -56,6 +69,13 @@ pub struct Convoy {
     pub completed_at: Option<DateTime<Utc>>,
     /// Arbitrary metadata
     pub metadata: HashMap<String, String>,
+    /// Convoys this one builds on; its beads may depend on their beads
+    #[serde(default)]
+    pub depends_on: Vec<ConvoyId>,
+    /// Criteria for the convoy as a whole, checked by the quality gate once
+    /// every bead has finished
+    #[serde(default)]
+    pub acceptance_criteria: Vec<String>,
 }
 
 impl Convoy {
@@ -70,6 +90,8 @@ impl Convoy {
             created_at: Utc::now(),
             completed_at: None,
             metadata: HashMap::new(),
+            depends_on: Vec::new(),
+            acceptance_criteria: Vec::new(),
         }
     }
 
@@ -84,9 +106,21 @@ impl Convoy {
             created_at: Utc::now(),
             completed_at: None,
             metadata: HashMap::new(),
+            depends_on: Vec::new(),
+            acceptance_criteria: Vec::new(),
         }
     }
 
+    /// Generate a convoy name from a goal, unique among `existing` names
+    ///
+    /// The name is a kebab-case slug of the goal's first few significant
+    /// words plus a short random suffix, e.g. "oauth2-authentication-k3x9q".
+    pub fn name_from_goal(goal: &str, existing: &HashSet<String>) -> String {
+        unique_name(&slugify_goal(goal), existing, || {
+            random_suffix(NAME_SUFFIX_LEN)
+        })
+    }
+
     /// Add a bead to the convoy
     pub fn add_bead(&mut self, bead_id: BeadId) {
         if !self.beads.contains(&bead_id) {
@@ -94,6 +128,87 @@ impl Convoy {
         }
     }
 
+    /// Add beads to the convoy, refusing them if their dependencies form a cycle
+    ///
+    /// `beads` are checked against each other only, so pass the convoy's
+    /// current beads too when adding to a convoy that already has some.
+    pub fn add_beads(&mut self, beads: &[Bead]) -> Result<()> {
+        if let Some(cycle) = detect_cycle(beads) {
+            return Err(RigsError::DependencyCycle(cycle));
+        }
+        for bead in beads {
+            self.add_bead(bead.id.clone());
+        }
+        Ok(())
+    }
+
+    /// Group the convoy's beads into layers that respect their dependencies
+    ///
+    /// Each bead's dependencies are all in earlier layers, so the beads of a
+    /// layer can run concurrently. Layers keep the convoy's bead order.
+    /// Fails with `UnmetDependencies` if a bead depends on one outside the
+    /// convoy, and with `DependencyCycle` if the dependencies loop.
+    pub fn execution_order(&self, beads: &HashMap<BeadId, Bead>) -> Result<Vec<Vec<BeadId>>> {
+        let mut waiting_on: HashMap<&BeadId, usize> = HashMap::new();
+        let mut dependents: HashMap<&BeadId, Vec<&BeadId>> = HashMap::new();
+        for id in &self.beads {
+            let bead = beads
+                .get(id)
+                .ok_or_else(|| RigsError::BeadNotFound(id.clone()))?;
+            let deps: HashSet<&BeadId> = bead.dependencies.iter().collect();
+            if deps.iter().any(|dep| !self.beads.contains(dep)) {
+                return Err(RigsError::UnmetDependencies(id.clone()));
+            }
+            waiting_on.insert(id, deps.len());
+            for dep in deps {
+                dependents.entry(dep).or_default().push(id);
+            }
+        }
+
+        // Kahn's algorithm, releasing a whole layer at a time
+        let mut layers: Vec<Vec<BeadId>> = Vec::new();
+        let mut ready: HashSet<&BeadId> = waiting_on
+            .iter()
+            .filter(|(_, waiting)| **waiting == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        while !ready.is_empty() {
+            let layer: Vec<BeadId> = self
+                .beads
+                .iter()
+                .filter(|id| ready.contains(id))
+                .cloned()
+                .collect();
+            let mut next = HashSet::new();
+            for id in ready {
+                waiting_on.remove(id);
+                for dependent in dependents.get(id).into_iter().flatten() {
+                    if let Some(waiting) = waiting_on.get_mut(dependent) {
+                        *waiting -= 1;
+                        if *waiting == 0 {
+                            next.insert(*dependent);
+                        }
+                    }
+                }
+            }
+            layers.push(layer);
+            ready = next;
+        }
+
+        if !waiting_on.is_empty() {
+            let stuck: Vec<Bead> = self
+                .beads
+                .iter()
+                .filter(|id| waiting_on.contains_key(id))
+                .map(|id| beads[id].clone())
+                .collect();
+            let cycle =
+                detect_cycle(&stuck).unwrap_or_else(|| stuck.into_iter().map(|b| b.id).collect());
+            return Err(RigsError::DependencyCycle(cycle));
+        }
+        Ok(layers)
+    }
+
     /// Calculate progress (0.0 to 1.0)
     pub fn progress(&self, bead_statuses: &HashMap<BeadId, BeadStatus>) -> f32 {
         if self.beads.is_empty() {
@@ -117,7 +232,7 @@ impl Convoy {
     /// Count beads by status
     pub fn status_counts(&self, bead_statuses: &HashMap<BeadId, BeadStatus>) -> StatusCounts {
         let mut counts = StatusCounts::default();
-        
+
         for id in &self.beads {
             match bead_statuses.get(id) {
                 Some(BeadStatus::Completed) => counts.completed += 1,
@@ -129,7 +244,7 @@ impl Convoy {
                 _ => counts.pending += 1,
             }
         }
-        
+
         counts
     }
 
@@ -153,12 +268,277 @@ impl Convoy {
         })
     }
 
+    /// Requeue this convoy's failed beads for another attempt
+    ///
+    /// Only `Failed` beads with retry budget left are reset; completed beads
+    /// are untouched. If anything was requeued the convoy becomes active again.
+    pub fn retry_failed(&mut self, beads: &mut [Bead]) -> RetrySummary {
+        let mut summary = RetrySummary::default();
+
+        for bead in beads.iter_mut().filter(|b| self.beads.contains(&b.id)) {
+            if bead.status != BeadStatus::Failed {
+                continue;
+            }
+            if bead.requeue_for_retry() {
+                summary.requeued.push(bead.id.clone());
+            } else {
+                summary.exhausted.push(bead.id.clone());
+            }
+        }
+
+        if !summary.requeued.is_empty() {
+            self.status = ConvoyStatus::InProgress;
+            self.completed_at = None;
+        }
+
+        summary
+    }
+
+    /// Move every bead of `src` into this convoy and archive `src`
+    ///
+    /// `beads` must hold the beads of both convoys; moved beads get their
+    /// `convoy_id` updated and keep their dependencies. This convoy's status
+    /// is then recomputed from the combined beads. Merging a convoy into
+    /// itself, or merging finished convoys, is rejected. Returns the number
+    /// of beads moved.
+    pub fn merge_from(&mut self, src: &mut Convoy, beads: &mut [Bead]) -> Result<usize> {
+        if src.id == self.id {
+            return Err(RigsError::InvalidMerge(
+                "cannot merge a convoy into itself".into(),
+            ));
+        }
+        for convoy in [&*src, &*self] {
+            if convoy.status.is_terminal() {
+                return Err(RigsError::InvalidMerge(format!(
+                    "convoy {} is {:?}",
+                    convoy.name, convoy.status
+                )));
+            }
+        }
+
+        let mut moved = 0;
+        for bead in beads.iter_mut() {
+            if bead.convoy_id.as_deref() == Some(src.id.as_str()) || src.beads.contains(&bead.id) {
+                bead.convoy_id = Some(self.id.clone());
+                self.add_bead(bead.id.clone());
+                moved += 1;
+            }
+        }
+
+        src.beads.clear();
+        src.status = ConvoyStatus::Archived;
+        src.completed_at = Some(Utc::now());
+        src.set_metadata("merged_into", self.id.clone());
+
+        let statuses: HashMap<BeadId, BeadStatus> =
+            beads.iter().map(|b| (b.id.clone(), b.status)).collect();
+        self.refresh_status(&statuses);
+        Ok(moved)
+    }
+
+    /// Copy this convoy and its beads as a fresh, queued convoy named `name`
+    ///
+    /// The copies get new ids and keep each bead's task content (title,
+    /// description, type, priority, criteria, context and estimate) plus the
+    /// dependencies between them, remapped to the new ids. Dependencies on
+    /// beads outside the convoy are kept as they are. Everything about the
+    /// previous run (status, output, timestamps, retries) starts over.
+    pub fn clone_as(&self, name: impl Into<String>, beads: &[Bead]) -> (Convoy, Vec<Bead>) {
+        let mut convoy = Convoy::new(name);
+        convoy.goal = self.goal.clone();
+        convoy.metadata = self.metadata.clone();
+        convoy.depends_on = self.depends_on.clone();
+        convoy.acceptance_criteria = self.acceptance_criteria.clone();
+        convoy.set_metadata("cloned_from", self.id.clone());
+        convoy.status = ConvoyStatus::Queued;
+
+        let members: Vec<&Bead> = self
+            .beads
+            .iter()
+            .filter_map(|id| beads.iter().find(|b| &b.id == id))
+            .collect();
+        let remap: HashMap<BeadId, BeadId> = members
+            .iter()
+            .map(|b| (b.id.clone(), BeadId::new()))
+            .collect();
+
+        let copies = members
+            .into_iter()
+            .map(|original| {
+                let mut bead = Bead::new(
+                    original.title.clone(),
+                    original.description.clone(),
+                    original.task_type,
+                );
+                bead.id = remap[&original.id].clone();
+                bead.priority = original.priority;
+                bead.preferred_provider = original.preferred_provider;
+                bead.acceptance_criteria = original.acceptance_criteria.clone();
+                bead.context = original.context.clone();
+                bead.estimated_tokens = original.estimated_tokens;
+                bead.optimized_prompt = original.optimized_prompt.clone();
+                bead.max_retries = original.max_retries;
+                bead.dependencies = original
+                    .dependencies
+                    .iter()
+                    .map(|dep| remap.get(dep).unwrap_or(dep).clone())
+                    .collect();
+                bead.convoy_id = Some(convoy.id.clone());
+                convoy.add_bead(bead.id.clone());
+                bead
+            })
+            .collect();
+        (convoy, copies)
+    }
+
+    /// Recompute the status from the beads' statuses
+    ///
+    /// A convoy whose beads are all finished becomes Completed (or
+    /// CompletedWithErrors); one with started or finished work becomes
+    /// InProgress, so a merge that adds pending beads reopens it. Planning,
+    /// Queued and Paused convoys with nothing started are left alone.
+    pub fn refresh_status(&mut self, bead_statuses: &HashMap<BeadId, BeadStatus>) {
+        if !self.beads.is_empty() && self.is_complete(bead_statuses) {
+            self.status = if self.has_failures(bead_statuses) {
+                ConvoyStatus::CompletedWithErrors
+            } else {
+                ConvoyStatus::Completed
+            };
+            self.completed_at.get_or_insert_with(Utc::now);
+            return;
+        }
+
+        let started = self.beads.iter().any(|id| {
+            bead_statuses
+                .get(id)
+                .is_some_and(|s| s.is_active() || s.is_terminal())
+        });
+        if started && self.status != ConvoyStatus::Paused {
+            self.status = ConvoyStatus::InProgress;
+        }
+        self.completed_at = None;
+    }
+
     /// Set metadata value
     pub fn set_metadata(&mut self, key: impl Into<String>, value: impl Into<String>) {
         self.metadata.insert(key.into(), value.into());
     }
 }
 
+/// Dependencies of `bead` that reach outside its convoy's scope
+///
+/// A convoy bead may depend on beads of its own convoy and of the convoys it
+/// depends on, directly or transitively. `dependency_convoys` maps each
+/// dependency that exists to its convoy; missing ones aren't reported here.
+/// `convoy_deps` holds each convoy's direct prerequisites. Beads outside any
+/// convoy aren't checked.
+pub fn out_of_scope_dependencies(
+    bead: &Bead,
+    dependency_convoys: &HashMap<BeadId, Option<ConvoyId>>,
+    convoy_deps: &HashMap<ConvoyId, Vec<ConvoyId>>,
+) -> Vec<BeadId> {
+    let Some(own) = &bead.convoy_id else {
+        return Vec::new();
+    };
+    let mut scope: HashSet<&ConvoyId> = HashSet::from([own]);
+    let mut pending = vec![own];
+    while let Some(convoy) = pending.pop() {
+        for prerequisite in convoy_deps.get(convoy).into_iter().flatten() {
+            if scope.insert(prerequisite) {
+                pending.push(prerequisite);
+            }
+        }
+    }
+
+    bead.dependencies
+        .iter()
+        .filter(|dep| match dependency_convoys.get(*dep) {
+            Some(Some(convoy)) => !scope.contains(convoy),
+            Some(None) => true,
+            None => false,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Maximum number of goal words used in a generated convoy name
+const NAME_MAX_WORDS: usize = 4;
+
+/// Length of the random suffix appended to generated convoy names
+const NAME_SUFFIX_LEN: usize = 5;
+
+/// Filler words skipped when slugifying a goal
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "the", "to", "for", "of", "in", "on", "with", "into", "from", "by", "at",
+    "our", "my", "some", "please",
+];
+
+/// Turn a goal into a filesystem-safe kebab-case slug
+///
+/// Only ASCII alphanumerics survive; everything else separates words.
+/// Falls back to "convoy" when no significant words remain.
+pub fn slugify_goal(goal: &str) -> String {
+    let words: Vec<String> = goal
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_ascii_lowercase())
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .take(NAME_MAX_WORDS)
+        .collect();
+
+    if words.is_empty() {
+        "convoy".to_string()
+    } else {
+        words.join("-")
+    }
+}
+
+/// Append suffixes from `next_suffix` to `slug` until the name is unused
+fn unique_name(
+    slug: &str,
+    existing: &HashSet<String>,
+    mut next_suffix: impl FnMut() -> String,
+) -> String {
+    const MAX_ATTEMPTS: usize = 16;
+
+    for _ in 0..MAX_ATTEMPTS {
+        let name = format!("{}-{}", slug, next_suffix());
+        if !existing.contains(&name) {
+            return name;
+        }
+    }
+
+    // Suffix space is effectively exhausted; fall back to a counter
+    (2..)
+        .map(|n| format!("{}-{}", slug, n))
+        .find(|name| !existing.contains(name))
+        .expect("unbounded counter always yields a free name")
+}
+
+/// Generate a random lowercase alphanumeric suffix
+fn random_suffix(len: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..len)
+        .map(|_| {
+            let idx = rng.gen_range(0..36);
+            if idx < 10 {
+                (b'0' + idx) as char
+            } else {
+                (b'a' + idx - 10) as char
+            }
+        })
+        .collect()
+}
+
+/// Outcome of [`Convoy::retry_failed`]
+#[derive(Debug, Clone, Default)]
+pub struct RetrySummary {
+    /// Beads reset to `Queued`
+    pub requeued: Vec<BeadId>,
+    /// Failed beads left alone because their retry budget is spent
+    pub exhausted: Vec<BeadId>,
+}
+
 /// Counts of beads by status in a convoy
 #[derive(Debug, Clone, Default)]
 pub struct StatusCounts {
@@ -200,4 +580,306 @@ mod tests {
 
         assert!((convoy.progress(&statuses) - 0.5).abs() < 0.001);
     }
+
+    #[test]
+    fn test_add_beads_rejects_cycles() {
+        use crate::core::bead::TaskType;
+
+        let mut design = Bead::new("Design", "d", TaskType::Design);
+        let build = Bead::new("Build", "b", TaskType::Implementation)
+            .with_dependencies(vec![design.id.clone()]);
+        let mut convoy = Convoy::new("Plan");
+        convoy.add_beads(&[design.clone(), build.clone()]).unwrap();
+        assert_eq!(convoy.beads, vec![design.id.clone(), build.id.clone()]);
+
+        design.dependencies = vec![build.id.clone()];
+        let mut convoy = Convoy::new("Cyclic");
+        assert!(matches!(
+            convoy.add_beads(&[design, build]),
+            Err(RigsError::DependencyCycle(cycle)) if cycle.len() == 2
+        ));
+        assert!(convoy.beads.is_empty());
+    }
+
+    fn ordered(beads: &[Bead]) -> Result<Vec<Vec<BeadId>>> {
+        let mut convoy = Convoy::new("Ordered");
+        for bead in beads {
+            convoy.add_bead(bead.id.clone());
+        }
+        let beads = beads.iter().map(|b| (b.id.clone(), b.clone())).collect();
+        convoy.execution_order(&beads)
+    }
+
+    #[test]
+    fn test_execution_order_shapes() {
+        use crate::core::bead::TaskType;
+
+        let bead = |title: &str, deps: &[&Bead]| {
+            Bead::new(title, title, TaskType::Implementation)
+                .with_dependencies(deps.iter().map(|d| d.id.clone()).collect())
+        };
+
+        // Chain: one bead per layer
+        let a = bead("A", &[]);
+        let b = bead("B", &[&a]);
+        let c = bead("C", &[&b]);
+        assert_eq!(
+            ordered(&[c.clone(), a.clone(), b.clone()]).unwrap(),
+            vec![vec![a.id.clone()], vec![b.id.clone()], vec![c.id.clone()]]
+        );
+
+        // Fan-out: everything after the root runs together, in convoy order
+        let root = bead("Root", &[]);
+        let left = bead("Left", &[&root]);
+        let mid = bead("Mid", &[&root]);
+        let right = bead("Right", &[&root]);
+        assert_eq!(
+            ordered(&[right.clone(), root.clone(), left.clone(), mid.clone()]).unwrap(),
+            vec![
+                vec![root.id.clone()],
+                vec![right.id.clone(), left.id.clone(), mid.id.clone()]
+            ]
+        );
+
+        // Fan-in: the sink waits for its deepest dependency
+        let x = bead("X", &[]);
+        let y = bead("Y", &[]);
+        let z = bead("Z", &[&y]);
+        let sink = bead("Sink", &[&x, &z, &y]);
+        assert_eq!(
+            ordered(&[sink.clone(), x.clone(), y.clone(), z.clone()]).unwrap(),
+            vec![
+                vec![x.id.clone(), y.id.clone()],
+                vec![z.id.clone()],
+                vec![sink.id.clone()]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execution_order_rejects_bad_graphs() {
+        use crate::core::bead::TaskType;
+
+        let outside = Bead::new("Outside", "o", TaskType::Research);
+        let inside =
+            Bead::new("Inside", "i", TaskType::Design).with_dependencies(vec![outside.id.clone()]);
+        assert!(matches!(
+            ordered(std::slice::from_ref(&inside)),
+            Err(RigsError::UnmetDependencies(id)) if id == inside.id
+        ));
+
+        let mut first = Bead::new("First", "f", TaskType::Design);
+        let second =
+            Bead::new("Second", "s", TaskType::Design).with_dependencies(vec![first.id.clone()]);
+        first.dependencies = vec![second.id.clone()];
+        let after =
+            Bead::new("After", "a", TaskType::Test).with_dependencies(vec![second.id.clone()]);
+        let err = ordered(&[first.clone(), second.clone(), after]).unwrap_err();
+        assert!(matches!(
+            err,
+            RigsError::DependencyCycle(cycle) if cycle == vec![first.id, second.id]
+        ));
+    }
+
+    #[test]
+    fn test_retry_failed_requeues_only_failures() {
+        use crate::core::bead::TaskType;
+
+        let mut done = Bead::new("Done", "done", TaskType::Research);
+        done.status = BeadStatus::Completed;
+        done.output = Some("result".into());
+        let mut failed = Bead::new("Failed", "failed", TaskType::Implementation);
+        failed.status = BeadStatus::Failed;
+        failed.error = Some("provider error".into());
+        let mut spent = Bead::new("Spent", "spent", TaskType::Test);
+        spent.status = BeadStatus::Failed;
+        spent.retry_count = spent.max_retries;
+
+        let mut convoy = Convoy::new("Mixed");
+        for bead in [&done, &failed, &spent] {
+            convoy.add_bead(bead.id.clone());
+        }
+        convoy.status = ConvoyStatus::CompletedWithErrors;
+        convoy.completed_at = Some(Utc::now());
+
+        let mut beads = vec![done, failed, spent];
+        let summary = convoy.retry_failed(&mut beads);
+
+        assert_eq!(summary.requeued, vec![beads[1].id.clone()]);
+        assert_eq!(summary.exhausted, vec![beads[2].id.clone()]);
+        assert_eq!(convoy.status, ConvoyStatus::InProgress);
+        assert!(convoy.completed_at.is_none());
+
+        assert_eq!(beads[0].status, BeadStatus::Completed);
+        assert_eq!(beads[0].output.as_deref(), Some("result"));
+        assert_eq!(beads[1].status, BeadStatus::Queued);
+        assert!(beads[1].error.is_none());
+        assert_eq!(beads[2].status, BeadStatus::Failed);
+    }
+
+    #[test]
+    fn test_merge_moves_beads_and_archives_source() {
+        use crate::core::bead::TaskType;
+
+        let mut src = Convoy::new("Auth backend");
+        let mut dst = Convoy::new("Auth");
+        let mut beads: Vec<Bead> = (0..5)
+            .map(|i| Bead::new(format!("Step {}", i), "step", TaskType::Implementation))
+            .collect();
+        for (i, bead) in beads.iter_mut().enumerate() {
+            let convoy = if i < 3 { &mut src } else { &mut dst };
+            bead.convoy_id = Some(convoy.id.clone());
+            convoy.add_bead(bead.id.clone());
+        }
+        // Cross-convoy dependency survives the merge
+        beads[0].dependencies = vec![beads[3].id.clone()];
+        beads[3].status = BeadStatus::Completed;
+
+        assert_eq!(dst.merge_from(&mut src, &mut beads).unwrap(), 3);
+
+        assert_eq!(dst.beads.len(), 5);
+        assert!(beads
+            .iter()
+            .all(|b| b.convoy_id.as_deref() == Some(dst.id.as_str())));
+        assert_eq!(beads[0].dependencies, vec![beads[3].id.clone()]);
+        assert_eq!(dst.status, ConvoyStatus::InProgress);
+        assert!(src.beads.is_empty());
+        assert_eq!(src.status, ConvoyStatus::Archived);
+        assert_eq!(src.metadata["merged_into"], dst.id);
+    }
+
+    #[test]
+    fn test_clone_resets_beads_and_keeps_dependency_shape() {
+        use crate::core::bead::TaskType;
+
+        let mut done = Convoy::new("Auth");
+        let external = BeadId::new();
+        let mut beads: Vec<Bead> = (0..3)
+            .map(|i| Bead::new(format!("Step {}", i), "step", TaskType::Implementation))
+            .collect();
+        beads[1].dependencies = vec![beads[0].id.clone()];
+        beads[2].dependencies = vec![beads[0].id.clone(), beads[1].id.clone(), external.clone()];
+        for bead in &mut beads {
+            bead.convoy_id = Some(done.id.clone());
+            bead.status = BeadStatus::Completed;
+            bead.output = Some("done".into());
+            bead.actual_tokens = Some(1_000);
+            bead.completed_at = Some(Utc::now());
+            done.add_bead(bead.id.clone());
+        }
+        done.status = ConvoyStatus::Completed;
+
+        let (copy, cloned) = done.clone_as("Auth again", &beads);
+
+        assert_ne!(copy.id, done.id);
+        assert_eq!(copy.status, ConvoyStatus::Queued);
+        assert_eq!(copy.metadata["cloned_from"], done.id);
+        assert_eq!(
+            copy.beads,
+            cloned.iter().map(|b| b.id.clone()).collect::<Vec<_>>()
+        );
+        for (original, clone) in beads.iter().zip(&cloned) {
+            assert_ne!(clone.id, original.id);
+            assert_eq!(clone.title, original.title);
+            assert_eq!(clone.status, BeadStatus::Pending);
+            assert_eq!(clone.convoy_id.as_deref(), Some(copy.id.as_str()));
+            assert!(clone.output.is_none() && clone.actual_tokens.is_none());
+            assert!(clone.completed_at.is_none());
+        }
+        assert!(cloned[0].dependencies.is_empty());
+        assert_eq!(cloned[1].dependencies, vec![cloned[0].id.clone()]);
+        assert_eq!(
+            cloned[2].dependencies,
+            vec![cloned[0].id.clone(), cloned[1].id.clone(), external]
+        );
+    }
+
+    #[test]
+    fn test_dependency_scope() {
+        use crate::core::bead::TaskType;
+
+        let member = |convoy: &str| {
+            let mut bead = Bead::new("Step", "step", TaskType::Implementation);
+            bead.convoy_id = Some(convoy.to_string());
+            bead
+        };
+        let (backend, schema, docs) = (member("backend"), member("schema"), member("docs"));
+        let loose = Bead::new("Loose", "loose", TaskType::Research);
+        let convoys: HashMap<BeadId, Option<ConvoyId>> = [&backend, &schema, &docs, &loose]
+            .into_iter()
+            .map(|b| (b.id.clone(), b.convoy_id.clone()))
+            .collect();
+        // api builds on backend, which builds on schema
+        let convoy_deps = HashMap::from([
+            ("api".to_string(), vec!["backend".to_string()]),
+            ("backend".to_string(), vec!["schema".to_string()]),
+        ]);
+        let check = |deps: &[&Bead]| {
+            let bead = member("api").with_dependencies(deps.iter().map(|d| d.id.clone()).collect());
+            out_of_scope_dependencies(&bead, &convoys, &convoy_deps)
+        };
+
+        // Same convoy
+        let sibling = member("api");
+        let mut with_sibling = convoys.clone();
+        with_sibling.insert(sibling.id.clone(), sibling.convoy_id.clone());
+        let bead = member("api").with_dependencies(vec![sibling.id.clone()]);
+        assert!(out_of_scope_dependencies(&bead, &with_sibling, &convoy_deps).is_empty());
+
+        // Prerequisite convoys, direct and transitive
+        assert!(check(&[&backend, &schema]).is_empty());
+
+        // Unrelated convoy, or no convoy at all
+        assert_eq!(check(&[&backend, &docs]), vec![docs.id.clone()]);
+        assert_eq!(check(&[&loose]), vec![loose.id.clone()]);
+    }
+
+    #[test]
+    fn test_merge_rejects_self_and_terminal() {
+        let mut convoy = Convoy::new("Solo");
+        let mut same = convoy.clone();
+        assert!(matches!(
+            convoy.merge_from(&mut same, &mut []),
+            Err(RigsError::InvalidMerge(_))
+        ));
+
+        let mut done = Convoy::new("Done");
+        done.status = ConvoyStatus::Completed;
+        assert!(convoy.merge_from(&mut done, &mut []).is_err());
+        assert!(done.merge_from(&mut convoy, &mut []).is_err());
+        assert_eq!(convoy.status, ConvoyStatus::Planning);
+    }
+
+    #[test]
+    fn test_slugify_goal() {
+        assert_eq!(
+            slugify_goal("Add OAuth2 authentication with Google and GitHub"),
+            "add-oauth2-authentication-google"
+        );
+        assert_eq!(
+            slugify_goal("Fix the ../../etc/passwd bug!"),
+            "fix-etc-passwd-bug"
+        );
+        assert_eq!(slugify_goal("  ...  "), "convoy");
+    }
+
+    #[test]
+    fn test_generated_name_shape() {
+        let name = Convoy::name_from_goal("Write the docs", &HashSet::new());
+        let (slug, suffix) = name.rsplit_once('-').unwrap();
+        assert_eq!(slug, "write-docs");
+        assert_eq!(suffix.len(), NAME_SUFFIX_LEN);
+        assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+    }
+
+    #[test]
+    fn test_name_collision_suffixing() {
+        let existing: HashSet<String> = ["docs-aaaaa".to_string()].into_iter().collect();
+        let mut suffixes = vec!["bbbbb", "aaaaa"];
+        let name = unique_name("docs", &existing, || suffixes.pop().unwrap().to_string());
+        assert_eq!(name, "docs-bbbbb");
+
+        let name = unique_name("docs", &existing, || "aaaaa".to_string());
+        assert_eq!(name, "docs-2");
+    }
 }
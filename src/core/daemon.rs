@@ -0,0 +1,181 @@
+//! Foreman daemon state
+//!
+//! A running foreman records its PID and start time in `foreman.pid` in the
+//! workspace, so `rigs foreman status` and `stop` can find it and `start`
+//! can refuse to run a second one. A file left behind by a daemon that
+//! crashed is stale: its process is gone, and it is ignored and removed.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::error::{Result, RigsError};
+
+/// Name of the state file in the workspace
+pub const PID_FILE: &str = "foreman.pid";
+
+/// What a running foreman daemon records about itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonState {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+}
+
+impl DaemonState {
+    /// State for the current process, started now
+    pub fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            started_at: Utc::now(),
+        }
+    }
+
+    /// The state file for `workspace`
+    pub fn path(workspace: &Path) -> PathBuf {
+        workspace.join(PID_FILE)
+    }
+
+    /// Read the state file at `path`, `None` if there isn't one
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&content).map(Some).map_err(|e| {
+            RigsError::Other(format!("Corrupt foreman state {}: {}", path.display(), e))
+        })
+    }
+
+    /// Write the state file at `path`, creating its directory if needed
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Remove the state file at `path`, if it is there
+    pub fn remove(path: &Path) -> Result<()> {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// The daemon recorded at `path`, if its process is still alive
+    ///
+    /// A stale file is removed.
+    pub fn running(path: &Path) -> Result<Option<Self>> {
+        Self::running_with(path, is_alive)
+    }
+
+    /// Like [`DaemonState::running`], deciding liveness with `alive`
+    pub fn running_with(path: &Path, alive: impl Fn(u32) -> bool) -> Result<Option<Self>> {
+        match Self::read(path)? {
+            Some(state) if alive(state.pid) => Ok(Some(state)),
+            Some(_) => {
+                Self::remove(path)?;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Check whether process `pid` exists
+#[cfg(unix)]
+pub fn is_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Check whether process `pid` exists
+#[cfg(not(unix))]
+pub fn is_alive(pid: u32) -> bool {
+    // No cheap check; trust the state file
+    pid != 0
+}
+
+/// Ask process `pid` to shut down (SIGTERM), as Ctrl+C would
+#[cfg(unix)]
+pub fn terminate(pid: u32) -> Result<()> {
+    let status = std::process::Command::new("kill")
+        .args(["-TERM", &pid.to_string()])
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(RigsError::Other(format!(
+            "Failed to signal process {}",
+            pid
+        )))
+    }
+}
+
+/// Ask process `pid` to shut down
+#[cfg(not(unix))]
+pub fn terminate(pid: u32) -> Result<()> {
+    Err(RigsError::Other(format!(
+        "Stopping the foreman (PID {}) is only supported on Unix",
+        pid
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trips_and_removes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = DaemonState::path(dir.path());
+        assert_eq!(DaemonState::read(&path).unwrap(), None);
+
+        let state = DaemonState::current();
+        state.write(&path).unwrap();
+        assert_eq!(DaemonState::read(&path).unwrap(), Some(state));
+
+        DaemonState::remove(&path).unwrap();
+        assert!(!path.exists());
+        DaemonState::remove(&path).unwrap();
+
+        std::fs::write(&path, "12345").unwrap();
+        assert!(DaemonState::read(&path).is_err());
+    }
+
+    #[test]
+    fn test_stale_state_is_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = DaemonState::path(dir.path());
+        let state = DaemonState {
+            pid: 4242,
+            started_at: Utc::now(),
+        };
+        state.write(&path).unwrap();
+
+        assert_eq!(
+            DaemonState::running_with(&path, |pid| pid == 4242).unwrap(),
+            Some(state)
+        );
+        assert!(path.exists());
+
+        assert_eq!(DaemonState::running_with(&path, |_| false).unwrap(), None);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_liveness_of_real_processes() {
+        assert!(is_alive(std::process::id()));
+
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!is_alive(pid));
+    }
+}
@@ -1,10 +1,13 @@
 //! Error types for Rigs
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use thiserror::Error;
 
-use super::bead::{BeadId, BeadStatus};
-use super::provider::Provider;
+use super::bead::{BeadId, BeadStatus, InvalidBeadId};
+use super::executor::ExecutionDiagnostics;
+use super::provider::{Provider, UnknownProvider};
 
 /// Result type alias for Rigs operations
 pub type Result<T> = std::result::Result<T, RigsError>;
@@ -19,6 +22,9 @@ pub enum RigsError {
     #[error("Provider {0} is disabled")]
     ProviderDisabled(Provider),
 
+    #[error("{0}")]
+    UnknownProvider(#[from] UnknownProvider),
+
     #[error("Rate limit exceeded for {provider}: {remaining} tokens remaining, need {requested}")]
     RateLimitExceeded {
         provider: Provider,
@@ -32,13 +38,81 @@ pub enum RigsError {
     #[error("Provider {0} API error: {1}")]
     ProviderApiError(Provider, String),
 
+    /// A provider CLI exited unsuccessfully
+    #[error("Provider {provider} API error: {diagnostics}")]
+    CommandFailed {
+        provider: Provider,
+        diagnostics: Box<ExecutionDiagnostics>,
+    },
+
+    /// A failure worth resending the same request for (network error, 5xx)
+    #[error("Provider {0} temporarily unavailable: {1}")]
+    ProviderUnavailable(Provider, String),
+
+    /// The provider is busy for everyone (Anthropic 529); says nothing
+    /// about our own quota
+    #[error("Provider {0} overloaded: {1}")]
+    ProviderOverloaded(Provider, String),
+
+    /// The provider refused the request because our quota is used up (429)
+    #[error("Provider {provider} rate limited{}", until(.reset))]
+    ProviderRateLimited {
+        provider: Provider,
+        reset: Option<DateTime<Utc>>,
+    },
+
+    #[error(
+        "Prompt of ~{tokens} tokens exceeds {provider}'s context window of {max_context} tokens; {suggestion}"
+    )]
+    ContextTooLarge {
+        provider: Provider,
+        tokens: u64,
+        max_context: u64,
+        /// What the user can do about it
+        suggestion: String,
+    },
+
+    #[error(
+        "No execution providers are enabled and authenticated. \
+         Run `rigs provider add <claude|codex|gemini>` to configure one."
+    )]
+    NoExecutionProviders,
+
+    #[error(
+        "local_only is set but Ollama is disabled; enable [providers.ollama] or unset local_only"
+    )]
+    NoLocalProvider,
+
+    #[error("{provider} needs an API key in ${var}, which is unset or empty")]
+    MissingApiKey { provider: Provider, var: String },
+
+    #[error("The foreman is already running (PID {0}); stop it with `rigs foreman stop`")]
+    ForemanAlreadyRunning(u32),
+
+    #[error("None of the providers in --provider-order can take bead {0}")]
+    ProviderOrderExhausted(BeadId),
+
+    #[error("Bead {bead_id} can't be served locally under local_only: {reason}")]
+    NotServableLocally { bead_id: BeadId, reason: String },
+
     // Bead errors
     #[error("Bead {0} not found")]
     BeadNotFound(BeadId),
 
-    #[error("Invalid bead ID: {0}")]
+    #[error("Bead #{0} not found")]
+    BeadSeqNotFound(u64),
+
+    #[error(
+        "Bead {bead_id} depends on {dependency}, which is outside its convoy and the convoys it depends on"
+    )]
+    DependencyOutOfScope { bead_id: BeadId, dependency: BeadId },
+
+    #[error("Invalid bead ID '{0}': expected 'gt-' followed by 5 alphanumeric characters")]
     InvalidBeadId(String),
 
+    #[error("Bead {bead_id} is already {status:?}")]
+    BeadAlreadyFinished { bead_id: BeadId, status: BeadStatus },
+
     #[error("Invalid state transition: {from:?} -> {to:?}")]
     InvalidStateTransition { from: BeadStatus, to: BeadStatus },
 
@@ -49,9 +123,18 @@ pub enum RigsError {
     #[error("Convoy {0} not found")]
     ConvoyNotFound(String),
 
+    #[error("Convoy {0} finished with failed beads")]
+    ConvoyFailed(String),
+
+    #[error("Cannot merge convoys: {0}")]
+    InvalidMerge(String),
+
     #[error("Dependency cycle detected: {0:?}")]
     DependencyCycle(Vec<BeadId>),
 
+    #[error("Invalid bundle: {0}")]
+    InvalidBundle(String),
+
     // Assayer errors
     #[error("Assayer error: {0}")]
     AssayerError(String),
@@ -62,6 +145,9 @@ pub enum RigsError {
     #[error("Failed to parse LLM response: {0}")]
     LlmParseError(String),
 
+    #[error("Rejected by quality gate: {}", .0.join("; "))]
+    QualityGateRejected(Vec<String>),
+
     // Configuration errors
     #[error("Configuration error: {0}")]
     ConfigError(String),
@@ -76,9 +162,31 @@ pub enum RigsError {
     #[error("Database error: {0}")]
     DatabaseError(#[from] sqlx::Error),
 
+    /// A database error with the operation that hit it (see [`ResultExt::context`])
+    #[error("Database error while {operation}: {source}")]
+    DatabaseContext {
+        operation: String,
+        #[source]
+        source: sqlx::Error,
+    },
+
     #[error("Migration error: {0}")]
     MigrationError(#[from] sqlx::migrate::MigrateError),
 
+    #[error(
+        "Database is missing tables ({}) after migrating; rigs was likely built without its \
+         migrations directory. Rebuild from a checkout that includes ./migrations.",
+        .0.join(", ")
+    )]
+    SchemaMissing(Vec<String>),
+
+    /// The configured storage backend can't serve this request
+    #[error("Storage backend {backend}: {reason}")]
+    BackendUnsupported {
+        backend: &'static str,
+        reason: String,
+    },
+
     // IO errors
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -94,11 +202,60 @@ pub enum RigsError {
     #[error("HTTP error: {0}")]
     HttpError(#[from] reqwest::Error),
 
+    #[error(
+        "TLS verification failed for {0}: {1}. Set `ca_cert` to your CA's PEM file, \
+         or `danger_accept_invalid_certs = true` to skip verification (insecure)"
+    )]
+    TlsError(String, String),
+
+    // Interaction errors
+    #[error("Confirmation required but stdin is not a terminal. Re-run with {0} to proceed.")]
+    ConfirmationRequired(&'static str),
+
     // Generic errors
     #[error("{0}")]
     Other(String),
 }
 
+impl From<InvalidBeadId> for RigsError {
+    fn from(e: InvalidBeadId) -> Self {
+        RigsError::InvalidBeadId(e.0)
+    }
+}
+
+/// How a failed attempt should be retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryClass {
+    /// Worth resending as is (network error, 5xx, provider overloaded)
+    Transient,
+    /// Our quota ran out; the same request works after the reset
+    RateLimited,
+    /// Retrying won't help without a change (bad prompt, auth, rejected output)
+    Fatal,
+}
+
+impl RetryClass {
+    /// Whether another attempt can succeed without changing the bead
+    pub fn is_recoverable(&self) -> bool {
+        *self != RetryClass::Fatal
+    }
+}
+
+impl fmt::Display for RetryClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RetryClass::Transient => "transient",
+            RetryClass::RateLimited => "rate limited",
+            RetryClass::Fatal => "fatal",
+        })
+    }
+}
+
+fn until(reset: &Option<DateTime<Utc>>) -> String {
+    reset.map(|r| format!(" until {}", r)).unwrap_or_default()
+}
+
 impl RigsError {
     /// Check if this error is recoverable (can retry)
     pub fn is_recoverable(&self) -> bool {
@@ -108,14 +265,53 @@ impl RigsError {
                 | RigsError::AllProvidersExhausted(_)
                 | RigsError::OllamaNotAvailable(_)
                 | RigsError::HttpError(_)
+                | RigsError::ProviderRateLimited { .. }
         )
     }
 
+    /// Check if this is a transient request failure (network error, 5xx,
+    /// provider overloaded)
+    ///
+    /// These are retried inside the executor and never count against a
+    /// bead's `retry_count`.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RigsError::ProviderUnavailable(..)
+            | RigsError::ProviderOverloaded(..)
+            | RigsError::OllamaNotAvailable(_) => true,
+            RigsError::HttpError(e) => {
+                e.is_connect() || e.is_timeout() || e.status().is_some_and(|s| s.is_server_error())
+            }
+            _ => false,
+        }
+    }
+
+    /// How a bead that failed with this error should be retried
+    pub fn retry_class(&self) -> RetryClass {
+        if self.is_transient() {
+            RetryClass::Transient
+        } else if self.is_rate_limit() {
+            RetryClass::RateLimited
+        } else {
+            RetryClass::Fatal
+        }
+    }
+
+    /// How the provider process ran, if this error came from one
+    pub fn diagnostics(&self) -> Option<&ExecutionDiagnostics> {
+        match self {
+            RigsError::CommandFailed { diagnostics, .. } => Some(diagnostics),
+            _ => None,
+        }
+    }
+
     /// Check if this is a rate limit error
     pub fn is_rate_limit(&self) -> bool {
         matches!(
             self,
-            RigsError::RateLimitExceeded { .. } | RigsError::AllProvidersExhausted(_)
+            RigsError::RateLimitExceeded { .. }
+                | RigsError::ProviderRateLimited { .. }
+                | RigsError::AllProvidersExhausted(_)
         )
     }
 
@@ -126,12 +322,26 @@ impl RigsError {
                 let now = Utc::now();
                 if *reset_time > now {
                     let duration = *reset_time - now;
-                    Some(duration.to_std().unwrap_or(std::time::Duration::from_secs(60)))
+                    Some(
+                        duration
+                            .to_std()
+                            .unwrap_or(std::time::Duration::from_secs(60)),
+                    )
                 } else {
                     Some(std::time::Duration::from_secs(60))
                 }
             }
-            RigsError::RateLimitExceeded { .. } => Some(std::time::Duration::from_secs(300)), // 5 min
+            RigsError::ProviderRateLimited {
+                reset: Some(reset), ..
+            } => Some(
+                (*reset - Utc::now())
+                    .to_std()
+                    .unwrap_or(std::time::Duration::from_secs(60)),
+            ),
+            RigsError::RateLimitExceeded { .. } | RigsError::ProviderRateLimited { .. } => {
+                Some(std::time::Duration::from_secs(300)) // 5 min
+            }
+            RigsError::ProviderOverloaded(..) => Some(std::time::Duration::from_secs(5)),
             RigsError::OllamaNotAvailable(_) => Some(std::time::Duration::from_secs(10)),
             RigsError::HttpError(_) => Some(std::time::Duration::from_secs(5)),
             _ => None,
@@ -140,13 +350,23 @@ impl RigsError {
 }
 
 /// Extension trait for adding context to errors
+///
+/// `msg` names the operation, e.g. "creating bead gt-ab12c". Database errors
+/// become [`RigsError::DatabaseContext`]; anything else is flattened into
+/// [`RigsError::Other`].
 pub trait ResultExt<T> {
     fn context(self, msg: impl Into<String>) -> Result<T>;
 }
 
-impl<T, E: std::error::Error> ResultExt<T> for std::result::Result<T, E> {
+impl<T, E: Into<RigsError>> ResultExt<T> for std::result::Result<T, E> {
     fn context(self, msg: impl Into<String>) -> Result<T> {
-        self.map_err(|e| RigsError::Other(format!("{}: {}", msg.into(), e)))
+        self.map_err(|e| match e.into() {
+            RigsError::DatabaseError(source) => RigsError::DatabaseContext {
+                operation: msg.into(),
+                source,
+            },
+            e => RigsError::Other(format!("{}: {}", msg.into(), e)),
+        })
     }
 }
 
@@ -0,0 +1,82 @@
+//! Foreman events
+//!
+//! The Foreman publishes an event on every state change it makes, so the
+//! attach TUI and other subscribers can follow progress without polling the
+//! database.
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::bead::{BeadId, BeadStatus};
+use super::provider::Provider;
+use super::tank::Tank;
+
+/// Events buffered per subscriber before the slowest one starts lagging
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// A state change published by the Foreman
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ForemanEvent {
+    /// A bead was handed to a provider
+    BeadStarted { bead_id: BeadId, provider: Provider },
+    /// A bead reached a terminal status
+    BeadCompleted {
+        bead_id: BeadId,
+        provider: Provider,
+        status: BeadStatus,
+    },
+    /// A bead failed repeatedly on a provider and will be retried elsewhere
+    ProviderSwitched {
+        bead_id: BeadId,
+        from: Provider,
+        to: Provider,
+    },
+    /// A provider's rate limit state changed
+    TankUpdated { tank: Box<Tank> },
+    /// A bead in a convoy finished
+    ConvoyProgress {
+        convoy_id: String,
+        finished: usize,
+        failed: usize,
+        total: usize,
+    },
+    /// A convoy failed fast: its remaining beads were cancelled
+    ConvoyHalted {
+        convoy_id: String,
+        failed: usize,
+        cancelled: usize,
+        total: usize,
+    },
+}
+
+/// Publishing side of the event channel
+///
+/// Publishing never fails: with no subscribers the event is dropped.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ForemanEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish an event to every current subscriber
+    pub fn publish(&self, event: ForemanEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ForemanEvent> {
+        self.sender.subscribe()
+    }
+}
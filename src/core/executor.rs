@@ -0,0 +1,855 @@
+//! Executor (provider worker) abstraction
+//!
+//! An Executor sends a bead's prompt to an LLM provider and returns the
+//! response. Concrete implementations wrap the provider CLIs or HTTP APIs;
+//! tests substitute fakes.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::debug;
+
+use super::bead::{Bead, BeadId, Provenance};
+use super::error::{Result, RigsError};
+use super::prompt::PromptTemplate;
+use super::provider::Provider;
+
+/// A single prompt to send to a provider
+#[derive(Debug, Clone)]
+pub struct ExecutionRequest {
+    /// Provider to send the prompt to
+    pub provider: Provider,
+    /// Resolved model string
+    pub model: String,
+    /// Full prompt, including any injected context files
+    pub prompt: String,
+    /// Directory to run in, if the bead has one
+    pub working_dir: Option<PathBuf>,
+}
+
+/// What a provider returned for a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionOutput {
+    /// Response text
+    pub text: String,
+    /// Tokens in the prompt, as reported by the provider (or estimated)
+    pub prompt_tokens: u64,
+    /// Tokens in the response, as reported by the provider (or estimated)
+    pub completion_tokens: u64,
+    /// Model that produced the response
+    pub model: String,
+    /// Why generation stopped (e.g. "stop", "length"), if the provider says
+    pub finish_reason: Option<String>,
+    /// Wall-clock time the request took
+    pub duration: Duration,
+    /// What the process printed and how it exited, for executors that run one
+    pub diagnostics: Option<ExecutionDiagnostics>,
+}
+
+/// How a provider process ran: the command line, its exit and its output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionDiagnostics {
+    pub command: String,
+    /// `None` when the process was killed by a signal
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl fmt::Display for ExecutionDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.exit_code {
+            Some(code) => write!(f, "`{}` exited with {}", self.command, code)?,
+            None => write!(f, "`{}` was killed by a signal", self.command)?,
+        }
+        match self.stderr.trim() {
+            "" => Ok(()),
+            stderr => write!(f, ": {}", stderr),
+        }
+    }
+}
+
+impl ExecutionOutput {
+    /// Output for a provider that doesn't report usage, with estimated token counts
+    pub fn estimated(request: &ExecutionRequest, text: String, duration: Duration) -> Self {
+        Self {
+            prompt_tokens: estimate_prompt_tokens(&request.prompt),
+            completion_tokens: estimate_prompt_tokens(&text),
+            text,
+            model: request.model.clone(),
+            finish_reason: None,
+            duration,
+            diagnostics: None,
+        }
+    }
+
+    /// Prompt and completion tokens together
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    /// Check if the provider stopped because it hit its output limit
+    ///
+    /// OpenAI-style APIs report "length", Anthropic "max_tokens", and Gemini
+    /// "MAX_TOKENS".
+    pub fn is_truncated(&self) -> bool {
+        matches!(
+            self.finish_reason.as_deref(),
+            Some("length" | "max_tokens" | "MAX_TOKENS")
+        )
+    }
+
+    /// Append a continuation, summing usage and keeping its finish reason
+    fn extend(&mut self, next: ExecutionOutput) {
+        self.text.push_str(&next.text);
+        self.prompt_tokens += next.prompt_tokens;
+        self.completion_tokens += next.completion_tokens;
+        self.finish_reason = next.finish_reason;
+        self.duration += next.duration;
+        self.diagnostics = next.diagnostics;
+    }
+}
+
+/// One execution attempt of a bead, as kept in the execution history
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionRecord {
+    pub bead_id: BeadId,
+    pub provider: Provider,
+    pub estimated_tokens: u64,
+    /// Tokens used, or 0 if the attempt failed
+    pub actual_tokens: u64,
+    pub duration: Duration,
+    /// Why the attempt failed; `None` if it succeeded
+    pub error: Option<String>,
+    pub diagnostics: Option<ExecutionDiagnostics>,
+    pub completed_at: DateTime<Utc>,
+}
+
+impl ExecutionRecord {
+    /// Record `bead`'s attempt on `provider` that ended in `outcome` after `duration`
+    pub fn new(
+        bead: &Bead,
+        provider: Provider,
+        outcome: &Result<ExecutionOutput>,
+        duration: Duration,
+    ) -> Self {
+        let (actual_tokens, error, diagnostics) = match outcome {
+            Ok(output) => (output.total_tokens(), None, output.diagnostics.clone()),
+            Err(e) => (0, Some(e.to_string()), e.diagnostics().cloned()),
+        };
+        Self {
+            bead_id: bead.id.clone(),
+            provider,
+            estimated_tokens: bead.estimated_tokens,
+            actual_tokens,
+            duration,
+            error,
+            diagnostics,
+            completed_at: Utc::now(),
+        }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Instruction appended when asking a provider to resume truncated output
+const CONTINUE_INSTRUCTION: &str =
+    "Your previous response was cut off. Continue exactly where it stopped, \
+     without repeating anything already written.";
+
+/// Send a request, following up while the output is truncated
+///
+/// Each follow-up resends the original prompt with the output so far and
+/// asks the model to carry on. Stops once the model finishes or after
+/// `max_continuations` follow-ups; the stitched output sums all usage.
+pub async fn execute_with_continuation(
+    executor: &dyn Executor,
+    request: &ExecutionRequest,
+    max_continuations: u32,
+) -> Result<ExecutionOutput> {
+    let mut output = executor.execute(request).await?;
+
+    for _ in 0..max_continuations {
+        if !output.is_truncated() {
+            break;
+        }
+        let follow_up = ExecutionRequest {
+            prompt: format!(
+                "{}\n\n--- Response so far ---\n{}\n\n{}",
+                request.prompt, output.text, CONTINUE_INSTRUCTION
+            ),
+            ..request.clone()
+        };
+        output.extend(executor.execute(&follow_up).await?);
+    }
+    Ok(output)
+}
+
+/// Sends prompts to an LLM provider
+#[async_trait]
+pub trait Executor: Send + Sync {
+    /// Short name of the executor type, recorded in provenance (e.g. "cli")
+    fn kind(&self) -> &'static str;
+
+    /// Send a request to its provider and return its output
+    async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput>;
+}
+
+/// Command line used to invoke a provider's CLI
+///
+/// `{model}` in any argument is replaced with the requested model; the
+/// prompt is written to the process's stdin.
+#[derive(Debug, Clone)]
+pub struct CliCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl CliCommand {
+    pub fn new(program: impl Into<String>, args: &[&str]) -> Self {
+        Self {
+            program: program.into(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+/// Executor that shells out to the provider CLIs (claude, codex, gemini)
+pub struct CliExecutor {
+    commands: HashMap<Provider, CliCommand>,
+}
+
+impl Default for CliExecutor {
+    fn default() -> Self {
+        let mut commands = HashMap::new();
+        commands.insert(
+            Provider::Claude,
+            CliCommand::new("claude", &["--print", "--model", "{model}"]),
+        );
+        commands.insert(
+            Provider::Codex,
+            CliCommand::new("codex", &["exec", "--model", "{model}", "-"]),
+        );
+        commands.insert(
+            Provider::Gemini,
+            CliCommand::new("gemini", &["--model", "{model}"]),
+        );
+        Self { commands }
+    }
+}
+
+impl CliExecutor {
+    /// Override the command used for a provider
+    pub fn with_command(mut self, provider: Provider, command: CliCommand) -> Self {
+        self.commands.insert(provider, command);
+        self
+    }
+}
+
+#[async_trait]
+impl Executor for CliExecutor {
+    fn kind(&self) -> &'static str {
+        "cli"
+    }
+
+    async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+        let provider = request.provider;
+        let command = self
+            .commands
+            .get(&provider)
+            .ok_or(RigsError::ProviderNotConfigured(provider))?;
+
+        let args: Vec<String> = command
+            .args
+            .iter()
+            .map(|a| a.replace("{model}", &request.model))
+            .collect();
+        let mut cmd = Command::new(&command.program);
+        cmd.args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(dir) = &request.working_dir {
+            cmd.current_dir(dir);
+        }
+
+        let start = Instant::now();
+        let mut child = cmd.spawn().map_err(|e| {
+            RigsError::ProviderApiError(
+                provider,
+                format!("failed to run `{}`: {}", command.program, e),
+            )
+        })?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A CLI that exits without reading its prompt closes the pipe early;
+            // its exit status below is what matters
+            if let Err(e) = stdin.write_all(request.prompt.as_bytes()).await {
+                if e.kind() != std::io::ErrorKind::BrokenPipe {
+                    return Err(e.into());
+                }
+            }
+        }
+
+        let output = child.wait_with_output().await?;
+        let diagnostics = ExecutionDiagnostics {
+            command: std::iter::once(command.program.clone())
+                .chain(args)
+                .collect::<Vec<_>>()
+                .join(" "),
+            exit_code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        };
+        if !output.status.success() {
+            return Err(RigsError::CommandFailed {
+                provider,
+                diagnostics: Box::new(diagnostics),
+            });
+        }
+
+        // The CLIs print only the response text, so usage has to be estimated
+        Ok(ExecutionOutput {
+            diagnostics: Some(diagnostics.clone()),
+            ..ExecutionOutput::estimated(request, diagnostics.stdout, start.elapsed())
+        })
+    }
+}
+
+/// Resends requests that fail transiently, with exponential backoff
+///
+/// Only errors for which [`RigsError::is_transient`] holds are retried, so a
+/// blip never reaches the bead and never uses up its `retry_count`. Anything
+/// else, or a transient error outlasting `retries` resends, is returned as is.
+pub struct RetryingExecutor {
+    inner: Arc<dyn Executor>,
+    retries: u32,
+    backoff: Duration,
+}
+
+impl RetryingExecutor {
+    /// Wrap `inner`, waiting `backoff` before the first resend and doubling after each
+    pub fn new(inner: Arc<dyn Executor>, retries: u32, backoff: Duration) -> Self {
+        Self {
+            inner,
+            retries,
+            backoff,
+        }
+    }
+}
+
+#[async_trait]
+impl Executor for RetryingExecutor {
+    fn kind(&self) -> &'static str {
+        self.inner.kind()
+    }
+
+    async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+        let mut delay = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match self.inner.execute(request).await {
+                Err(e) if e.is_transient() && attempt < self.retries => {
+                    attempt += 1;
+                    debug!(provider = %request.provider, attempt, error = %e, "Resending after transient failure");
+                    tokio::time::sleep(delay).await;
+                    delay = delay.saturating_mul(2);
+                }
+                outcome => return outcome,
+            }
+        }
+    }
+}
+
+/// Rough token count for a prompt (about four bytes per token)
+pub fn estimate_prompt_tokens(prompt: &str) -> u64 {
+    (prompt.len() as u64).div_ceil(4)
+}
+
+/// Advice for a prompt too large for its provider: name the execution
+/// providers that could take it, or say that splitting is the only option
+fn context_suggestion(tokens: u64) -> String {
+    let fitting: Vec<String> = Provider::execution()
+        .filter(|p| p.capabilities().fits_context(tokens))
+        .map(|p| format!("{} ({} tokens)", p, p.capabilities().max_context))
+        .collect();
+    if fitting.is_empty() {
+        "split the bead into smaller beads; no provider's context window fits it".to_string()
+    } else {
+        format!(
+            "split the bead into smaller beads or prefer a larger-context provider: {}",
+            fitting.join(", ")
+        )
+    }
+}
+
+/// Execute a bead, recording provenance and storing its output
+///
+/// Provenance is recorded before the request is sent so it is available
+/// even when execution fails. On success the bead's `actual_tokens` is set
+/// from the reported usage and provenance picks up the model that answered.
+/// Truncated output is continued up to `max_continuations` times. The
+/// prompt is rendered through `template`, preceded by `dependency_outputs`
+/// when that isn't empty, before context files are added.
+pub async fn execute_bead(
+    executor: &dyn Executor,
+    bead: &mut Bead,
+    template: &PromptTemplate,
+    dependency_outputs: &str,
+    provider: Provider,
+    model: &str,
+    max_continuations: u32,
+) -> Result<ExecutionOutput> {
+    let mut prompt = template.render(bead);
+    if !dependency_outputs.is_empty() {
+        prompt = format!("{}\n\n{}", dependency_outputs, prompt);
+    }
+    let request = ExecutionRequest {
+        provider,
+        model: model.to_string(),
+        prompt: bead.context.render_prompt(&prompt)?,
+        working_dir: bead.context.working_dir.clone(),
+    };
+
+    // Refuse up front rather than let the provider truncate or reject it
+    let tokens = estimate_prompt_tokens(&request.prompt);
+    let max_context = provider.capabilities().max_context;
+    if tokens > max_context {
+        let e = RigsError::ContextTooLarge {
+            provider,
+            tokens,
+            max_context,
+            suggestion: context_suggestion(tokens),
+        };
+        bead.record_error(&e);
+        return Err(e);
+    }
+
+    bead.assigned_provider = Some(provider);
+    bead.provenance = Some(Provenance::new(
+        provider,
+        model,
+        &request.prompt,
+        executor.kind(),
+    ));
+
+    match execute_with_continuation(executor, &request, max_continuations).await {
+        Ok(output) => {
+            if let Some(provenance) = bead.provenance.as_mut() {
+                if !output.model.is_empty() {
+                    provenance.model = output.model.clone();
+                }
+            }
+            bead.actual_tokens = Some(output.total_tokens());
+            bead.output = Some(output.text.clone());
+            bead.error = None;
+            bead.error_class = None;
+            Ok(output)
+        }
+        Err(e) => {
+            bead.record_error(&e);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::bead::{hash_prompt, BeadContext, TaskType};
+    use std::sync::Mutex;
+
+    /// Executor that echoes prompts and remembers what it was sent
+    struct FakeExecutor {
+        sent: Mutex<Vec<ExecutionRequest>>,
+        /// Model to report instead of the requested one
+        served_model: Option<&'static str>,
+    }
+
+    #[async_trait]
+    impl Executor for FakeExecutor {
+        fn kind(&self) -> &'static str {
+            "fake"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            self.sent.lock().unwrap().push(request.clone());
+            Ok(ExecutionOutput {
+                text: format!("done: {}", request.prompt),
+                prompt_tokens: 120,
+                completion_tokens: 30,
+                model: self
+                    .served_model
+                    .map_or_else(|| request.model.clone(), String::from),
+                finish_reason: Some("stop".into()),
+                duration: Duration::from_millis(250),
+                diagnostics: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execution_records_provenance() {
+        let executor = FakeExecutor {
+            sent: Mutex::new(vec![]),
+            served_model: None,
+        };
+        let mut bead = Bead::new("Task", "Write a parser", TaskType::Implementation);
+        bead.optimized_prompt = Some("Write a recursive-descent parser".into());
+
+        execute_bead(
+            &executor,
+            &mut bead,
+            &PromptTemplate::passthrough(),
+            "",
+            Provider::Claude,
+            "claude-sonnet-4",
+            0,
+        )
+        .await
+        .unwrap();
+
+        let sent = executor.sent.lock().unwrap();
+        let provenance = bead.provenance.as_ref().unwrap();
+        assert_eq!(provenance.provider, Provider::Claude);
+        assert_eq!(provenance.model, "claude-sonnet-4");
+        assert_eq!(provenance.executor, "fake");
+        assert_eq!(provenance.prompt_hash, hash_prompt(&sent[0].prompt));
+        assert!(provenance.matches_prompt("Write a recursive-descent parser"));
+        assert_eq!(bead.assigned_provider, Some(Provider::Claude));
+        assert!(bead.output.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_output_metadata_propagates_to_bead() {
+        let executor = FakeExecutor {
+            sent: Mutex::new(vec![]),
+            served_model: Some("claude-sonnet-4-20250514"),
+        };
+        let mut bead = Bead::new("Task", "Write a lexer", TaskType::Implementation);
+
+        let output = execute_bead(
+            &executor,
+            &mut bead,
+            &PromptTemplate::passthrough(),
+            "",
+            Provider::Claude,
+            "claude-sonnet-4",
+            0,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(output.prompt_tokens, 120);
+        assert_eq!(output.completion_tokens, 30);
+        assert_eq!(output.finish_reason.as_deref(), Some("stop"));
+        assert_eq!(output.duration, Duration::from_millis(250));
+        assert_eq!(bead.actual_tokens, Some(150));
+        assert_eq!(bead.output.as_deref(), Some("done: Write a lexer"));
+        assert_eq!(bead.provenance.unwrap().model, "claude-sonnet-4-20250514");
+    }
+
+    /// Executor that stops at its output limit until it has been asked to continue twice
+    struct TruncatingExecutor {
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Executor for TruncatingExecutor {
+        fn kind(&self) -> &'static str {
+            "truncating"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            let mut calls = self.calls.lock().unwrap();
+            calls.push(request.prompt.clone());
+            let part = ["fn parse() {", "\n    todo!()", "\n}"][calls.len() - 1];
+            Ok(ExecutionOutput {
+                text: part.into(),
+                prompt_tokens: 100,
+                completion_tokens: 10,
+                model: request.model.clone(),
+                finish_reason: Some(if calls.len() < 3 { "length" } else { "stop" }.into()),
+                duration: Duration::from_millis(100),
+                diagnostics: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_truncated_output_is_continued() {
+        let executor = TruncatingExecutor {
+            calls: Mutex::new(vec![]),
+        };
+        let mut bead = Bead::new("Parser", "Write a parser", TaskType::Implementation);
+
+        let output = execute_bead(
+            &executor,
+            &mut bead,
+            &PromptTemplate::passthrough(),
+            "",
+            Provider::Codex,
+            "codex",
+            3,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(output.text, "fn parse() {\n    todo!()\n}");
+        assert_eq!(output.finish_reason.as_deref(), Some("stop"));
+        assert_eq!(output.duration, Duration::from_millis(300));
+        assert_eq!(bead.actual_tokens, Some(330));
+        assert_eq!(bead.output.as_deref(), Some(output.text.as_str()));
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert!(calls[2].contains("fn parse() {\n    todo!()"));
+    }
+
+    #[tokio::test]
+    async fn test_continuation_cap() {
+        let executor = TruncatingExecutor {
+            calls: Mutex::new(vec![]),
+        };
+        let mut bead = Bead::new("Parser", "Write a parser", TaskType::Implementation);
+
+        let output = execute_bead(
+            &executor,
+            &mut bead,
+            &PromptTemplate::passthrough(),
+            "",
+            Provider::Codex,
+            "codex",
+            1,
+        )
+        .await
+        .unwrap();
+        assert!(output.is_truncated());
+        assert_eq!(executor.calls.lock().unwrap().len(), 2);
+
+        let executor = TruncatingExecutor {
+            calls: Mutex::new(vec![]),
+        };
+        let output = execute_bead(
+            &executor,
+            &mut bead,
+            &PromptTemplate::passthrough(),
+            "",
+            Provider::Codex,
+            "codex",
+            0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output.text, "fn parse() {");
+    }
+
+    #[tokio::test]
+    async fn test_context_reaches_executor() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+        let executor = FakeExecutor {
+            sent: Mutex::new(vec![]),
+            served_model: None,
+        };
+        let mut bead = Bead::new("Fix", "Fix main", TaskType::Debug).with_context(BeadContext {
+            working_dir: Some(dir.path().to_path_buf()),
+            files: vec!["main.rs".into()],
+        });
+
+        execute_bead(
+            &executor,
+            &mut bead,
+            &PromptTemplate::passthrough(),
+            "",
+            Provider::Codex,
+            "codex",
+            0,
+        )
+        .await
+        .unwrap();
+
+        let sent = executor.sent.lock().unwrap();
+        assert!(sent[0].prompt.starts_with("Fix main"));
+        assert!(sent[0]
+            .prompt
+            .contains("--- File: main.rs ---\nfn main() {}"));
+        assert_eq!(sent[0].working_dir.as_deref(), Some(dir.path()));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_prompt_not_sent() {
+        let executor = FakeExecutor {
+            sent: Mutex::new(vec![]),
+            served_model: None,
+        };
+        let max_context = Provider::Ollama.capabilities().max_context;
+        let huge = "x".repeat(max_context as usize * 4 + 4);
+        let mut bead = Bead::new("Huge", huge, TaskType::Research);
+
+        let err = execute_bead(
+            &executor,
+            &mut bead,
+            &PromptTemplate::passthrough(),
+            "",
+            Provider::Ollama,
+            "llama3.2:3b",
+            0,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            RigsError::ContextTooLarge {
+                provider: Provider::Ollama,
+                ..
+            }
+        ));
+        assert!(executor.sent.lock().unwrap().is_empty());
+        let message = bead.error.unwrap();
+        assert!(
+            message.contains("context window of 8192 tokens"),
+            "{}",
+            message
+        );
+        assert!(message.contains("larger-context provider"), "{}", message);
+        assert!(message.contains("Claude (200000 tokens)"), "{}", message);
+
+        // Nothing is big enough: splitting is the only advice
+        let err = execute_bead(
+            &executor,
+            &mut Bead::new("Huger", "x".repeat(4_000_008), TaskType::Research),
+            &PromptTemplate::passthrough(),
+            "",
+            Provider::Gemini,
+            "gemini-2.5-pro",
+            0,
+        )
+        .await
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("no provider's context window fits it"));
+    }
+
+    #[tokio::test]
+    async fn test_cli_executor_runs_in_working_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let executor =
+            CliExecutor::default().with_command(Provider::Claude, CliCommand::new("pwd", &[]));
+        let request = ExecutionRequest {
+            provider: Provider::Claude,
+            model: "claude-sonnet-4".into(),
+            prompt: "ignored".into(),
+            working_dir: Some(dir.path().to_path_buf()),
+        };
+
+        let output = executor.execute(&request).await.unwrap();
+        assert_eq!(output.model, "claude-sonnet-4");
+        assert_eq!(
+            output.completion_tokens,
+            estimate_prompt_tokens(&output.text)
+        );
+        assert_eq!(
+            std::fs::canonicalize(output.text.trim()).unwrap(),
+            std::fs::canonicalize(dir.path()).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cli_executor_reports_failure() {
+        let executor = CliExecutor::default().with_command(
+            Provider::Codex,
+            CliCommand::new("sh", &["-c", "echo bad model {model} >&2; exit 3"]),
+        );
+        let request = ExecutionRequest {
+            provider: Provider::Codex,
+            model: "nope".into(),
+            prompt: String::new(),
+            working_dir: None,
+        };
+
+        let err = executor.execute(&request).await.unwrap_err();
+        assert!(err.to_string().contains("bad model nope"));
+        let diagnostics = err.diagnostics().unwrap();
+        assert_eq!(diagnostics.exit_code, Some(3));
+        assert_eq!(diagnostics.stderr, "bad model nope\n");
+        assert!(diagnostics.command.starts_with("sh -c"));
+    }
+
+    struct UnavailableExecutor {
+        calls: Mutex<u32>,
+        blips: u32,
+        error: fn(Provider) -> RigsError,
+    }
+
+    #[async_trait]
+    impl Executor for UnavailableExecutor {
+        fn kind(&self) -> &'static str {
+            "unavailable"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            let mut calls = self.calls.lock().unwrap();
+            *calls += 1;
+            if *calls <= self.blips {
+                return Err((self.error)(request.provider));
+            }
+            Ok(ExecutionOutput::estimated(
+                request,
+                "ok".into(),
+                Duration::ZERO,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_resent_with_backoff() {
+        let request = ExecutionRequest {
+            provider: Provider::Claude,
+            model: "sonnet".into(),
+            prompt: "p".into(),
+            working_dir: None,
+        };
+        let unavailable = |p| RigsError::ProviderUnavailable(p, "503".into());
+        let retrying = |blips, error| {
+            let inner = Arc::new(UnavailableExecutor {
+                calls: Mutex::new(0),
+                blips,
+                error,
+            });
+            (
+                RetryingExecutor::new(inner.clone(), 2, Duration::from_millis(1)),
+                inner,
+            )
+        };
+
+        let (executor, inner) = retrying(2, unavailable);
+        assert_eq!(executor.execute(&request).await.unwrap().text, "ok");
+        assert_eq!(*inner.calls.lock().unwrap(), 3);
+
+        let (executor, inner) = retrying(3, unavailable);
+        assert!(executor.execute(&request).await.unwrap_err().is_transient());
+        assert_eq!(*inner.calls.lock().unwrap(), 3);
+
+        // An overloaded provider is resent shortly, like any other blip
+        let (executor, inner) = retrying(1, |p| RigsError::ProviderOverloaded(p, "529".into()));
+        assert_eq!(executor.execute(&request).await.unwrap().text, "ok");
+        assert_eq!(*inner.calls.lock().unwrap(), 2);
+
+        // Quality failures are left to the bead's own retries
+        let (executor, inner) = retrying(1, |p| RigsError::ProviderApiError(p, "bad".into()));
+        assert!(executor.execute(&request).await.is_err());
+        assert_eq!(*inner.calls.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,2262 @@
+//! Foreman (orchestrator)
+//!
+//! The Foreman pulls pending beads from the repository and dispatches them to
+//! an executor, keeping at most `max_concurrent` executions in flight (and
+//! at most `type_concurrency[task_type]` of any limited task type).
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
+
+use super::assayer::Assay;
+use super::bead::{Bead, BeadId, BeadStatus, TaskType};
+use super::budget::BudgetTracker;
+use super::convoy::ConvoyStatus;
+use super::error::{Result, RigsError};
+use super::events::{EventBus, ForemanEvent};
+use super::executor::{execute_bead, ExecutionRecord, Executor, RetryingExecutor};
+use super::gate::{QualityGate, ReviewOutcome};
+use super::postprocess::{apply_all, PostProcessor};
+use super::prompt::{render_dependency_outputs, PromptTemplate};
+use super::provider::{Provider, ProviderConfig};
+use super::retry::{Backoff, RecoveringExecutor};
+use super::routing::{rank_by_order, route_local, LatencyTable};
+use super::schedule::{plan_dispatch, DispatchPlan};
+use super::tank::{Tank, TankHealth, UsageEvent};
+use crate::config::ForemanConfig;
+use crate::db::repository::{
+    self, BeadRepository, CompletionRepository, ForemanStateRepository, LatencyRepository,
+    UsageRepository,
+};
+
+/// Central orchestrator dispatching beads to an executor
+pub struct Foreman {
+    beads: Arc<dyn BeadRepository>,
+    executor: Arc<dyn Executor>,
+    config: ForemanConfig,
+    latency: Option<Arc<dyn LatencyRepository>>,
+    state: Option<Arc<dyn ForemanStateRepository>>,
+    paused: Arc<AtomicBool>,
+    history: Option<Arc<dyn CompletionRepository>>,
+    convoys: Option<Arc<dyn repository::ConvoyRepository>>,
+    ledger: Option<TankLedger>,
+    assay: Option<AssayStage>,
+    gate: Option<Arc<dyn QualityGate>>,
+    events: EventBus,
+    budget: Option<Arc<Mutex<BudgetTracker>>>,
+    wake: Arc<Notify>,
+    local_only: bool,
+    provider_order: Option<Arc<Vec<Provider>>>,
+    models: Option<Arc<HashMap<Provider, ProviderConfig>>>,
+    template: Arc<PromptTemplate>,
+    convoy: Option<String>,
+}
+
+/// What happened to in-flight beads when [`Foreman::run`] was shut down
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrainReport {
+    /// Beads that finished within `shutdown_grace_secs`
+    pub finished: usize,
+    /// Beads still running after it, put back in the queue
+    pub requeued: Vec<BeadId>,
+}
+
+/// Assayer run on beads before execution, with its own concurrency limit
+#[derive(Clone)]
+struct AssayStage {
+    assayer: Arc<dyn Assay>,
+    slots: Arc<Semaphore>,
+}
+
+/// Where dispatch charges the tokens each execution used
+#[derive(Clone)]
+struct TankLedger {
+    tanks: Arc<dyn repository::TankRepository>,
+    usage: Arc<dyn UsageRepository>,
+    /// Held while a saved tank is read, charged and written back, so
+    /// concurrent dispatches to one provider don't overwrite each other
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+/// Where dispatch records execution latency
+#[derive(Clone)]
+struct LatencyRecorder {
+    store: Arc<dyn LatencyRepository>,
+    table: Arc<Mutex<LatencyTable>>,
+}
+
+impl Foreman {
+    /// Create a foreman over a bead repository and executor
+    ///
+    /// Unless `request_retries` is 0, the executor is wrapped in a
+    /// [`RetryingExecutor`] so transient failures are resent before they
+    /// fail the bead. Likewise, unless `recover_retries` is 0, a
+    /// [`RecoveringExecutor`] resends requests that hit a recoverable error
+    /// such as a rate limit.
+    pub fn new(
+        beads: Arc<dyn BeadRepository>,
+        executor: Arc<dyn Executor>,
+        config: ForemanConfig,
+    ) -> Self {
+        let executor: Arc<dyn Executor> = if config.request_retries > 0 {
+            Arc::new(RetryingExecutor::new(
+                executor,
+                config.request_retries,
+                Duration::from_millis(config.request_backoff_ms),
+            ))
+        } else {
+            executor
+        };
+        let executor: Arc<dyn Executor> = if config.recover_retries > 0 {
+            Arc::new(RecoveringExecutor::new(
+                executor,
+                config.recover_retries + 1,
+                Backoff {
+                    cap: Duration::from_secs(config.recover_backoff_cap_secs),
+                    ..Backoff::default()
+                },
+            ))
+        } else {
+            executor
+        };
+        Self {
+            beads,
+            executor,
+            config,
+            latency: None,
+            state: None,
+            paused: Arc::default(),
+            history: None,
+            convoys: None,
+            ledger: None,
+            assay: None,
+            gate: None,
+            events: EventBus::new(),
+            budget: None,
+            wake: Arc::new(Notify::new()),
+            local_only: false,
+            provider_order: None,
+            models: None,
+            template: Arc::new(PromptTemplate::passthrough()),
+            convoy: None,
+        }
+    }
+
+    /// Only dispatch the beads of one convoy, leaving the rest of the queue alone
+    pub fn with_convoy(mut self, convoy_id: impl Into<String>) -> Self {
+        self.convoy = Some(convoy_id.into());
+        self
+    }
+
+    /// Render bead prompts through `template` (`[prompts] bead_template`)
+    ///
+    /// Without one, each bead's prompt is sent unchanged.
+    pub fn with_prompt_template(mut self, template: PromptTemplate) -> Self {
+        self.template = Arc::new(template);
+        self
+    }
+
+    /// Try providers in exactly this order for every bead, ignoring affinity
+    ///
+    /// Leave disabled and exhausted providers out of `order`. A bead none of
+    /// them can take is failed with [`RigsError::ProviderOrderExhausted`].
+    /// Local-only mode takes precedence.
+    pub fn with_provider_order(mut self, order: Vec<Provider>) -> Self {
+        self.provider_order = Some(Arc::new(order));
+        self
+    }
+
+    /// Pick each provider's model from its config instead of the built-in default
+    ///
+    /// The model follows [`ProviderConfig::effective_model`] for the health
+    /// of the provider's tank in the pass, so with `fallback_under_pressure`
+    /// a provider running low moves to its cheaper fallback model.
+    pub fn with_models(mut self, models: HashMap<Provider, ProviderConfig>) -> Self {
+        self.models = Some(Arc::new(models));
+        self
+    }
+
+    /// Run every bead on Ollama (`general.local_only`)
+    ///
+    /// Beads too large for a local model are failed with
+    /// [`RigsError::NotServableLocally`] instead of being sent anywhere else.
+    pub fn with_local_only(mut self, local_only: bool) -> Self {
+        self.local_only = local_only;
+        self
+    }
+
+    /// Stop dispatching once a token, cost, or time budget would be exceeded
+    ///
+    /// Beads that don't fit are marked `Deferred` (with no wake-up time) and
+    /// the reason is stored in their `error`.
+    pub fn with_budget(mut self, budget: BudgetTracker) -> Self {
+        self.budget = Some(Arc::new(Mutex::new(budget)));
+        self
+    }
+
+    /// Tokens and estimated cost committed against the budget so far
+    pub fn budget_spent(&self) -> Option<(u64, f64)> {
+        self.budget
+            .as_ref()
+            .and_then(|b| b.lock().ok().map(|b| (b.tokens(), b.cost())))
+    }
+
+    /// Subscribe to the events published as beads progress
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<ForemanEvent> {
+        self.events.subscribe()
+    }
+
+    /// The bus events are published on (e.g. for tank updates)
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Handle that wakes an idle [`Foreman::run`] loop, e.g. after adding a bead
+    pub fn waker(&self) -> Arc<Notify> {
+        self.wake.clone()
+    }
+
+    /// Record per-provider execution latency for latency-aware routing
+    pub fn with_latency(mut self, store: Arc<dyn LatencyRepository>) -> Self {
+        self.latency = Some(store);
+        self
+    }
+
+    /// Keep a record of every execution attempt, with its process
+    /// diagnostics, for `rigs bead logs`
+    pub fn with_history(mut self, store: Arc<dyn CompletionRepository>) -> Self {
+        self.history = Some(store);
+        self
+    }
+
+    /// Mark convoys halted by `convoy_failure_ratio` as `Failed` in `store`
+    pub fn with_convoys(mut self, store: Arc<dyn repository::ConvoyRepository>) -> Self {
+        self.convoys = Some(store);
+        self
+    }
+
+    /// Charge the tokens each execution used to its provider's tank in
+    /// `tanks` and add them to the usage history in `usage`
+    ///
+    /// A provider without a saved tank starts from the one it was given for
+    /// the pass; providers with neither aren't tracked.
+    pub fn with_tanks(
+        mut self,
+        tanks: Arc<dyn repository::TankRepository>,
+        usage: Arc<dyn UsageRepository>,
+    ) -> Self {
+        self.ledger = Some(TankLedger {
+            tanks,
+            usage,
+            lock: Arc::default(),
+        });
+        self
+    }
+
+    /// Assay beads without an optimized prompt before executing them
+    ///
+    /// At most `max_concurrent` beads (`[assayer] max_concurrent`) are
+    /// assayed at once. The stage doesn't hold execution slots, so execution
+    /// keeps going at `foreman.max_concurrent` meanwhile. A failed assay is
+    /// logged and the bead runs as it is.
+    pub fn with_assayer(mut self, assayer: Arc<dyn Assay>, max_concurrent: u32) -> Self {
+        self.assay = Some(AssayStage {
+            assayer,
+            slots: Arc::new(Semaphore::new(max_concurrent.max(1) as usize)),
+        });
+        self
+    }
+
+    /// Review each successful bead's output against its acceptance criteria
+    ///
+    /// Beads with criteria go through `Reviewing` before they complete. A
+    /// rejected bead is requeued while it has retries left, with the
+    /// reviewer's reasons kept as its error, and fails after that. A gate
+    /// that can't be reached is logged and the bead completes.
+    pub fn with_gate(mut self, gate: Arc<dyn QualityGate>) -> Self {
+        self.gate = Some(gate);
+        self
+    }
+
+    /// Keep the paused flag in `store`, so `rigs foreman pause` reaches a
+    /// running daemon
+    pub fn with_state(mut self, store: Arc<dyn ForemanStateRepository>) -> Self {
+        self.state = Some(store);
+        self
+    }
+
+    /// Stop or restart picking up beads
+    ///
+    /// A paused foreman starts no new beads (not even ones already picked up
+    /// in the current pass); beads already executing run to completion.
+    pub async fn set_paused(&self, paused: bool) -> Result<()> {
+        if let Some(state) = &self.state {
+            state.set_paused(paused).await?;
+        }
+        self.paused.store(paused, Ordering::SeqCst);
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    /// Whether the foreman is paused, as last stored by any process
+    pub async fn is_paused(&self) -> Result<bool> {
+        if let Some(state) = &self.state {
+            self.paused
+                .store(state.is_paused().await?, Ordering::SeqCst);
+        }
+        Ok(self.paused.load(Ordering::SeqCst))
+    }
+
+    /// Run a single scheduling pass
+    ///
+    /// Dispatches every pending bead whose dependencies are complete, highest
+    /// priority first, and waits for them to finish. Returns the number of
+    /// beads processed, which is 0 while paused.
+    pub async fn run_once(&self) -> Result<usize> {
+        self.run_once_within(&[]).await
+    }
+
+    /// Run a single scheduling pass within the capacity of `tanks`
+    ///
+    /// Like [`Foreman::run_once`], but a bead whose provider's tank can't
+    /// cover its estimate (after the beads dispatched before it in this
+    /// pass) is deferred until the tank's window ends. Providers without a
+    /// tank in `tanks` aren't limited.
+    pub async fn run_once_within(&self, tanks: &[Tank]) -> Result<usize> {
+        let (processed, _) = self.pass(tanks, pin!(std::future::pending())).await?;
+        Ok(processed)
+    }
+
+    /// One scheduling pass that drains early if `shutdown` resolves
+    ///
+    /// Returns the number of beads processed, plus a drain report if the
+    /// pass was cut short.
+    async fn pass<S>(
+        &self,
+        tanks: &[Tank],
+        mut shutdown: Pin<&mut S>,
+    ) -> Result<(usize, Option<DrainReport>)>
+    where
+        S: Future<Output = ()>,
+    {
+        if self.is_paused().await? {
+            return Ok((0, None));
+        }
+        let given: Arc<HashMap<Provider, Tank>> = Arc::new(
+            tanks
+                .iter()
+                .map(|t| (t.provider, refreshed(t.clone())))
+                .collect(),
+        );
+        let mut tanks = (*given).clone();
+        let completed = self.completed_ids().await?;
+        let ready: Vec<Bead> = self
+            .beads
+            .get_pending_ordered()
+            .await?
+            .into_iter()
+            .filter(|b| self.convoy.is_none() || b.convoy_id == self.convoy)
+            .filter(|b| b.dependencies_met(&completed))
+            .collect();
+
+        let recorder = match &self.latency {
+            Some(store) => Some(LatencyRecorder {
+                store: store.clone(),
+                table: Arc::new(Mutex::new(LatencyTable::new(store.get_latencies().await?))),
+            }),
+            None => None,
+        };
+
+        let slots = Arc::new(Semaphore::new(self.config.max_concurrent.max(1) as usize));
+        let type_slots: HashMap<TaskType, Arc<Semaphore>> = self
+            .config
+            .type_concurrency
+            .iter()
+            .map(|(&task_type, &limit)| {
+                (task_type, Arc::new(Semaphore::new(limit.max(1) as usize)))
+            })
+            .collect();
+        let policy = DispatchPolicy {
+            continuations: self.config.continuation_limit(),
+            switch_provider_after: self.config.switch_provider_after,
+            convoy_failure_ratio: self.config.convoy_failure_ratio,
+            postprocess: Arc::new(self.config.postprocess.clone()),
+            halted: Arc::default(),
+            local_only: self.local_only,
+            provider_order: self.provider_order.clone(),
+            models: self.models.clone(),
+            template: self.template.clone(),
+            history: self.history.clone(),
+            convoys: self.convoys.clone(),
+            gate: self.gate.clone(),
+            dependency_output: self
+                .config
+                .inject_dependency_output
+                .then(|| DependencyOutput {
+                    header: self.config.dependency_output_header.as_str().into(),
+                    max_tokens: self.config.dependency_output_tokens,
+                }),
+            draining: Arc::default(),
+            paused: self.paused.clone(),
+            in_flight: Arc::default(),
+        };
+        let mut tasks = JoinSet::new();
+
+        for mut bead in ready {
+            if self.paused.load(Ordering::SeqCst) {
+                break;
+            }
+            let provider = match policy.provider_for(&bead) {
+                Ok(provider) => provider,
+                Err(e) => {
+                    bead.status = BeadStatus::Failed;
+                    bead.record_error(&e);
+                    bead.completed_at = Some(Utc::now());
+                    self.beads.update(&bead).await?;
+                    continue;
+                }
+            };
+            let health = tanks.get(&provider).map_or(TankHealth::Green, |t| t.health);
+            if let Some(tank) = tanks.get_mut(&provider) {
+                if !tank.can_consume(bead.estimated_tokens) {
+                    bead.status = BeadStatus::Deferred;
+                    bead.deferred_until = Some(tank.window_end);
+                    bead.error = Some(format!(
+                        "Deferred: {} tank has {} of the {} tokens estimated",
+                        provider, tank.remaining, bead.estimated_tokens
+                    ));
+                    self.beads.update(&bead).await?;
+                    continue;
+                }
+                // Held for this pass only; the saved tank is charged what the
+                // bead actually used once it has run
+                tank.remaining -= bead.estimated_tokens;
+            }
+            if let Some(budget) = &self.budget {
+                let admitted = budget
+                    .lock()
+                    .map_err(|_| RigsError::Other("Budget tracker poisoned".into()))?
+                    .reserve(provider, bead.estimated_tokens, Utc::now());
+                if let Err(reason) = admitted {
+                    // Give back the tank hold taken above for this bead
+                    if let Some(tank) = tanks.get_mut(&provider) {
+                        tank.remaining += bead.estimated_tokens;
+                    }
+                    bead.status = BeadStatus::Deferred;
+                    bead.deferred_until = None;
+                    bead.error = Some(format!("Deferred: {}", reason));
+                    self.beads.update(&bead).await?;
+                    continue;
+                }
+            }
+
+            let slots = slots.clone();
+            let type_slot = type_slots.get(&bead.task_type).cloned();
+            let beads = self.beads.clone();
+            let executor = self.executor.clone();
+            let recorder = recorder.clone();
+            let events = self.events.clone();
+            let budget = self.budget.clone();
+            let ledger = self.ledger.clone();
+            let given = given.clone();
+            let policy = policy.clone();
+            let assay = self.assay.clone();
+
+            // Permits are taken inside the task so a bead waiting on its type's
+            // limit doesn't hold up beads of other types behind it
+            tasks.spawn(async move {
+                let reserved = bead.estimated_tokens;
+                let bead = match assay {
+                    Some(stage) if bead.optimized_prompt.is_none() => {
+                        run_assay(beads.as_ref(), &stage, &policy, bead).await?
+                    }
+                    _ => bead,
+                };
+                let _type_permit = match type_slot {
+                    Some(type_slot) => Some(acquire(type_slot).await?),
+                    None => None,
+                };
+                let _permit = acquire(slots).await?;
+                let bead = dispatch(
+                    beads.as_ref(),
+                    executor.as_ref(),
+                    recorder,
+                    &events,
+                    &policy,
+                    bead,
+                    health,
+                )
+                .await?;
+                if let Some(budget) = budget {
+                    // A skipped bead used nothing, so its whole reservation is returned
+                    let actual = bead.as_ref().map_or(Some(0), |b| b.actual_tokens);
+                    if let (Some(actual), Ok(mut budget)) = (actual, budget.lock()) {
+                        budget.settle(provider, reserved, actual);
+                    }
+                }
+                let used = bead.as_ref().and_then(|b| b.actual_tokens);
+                if let (Some(ledger), Some(tokens)) = (ledger, used.filter(|&t| t > 0)) {
+                    ledger
+                        .charge(provider, given.get(&provider), tokens, &events)
+                        .await?;
+                }
+                Ok::<_, RigsError>(bead.is_some())
+            });
+        }
+
+        let mut processed = 0;
+        loop {
+            tokio::select! {
+                joined = tasks.join_next() => match joined {
+                    Some(joined) => {
+                        if joined
+                            .map_err(|e| RigsError::Other(format!("Dispatch task failed: {}", e)))??
+                        {
+                            processed += 1;
+                        }
+                    }
+                    None => return Ok((processed, None)),
+                },
+                _ = &mut shutdown => break,
+            }
+        }
+
+        let report = self.drain(tasks, &policy).await?;
+        Ok((processed + report.finished, Some(report)))
+    }
+
+    /// Let in-flight beads finish within the grace period, then requeue the rest
+    ///
+    /// Beads that haven't started yet stay queued.
+    async fn drain(
+        &self,
+        mut tasks: JoinSet<Result<bool>>,
+        policy: &DispatchPolicy,
+    ) -> Result<DrainReport> {
+        policy.draining.store(true, Ordering::SeqCst);
+        let grace = Duration::from_secs(self.config.shutdown_grace_secs);
+        info!(grace_secs = grace.as_secs(), "Draining in-flight beads");
+
+        let deadline = tokio::time::Instant::now() + grace;
+        let mut report = DrainReport::default();
+        while let Ok(Some(joined)) = tokio::time::timeout_at(deadline, tasks.join_next()).await {
+            // Keep draining the others even if one of them fails
+            match joined {
+                Ok(Ok(true)) => report.finished += 1,
+                Ok(Ok(false)) => {}
+                Ok(Err(e)) => warn!(error = %e, "Dispatch failed while draining"),
+                Err(e) => warn!(error = %e, "Dispatch task failed while draining"),
+            }
+        }
+        tasks.shutdown().await;
+
+        let stragglers: Vec<BeadId> = policy
+            .in_flight
+            .lock()
+            .map_err(|_| RigsError::Other("In-flight set poisoned".into()))?
+            .drain()
+            .collect();
+        for id in stragglers {
+            let Some(mut bead) = self.beads.get(&id).await? else {
+                continue;
+            };
+            if !matches!(bead.status, BeadStatus::InProgress | BeadStatus::Optimizing) {
+                continue;
+            }
+            bead.requeue_interrupted();
+            self.beads.update(&bead).await?;
+            warn!(bead = %id, "Requeued bead still running at shutdown");
+            report.requeued.push(id);
+        }
+        Ok(report)
+    }
+
+    /// Keep dispatching until `shutdown` resolves
+    ///
+    /// Once it does, no more beads are started; in-flight ones get
+    /// `shutdown_grace_secs` to finish and are requeued if they don't.
+    ///
+    /// Deferred beads whose wait is over are re-queued before each pass.
+    /// When a pass finds nothing to do, the loop sleeps until the soonest
+    /// moment something could change (see [`Foreman::next_wake`]), capped at
+    /// `poll_interval` (± `poll_jitter_pct`), or until [`Foreman::waker`] is
+    /// notified. `tanks` is awaited before every pass and every sleep, so
+    /// each pass dispatches within current capacity (see
+    /// [`Foreman::run_once_within`]) and reset times stay current. While
+    /// paused (see [`Foreman::set_paused`]) nothing is dispatched and the
+    /// flag is checked again every poll.
+    pub async fn run<F, T>(
+        &self,
+        tanks: F,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<DrainReport>
+    where
+        F: Fn() -> T,
+        T: Future<Output = Result<Vec<Tank>>>,
+    {
+        let base_poll = Duration::from_secs(self.config.poll_interval.max(1));
+        tokio::pin!(shutdown);
+
+        loop {
+            let paused = self.is_paused().await?;
+            if !paused {
+                self.release_deferred().await?;
+                match self.pass(&tanks().await?, shutdown.as_mut()).await? {
+                    (_, Some(report)) => return Ok(report),
+                    (processed, None) if processed > 0 => continue,
+                    _ => {}
+                }
+            }
+
+            let poll = jittered(
+                base_poll,
+                self.config.poll_jitter_pct,
+                &mut rand::thread_rng(),
+            );
+            let wait = if paused {
+                debug!("Foreman paused");
+                poll
+            } else {
+                let wake_at = self.next_wake(&tanks().await?).await?;
+                debug!(?wake_at, "Foreman idle");
+                idle_duration(wake_at, Utc::now(), poll)
+            };
+            tokio::select! {
+                _ = &mut shutdown => return Ok(DrainReport::default()),
+                _ = self.wake.notified() => {}
+                _ = tokio::time::sleep(wait) => {}
+            }
+        }
+    }
+
+    /// Soonest time a deferred bead becomes ready or a partly used tank resets
+    ///
+    /// `None` means nothing is scheduled to change on its own; only new
+    /// beads can create work.
+    pub async fn next_wake(&self, tanks: &[Tank]) -> Result<Option<DateTime<Utc>>> {
+        let deferred = self
+            .beads
+            .list_by_status(BeadStatus::Deferred)
+            .await?
+            .into_iter()
+            .filter_map(|b| b.deferred_until);
+        let resets = tanks
+            .iter()
+            .filter(|t| !t.is_unlimited() && t.remaining < t.capacity)
+            .map(|t| t.window_end);
+        Ok(deferred.chain(resets).min())
+    }
+
+    /// Move deferred beads whose wait is over back into the queue
+    async fn release_deferred(&self) -> Result<()> {
+        for mut bead in self.beads.get_deferred_ready().await? {
+            bead.status = BeadStatus::Queued;
+            bead.deferred_until = None;
+            self.beads.update(&bead).await?;
+        }
+        Ok(())
+    }
+
+    /// Preview the order the current queue would be dispatched in
+    ///
+    /// Uses the same queue order and dependency rules as `run_once`, without
+    /// executing anything.
+    pub async fn plan(&self, tanks: &[Tank]) -> Result<DispatchPlan> {
+        let completed = self.completed_ids().await?;
+        let pending = self.beads.get_pending_ordered().await?;
+        Ok(plan_dispatch(&pending, &completed, tanks))
+    }
+
+    async fn completed_ids(&self) -> Result<HashSet<BeadId>> {
+        Ok(self
+            .beads
+            .list_by_status(BeadStatus::Completed)
+            .await?
+            .into_iter()
+            .map(|b| b.id)
+            .collect())
+    }
+}
+
+/// How long an idle loop sleeps: until `wake_at`, but never past `poll`
+fn idle_duration(wake_at: Option<DateTime<Utc>>, now: DateTime<Utc>, poll: Duration) -> Duration {
+    wake_at
+        .map(|at| (at - now).to_std().unwrap_or(Duration::ZERO))
+        .map_or(poll, |until| until.min(poll))
+}
+
+/// `poll` lengthened or shortened by a random amount of up to `jitter_pct`
+/// percent (capped at 100)
+fn jittered(poll: Duration, jitter_pct: u8, rng: &mut impl Rng) -> Duration {
+    let spread = f64::from(jitter_pct.min(100)) / 100.0;
+    if spread == 0.0 {
+        return poll;
+    }
+    poll.mul_f64(1.0 + rng.gen_range(-spread..=spread))
+}
+
+/// Wait for a dispatch slot
+async fn acquire(slots: Arc<Semaphore>) -> Result<OwnedSemaphorePermit> {
+    slots
+        .acquire_owned()
+        .await
+        .map_err(|e| RigsError::Other(format!("Dispatch slots closed: {}", e)))
+}
+
+impl TankLedger {
+    /// Take `tokens` from `provider`'s saved tank (or `fallback` when none is
+    /// saved yet), save it and record the usage
+    async fn charge(
+        &self,
+        provider: Provider,
+        fallback: Option<&Tank>,
+        tokens: u64,
+        events: &EventBus,
+    ) -> Result<()> {
+        let _held = self.lock.lock().await;
+        let Some(tank) = self
+            .tanks
+            .get(provider)
+            .await?
+            .or_else(|| fallback.cloned())
+        else {
+            return Ok(());
+        };
+        let mut tank = refreshed(tank);
+        if let Err(e) = tank.consume(tokens) {
+            // The provider has already served the request, so the tokens
+            // count even though the tank didn't have room for them
+            warn!(error = %e, "Execution used more than its tank allowed");
+            let remaining = tank.remaining.saturating_sub(tokens);
+            tank.update_remaining(remaining);
+        }
+        self.tanks.upsert(&tank).await?;
+        self.usage
+            .record_usage(&UsageEvent::new(&tank, tokens))
+            .await?;
+        events.publish(ForemanEvent::TankUpdated {
+            tank: Box::new(tank),
+        });
+        Ok(())
+    }
+}
+
+/// `tank` with its window rolled over if it has ended
+///
+/// A stale saved tank would otherwise defer beads to a time already past.
+fn refreshed(mut tank: Tank) -> Tank {
+    if tank.needs_refresh() {
+        let hours = tank.window_hours();
+        tank.reset_window(hours);
+    }
+    tank
+}
+
+/// Per-run settings shared by every dispatch task
+#[derive(Clone)]
+struct DispatchPolicy {
+    continuations: u32,
+    switch_provider_after: u32,
+    convoy_failure_ratio: Option<f64>,
+    postprocess: Arc<HashMap<TaskType, Vec<PostProcessor>>>,
+    /// Convoys that failed fast during this run
+    halted: Arc<Mutex<HashSet<String>>>,
+    local_only: bool,
+    provider_order: Option<Arc<Vec<Provider>>>,
+    models: Option<Arc<HashMap<Provider, ProviderConfig>>>,
+    template: Arc<PromptTemplate>,
+    history: Option<Arc<dyn CompletionRepository>>,
+    convoys: Option<Arc<dyn repository::ConvoyRepository>>,
+    gate: Option<Arc<dyn QualityGate>>,
+    /// Set when dependencies' output goes into their dependents' prompts
+    dependency_output: Option<DependencyOutput>,
+    /// Set at shutdown so beads not yet started are left queued
+    draining: Arc<AtomicBool>,
+    /// The foreman's paused flag; like `draining`, leaves beads not yet started queued
+    paused: Arc<AtomicBool>,
+    /// Beads currently executing
+    in_flight: Arc<Mutex<HashSet<BeadId>>>,
+}
+
+#[derive(Clone)]
+struct DependencyOutput {
+    header: Arc<str>,
+    max_tokens: u64,
+}
+
+impl DispatchPolicy {
+    fn provider_for(&self, bead: &Bead) -> Result<Provider> {
+        if self.local_only {
+            return route_local(bead);
+        }
+        match &self.provider_order {
+            Some(order) => rank_by_order(bead, order)
+                .first()
+                .copied()
+                .ok_or_else(|| RigsError::ProviderOrderExhausted(bead.id.clone())),
+            None => Ok(bead.dispatch_provider()),
+        }
+    }
+
+    /// The model to run `provider` on while its tank is at `health`
+    fn model_for(&self, provider: Provider, health: TankHealth) -> String {
+        match self
+            .models
+            .as_ref()
+            .and_then(|models| models.get(&provider))
+        {
+            Some(config) => config.effective_model(health).to_string(),
+            None => provider.default_model().to_string(),
+        }
+    }
+
+    fn is_halted(&self, convoy_id: &str) -> bool {
+        self.halted
+            .lock()
+            .map(|halted| halted.contains(convoy_id))
+            .unwrap_or(false)
+    }
+}
+
+/// Assay a bead, marking it `Optimizing` meanwhile, then return it to the queue
+async fn run_assay(
+    beads: &dyn BeadRepository,
+    stage: &AssayStage,
+    policy: &DispatchPolicy,
+    mut bead: Bead,
+) -> Result<Bead> {
+    let _permit = acquire(stage.slots.clone()).await?;
+    if policy.draining.load(Ordering::SeqCst) {
+        return Ok(bead);
+    }
+    let status = bead.status;
+    bead.status = BeadStatus::Optimizing;
+    if let Ok(mut in_flight) = policy.in_flight.lock() {
+        in_flight.insert(bead.id.clone());
+    }
+    beads.update(&bead).await?;
+
+    if let Err(e) = stage.assayer.assay(&mut bead).await {
+        warn!(bead = %bead.id, error = %e, "Assay failed; executing the bead as it is");
+    }
+
+    bead.status = status;
+    beads.update(&bead).await?;
+    if let Ok(mut in_flight) = policy.in_flight.lock() {
+        in_flight.remove(&bead.id);
+    }
+    Ok(bead)
+}
+
+/// Execute one bead and persist each status change, returning its final state
+///
+/// Returns `None` without executing if the bead's convoy was halted after
+/// the bead was picked up, or if the foreman started draining or was paused meanwhile.
+async fn dispatch(
+    beads: &dyn BeadRepository,
+    executor: &dyn Executor,
+    recorder: Option<LatencyRecorder>,
+    events: &EventBus,
+    policy: &DispatchPolicy,
+    mut bead: Bead,
+    health: TankHealth,
+) -> Result<Option<Bead>> {
+    if policy.draining.load(Ordering::SeqCst)
+        || policy.paused.load(Ordering::SeqCst)
+        || bead
+            .convoy_id
+            .as_deref()
+            .is_some_and(|id| policy.is_halted(id))
+    {
+        return Ok(None);
+    }
+    let provider = policy.provider_for(&bead)?;
+
+    bead.status = BeadStatus::InProgress;
+    bead.started_at = Some(Utc::now());
+    if let Ok(mut in_flight) = policy.in_flight.lock() {
+        in_flight.insert(bead.id.clone());
+    }
+    beads.update(&bead).await?;
+    events.publish(ForemanEvent::BeadStarted {
+        bead_id: bead.id.clone(),
+        provider,
+    });
+
+    let dependency_outputs = match &policy.dependency_output {
+        Some(injection) => {
+            let mut dependencies = Vec::with_capacity(bead.dependencies.len());
+            for id in &bead.dependencies {
+                dependencies.extend(beads.get(id).await?);
+            }
+            render_dependency_outputs(&dependencies, &injection.header, injection.max_tokens)
+        }
+        None => String::new(),
+    };
+    let started = Instant::now();
+    let outcome = execute_bead(
+        executor,
+        &mut bead,
+        &policy.template,
+        &dependency_outputs,
+        provider,
+        &policy.model_for(provider, health),
+        policy.continuations,
+    )
+    .await;
+    if let Some(history) = &policy.history {
+        let record = ExecutionRecord::new(&bead, provider, &outcome, started.elapsed());
+        history.record_completion(&record).await?;
+    }
+
+    // Failures often return early, so only successful runs say anything
+    // about how fast a provider is
+    if let (Ok(output), Some(recorder)) = (&outcome, recorder) {
+        let latency = recorder
+            .table
+            .lock()
+            .map_err(|_| RigsError::Other("Latency table poisoned".into()))?
+            .record(provider, output.duration)
+            .clone();
+        recorder.store.upsert_latency(&latency).await?;
+    }
+
+    // Checkpoint: `rigs bead cancel` may have asked for the bead meanwhile
+    if beads.cancel_requested(&bead.id).await? {
+        info!(bead = %bead.id, "Bead cancelled while executing");
+        bead.status = BeadStatus::Cancelled;
+    } else if outcome.is_ok() {
+        if let (Some(processors), Some(output)) = (
+            policy.postprocess.get(&bead.task_type),
+            bead.output.as_mut(),
+        ) {
+            *output = apply_all(processors, output);
+        }
+        bead.status = BeadStatus::Completed;
+        bead.provider_failures = 0;
+        if let Some(gate) = &policy.gate {
+            review(beads, gate.as_ref(), &mut bead).await?;
+        }
+    } else {
+        bead.status = BeadStatus::Failed;
+        // There is nothing to switch to when only Ollama may run
+        if !policy.local_only
+            && bead.record_provider_failure(provider, policy.switch_provider_after)
+        {
+            let next = bead.dispatch_provider();
+            info!(bead = %bead.id, from = %provider, to = %next, "Switching provider after repeated failures");
+            events.publish(ForemanEvent::ProviderSwitched {
+                bead_id: bead.id.clone(),
+                from: provider,
+                to: next,
+            });
+        }
+    }
+    if bead.status != BeadStatus::Queued {
+        bead.completed_at = Some(Utc::now());
+    }
+    beads.update(&bead).await?;
+    if let Ok(mut in_flight) = policy.in_flight.lock() {
+        in_flight.remove(&bead.id);
+    }
+    events.publish(ForemanEvent::BeadCompleted {
+        bead_id: bead.id.clone(),
+        provider,
+        status: bead.status,
+    });
+
+    if let Some(convoy_id) = &bead.convoy_id {
+        let members = beads.list_by_convoy(convoy_id).await?;
+        events.publish(ForemanEvent::ConvoyProgress {
+            convoy_id: convoy_id.clone(),
+            finished: members.iter().filter(|b| b.status.is_terminal()).count(),
+            failed: members
+                .iter()
+                .filter(|b| b.status == BeadStatus::Failed)
+                .count(),
+            total: members.len(),
+        });
+
+        if bead.status == BeadStatus::Failed {
+            if let Some(ratio) = policy.convoy_failure_ratio {
+                halt_if_failing(beads, events, policy, convoy_id, &members, ratio).await?;
+            }
+        }
+    }
+    Ok(Some(bead))
+}
+
+/// Put a successfully executed bead through the quality gate
+///
+/// Leaves the bead `Completed`, requeued with the rejection as its error, or
+/// `Failed` once it has no retries left.
+async fn review(beads: &dyn BeadRepository, gate: &dyn QualityGate, bead: &mut Bead) -> Result<()> {
+    if bead.acceptance_criteria.is_empty() {
+        return Ok(());
+    }
+    bead.status = BeadStatus::Reviewing;
+    beads.update(bead).await?;
+
+    match gate.review_bead(bead).await {
+        Ok(ReviewOutcome::Pass) => bead.status = BeadStatus::Completed,
+        Ok(ReviewOutcome::Fail { reasons }) => {
+            let rejection = RigsError::QualityGateRejected(reasons);
+            info!(bead = %bead.id, "{}", rejection);
+            bead.status = BeadStatus::Failed;
+            bead.requeue_for_retry();
+            bead.record_error(&rejection);
+        }
+        Err(e) => {
+            warn!(bead = %bead.id, error = %e, "Quality gate unavailable; accepting the output");
+            bead.status = BeadStatus::Completed;
+        }
+    }
+    Ok(())
+}
+
+/// Cancel a convoy's remaining beads once too many of them have failed
+///
+/// Marks the convoy halted for the rest of the run so already-picked-up
+/// beads are skipped too, and stores it as `Failed` when the foreman has a
+/// convoy repository.
+async fn halt_if_failing(
+    beads: &dyn BeadRepository,
+    events: &EventBus,
+    policy: &DispatchPolicy,
+    convoy_id: &str,
+    members: &[Bead],
+    ratio: f64,
+) -> Result<()> {
+    let failed = members
+        .iter()
+        .filter(|b| b.status == BeadStatus::Failed)
+        .count();
+    if (failed as f64) <= ratio * members.len() as f64 {
+        return Ok(());
+    }
+    let newly_halted = policy
+        .halted
+        .lock()
+        .map_err(|_| RigsError::Other("Halted convoy set poisoned".into()))?
+        .insert(convoy_id.to_string());
+    if !newly_halted {
+        return Ok(());
+    }
+
+    let mut cancelled = 0;
+    for member in members {
+        if member.status.is_terminal() || member.status.is_active() {
+            continue;
+        }
+        let mut member = member.clone();
+        member.status = BeadStatus::Cancelled;
+        member.error = Some(format!(
+            "Cancelled: {} of {} beads in convoy {} failed",
+            failed,
+            members.len(),
+            convoy_id
+        ));
+        beads.update(&member).await?;
+        cancelled += 1;
+    }
+    if let Some(convoys) = &policy.convoys {
+        if let Some(mut convoy) = convoys.get(convoy_id).await? {
+            convoy.status = ConvoyStatus::Failed;
+            convoy.completed_at = Some(Utc::now());
+            convoys.update(&convoy).await?;
+        }
+    }
+    events.publish(ForemanEvent::ConvoyHalted {
+        convoy_id: convoy_id.to_string(),
+        failed,
+        cancelled,
+        total: members.len(),
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::budget::Budget;
+    use crate::core::executor::{ExecutionOutput, ExecutionRequest};
+    use crate::core::gate::StubGate;
+    use crate::core::pricing::PricingTable;
+    use crate::core::{Convoy, Provider, TaskType};
+    use crate::db::{init_memory_pool, repository::SqliteRepository};
+    use async_trait::async_trait;
+
+    struct EchoExecutor;
+
+    #[async_trait]
+    impl Executor for EchoExecutor {
+        fn kind(&self) -> &'static str {
+            "echo"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            if request.prompt.contains("fail") {
+                return Err(RigsError::ProviderApiError(request.provider, "boom".into()));
+            }
+            Ok(ExecutionOutput::estimated(
+                request,
+                request.prompt.to_uppercase(),
+                std::time::Duration::ZERO,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_matches_dispatch_order() {
+        let (foreman, repo) = foreman(1).await;
+        let research = Bead::new("Research", "r", TaskType::Research);
+        let design =
+            Bead::new("Design", "d", TaskType::Design).with_dependencies(vec![research.id.clone()]);
+        let build = Bead::new("Build", "b", TaskType::Implementation)
+            .with_priority(crate::core::Priority::Critical)
+            .with_dependencies(vec![design.id.clone()]);
+        let docs = Bead::new("Docs", "d", TaskType::Documentation)
+            .with_priority(crate::core::Priority::High);
+        let review =
+            Bead::new("Review", "r", TaskType::Review).with_priority(crate::core::Priority::Low);
+        for (i, bead) in [&research, &design, &build, &docs, &review]
+            .into_iter()
+            .enumerate()
+        {
+            let mut bead = bead.clone();
+            bead.created_at += chrono::Duration::milliseconds(i as i64);
+            repo.create(&bead).await.unwrap();
+        }
+
+        let plan = foreman.plan(&[]).await.unwrap();
+        assert!(plan.blocked.is_empty());
+
+        let mut events = foreman.subscribe();
+        while foreman.run_once().await.unwrap() > 0 {}
+        let mut started = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let ForemanEvent::BeadStarted { bead_id, .. } = event {
+                started.push(bead_id);
+            }
+        }
+
+        assert_eq!(plan.order(), started);
+        assert_eq!(
+            started,
+            vec![docs.id, research.id, review.id, design.id, build.id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_convoy_failure_ratio_halts_dispatch() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let config = ForemanConfig {
+            convoy_failure_ratio: Some(0.25),
+            ..ForemanConfig::default()
+        };
+        let foreman =
+            Foreman::new(repo.clone(), Arc::new(EchoExecutor), config).with_convoys(repo.clone());
+        let mut events = foreman.subscribe();
+        let mut convoy = Convoy::new("halting");
+        convoy.id = "cv-1".into();
+        repository::ConvoyRepository::create(repo.as_ref(), &convoy)
+            .await
+            .unwrap();
+
+        let mut ids = Vec::new();
+        for (i, prompt) in ["fail", "fail", "ok", "ok", "ok"].into_iter().enumerate() {
+            let mut bead = Bead::new(format!("Step {}", i), prompt, TaskType::Review);
+            bead.convoy_id = Some("cv-1".into());
+            bead.created_at += chrono::Duration::milliseconds(i as i64);
+            repo.create(&bead).await.unwrap();
+            ids.push(bead.id);
+        }
+
+        // 1 of 5 failed is within the ratio; the second failure crosses it
+        assert_eq!(foreman.run_once().await.unwrap(), 2);
+        assert_eq!(foreman.run_once().await.unwrap(), 0);
+
+        let statuses: Vec<BeadStatus> = bead_statuses(&repo, &ids).await;
+        assert_eq!(
+            statuses,
+            vec![
+                BeadStatus::Failed,
+                BeadStatus::Failed,
+                BeadStatus::Cancelled,
+                BeadStatus::Cancelled,
+                BeadStatus::Cancelled,
+            ]
+        );
+
+        let mut halted = None;
+        while let Ok(event) = events.try_recv() {
+            if let ForemanEvent::ConvoyHalted { .. } = event {
+                halted = Some(event);
+            }
+        }
+        assert!(matches!(
+            halted,
+            Some(ForemanEvent::ConvoyHalted {
+                failed: 2,
+                cancelled: 3,
+                total: 5,
+                ..
+            })
+        ));
+        let stored = repository::ConvoyRepository::get(repo.as_ref(), "cv-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, ConvoyStatus::Failed);
+        assert!(stored.completed_at.is_some());
+    }
+
+    async fn bead_statuses(repo: &SqliteRepository, ids: &[BeadId]) -> Vec<BeadStatus> {
+        let mut statuses = Vec::new();
+        for id in ids {
+            statuses.push(repo.get(id).await.unwrap().unwrap().status);
+        }
+        statuses
+    }
+
+    /// Executor whose Claude integration is broken
+    struct ClaudeDownExecutor;
+
+    #[async_trait]
+    impl Executor for ClaudeDownExecutor {
+        fn kind(&self) -> &'static str {
+            "claude-down"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            if request.provider == Provider::Claude {
+                return Err(RigsError::ProviderApiError(
+                    request.provider,
+                    "overloaded".into(),
+                ));
+            }
+            EchoExecutor.execute(request).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failures_switch_provider() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let foreman = Foreman::new(
+            repo.clone(),
+            Arc::new(ClaudeDownExecutor),
+            ForemanConfig::default(),
+        );
+        let mut events = foreman.subscribe();
+        let bead = Bead::new("Parser", "parser", TaskType::Implementation);
+        repo.create(&bead).await.unwrap();
+
+        let mut providers = Vec::new();
+        for _ in 0..3 {
+            foreman.run_once().await.unwrap();
+            let mut stored = repo.get(&bead.id).await.unwrap().unwrap();
+            providers.push(stored.assigned_provider.unwrap());
+            if stored.requeue_for_retry() {
+                repo.update(&stored).await.unwrap();
+            }
+        }
+
+        assert_eq!(
+            providers,
+            vec![Provider::Claude, Provider::Claude, Provider::Codex]
+        );
+        let stored = repo.get(&bead.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, BeadStatus::Completed);
+        assert_eq!(stored.excluded_providers, vec![Provider::Claude]);
+
+        let mut switches = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let ForemanEvent::ProviderSwitched { from, to, .. } = event {
+                switches.push((from, to));
+            }
+        }
+        assert_eq!(switches, vec![(Provider::Claude, Provider::Codex)]);
+    }
+
+    #[tokio::test]
+    async fn test_run_once_publishes_events() {
+        let (foreman, repo) = foreman(1).await;
+        let mut events = foreman.subscribe();
+        let mut ok = Bead::new("Ok", "ok", TaskType::Review);
+        ok.convoy_id = Some("cv-1".into());
+        let mut bad = Bead::new("Bad", "fail", TaskType::Review);
+        bad.convoy_id = Some("cv-1".into());
+        bad.priority = crate::core::Priority::Low;
+        repo.create(&ok).await.unwrap();
+        repo.create(&bad).await.unwrap();
+
+        foreman.run_once().await.unwrap();
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event);
+        }
+        assert_eq!(received.len(), 6, "{:?}", received);
+        assert!(matches!(&received[0],
+            ForemanEvent::BeadStarted { bead_id, provider: Provider::Codex } if *bead_id == ok.id));
+        assert!(matches!(
+            &received[1],
+            ForemanEvent::BeadCompleted {
+                status: BeadStatus::Completed,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &received[2],
+            ForemanEvent::ConvoyProgress {
+                finished: 1,
+                failed: 0,
+                total: 2,
+                ..
+            }
+        ));
+        assert!(matches!(&received[4],
+            ForemanEvent::BeadCompleted { bead_id, status: BeadStatus::Failed, .. } if *bead_id == bad.id));
+        assert!(matches!(
+            &received[5],
+            ForemanEvent::ConvoyProgress {
+                finished: 2,
+                failed: 1,
+                total: 2,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_token_budget_stops_dispatch() {
+        let (foreman, repo) = foreman(1).await;
+        let budget = BudgetTracker::new(
+            Budget {
+                max_tokens: Some(2_500),
+                ..Budget::default()
+            },
+            PricingTable::default(),
+        );
+        let foreman = foreman.with_budget(budget);
+
+        // The echo executor reports 500 + 500 tokens, matching the estimate
+        let prompt = "x".repeat(2_000);
+        let mut ids = Vec::new();
+        for i in 0..5 {
+            let mut bead = Bead::new(format!("Step {}", i), &prompt, TaskType::Implementation)
+                .with_estimate(1_000);
+            bead.created_at += chrono::Duration::milliseconds(i);
+            repo.create(&bead).await.unwrap();
+            ids.push(bead.id);
+        }
+
+        assert_eq!(foreman.run_once().await.unwrap(), 2);
+        assert_eq!(foreman.run_once().await.unwrap(), 0);
+
+        for (i, id) in ids.iter().enumerate() {
+            let bead = repo.get(id).await.unwrap().unwrap();
+            if i < 2 {
+                assert_eq!(bead.status, BeadStatus::Completed);
+            } else {
+                assert_eq!(bead.status, BeadStatus::Deferred);
+                assert!(bead.error.unwrap().contains("token budget of 2500"));
+            }
+        }
+        assert_eq!(foreman.budget_spent().unwrap().0, 2_000);
+    }
+
+    #[tokio::test]
+    async fn test_executions_are_charged_to_the_saved_tank() {
+        let (foreman, repo) = foreman(1).await;
+        let foreman = foreman.with_tanks(repo.clone(), repo.clone());
+        let tank = Tank::new(Provider::Claude, 10_000, 5);
+
+        let prompt = "x".repeat(2_000);
+        let mut ids = Vec::new();
+        for i in 0..2 {
+            let mut bead = Bead::new(format!("Step {}", i), &prompt, TaskType::Implementation)
+                .with_estimate(1_000);
+            bead.created_at += chrono::Duration::milliseconds(i);
+            repo.create(&bead).await.unwrap();
+            ids.push(bead.id);
+        }
+        assert_eq!(foreman.run_once_within(&[tank]).await.unwrap(), 2);
+
+        let mut used = 0;
+        for id in &ids {
+            used += repo.get(id).await.unwrap().unwrap().actual_tokens.unwrap();
+        }
+        let saved = repository::TankRepository::get(repo.as_ref(), Provider::Claude)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.remaining, 10_000 - used);
+        assert_eq!(saved.requests_this_window, 2);
+
+        let history = repo
+            .list_usage(
+                Some(Provider::Claude),
+                Utc::now() - chrono::Duration::hours(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.iter().map(|e| e.tokens).sum::<u64>(), used);
+        assert_eq!(history.last().unwrap().remaining, saved.remaining);
+    }
+
+    #[tokio::test]
+    async fn test_expired_drained_tank_starts_a_new_window() {
+        let (foreman, repo) = foreman(1).await;
+        let foreman = foreman.with_tanks(repo.clone(), repo.clone());
+        let mut tank = Tank::new(Provider::Claude, 10_000, 5);
+        tank.consume(10_000).unwrap();
+        tank.window_start = Utc::now() - chrono::Duration::hours(10);
+        tank.window_end = Utc::now() - chrono::Duration::hours(5);
+        repository::TankRepository::upsert(repo.as_ref(), &tank)
+            .await
+            .unwrap();
+
+        let bead =
+            Bead::new("Step", "x".repeat(2_000), TaskType::Implementation).with_estimate(1_000);
+        repo.create(&bead).await.unwrap();
+        assert_eq!(foreman.run_once_within(&[tank]).await.unwrap(), 1);
+
+        let bead = repo.get(&bead.id).await.unwrap().unwrap();
+        assert_eq!(bead.status, BeadStatus::Completed);
+        let saved = repository::TankRepository::get(repo.as_ref(), Provider::Claude)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(saved.window_end > Utc::now());
+        assert_eq!(saved.remaining, 10_000 - bead.actual_tokens.unwrap());
+        let wake = foreman.next_wake(&[saved]).await.unwrap();
+        assert!(wake.is_none_or(|at| at > Utc::now()));
+    }
+
+    #[tokio::test]
+    async fn test_budget_deferral_returns_the_tank_hold() {
+        let (foreman, repo) = foreman(1).await;
+        let foreman = foreman.with_budget(BudgetTracker::new(
+            Budget {
+                max_tokens: Some(600),
+                ..Budget::default()
+            },
+            PricingTable::default(),
+        ));
+        let tank = Tank::new(Provider::Claude, 1_200, 5);
+
+        // Over the budget, then within both the budget and the tank
+        let large =
+            Bead::new("Large", "x".repeat(2_000), TaskType::Implementation).with_estimate(1_000);
+        let mut small =
+            Bead::new("Small", "x".repeat(400), TaskType::Implementation).with_estimate(500);
+        small.created_at += chrono::Duration::milliseconds(1);
+        repo.create(&large).await.unwrap();
+        repo.create(&small).await.unwrap();
+
+        assert_eq!(foreman.run_once_within(&[tank]).await.unwrap(), 1);
+        let large = repo.get(&large.id).await.unwrap().unwrap();
+        assert_eq!(large.status, BeadStatus::Deferred);
+        assert!(large.error.unwrap().contains("budget"));
+        let small = repo.get(&small.id).await.unwrap().unwrap();
+        assert_eq!(small.status, BeadStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_run_once_records_latency() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let foreman = Foreman::new(
+            repo.clone(),
+            Arc::new(EchoExecutor),
+            ForemanConfig::default(),
+        )
+        .with_latency(repo.clone());
+        repo.create(&Bead::new("Ok", "ok", TaskType::Review))
+            .await
+            .unwrap();
+        repo.create(&Bead::new("Bad", "fail", TaskType::Review))
+            .await
+            .unwrap();
+
+        foreman.run_once().await.unwrap();
+
+        let latencies = repo.get_latencies().await.unwrap();
+        assert_eq!(latencies.len(), 1);
+        assert_eq!(latencies[0].provider, Provider::Codex);
+        assert_eq!(latencies[0].samples, 1);
+    }
+
+    /// Executor that sleeps briefly and records peak concurrency per prompt
+    #[derive(Default)]
+    struct TrackingExecutor {
+        in_flight: Mutex<HashMap<String, (usize, usize)>>,
+    }
+
+    impl TrackingExecutor {
+        fn peak(&self, prompt: &str) -> usize {
+            self.in_flight.lock().unwrap()[prompt].1
+        }
+    }
+
+    #[async_trait]
+    impl Executor for TrackingExecutor {
+        fn kind(&self) -> &'static str {
+            "tracking"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            {
+                let mut in_flight = self.in_flight.lock().unwrap();
+                let (current, peak) = in_flight.entry(request.prompt.clone()).or_default();
+                *current += 1;
+                *peak = (*peak).max(*current);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            self.in_flight
+                .lock()
+                .unwrap()
+                .get_mut(&request.prompt)
+                .unwrap()
+                .0 -= 1;
+            Ok(ExecutionOutput::estimated(
+                request,
+                String::new(),
+                std::time::Duration::from_millis(50),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_output_postprocessed_per_task_type() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let config = ForemanConfig {
+            postprocess: HashMap::from([(
+                TaskType::Implementation,
+                vec![PostProcessor::ExtractCode, PostProcessor::Trim],
+            )]),
+            ..ForemanConfig::default()
+        };
+        let foreman = Foreman::new(repo.clone(), Arc::new(EchoExecutor), config);
+        let prompt = "Sure:\n```rust\nlet x = 1;\n```";
+        let code = Bead::new("Code", prompt, TaskType::Implementation);
+        let docs = Bead::new("Docs", prompt, TaskType::Documentation);
+        repo.create(&code).await.unwrap();
+        repo.create(&docs).await.unwrap();
+
+        foreman.run_once().await.unwrap();
+
+        let code = repo.get(&code.id).await.unwrap().unwrap();
+        assert_eq!(code.output.as_deref(), Some("LET X = 1;"));
+        let docs = repo.get(&docs.id).await.unwrap().unwrap();
+        assert!(docs.output.unwrap().starts_with("SURE:"));
+    }
+
+    #[tokio::test]
+    async fn test_type_concurrency_limit() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let executor = Arc::new(TrackingExecutor::default());
+        let config = ForemanConfig {
+            max_concurrent: 4,
+            type_concurrency: HashMap::from([(TaskType::Implementation, 1)]),
+            ..ForemanConfig::default()
+        };
+        let foreman = Foreman::new(repo.clone(), executor.clone(), config);
+
+        for i in 0..2 {
+            repo.create(&Bead::new(
+                format!("Impl {}", i),
+                "impl",
+                TaskType::Implementation,
+            ))
+            .await
+            .unwrap();
+            repo.create(&Bead::new(
+                format!("Review {}", i),
+                "review",
+                TaskType::Review,
+            ))
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(foreman.run_once().await.unwrap(), 4);
+        assert_eq!(executor.peak("impl"), 1);
+        assert_eq!(executor.peak("review"), 2);
+    }
+
+    /// Optimizes prompts to "impl" slowly, tracking how many run at once
+    #[derive(Default)]
+    struct SlowAssayer {
+        current: Mutex<(usize, usize)>,
+    }
+
+    #[async_trait]
+    impl Assay for SlowAssayer {
+        async fn assay(&self, bead: &mut Bead) -> Result<()> {
+            {
+                let mut current = self.current.lock().unwrap();
+                current.0 += 1;
+                current.1 = current.1.max(current.0);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            self.current.lock().unwrap().0 -= 1;
+            bead.optimized_prompt = Some("impl".into());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assay_stage_has_its_own_concurrency_limit() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let executor = Arc::new(TrackingExecutor::default());
+        let assayer = Arc::new(SlowAssayer::default());
+        let config = ForemanConfig {
+            max_concurrent: 4,
+            ..ForemanConfig::default()
+        };
+        let foreman =
+            Foreman::new(repo.clone(), executor.clone(), config).with_assayer(assayer.clone(), 1);
+        for i in 0..4 {
+            let bead = Bead::new(format!("Impl {}", i), "raw", TaskType::Implementation);
+            repo.create(&bead).await.unwrap();
+        }
+
+        assert_eq!(foreman.run_once().await.unwrap(), 4);
+        assert_eq!(assayer.current.lock().unwrap().1, 1);
+        // Later beads are still being assayed while earlier ones execute
+        assert!(executor.peak("impl") >= 2);
+        for bead in repo.list_by_status(BeadStatus::Completed).await.unwrap() {
+            assert_eq!(bead.optimized_prompt.as_deref(), Some("impl"));
+        }
+    }
+
+    async fn no_tanks() -> Result<Vec<Tank>> {
+        Ok(Vec::new())
+    }
+
+    async fn foreman(max_concurrent: u32) -> (Foreman, Arc<SqliteRepository>) {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let config = ForemanConfig {
+            max_concurrent,
+            ..ForemanConfig::default()
+        };
+        (
+            Foreman::new(repo.clone(), Arc::new(EchoExecutor), config),
+            repo,
+        )
+    }
+
+    #[test]
+    fn test_idle_duration_capped_by_poll_interval() {
+        let now = Utc::now();
+        let poll = Duration::from_secs(60);
+        let soon = now + chrono::Duration::seconds(20);
+        let later = now + chrono::Duration::minutes(10);
+        let past = now - chrono::Duration::seconds(5);
+
+        assert_eq!(
+            idle_duration(Some(soon), now, poll),
+            Duration::from_secs(20)
+        );
+        assert_eq!(idle_duration(Some(later), now, poll), poll);
+        assert_eq!(idle_duration(Some(past), now, poll), Duration::ZERO);
+        assert_eq!(idle_duration(None, now, poll), poll);
+    }
+
+    #[test]
+    fn test_poll_jitter_stays_within_band() {
+        use rand::SeedableRng;
+
+        let poll = Duration::from_secs(60);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let sleeps: Vec<Duration> = (0..50).map(|_| jittered(poll, 10, &mut rng)).collect();
+        assert!(sleeps
+            .iter()
+            .all(|&s| s >= Duration::from_secs(54) && s <= Duration::from_secs(66)));
+        assert!(sleeps.windows(2).any(|pair| pair[0] != pair[1]));
+        assert_eq!(jittered(poll, 0, &mut rng), poll);
+    }
+
+    #[tokio::test]
+    async fn test_run_sleeps_until_deferred_bead_is_ready() {
+        use chrono::SubsecRound;
+
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let config = ForemanConfig {
+            poll_interval: 60,
+            ..ForemanConfig::default()
+        };
+        let foreman = Foreman::new(repo.clone(), Arc::new(EchoExecutor), config);
+
+        // Stored timestamps keep microseconds
+        let ready_at = (Utc::now() + chrono::Duration::milliseconds(300)).trunc_subsecs(6);
+        let mut bead = Bead::new("Later", "later", TaskType::Implementation);
+        bead.status = BeadStatus::Deferred;
+        bead.deferred_until = Some(ready_at);
+        repo.create(&bead).await.unwrap();
+        let mut tank = Tank::new(Provider::Claude, 1_000, 5);
+        tank.remaining = 0;
+        assert_eq!(foreman.next_wake(&[tank]).await.unwrap(), Some(ready_at));
+
+        let mut events = foreman.subscribe();
+        let done = async {
+            while let Ok(event) = events.recv().await {
+                if matches!(event, ForemanEvent::BeadCompleted { .. }) {
+                    break;
+                }
+            }
+        };
+        // One poll interval is a minute, so finishing within seconds means the
+        // loop woke at the deferral time instead of waiting for the next poll
+        tokio::time::timeout(Duration::from_secs(5), foreman.run(no_tanks, done))
+            .await
+            .expect("foreman slept past the wake time")
+            .unwrap();
+
+        let bead = repo.get(&bead.id).await.unwrap().unwrap();
+        assert_eq!(bead.status, BeadStatus::Completed);
+        assert!(bead.started_at.unwrap() >= ready_at);
+    }
+
+    #[tokio::test]
+    async fn test_waker_interrupts_idle_sleep() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let config = ForemanConfig {
+            poll_interval: 60,
+            ..ForemanConfig::default()
+        };
+        let foreman = Foreman::new(repo.clone(), Arc::new(EchoExecutor), config);
+        let waker = foreman.waker();
+        let mut events = foreman.subscribe();
+        let bead = Bead::new("New", "new", TaskType::Implementation);
+
+        let add = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            repo.create(&bead).await.unwrap();
+            waker.notify_one();
+            while let Ok(event) = events.recv().await {
+                if matches!(event, ForemanEvent::BeadCompleted { .. }) {
+                    break;
+                }
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), foreman.run(no_tanks, add))
+            .await
+            .expect("new bead waited for the next poll")
+            .unwrap();
+        assert_eq!(
+            repo.get(&bead.id).await.unwrap().unwrap().status,
+            BeadStatus::Completed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_once_processes_pending() {
+        let (foreman, repo) = foreman(2).await;
+        let ok = Bead::new("Ok", "do it", TaskType::Implementation);
+        let bad = Bead::new("Bad", "fail please", TaskType::Review);
+        repo.create(&ok).await.unwrap();
+        repo.create(&bad).await.unwrap();
+
+        assert_eq!(foreman.run_once().await.unwrap(), 2);
+
+        let ok = repo.get(&ok.id).await.unwrap().unwrap();
+        assert_eq!(ok.status, BeadStatus::Completed);
+        assert_eq!(ok.output.as_deref(), Some("DO IT"));
+        assert_eq!(ok.assigned_provider, Some(Provider::Claude));
+
+        let bad = repo.get(&bad.id).await.unwrap().unwrap();
+        assert_eq!(bad.status, BeadStatus::Failed);
+        assert!(bad.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_convoy_foreman_leaves_other_beads_pending() {
+        let (foreman, repo) = foreman(2).await;
+        let foreman = foreman.with_convoy("cv-1");
+        let mut mine = Bead::new("Mine", "mine", TaskType::Review);
+        mine.convoy_id = Some("cv-1".into());
+        let other = Bead::new("Other", "other", TaskType::Review);
+        repo.create(&mine).await.unwrap();
+        repo.create(&other).await.unwrap();
+
+        assert_eq!(foreman.run_once().await.unwrap(), 1);
+        assert_eq!(
+            bead_statuses(&repo, &[mine.id, other.id]).await,
+            [BeadStatus::Completed, BeadStatus::Pending]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_once_waits_for_dependencies() {
+        let (foreman, repo) = foreman(1).await;
+        let first = Bead::new("First", "first", TaskType::Research);
+        let second = Bead::new("Second", "second", TaskType::Design)
+            .with_dependencies(vec![first.id.clone()]);
+        repo.create(&second).await.unwrap();
+        repo.create(&first).await.unwrap();
+
+        assert_eq!(foreman.run_once().await.unwrap(), 1);
+        assert_eq!(
+            repo.get(&second.id).await.unwrap().unwrap().status,
+            BeadStatus::Pending
+        );
+
+        assert_eq!(foreman.run_once().await.unwrap(), 1);
+        assert_eq!(
+            repo.get(&second.id).await.unwrap().unwrap().status,
+            BeadStatus::Completed
+        );
+    }
+
+    /// Records every prompt it is sent and answers with a fixed text
+    struct PromptLog(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl Executor for PromptLog {
+        fn kind(&self) -> &'static str {
+            "log"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            self.0.lock().unwrap().push(request.prompt.clone());
+            Ok(ExecutionOutput::estimated(
+                request,
+                "Use a users table keyed by email".into(),
+                std::time::Duration::ZERO,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dependency_output_injected_into_dependent_prompt() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let executor = Arc::new(PromptLog(Mutex::default()));
+        let config = ForemanConfig {
+            inject_dependency_output: true,
+            ..ForemanConfig::default()
+        };
+        let foreman = Foreman::new(repo.clone(), executor.clone(), config);
+        let design = Bead::new("Schema", "design the schema", TaskType::Design);
+        let build = Bead::new("Build", "implement the schema", TaskType::Implementation)
+            .with_dependencies(vec![design.id.clone()]);
+        repo.create(&design).await.unwrap();
+        repo.create(&build).await.unwrap();
+
+        assert_eq!(foreman.run_once().await.unwrap(), 1);
+        assert_eq!(foreman.run_once().await.unwrap(), 1);
+
+        let sent = executor.0.lock().unwrap();
+        assert!(!sent[0].contains("Output of"));
+        assert!(sent[1].starts_with(&format!(
+            "## Output of Schema ({})\n\nUse a users table keyed by email\n\n",
+            design.id
+        )));
+        assert!(sent[1].contains("implement the schema"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_execution_diagnostics_kept_in_history() {
+        use crate::core::executor::{CliCommand, CliExecutor};
+
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let executor = CliExecutor::default().with_command(
+            Provider::Claude,
+            CliCommand::new(
+                "sh",
+                &["-c", "echo partial; echo quota exceeded >&2; exit 2"],
+            ),
+        );
+        let foreman = Foreman::new(repo.clone(), Arc::new(executor), ForemanConfig::default())
+            .with_history(repo.clone());
+        let bead = Bead::new("Plan", "plan it", TaskType::Design).with_provider(Provider::Claude);
+        repo.create(&bead).await.unwrap();
+
+        assert_eq!(foreman.run_once().await.unwrap(), 1);
+
+        let attempts = repo.list_completions(&bead.id).await.unwrap();
+        assert_eq!(attempts.len(), 1);
+        assert!(!attempts[0].succeeded());
+        assert_eq!(attempts[0].provider, Provider::Claude);
+        let diagnostics = attempts[0].diagnostics.as_ref().unwrap();
+        assert_eq!(diagnostics.exit_code, Some(2));
+        assert_eq!(diagnostics.stderr, "quota exceeded\n");
+        assert_eq!(diagnostics.stdout, "partial\n");
+        assert!(attempts[0]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("quota exceeded"));
+    }
+
+    #[tokio::test]
+    async fn test_local_only_runs_on_ollama() {
+        let (foreman, repo) = foreman(2).await;
+        let foreman = foreman.with_local_only(true);
+        let small =
+            Bead::new("Small", "do it", TaskType::Implementation).with_provider(Provider::Claude);
+        let huge = Bead::new("Huge", "everything", TaskType::Review).with_estimate(1_000_000);
+        repo.create(&small).await.unwrap();
+        repo.create(&huge).await.unwrap();
+
+        assert_eq!(foreman.run_once().await.unwrap(), 1);
+
+        let small = repo.get(&small.id).await.unwrap().unwrap();
+        assert_eq!(small.status, BeadStatus::Completed);
+        assert_eq!(small.assigned_provider, Some(Provider::Ollama));
+
+        let huge = repo.get(&huge.id).await.unwrap().unwrap();
+        assert_eq!(huge.status, BeadStatus::Failed);
+        assert!(huge.error.unwrap().contains("can't be served locally"));
+    }
+
+    #[tokio::test]
+    async fn test_provider_order_overrides_affinity() {
+        let (foreman, repo) = foreman(1).await;
+        let foreman =
+            foreman.with_provider_order(vec![Provider::Gemini, Provider::Claude, Provider::Codex]);
+        let review = Bead::new("Review", "review", TaskType::Review);
+        let mut retried = Bead::new("Retried", "retried", TaskType::Review);
+        retried.excluded_providers = vec![Provider::Gemini];
+        retried.created_at += chrono::Duration::milliseconds(1);
+        repo.create(&review).await.unwrap();
+        repo.create(&retried).await.unwrap();
+
+        assert_eq!(foreman.run_once().await.unwrap(), 2);
+        let provider = |bead: Option<Bead>| bead.unwrap().assigned_provider;
+        assert_eq!(
+            provider(repo.get(&review.id).await.unwrap()),
+            Some(Provider::Gemini)
+        );
+        assert_eq!(
+            provider(repo.get(&retried.id).await.unwrap()),
+            Some(Provider::Claude)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quality_gate_decides_completion() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let reasons = vec!["Missing error handling".to_string()];
+        let foreman = |gate: StubGate| {
+            Foreman::new(
+                repo.clone(),
+                Arc::new(EchoExecutor),
+                ForemanConfig::default(),
+            )
+            .with_gate(Arc::new(gate))
+        };
+        let mut bead = Bead::new("Parser", "parse", TaskType::Implementation)
+            .with_criteria(vec!["Handles errors".into()]);
+        bead.max_retries = 1;
+        repo.create(&bead).await.unwrap();
+
+        // Rejected with a retry left: back in the queue with the reasons
+        foreman(StubGate::Fail(reasons.clone()))
+            .run_once()
+            .await
+            .unwrap();
+        let stored = repo.get(&bead.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, BeadStatus::Queued);
+        assert_eq!(stored.retry_count, 1);
+        assert_eq!(
+            stored.error.as_deref(),
+            Some("Rejected by quality gate: Missing error handling")
+        );
+
+        // Rejected again: out of retries
+        foreman(StubGate::Fail(reasons)).run_once().await.unwrap();
+        let stored = repo.get(&bead.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, BeadStatus::Failed);
+        assert!(stored.completed_at.is_some());
+
+        let passing = Bead::new("Lexer", "lex", TaskType::Implementation)
+            .with_criteria(vec!["Handles errors".into()]);
+        repo.create(&passing).await.unwrap();
+        foreman(StubGate::Pass).run_once().await.unwrap();
+        let stored = repo.get(&passing.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, BeadStatus::Completed);
+        assert_eq!(stored.output.as_deref(), Some("LEX"));
+    }
+
+    #[tokio::test]
+    async fn test_beads_beyond_tank_capacity_are_deferred() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let foreman = Foreman::new(
+            repo.clone(),
+            Arc::new(EchoExecutor),
+            ForemanConfig::default(),
+        );
+        let beads: Vec<Bead> = (0..3)
+            .map(|i| {
+                Bead::new(format!("Step {}", i), "step", TaskType::Implementation)
+                    .with_provider(Provider::Claude)
+                    .with_estimate(400)
+            })
+            .collect();
+        for bead in &beads {
+            repo.create(bead).await.unwrap();
+        }
+        let mut claude = Tank::new(Provider::Claude, 1_000, 5);
+        claude.window_end = "2030-01-01T00:00:00Z".parse().unwrap();
+
+        assert_eq!(
+            foreman
+                .run_once_within(std::slice::from_ref(&claude))
+                .await
+                .unwrap(),
+            2
+        );
+        let deferred = repo.list_by_status(BeadStatus::Deferred).await.unwrap();
+        assert_eq!(deferred.len(), 1);
+        assert_eq!(deferred[0].deferred_until, Some(claude.window_end));
+        assert_eq!(
+            repo.list_by_status(BeadStatus::Completed)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // A tank for another provider doesn't hold Claude beads back
+        let mut queued = deferred[0].clone();
+        queued.status = BeadStatus::Queued;
+        repo.update(&queued).await.unwrap();
+        let codex = Tank::new(Provider::Codex, 0, 5);
+        assert_eq!(foreman.run_once_within(&[codex]).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_low_tank_runs_on_fallback_model() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let mut claude_config = ProviderConfig::claude_default();
+        claude_config.fallback_under_pressure = true;
+        let foreman = Foreman::new(
+            repo.clone(),
+            Arc::new(EchoExecutor),
+            ForemanConfig::default(),
+        )
+        .with_models(HashMap::from([(Provider::Claude, claude_config)]));
+        let bead = Bead::new("Parser", "parser", TaskType::Implementation)
+            .with_provider(Provider::Claude)
+            .with_estimate(400);
+        repo.create(&bead).await.unwrap();
+
+        let mut claude = Tank::new(Provider::Claude, 100_000, 5);
+        claude.update_remaining(10_000);
+        assert_eq!(claude.health, TankHealth::Red);
+        foreman
+            .run_once_within(std::slice::from_ref(&claude))
+            .await
+            .unwrap();
+
+        let done = repo.get(&bead.id).await.unwrap().unwrap();
+        assert_eq!(done.status, BeadStatus::Completed);
+        assert_eq!(done.provenance.unwrap().model, "claude-haiku-4-20250514");
+    }
+
+    /// Fails with a 503 the first `blips` times it is called
+    struct FlakyExecutor {
+        blips: std::sync::atomic::AtomicU32,
+    }
+
+    #[async_trait]
+    impl Executor for FlakyExecutor {
+        fn kind(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            use std::sync::atomic::Ordering;
+            if self
+                .blips
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(RigsError::ProviderUnavailable(
+                    request.provider,
+                    "503 Service Unavailable".into(),
+                ));
+            }
+            EchoExecutor.execute(request).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failure_does_not_use_bead_retries() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let config = ForemanConfig {
+            request_retries: 2,
+            request_backoff_ms: 1,
+            ..ForemanConfig::default()
+        };
+        let executor = FlakyExecutor { blips: 1.into() };
+        let foreman = Foreman::new(repo.clone(), Arc::new(executor), config);
+        let blip = Bead::new("Blip", "ok", TaskType::Review);
+        let bad = Bead::new("Bad", "fail", TaskType::Review);
+        repo.create(&blip).await.unwrap();
+        repo.create(&bad).await.unwrap();
+
+        assert_eq!(foreman.run_once().await.unwrap(), 2);
+
+        // The 503 was resent inside the executor
+        let blip = repo.get(&blip.id).await.unwrap().unwrap();
+        assert_eq!(blip.status, BeadStatus::Completed);
+        assert_eq!(blip.retry_count, 0);
+
+        // A real failure reaches the bead, and retrying it is counted
+        let mut bad = repo.get(&bad.id).await.unwrap().unwrap();
+        assert_eq!(bad.status, BeadStatus::Failed);
+        assert!(bad.requeue_for_retry());
+        assert_eq!(bad.retry_count, 1);
+    }
+
+    /// Takes as many milliseconds as the prompt says
+    struct SlowExecutor;
+
+    #[async_trait]
+    impl Executor for SlowExecutor {
+        fn kind(&self) -> &'static str {
+            "slow"
+        }
+
+        async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+            let millis = request.prompt.parse().unwrap_or(0);
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            EchoExecutor.execute(request).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_then_requeues_stragglers() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let config = ForemanConfig {
+            max_concurrent: 2,
+            shutdown_grace_secs: 1,
+            ..ForemanConfig::default()
+        };
+        let foreman = Foreman::new(repo.clone(), Arc::new(SlowExecutor), config);
+        let quick = Bead::new("Quick", "500", TaskType::Review);
+        let stuck = Bead::new("Stuck", "60000", TaskType::Review);
+        let waiting = Bead::new("Waiting", "0", TaskType::Review);
+        for bead in [&quick, &stuck, &waiting] {
+            repo.create(bead).await.unwrap();
+        }
+
+        let stop = tokio::time::sleep(Duration::from_millis(100));
+        let report = tokio::time::timeout(Duration::from_secs(5), foreman.run(no_tanks, stop))
+            .await
+            .expect("drain outlived the grace period")
+            .unwrap();
+
+        assert_eq!(report.finished, 1);
+        assert_eq!(report.requeued, vec![stuck.id.clone()]);
+        let status = |bead: Option<Bead>| bead.unwrap().status;
+        assert_eq!(
+            status(repo.get(&quick.id).await.unwrap()),
+            BeadStatus::Completed
+        );
+        let stuck = repo.get(&stuck.id).await.unwrap().unwrap();
+        assert_eq!(stuck.status, BeadStatus::Queued);
+        assert_eq!((stuck.retry_count, stuck.started_at), (0, None));
+        // Still waiting for a slot at shutdown, so never started
+        assert_eq!(
+            status(repo.get(&waiting.id).await.unwrap()),
+            BeadStatus::Pending
+        );
+    }
+
+    #[tokio::test]
+    async fn test_paused_foreman_dispatches_nothing() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let foreman = Foreman::new(
+            repo.clone(),
+            Arc::new(EchoExecutor),
+            ForemanConfig::default(),
+        )
+        .with_state(repo.clone());
+        let bead = Bead::new("Parser", "parser", TaskType::Review);
+        repo.create(&bead).await.unwrap();
+
+        foreman.set_paused(true).await.unwrap();
+        // The flag is durable, e.g. for a daemon paused from the CLI
+        let other = Foreman::new(
+            repo.clone(),
+            Arc::new(EchoExecutor),
+            ForemanConfig::default(),
+        )
+        .with_state(repo.clone());
+        assert!(other.is_paused().await.unwrap());
+
+        assert_eq!(other.run_once().await.unwrap(), 0);
+        let status = repo.get(&bead.id).await.unwrap().unwrap().status;
+        assert_eq!(status, BeadStatus::Pending);
+
+        foreman.set_paused(false).await.unwrap();
+        assert_eq!(other.run_once().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pause_lets_in_flight_beads_finish() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let foreman = Foreman::new(
+            repo.clone(),
+            Arc::new(SlowExecutor),
+            ForemanConfig::default(),
+        );
+        let running = Bead::new("Running", "300", TaskType::Review);
+        let next = Bead::new("Next", "0", TaskType::Review);
+        repo.create(&running).await.unwrap();
+        repo.create(&next).await.unwrap();
+
+        let (processed, _) = tokio::join!(foreman.run_once(), async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            foreman.set_paused(true).await.unwrap();
+        });
+
+        assert_eq!(processed.unwrap(), 1);
+        let status = |bead: Option<Bead>| bead.unwrap().status;
+        assert_eq!(
+            status(repo.get(&running.id).await.unwrap()),
+            BeadStatus::Completed
+        );
+        assert_eq!(
+            status(repo.get(&next.id).await.unwrap()),
+            BeadStatus::Pending
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_requested_mid_execution_is_honored() {
+        let repo = Arc::new(SqliteRepository::new(init_memory_pool().await.unwrap()));
+        let foreman = Foreman::new(
+            repo.clone(),
+            Arc::new(SlowExecutor),
+            ForemanConfig::default(),
+        );
+        let bead = Bead::new("Running", "300", TaskType::Review);
+        repo.create(&bead).await.unwrap();
+
+        let (processed, _) = tokio::join!(foreman.run_once(), async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            repo.request_cancel(&bead.id).await.unwrap();
+        });
+
+        processed.unwrap();
+        let stored = repo.get(&bead.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, BeadStatus::Cancelled);
+        assert!(stored.completed_at.is_some());
+        // Saving the cancelled bead leaves the request in place
+        assert!(repo.cancel_requested(&bead.id).await.unwrap());
+    }
+}
@@ -0,0 +1,222 @@
+//! Quality gate (post-execution review)
+//!
+//! Where assayers look at a bead before it runs, the quality gate judges
+//! finished work against acceptance criteria. A convoy with criteria of its
+//! own gets one last review of its beads' combined output once they have all
+//! finished, catching what no single bead can show (e.g. "all tests pass
+//! across beads").
+
+use async_trait::async_trait;
+
+use super::bead::Bead;
+use super::convoy::{Convoy, ConvoyStatus};
+use super::error::Result;
+use super::prompt::render_dependency_outputs;
+
+/// Metadata key holding why a convoy failed its gate
+pub const GATE_FAILURE_KEY: &str = "gate_failure";
+
+/// Heading of each bead's section in a convoy's combined output
+const SECTION_HEADER: &str = "## {title} ({id})";
+
+/// Outcome of a review
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verdict {
+    pub passed: bool,
+    /// Why the work was rejected (or any remarks when it passed)
+    pub reason: String,
+}
+
+/// Outcome of reviewing one bead
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewOutcome {
+    Pass,
+    Fail { reasons: Vec<String> },
+}
+
+impl From<Verdict> for ReviewOutcome {
+    /// Each non-empty line of a rejection's reason is one reason
+    fn from(verdict: Verdict) -> Self {
+        if verdict.passed {
+            return ReviewOutcome::Pass;
+        }
+        let mut reasons: Vec<String> = verdict
+            .reason
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
+        if reasons.is_empty() {
+            reasons.push("no reason given".into());
+        }
+        ReviewOutcome::Fail { reasons }
+    }
+}
+
+/// Judges output against acceptance criteria
+#[async_trait]
+pub trait QualityGate: Send + Sync {
+    async fn review(&self, criteria: &[String], output: &str) -> Result<Verdict>;
+
+    /// Review a finished bead's output against its acceptance criteria
+    ///
+    /// Beads without criteria pass without a review.
+    async fn review_bead(&self, bead: &Bead) -> Result<ReviewOutcome> {
+        if bead.acceptance_criteria.is_empty() {
+            return Ok(ReviewOutcome::Pass);
+        }
+        let output = bead.output.as_deref().unwrap_or_default();
+        Ok(self.review(&bead.acceptance_criteria, output).await?.into())
+    }
+}
+
+/// A gate with a fixed answer, for tests and dry runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StubGate {
+    Pass,
+    Fail(Vec<String>),
+}
+
+#[async_trait]
+impl QualityGate for StubGate {
+    async fn review(&self, _criteria: &[String], _output: &str) -> Result<Verdict> {
+        Ok(match self {
+            StubGate::Pass => Verdict {
+                passed: true,
+                reason: String::new(),
+            },
+            StubGate::Fail(reasons) => Verdict {
+                passed: false,
+                reason: reasons.join("\n"),
+            },
+        })
+    }
+}
+
+/// Run the convoy-level gate on a convoy whose beads have all completed
+///
+/// Only `Completed` convoys with acceptance criteria are reviewed, against
+/// their beads' outputs in convoy order. A rejection turns the convoy
+/// `CompletedWithErrors` and records the reason under [`GATE_FAILURE_KEY`].
+/// Returns the verdict if the gate ran.
+pub async fn review_convoy(
+    convoy: &mut Convoy,
+    beads: &[Bead],
+    gate: &dyn QualityGate,
+) -> Result<Option<Verdict>> {
+    if convoy.status != ConvoyStatus::Completed || convoy.acceptance_criteria.is_empty() {
+        return Ok(None);
+    }
+    let members: Vec<Bead> = convoy
+        .beads
+        .iter()
+        .filter_map(|id| beads.iter().find(|b| &b.id == id).cloned())
+        .collect();
+    let output = render_dependency_outputs(&members, SECTION_HEADER, u64::MAX);
+
+    let verdict = gate.review(&convoy.acceptance_criteria, &output).await?;
+    if !verdict.passed {
+        convoy.status = ConvoyStatus::CompletedWithErrors;
+        convoy.set_metadata(GATE_FAILURE_KEY, verdict.reason.clone());
+    }
+    Ok(Some(verdict))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::bead::{BeadId, BeadStatus, TaskType};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Fails any output that mentions a failing test
+    #[derive(Default)]
+    struct TestsPassGate {
+        seen: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl QualityGate for TestsPassGate {
+        async fn review(&self, _criteria: &[String], output: &str) -> Result<Verdict> {
+            self.seen.lock().unwrap().push(output.to_string());
+            let passed = !output.contains("FAILED");
+            Ok(Verdict {
+                passed,
+                reason: if passed {
+                    "ok"
+                } else {
+                    "tests fail across beads"
+                }
+                .into(),
+            })
+        }
+    }
+
+    fn completed(title: &str, output: &str) -> Bead {
+        let mut bead = Bead::new(title, title, TaskType::Implementation);
+        bead.status = BeadStatus::Completed;
+        bead.output = Some(output.into());
+        bead
+    }
+
+    #[tokio::test]
+    async fn test_review_bead() {
+        let mut bead = completed("Add parser", "parser done");
+        let reasons = vec!["No tests".to_string(), "Panics on empty input".to_string()];
+        let failing = StubGate::Fail(reasons.clone());
+
+        assert_eq!(
+            failing.review_bead(&bead).await.unwrap(),
+            ReviewOutcome::Pass,
+            "nothing to check without criteria"
+        );
+        bead.acceptance_criteria = vec!["Has tests".into()];
+        assert_eq!(
+            failing.review_bead(&bead).await.unwrap(),
+            ReviewOutcome::Fail { reasons }
+        );
+        assert_eq!(
+            StubGate::Pass.review_bead(&bead).await.unwrap(),
+            ReviewOutcome::Pass
+        );
+    }
+
+    #[tokio::test]
+    async fn test_convoy_gate_failure_overrides_passing_beads() {
+        let beads = vec![
+            completed("Add parser", "parser done"),
+            completed("Wire parser into CLI", "cargo test: 1 FAILED"),
+        ];
+        let mut convoy = Convoy::new("parser");
+        convoy.acceptance_criteria = vec!["All tests pass".into()];
+        for bead in &beads {
+            convoy.add_bead(bead.id.clone());
+        }
+        let statuses: HashMap<BeadId, BeadStatus> =
+            beads.iter().map(|b| (b.id.clone(), b.status)).collect();
+        convoy.refresh_status(&statuses);
+        assert_eq!(convoy.status, ConvoyStatus::Completed);
+
+        let gate = TestsPassGate::default();
+        let verdict = review_convoy(&mut convoy, &beads, &gate).await.unwrap();
+        assert!(!verdict.unwrap().passed);
+        assert_eq!(convoy.status, ConvoyStatus::CompletedWithErrors);
+        assert_eq!(convoy.metadata[GATE_FAILURE_KEY], "tests fail across beads");
+        let seen = gate.seen.lock().unwrap()[0].clone();
+        assert!(seen.contains("parser done") && seen.contains("1 FAILED"));
+
+        // Unfinished convoys and convoys without criteria aren't reviewed
+        let mut open = Convoy::new("open");
+        open.acceptance_criteria = vec!["All tests pass".into()];
+        open.status = ConvoyStatus::InProgress;
+        assert_eq!(review_convoy(&mut open, &beads, &gate).await.unwrap(), None);
+        let mut plain = Convoy::new("plain");
+        plain.status = ConvoyStatus::Completed;
+        assert_eq!(
+            review_convoy(&mut plain, &beads, &gate).await.unwrap(),
+            None
+        );
+        assert_eq!(gate.seen.lock().unwrap().len(), 1);
+    }
+}
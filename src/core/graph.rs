@@ -0,0 +1,113 @@
+//! Dependency graphs over beads
+//!
+//! Beads name their prerequisites in `dependencies`. These helpers look at a
+//! set of beads as a graph; dependencies on beads outside the set are
+//! ignored.
+
+use std::collections::{HashMap, HashSet};
+
+use super::bead::{Bead, BeadId};
+
+/// Find a dependency cycle among `beads`, if there is one
+///
+/// Returns the beads on the cycle, each depending on the next and the last
+/// on the first. The search is an iterative depth-first walk, so long
+/// dependency chains can't overflow the stack.
+pub fn detect_cycle(beads: &[Bead]) -> Option<Vec<BeadId>> {
+    let deps: HashMap<&BeadId, &[BeadId]> = beads
+        .iter()
+        .map(|b| (&b.id, b.dependencies.as_slice()))
+        .collect();
+    let mut visited: HashSet<&BeadId> = HashSet::new();
+
+    for root in beads {
+        if !visited.insert(&root.id) {
+            continue;
+        }
+        // The path being explored, with the next dependency to follow from each bead
+        let mut stack: Vec<(&BeadId, usize)> = vec![(&root.id, 0)];
+        let mut on_stack: HashSet<&BeadId> = HashSet::from([&root.id]);
+
+        while let Some((id, next)) = stack.last_mut() {
+            let id: &BeadId = id;
+            let Some(dep) = deps[id].get(*next) else {
+                on_stack.remove(id);
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+
+            if on_stack.contains(dep) {
+                let start = stack.iter().position(|(on_path, _)| *on_path == dep)?;
+                return Some(stack[start..].iter().map(|(id, _)| (*id).clone()).collect());
+            }
+            if let Some((dep, _)) = deps.get_key_value(dep) {
+                if visited.insert(dep) {
+                    on_stack.insert(dep);
+                    stack.push((dep, 0));
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::bead::TaskType;
+
+    fn bead(title: &str) -> Bead {
+        Bead::new(title, title, TaskType::Implementation)
+    }
+
+    #[test]
+    fn test_three_node_cycle() {
+        let mut a = bead("A");
+        let b = bead("B").with_dependencies(vec![a.id.clone()]);
+        let c = bead("C").with_dependencies(vec![b.id.clone()]);
+        a.dependencies = vec![c.id.clone()];
+        let outside = bead("Outside").with_dependencies(vec![a.id.clone()]);
+
+        let cycle = detect_cycle(&[outside, a.clone(), b.clone(), c.clone()]).unwrap();
+        assert_eq!(cycle, vec![a.id.clone(), c.id.clone(), b.id.clone()]);
+
+        let mut lone = bead("Lone");
+        lone.dependencies = vec![lone.id.clone()];
+        assert_eq!(detect_cycle(&[lone.clone()]), Some(vec![lone.id]));
+    }
+
+    #[test]
+    fn test_diamond_is_acyclic() {
+        let top = bead("Top");
+        let left = bead("Left").with_dependencies(vec![top.id.clone()]);
+        let right = bead("Right").with_dependencies(vec![top.id.clone()]);
+        let bottom = bead("Bottom").with_dependencies(vec![
+            left.id.clone(),
+            right.id.clone(),
+            BeadId::new(),
+        ]);
+
+        assert_eq!(detect_cycle(&[bottom, left, right, top]), None);
+    }
+
+    #[test]
+    fn test_long_chain_does_not_overflow() {
+        let mut beads: Vec<Bead> = Vec::new();
+        for i in 0..50_000 {
+            // Random ids would collide at this size
+            let mut next = bead(&i.to_string());
+            next.id = BeadId::parse(&format!("gt-{:05}", i)).unwrap();
+            if let Some(previous) = beads.last() {
+                next.dependencies = vec![previous.id.clone()];
+            }
+            beads.push(next);
+        }
+        beads.reverse();
+        assert_eq!(detect_cycle(&beads), None);
+
+        let last = beads[0].id.clone();
+        beads.last_mut().unwrap().dependencies.push(last);
+        assert_eq!(detect_cycle(&beads).map(|c| c.len()), Some(50_000));
+    }
+}
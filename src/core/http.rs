@@ -0,0 +1,107 @@
+//! Outbound HTTP clients
+//!
+//! Clients for provider endpoints are built here so the per-provider TLS
+//! settings (`ca_cert`, `danger_accept_invalid_certs`) apply everywhere.
+
+use std::error::Error as _;
+use std::path::Path;
+use std::time::Duration;
+
+use super::error::{Result, RigsError};
+use crate::config::TlsOptions;
+
+/// Build a client with the given TLS settings and request timeout
+///
+/// Verification stays strict unless `danger_accept_invalid_certs` is set;
+/// `ca_cert` adds a trusted root alongside the system ones.
+pub fn build_client(tls: &TlsOptions, timeout: Duration) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().timeout(timeout);
+    if let Some(path) = &tls.ca_cert {
+        builder = builder.add_root_certificate(load_ca_cert(path)?);
+    }
+    if tls.danger_accept_invalid_certs {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    Ok(builder.build()?)
+}
+
+/// Read a PEM-encoded CA certificate
+pub fn load_ca_cert(path: &Path) -> Result<reqwest::Certificate> {
+    let pem = std::fs::read(path)
+        .map_err(|e| RigsError::ConfigError(format!("ca_cert {}: {}", path.display(), e)))?;
+    reqwest::Certificate::from_pem(&pem).map_err(|e| {
+        RigsError::ConfigError(format!(
+            "ca_cert {}: not a PEM certificate: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Explain a certificate verification failure, if that's what `e` is
+///
+/// reqwest reports these as connect errors, so they would otherwise look
+/// like an unreachable endpoint.
+pub(crate) fn tls_error(endpoint: &str, e: &reqwest::Error) -> Option<RigsError> {
+    let mut source = e.source();
+    while let Some(cause) = source {
+        if cause.to_string().to_lowercase().contains("certificate") {
+            return Some(RigsError::TlsError(endpoint.to_string(), cause.to_string()));
+        }
+        source = cause.source();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const TEST_CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBhTCCASugAwIBAgIUdlg4XN/EurR3ocjdYK73odx8frEwCgYIKoZIzj0EAwIw
+FzEVMBMGA1UEAwwMUmlncyBUZXN0IENBMCAXDTI2MTAxNjA4NTMwNloYDzIxMjYw
+OTIyMDg1MzA2WjAXMRUwEwYDVQQDDAxSaWdzIFRlc3QgQ0EwWTATBgcqhkjOPQIB
+BggqhkjOPQMBBwNCAASGzCm3NblRuwL86TUiN7pGxprvWznRySPG4UCFfhUVXaBF
+qS1k0yuysdmFVUWc9t1V2xHUIGHmRw8C/ZstvIaUo1MwUTAdBgNVHQ4EFgQUSt65
+Twd6DIRn2YdKgk/iiarYLFswHwYDVR0jBBgwFoAUSt65Twd6DIRn2YdKgk/iiarY
+LFswDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNIADBFAiEA0xb3WL2KWp4Q
+22RLRn3lSD837nlHASLGh9sndXrfokICIAhJdxhDwJlsFSiF91MViYZfZqmTRsyn
+aLaBxzFagh+q
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn test_client_with_custom_ca() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corp-ca.pem");
+        std::fs::write(&path, TEST_CA).unwrap();
+
+        assert!(load_ca_cert(&path).is_ok());
+        let tls = TlsOptions {
+            ca_cert: Some(path),
+            danger_accept_invalid_certs: false,
+        };
+        assert!(build_client(&tls, Duration::from_secs(5)).is_ok());
+    }
+
+    #[test]
+    fn test_bad_ca_path_is_config_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let garbage = dir.path().join("garbage.pem");
+        std::fs::write(&garbage, "not a certificate").unwrap();
+
+        for path in [garbage, PathBuf::from("/nonexistent/ca.pem")] {
+            let tls = TlsOptions {
+                ca_cert: Some(path.clone()),
+                ..TlsOptions::default()
+            };
+            match build_client(&tls, Duration::from_secs(5)) {
+                Err(RigsError::ConfigError(msg)) => {
+                    assert!(msg.contains(&path.display().to_string()), "{}", msg)
+                }
+                other => panic!("expected config error, got {:?}", other.map(|_| ())),
+            }
+        }
+    }
+}
@@ -3,14 +3,40 @@
 //! This module contains the fundamental data structures used throughout
 //! the Rigs orchestration system.
 
+pub mod assayer;
 pub mod bead;
+pub mod budget;
+pub mod bundle;
 pub mod convoy;
+pub mod daemon;
 pub mod error;
+pub mod events;
+pub mod executor;
+pub mod foreman;
+pub mod gate;
+pub mod graph;
+pub mod http;
+pub mod planner;
+pub mod postprocess;
+pub mod pricing;
+pub mod prompt;
 pub mod provider;
+pub mod ratelimit;
+pub mod retry;
+pub mod routing;
+pub mod schedule;
 pub mod tank;
 
-pub use bead::{Bead, BeadId, BeadStatus, Priority, TaskType};
-pub use convoy::{Convoy, ConvoyId, ConvoyStatus};
-pub use error::{Result, RigsError};
-pub use provider::{Provider, ProviderConfig, ProviderLimits};
-pub use tank::{Tank, TankHealth};
+pub use bead::{
+    Bead, BeadContext, BeadId, BeadRef, BeadStatus, Priority, Provenance, Reschedule, TaskType,
+};
+pub use bundle::ConvoyBundle;
+pub use convoy::{Convoy, ConvoyId, ConvoyStatus, RetrySummary};
+pub use error::{Result, RetryClass, RigsError};
+pub use events::{EventBus, ForemanEvent};
+pub use executor::{
+    ExecutionDiagnostics, ExecutionOutput, ExecutionRecord, ExecutionRequest, Executor,
+};
+pub use gate::{QualityGate, ReviewOutcome, StubGate, Verdict};
+pub use provider::{Provider, ProviderCapabilities, ProviderConfig, ProviderLimits, WeeklyReset};
+pub use tank::{CapacityLimit, ReconcilePolicy, Tank, TankHealth, UsageEvent, WindowKind};
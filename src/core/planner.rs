@@ -0,0 +1,196 @@
+//! Goal decomposition
+//!
+//! The [`Planner`] asks each configured decomposer in turn to split a goal
+//! into beads. When none of them can be reached it falls back to a fixed
+//! research → design → implement → test skeleton, so planning still works
+//! offline.
+
+use async_trait::async_trait;
+use std::fmt;
+use std::sync::Arc;
+use tracing::warn;
+
+use super::assayer::{Estimator, OllamaPlanner};
+use super::bead::{Bead, TaskType};
+use super::error::Result;
+use crate::config::Config;
+
+/// Something that can split a goal into beads (usually a model-backed assayer)
+#[async_trait]
+pub trait Decomposer: Send + Sync {
+    /// Shown as the plan's source, e.g. "DeepSeek R1"
+    fn name(&self) -> &str;
+
+    /// Beads for `goal`, with dependencies set between them
+    async fn decompose(&self, goal: &str) -> Result<Vec<Bead>>;
+}
+
+/// Where a plan came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanSource {
+    Decomposer(String),
+    /// No decomposer was reachable
+    Heuristic,
+}
+
+impl fmt::Display for PlanSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanSource::Decomposer(name) => write!(f, "{}", name),
+            PlanSource::Heuristic => write!(f, "heuristic (no LLM available)"),
+        }
+    }
+}
+
+/// A goal split into beads, in dependency order
+#[derive(Debug, Clone)]
+pub struct GoalPlan {
+    pub beads: Vec<Bead>,
+    pub source: PlanSource,
+}
+
+impl GoalPlan {
+    pub fn total_tokens(&self) -> u64 {
+        self.beads.iter().map(|b| b.estimated_tokens).sum()
+    }
+}
+
+/// Splits goals into beads, falling back to a heuristic skeleton
+#[derive(Default)]
+pub struct Planner {
+    decomposers: Vec<Arc<dyn Decomposer>>,
+}
+
+impl Planner {
+    /// Try `decomposers` in order
+    pub fn new(decomposers: Vec<Arc<dyn Decomposer>>) -> Self {
+        Self { decomposers }
+    }
+
+    /// The decomposers the configuration allows (Ollama when the assayer
+    /// may use it)
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut decomposers: Vec<Arc<dyn Decomposer>> = Vec::new();
+        if config.assayer.use_ollama && config.providers.ollama.enabled {
+            decomposers.push(Arc::new(OllamaPlanner::from_config(config)?));
+        }
+        Ok(Self::new(decomposers))
+    }
+
+    /// Decompose `goal` with the first decomposer that is reachable
+    ///
+    /// Decomposers that fail with a transient error (see
+    /// [`RigsError::is_transient`](super::error::RigsError::is_transient))
+    /// are skipped; any other error is returned. If none is left, the plan
+    /// is [`heuristic_plan`].
+    pub async fn plan(&self, goal: &str) -> Result<GoalPlan> {
+        for decomposer in &self.decomposers {
+            match decomposer.decompose(goal).await {
+                Ok(beads) => {
+                    return Ok(GoalPlan {
+                        beads,
+                        source: PlanSource::Decomposer(decomposer.name().to_string()),
+                    })
+                }
+                Err(e) if e.is_transient() => {
+                    warn!(decomposer = decomposer.name(), error = %e, "Decomposer unavailable");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(heuristic_plan(goal))
+    }
+}
+
+/// Research, design, implementation and test beads, each depending on the last
+///
+/// Estimates come from [`Estimator::heuristic`].
+pub fn heuristic_plan(goal: &str) -> GoalPlan {
+    let steps = [
+        (
+            TaskType::Research,
+            "Research",
+            "Survey the relevant code, prior art and constraints, and summarize the options.",
+        ),
+        (
+            TaskType::Design,
+            "Design",
+            "Choose an approach based on the research and describe the changes it needs.",
+        ),
+        (
+            TaskType::Implementation,
+            "Implement",
+            "Make the changes described in the design.",
+        ),
+        (
+            TaskType::Test,
+            "Test",
+            "Write tests covering the implementation and fix what they find.",
+        ),
+    ];
+
+    let mut beads: Vec<Bead> = Vec::with_capacity(steps.len());
+    for (task_type, verb, instructions) in steps {
+        let description = format!("Goal: {}\n\n{}", goal, instructions);
+        let mut bead = Bead::new(format!("{}: {}", verb, goal), description, task_type);
+        if let Some(previous) = beads.last() {
+            bead = bead.with_dependencies(vec![previous.id.clone()]);
+        }
+        bead.estimated_tokens = Estimator::heuristic(&bead);
+        beads.push(bead);
+    }
+    GoalPlan {
+        beads,
+        source: PlanSource::Heuristic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Provider, RigsError};
+
+    struct Unreachable(Provider);
+
+    #[async_trait]
+    impl Decomposer for Unreachable {
+        fn name(&self) -> &str {
+            self.0.display_name()
+        }
+
+        async fn decompose(&self, _goal: &str) -> Result<Vec<Bead>> {
+            Err(match self.0 {
+                Provider::Ollama => RigsError::OllamaNotAvailable("connection refused".into()),
+                p => RigsError::ProviderUnavailable(p, "503 Service Unavailable".into()),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_heuristic_when_no_decomposer_is_reachable() {
+        let planner = Planner::new(vec![
+            Arc::new(Unreachable(Provider::Ollama)),
+            Arc::new(Unreachable(Provider::DeepSeek)),
+        ]);
+        let plan = planner.plan("Add OAuth login").await.unwrap();
+
+        assert_eq!(plan.source, PlanSource::Heuristic);
+        assert_eq!(plan.source.to_string(), "heuristic (no LLM available)");
+        let types: Vec<TaskType> = plan.beads.iter().map(|b| b.task_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                TaskType::Research,
+                TaskType::Design,
+                TaskType::Implementation,
+                TaskType::Test
+            ]
+        );
+        assert_eq!(plan.beads[0].title, "Research: Add OAuth login");
+        assert!(plan.beads[0].dependencies.is_empty());
+        for pair in plan.beads.windows(2) {
+            assert_eq!(pair[1].dependencies, vec![pair[0].id.clone()]);
+        }
+        assert!(plan.beads.iter().all(|b| b.estimated_tokens > 0));
+    }
+}
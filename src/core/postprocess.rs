@@ -0,0 +1,119 @@
+//! Output post-processing
+//!
+//! Filters applied to a bead's output before it is stored, configured per
+//! task type under `[foreman.postprocess]`. Filters run in the order listed.
+
+use serde::{Deserialize, Serialize};
+
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+const FENCE: &str = "```";
+
+/// A transformation applied to execution output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostProcessor {
+    /// Drop `<think>...</think>` reasoning blocks (e.g. DeepSeek-R1)
+    StripThinkTags,
+    /// Keep only the contents of fenced code blocks
+    ExtractCode,
+    /// Trim surrounding whitespace
+    Trim,
+}
+
+impl PostProcessor {
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            PostProcessor::StripThinkTags => strip_think_tags(text),
+            PostProcessor::ExtractCode => extract_code(text).unwrap_or_else(|| text.to_string()),
+            PostProcessor::Trim => text.trim().to_string(),
+        }
+    }
+}
+
+/// Run `text` through every processor in order
+pub fn apply_all(processors: &[PostProcessor], text: &str) -> String {
+    processors
+        .iter()
+        .fold(text.to_string(), |text, p| p.apply(&text))
+}
+
+/// Remove reasoning blocks
+///
+/// Some chat templates open the block in the prompt, so output that starts
+/// mid-thought has a closing tag with no opening one; everything before it
+/// is dropped too. An unclosed block runs to the end of the output.
+fn strip_think_tags(text: &str) -> String {
+    let mut rest = match (text.find(THINK_OPEN), text.find(THINK_CLOSE)) {
+        (open, Some(close)) if open.is_none_or(|open| close < open) => {
+            &text[close + THINK_CLOSE.len()..]
+        }
+        _ => text,
+    };
+
+    let mut out = String::with_capacity(rest.len());
+    while let Some(open) = rest.find(THINK_OPEN) {
+        out.push_str(&rest[..open]);
+        rest = match rest[open..].find(THINK_CLOSE) {
+            Some(close) => &rest[open + close + THINK_CLOSE.len()..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out.trim_start().to_string()
+}
+
+/// Contents of every fenced code block, joined by blank lines
+///
+/// Returns `None` when the text has no complete fenced block.
+fn extract_code(text: &str) -> Option<String> {
+    let mut blocks = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(FENCE) {
+        let after_fence = &rest[start + FENCE.len()..];
+        // The rest of the opening line is the info string (language tag)
+        let body_start = after_fence.find('\n')? + 1;
+        let body = &after_fence[body_start..];
+        let end = body.find(FENCE)?;
+        blocks.push(body[..end].trim_end_matches('\n'));
+        rest = &body[end + FENCE.len()..];
+    }
+    (!blocks.is_empty()).then(|| blocks.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_think_tags() {
+        let text = "<think>\nThe user wants a greeting.\n</think>\n\nHello!";
+        assert_eq!(PostProcessor::StripThinkTags.apply(text), "Hello!");
+
+        // Opening tag consumed by the chat template
+        let text = "mid-thought reasoning</think>Answer";
+        assert_eq!(PostProcessor::StripThinkTags.apply(text), "Answer");
+
+        let text = "A<think>x</think>B<think>unclosed";
+        assert_eq!(PostProcessor::StripThinkTags.apply(text), "AB");
+    }
+
+    #[test]
+    fn test_extract_code() {
+        let text = "Here you go:\n\n```rust\nfn main() {}\n```\n\nLet me know!";
+        assert_eq!(PostProcessor::ExtractCode.apply(text), "fn main() {}");
+
+        let text = "```\na\n```\nand\n```py\nb\n```";
+        assert_eq!(PostProcessor::ExtractCode.apply(text), "a\n\nb");
+
+        assert_eq!(PostProcessor::ExtractCode.apply("no code"), "no code");
+    }
+
+    #[test]
+    fn test_apply_all_in_order() {
+        let text = "<think>plan</think>\n```sh\necho hi\n```\n";
+        let processors = [PostProcessor::StripThinkTags, PostProcessor::ExtractCode];
+        assert_eq!(apply_all(&processors, text), "echo hi");
+        assert_eq!(apply_all(&[], text), text);
+    }
+}
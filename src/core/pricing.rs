@@ -0,0 +1,89 @@
+//! Provider pricing
+//!
+//! Approximate blended (input + output) API prices used for cost previews
+//! and budgets. CLI-authenticated subscriptions don't bill per token, but
+//! pricing them at API rates keeps budgets meaningful across providers.
+
+use std::collections::HashMap;
+
+use super::provider::Provider;
+
+/// USD per million tokens, by provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricingTable {
+    per_million: HashMap<Provider, f64>,
+}
+
+impl Default for PricingTable {
+    fn default() -> Self {
+        Self {
+            per_million: HashMap::from([
+                (Provider::Claude, 15.0),
+                (Provider::Codex, 10.0),
+                (Provider::Gemini, 10.0),
+                (Provider::DeepSeek, 1.1),
+                (Provider::Ollama, 0.0),
+            ]),
+        }
+    }
+}
+
+impl PricingTable {
+    /// Default prices with `[pricing]` overrides from config applied
+    pub fn with_overrides(overrides: &HashMap<Provider, f64>) -> Self {
+        let mut table = Self::default();
+        table.per_million.extend(overrides);
+        table
+    }
+
+    /// USD per million tokens for a provider
+    pub fn per_million(&self, provider: Provider) -> f64 {
+        self.per_million.get(&provider).copied().unwrap_or(0.0)
+    }
+
+    /// Estimated USD cost of `tokens` on `provider`
+    pub fn cost(&self, provider: Provider, tokens: u64) -> f64 {
+        tokens as f64 / 1_000_000.0 * self.per_million(provider)
+    }
+
+    /// Cost of `tokens` on each provider, cheapest first
+    ///
+    /// Ties keep the order of `providers`.
+    pub fn rank(&self, providers: &[Provider], tokens: u64) -> Vec<(Provider, f64)> {
+        let mut ranked: Vec<(Provider, f64)> = providers
+            .iter()
+            .map(|&p| (p, self.cost(p, tokens)))
+            .collect();
+        ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost_with_overrides() {
+        let table = PricingTable::with_overrides(&HashMap::from([(Provider::Codex, 2.0)]));
+        assert!((table.cost(Provider::Claude, 100_000) - 1.5).abs() < 1e-9);
+        assert!((table.cost(Provider::Codex, 500_000) - 1.0).abs() < 1e-9);
+        assert_eq!(table.cost(Provider::Ollama, 1_000_000), 0.0);
+    }
+
+    #[test]
+    fn test_rank_cheapest_first() {
+        let table = PricingTable::with_overrides(&HashMap::from([(Provider::Gemini, 3.0)]));
+        let ranked = table.rank(
+            &[Provider::Claude, Provider::Codex, Provider::Gemini],
+            200_000,
+        );
+        let order: Vec<Provider> = ranked.iter().map(|(p, _)| *p).collect();
+        assert_eq!(
+            order,
+            vec![Provider::Gemini, Provider::Codex, Provider::Claude]
+        );
+        assert!((ranked[0].1 - 0.6).abs() < 1e-9);
+        assert!((ranked[2].1 - 3.0).abs() < 1e-9);
+    }
+}
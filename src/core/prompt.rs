@@ -0,0 +1,171 @@
+//! Bead prompt templates
+//!
+//! The prompt sent to a provider is rendered from `[prompts] bead_template`
+//! so task framing and acceptance criteria are added in one place. Context
+//! files are appended after rendering, and the output of finished
+//! dependencies can be prepended with [`render_dependency_outputs`].
+
+use std::fmt::Write;
+
+use super::bead::Bead;
+
+/// Template used when `[prompts] bead_template` isn't set
+pub const DEFAULT_BEAD_TEMPLATE: &str = "## {title} ({task_type})\n\n{description}\n\n{criteria}";
+
+/// A bead prompt template
+///
+/// Placeholders:
+/// - `{title}`, `{task_type}`
+/// - `{description}`: the optimized prompt if there is one, else the description
+/// - `{criteria}`: an "Acceptance criteria:" list, or nothing when there are none
+///
+/// Anything else in braces is left as written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate(String);
+
+impl PromptTemplate {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self(template.into())
+    }
+
+    /// Send the bead's prompt unchanged
+    pub fn passthrough() -> Self {
+        Self::new("{description}")
+    }
+
+    /// Fill in the placeholders for `bead`
+    ///
+    /// Placeholders are substituted in a single pass, so braces inside the
+    /// bead's own text are never expanded.
+    pub fn render(&self, bead: &Bead) -> String {
+        let mut out = String::with_capacity(self.0.len() + bead.effective_prompt().len());
+        let mut rest = self.0.as_str();
+        while let Some(open) = rest.find('{') {
+            out.push_str(&rest[..open]);
+            let after = &rest[open + 1..];
+            let value = after.find('}').and_then(|close| {
+                let value = match &after[..close] {
+                    "title" => bead.title.clone(),
+                    "task_type" => bead.task_type.to_string(),
+                    "description" => bead.effective_prompt().to_string(),
+                    "criteria" => render_criteria(&bead.acceptance_criteria),
+                    _ => return None,
+                };
+                Some((value, close))
+            });
+            match value {
+                Some((value, close)) => {
+                    out.push_str(&value);
+                    rest = &after[close + 1..];
+                }
+                None => {
+                    out.push('{');
+                    rest = after;
+                }
+            }
+        }
+        out.push_str(rest);
+        // An empty trailing placeholder (usually `{criteria}`) leaves blank lines
+        out.truncate(out.trim_end().len());
+        out
+    }
+}
+
+impl Default for PromptTemplate {
+    fn default() -> Self {
+        Self::new(DEFAULT_BEAD_TEMPLATE)
+    }
+}
+
+/// Each dependency's output under its own heading, oldest dependency first
+///
+/// `header` may name the dependency with `{title}` and `{id}`. Outputs longer
+/// than `max_tokens` (~4 characters per token) are cut and marked as such;
+/// dependencies without output are skipped.
+pub fn render_dependency_outputs(dependencies: &[Bead], header: &str, max_tokens: u64) -> String {
+    let max_chars = usize::try_from(max_tokens.saturating_mul(4)).unwrap_or(usize::MAX);
+    let mut sections = Vec::new();
+    for dependency in dependencies {
+        let Some(output) = dependency.output.as_deref() else {
+            continue;
+        };
+        // The title goes in last so braces in it are never expanded
+        let heading = header
+            .replace("{id}", dependency.id.as_str())
+            .replace("{title}", &dependency.title);
+        let output = output.trim();
+        let body = match output.char_indices().nth(max_chars) {
+            Some((cut, _)) => format!("{}\n[truncated]", &output[..cut]),
+            None => output.to_string(),
+        };
+        sections.push(format!("{}\n\n{}", heading, body));
+    }
+    sections.join("\n\n")
+}
+
+fn render_criteria(criteria: &[String]) -> String {
+    if criteria.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("Acceptance criteria:");
+    for criterion in criteria {
+        let _ = write!(out, "\n- {}", criterion);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TaskType;
+
+    #[test]
+    fn test_default_template_lists_criteria() {
+        let bead = Bead::new("Parser", "Write a TOML parser", TaskType::Implementation)
+            .with_criteria(vec!["Handles tables".into(), "Has tests".into()]);
+        assert_eq!(
+            PromptTemplate::default().render(&bead),
+            "## Parser (implementation)\n\nWrite a TOML parser\n\n\
+             Acceptance criteria:\n- Handles tables\n- Has tests"
+        );
+
+        let bare = Bead::new("Parser", "Write a TOML parser", TaskType::Implementation);
+        assert_eq!(
+            PromptTemplate::default().render(&bare),
+            "## Parser (implementation)\n\nWrite a TOML parser"
+        );
+    }
+
+    #[test]
+    fn test_custom_template_and_literal_braces() {
+        let mut bead = Bead::new("Fix", "Return {} on {title}", TaskType::Debug)
+            .with_criteria(vec!["No panics".into()]);
+        bead.optimized_prompt = Some("Return an empty map when {title} is missing".into());
+        let template = PromptTemplate::new("[{task_type}] {unknown} {description}\n{criteria}");
+        assert_eq!(
+            template.render(&bead),
+            "[debug] {unknown} Return an empty map when {title} is missing\n\
+             Acceptance criteria:\n- No panics"
+        );
+    }
+
+    #[test]
+    fn test_dependency_outputs_are_headed_and_truncated() {
+        let mut schema = Bead::new("Schema", "Design the schema", TaskType::Design);
+        schema.output = Some("id INTEGER\n".into());
+        let mut research = Bead::new("Research {id}", "Survey", TaskType::Research);
+        research.output = Some("x".repeat(20));
+        let pending = Bead::new("Pending", "Not run yet", TaskType::Research);
+
+        let dependencies = [schema.clone(), pending, research.clone()];
+        assert_eq!(
+            render_dependency_outputs(&dependencies, "# {title} [{id}]", 3),
+            format!(
+                "# Schema [{}]\n\nid INTEGER\n\n\
+                 # Research {{id}} [{}]\n\nxxxxxxxxxxxx\n[truncated]",
+                schema.id, research.id
+            )
+        );
+        assert_eq!(render_dependency_outputs(&[], "# {title}", 3), "");
+    }
+}
@@ -1,8 +1,12 @@
 //! Provider types and configuration
 
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use super::error::RigsError;
+use super::tank::TankHealth;
+
 /// Supported LLM providers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
 #[serde(rename_all = "lowercase")]
@@ -20,6 +24,18 @@ pub enum Provider {
 }
 
 impl Provider {
+    /// Returns every known provider
+    pub fn all() -> impl Iterator<Item = Provider> {
+        [
+            Provider::Claude,
+            Provider::Codex,
+            Provider::Gemini,
+            Provider::DeepSeek,
+            Provider::Ollama,
+        ]
+        .into_iter()
+    }
+
     /// Returns all remote providers (excludes Ollama)
     pub fn remote() -> impl Iterator<Item = Provider> {
         [
@@ -41,6 +57,17 @@ impl Provider {
         [Provider::DeepSeek, Provider::Ollama].into_iter()
     }
 
+    /// Lowercase name used in config keys and serialized data
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Provider::Claude => "claude",
+            Provider::Codex => "codex",
+            Provider::Gemini => "gemini",
+            Provider::DeepSeek => "deepseek",
+            Provider::Ollama => "ollama",
+        }
+    }
+
     /// Human-readable display name
     pub fn display_name(&self) -> &'static str {
         match self {
@@ -62,6 +89,32 @@ impl Provider {
             Provider::Ollama => "deepseek-r1:7b",
         }
     }
+
+    /// Built-in feature support for this provider
+    pub fn capabilities(&self) -> ProviderCapabilities {
+        ProviderConfig::default_for(*self).capabilities
+    }
+
+    /// Models this provider is known to accept
+    ///
+    /// Used to warn about likely typos in config; an empty list means any
+    /// model is plausible (e.g. whatever has been pulled into Ollama).
+    pub fn known_models(&self) -> &'static [&'static str] {
+        match self {
+            Provider::Claude => &[
+                "claude-opus-4-20250514",
+                "claude-sonnet-4-20250514",
+                "claude-haiku-4-20250514",
+                "opus",
+                "sonnet",
+                "haiku",
+            ],
+            Provider::Codex => &["codex", "gpt-5-codex", "o4-mini"],
+            Provider::Gemini => &["gemini-2.5-pro", "gemini-2.5-flash"],
+            Provider::DeepSeek => &["deepseek-chat", "deepseek-coder", "deepseek-reasoner"],
+            Provider::Ollama => &[],
+        }
+    }
 }
 
 impl fmt::Display for Provider {
@@ -70,6 +123,42 @@ impl fmt::Display for Provider {
     }
 }
 
+impl std::str::FromStr for Provider {
+    type Err = UnknownProvider;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Provider::all()
+            .find(|p| p.as_str() == s)
+            .ok_or_else(|| UnknownProvider(s.to_string()))
+    }
+}
+
+impl TryFrom<&str> for Provider {
+    type Error = UnknownProvider;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Error for a provider name that isn't recognized
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownProvider(pub String);
+
+impl fmt::Display for UnknownProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let known: Vec<&str> = Provider::all().map(|p| p.as_str()).collect();
+        write!(
+            f,
+            "Unknown provider '{}': expected one of {}",
+            self.0,
+            known.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownProvider {}
+
 /// Configuration for a provider
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderConfig {
@@ -80,11 +169,61 @@ pub struct ProviderConfig {
     pub threshold_yellow: f32,
     pub threshold_red: f32,
     pub fallback_model: Option<String>,
+    /// Switch to `fallback_model` while the tank is yellow or red
+    #[serde(default)]
+    pub fallback_under_pressure: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key_env: Option<String>,
+    #[serde(default)]
+    pub capabilities: ProviderCapabilities,
 }
 
 impl ProviderConfig {
+    /// Create the default config for any provider
+    pub fn default_for(provider: Provider) -> Self {
+        match provider {
+            Provider::Claude => Self::claude_default(),
+            Provider::Codex => Self::codex_default(),
+            Provider::Gemini => Self::gemini_default(),
+            Provider::DeepSeek => Self::deepseek_default(),
+            Provider::Ollama => Self::ollama_default(),
+        }
+    }
+
+    /// The model to run on while the provider's tank is at `health`
+    ///
+    /// With `fallback_under_pressure` set, a yellow or red (or empty) tank
+    /// degrades to `fallback_model`, when there is one, so budget lasts
+    /// longer instead of beads being deferred.
+    pub fn effective_model(&self, health: TankHealth) -> &str {
+        match (&self.fallback_model, health) {
+            (Some(fallback), TankHealth::Yellow | TankHealth::Red | TankHealth::Empty)
+                if self.fallback_under_pressure =>
+            {
+                fallback
+            }
+            _ => &self.model,
+        }
+    }
+
+    /// The API key from the `api_key_env` variable
+    ///
+    /// Returns `None` for providers that authenticate through their CLI (no
+    /// `api_key_env`), and fails with [`RigsError::MissingApiKey`] when the
+    /// variable is unset or empty.
+    pub fn resolve_api_key(&self) -> super::error::Result<Option<String>> {
+        let Some(var) = &self.api_key_env else {
+            return Ok(None);
+        };
+        match std::env::var(var) {
+            Ok(key) if !key.is_empty() => Ok(Some(key)),
+            _ => Err(RigsError::MissingApiKey {
+                provider: self.provider,
+                var: var.clone(),
+            }),
+        }
+    }
+
     /// Create default config for Claude
     pub fn claude_default() -> Self {
         Self {
@@ -97,11 +236,19 @@ impl ProviderConfig {
                 requests_per_minute: None,
                 weekly_cap: Some(500_000),
                 daily_cap: None,
+                weekly_reset: WeeklyReset::default(),
             },
             threshold_yellow: 0.5,
             threshold_red: 0.2,
             fallback_model: Some("claude-haiku-4-20250514".into()),
+            fallback_under_pressure: false,
             api_key_env: None, // Uses CLI auth
+            capabilities: ProviderCapabilities {
+                streaming: true,
+                tool_use: true,
+                vision: true,
+                max_context: 200_000,
+            },
         }
     }
 
@@ -117,11 +264,19 @@ impl ProviderConfig {
                 requests_per_minute: Some(60),
                 weekly_cap: None,
                 daily_cap: None,
+                weekly_reset: WeeklyReset::default(),
             },
             threshold_yellow: 0.4,
             threshold_red: 0.15,
             fallback_model: None,
+            fallback_under_pressure: false,
             api_key_env: None, // Uses CLI auth
+            capabilities: ProviderCapabilities {
+                streaming: true,
+                tool_use: true,
+                vision: false,
+                max_context: 128_000,
+            },
         }
     }
 
@@ -137,11 +292,19 @@ impl ProviderConfig {
                 requests_per_minute: Some(15),
                 weekly_cap: None,
                 daily_cap: Some(1_000_000),
+                weekly_reset: WeeklyReset::default(),
             },
             threshold_yellow: 0.3,
             threshold_red: 0.1,
             fallback_model: Some("gemini-2.5-flash".into()),
+            fallback_under_pressure: false,
             api_key_env: Some("GEMINI_API_KEY".into()),
+            capabilities: ProviderCapabilities {
+                streaming: true,
+                tool_use: true,
+                vision: true,
+                max_context: 1_000_000,
+            },
         }
     }
 
@@ -157,11 +320,19 @@ impl ProviderConfig {
                 requests_per_minute: Some(60),
                 weekly_cap: None,
                 daily_cap: None,
+                weekly_reset: WeeklyReset::default(),
             },
             threshold_yellow: 0.3,
             threshold_red: 0.1,
             fallback_model: Some("deepseek-coder".into()),
+            fallback_under_pressure: false,
             api_key_env: Some("DEEPSEEK_API_KEY".into()),
+            capabilities: ProviderCapabilities {
+                streaming: true,
+                tool_use: true,
+                vision: false,
+                max_context: 64_000,
+            },
         }
     }
 
@@ -177,11 +348,19 @@ impl ProviderConfig {
                 requests_per_minute: None,
                 weekly_cap: None,
                 daily_cap: None,
+                weekly_reset: WeeklyReset::default(),
             },
             threshold_yellow: 0.0,
             threshold_red: 0.0,
             fallback_model: Some("llama3.2:3b".into()),
+            fallback_under_pressure: false,
             api_key_env: None, // No auth needed
+            capabilities: ProviderCapabilities {
+                streaming: true,
+                tool_use: false,
+                vision: false,
+                max_context: 8_192,
+            },
         }
     }
 }
@@ -199,12 +378,163 @@ pub struct ProviderLimits {
     pub weekly_cap: Option<u64>,
     /// Daily token cap (if any)
     pub daily_cap: Option<u64>,
+    /// When the weekly cap resets
+    #[serde(default)]
+    pub weekly_reset: WeeklyReset,
+}
+
+/// Fixed weekly reset point for a weekly cap, in UTC
+///
+/// Providers reset weekly caps on a schedule (e.g. Monday 00:00 UTC) rather
+/// than seven days after first use. Written in config as `"mon 00:00"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct WeeklyReset {
+    pub weekday: Weekday,
+    pub time: NaiveTime,
+}
+
+impl Default for WeeklyReset {
+    fn default() -> Self {
+        Self {
+            weekday: Weekday::Mon,
+            time: NaiveTime::MIN,
+        }
+    }
+}
+
+impl WeeklyReset {
+    /// The first reset strictly after `now`
+    pub fn next_after(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let days_ahead =
+            (self.weekday.num_days_from_monday() + 7 - now.weekday().num_days_from_monday()) % 7;
+        let candidate = (now.date_naive() + Duration::days(days_ahead as i64))
+            .and_time(self.time)
+            .and_utc();
+        if candidate > now {
+            candidate
+        } else {
+            candidate + Duration::weeks(1)
+        }
+    }
+}
+
+impl std::str::FromStr for WeeklyReset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid weekly reset '{}': expected e.g. \"mon 00:00\"", s);
+        let (day, time) = s.trim().split_once(' ').ok_or_else(invalid)?;
+        Ok(Self {
+            weekday: day.parse().map_err(|_| invalid())?,
+            time: NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl TryFrom<String> for WeeklyReset {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<WeeklyReset> for String {
+    fn from(reset: WeeklyReset) -> Self {
+        reset.to_string()
+    }
+}
+
+impl fmt::Display for WeeklyReset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let day = self.weekday.to_string().to_lowercase();
+        write!(f, "{} {}", day, self.time.format("%H:%M"))
+    }
+}
+
+/// Features a provider supports
+///
+/// Executors use these to avoid requesting unsupported features and routing
+/// uses `max_context` to keep oversized beads off small-context providers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    /// Can stream output as it is generated
+    pub streaming: bool,
+    /// Supports tool/function calling
+    pub tool_use: bool,
+    /// Accepts image input
+    pub vision: bool,
+    /// Context window, in tokens
+    pub max_context: u64,
+}
+
+impl Default for ProviderCapabilities {
+    /// Conservative defaults for a provider we know nothing about
+    fn default() -> Self {
+        Self {
+            streaming: false,
+            tool_use: false,
+            vision: false,
+            max_context: 8_192,
+        }
+    }
+}
+
+impl ProviderCapabilities {
+    /// Check whether a request of `tokens` fits in the context window
+    pub fn fits_context(&self, tokens: u64) -> bool {
+        tokens <= self.max_context
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_provider_name_round_trip() {
+        for provider in Provider::all() {
+            assert_eq!(provider.as_str().parse::<Provider>().unwrap(), provider);
+            assert_eq!(Provider::try_from(provider.as_str()).unwrap(), provider);
+            assert_eq!(
+                serde_json::to_string(&provider).unwrap(),
+                format!("\"{}\"", provider.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_weekly_reset_aligns_to_anchor() {
+        let reset: WeeklyReset = "mon 00:00".parse().unwrap();
+        // Wednesday: the next reset is the coming Monday, not a week from now
+        let wednesday: DateTime<Utc> = "2026-01-14T10:00:00Z".parse().unwrap();
+        assert_eq!(
+            reset.next_after(wednesday),
+            "2026-01-19T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_ne!(reset.next_after(wednesday), wednesday + Duration::days(7));
+
+        // Exactly on the anchor rolls to the following week
+        let monday: DateTime<Utc> = "2026-01-19T00:00:00Z".parse().unwrap();
+        assert_eq!(reset.next_after(monday), monday + Duration::weeks(1));
+
+        let friday_evening: WeeklyReset = "fri 17:30".parse().unwrap();
+        assert_eq!(
+            friday_evening.next_after(wednesday),
+            "2026-01-16T17:30:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(friday_evening.to_string(), "fri 17:30");
+        assert!("someday 10:00".parse::<WeeklyReset>().is_err());
+    }
+
+    #[test]
+    fn test_unknown_provider_rejected() {
+        let err = "gpt4".parse::<Provider>().unwrap_err();
+        assert_eq!(err, UnknownProvider("gpt4".into()));
+        assert!(err.to_string().contains("expected one of claude, codex"));
+    }
+
     #[test]
     fn test_provider_serialization() {
         let provider = Provider::Claude;
@@ -229,5 +559,69 @@ mod tests {
 
         let deepseek = ProviderConfig::deepseek_default();
         assert!(deepseek.api_key_env.is_some());
+
+        for provider in Provider::all() {
+            assert_eq!(ProviderConfig::default_for(provider).provider, provider);
+        }
+
+        assert!(Provider::Gemini.capabilities().fits_context(500_000));
+        assert!(!Provider::Ollama.capabilities().fits_context(500_000));
+        assert!(!Provider::Ollama.capabilities().tool_use);
+    }
+
+    #[test]
+    fn test_fallback_model_under_pressure() {
+        let mut config = ProviderConfig::claude_default();
+        assert_eq!(config.effective_model(TankHealth::Red), config.model);
+
+        config.fallback_under_pressure = true;
+        assert_eq!(
+            config.effective_model(TankHealth::Green),
+            "claude-sonnet-4-20250514"
+        );
+        assert_eq!(
+            config.effective_model(TankHealth::Yellow),
+            "claude-haiku-4-20250514"
+        );
+        assert_eq!(
+            config.effective_model(TankHealth::Red),
+            "claude-haiku-4-20250514"
+        );
+
+        // Nothing to fall back to
+        let mut codex = ProviderConfig::codex_default();
+        codex.fallback_under_pressure = true;
+        assert_eq!(codex.effective_model(TankHealth::Red), codex.model);
+    }
+
+    #[test]
+    fn test_resolve_api_key() {
+        assert_eq!(
+            ProviderConfig::claude_default().resolve_api_key().unwrap(),
+            None
+        );
+
+        let mut gemini = ProviderConfig::gemini_default();
+        gemini.api_key_env = Some("RIGS_TEST_RESOLVE_API_KEY".into());
+        std::env::remove_var("RIGS_TEST_RESOLVE_API_KEY");
+        let err = gemini.resolve_api_key().unwrap_err();
+        assert!(matches!(
+            err,
+            RigsError::MissingApiKey {
+                provider: Provider::Gemini,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("$RIGS_TEST_RESOLVE_API_KEY"));
+
+        std::env::set_var("RIGS_TEST_RESOLVE_API_KEY", "");
+        assert!(gemini.resolve_api_key().is_err());
+
+        std::env::set_var("RIGS_TEST_RESOLVE_API_KEY", "sk-test");
+        assert_eq!(
+            gemini.resolve_api_key().unwrap().as_deref(),
+            Some("sk-test")
+        );
+        std::env::remove_var("RIGS_TEST_RESOLVE_API_KEY");
     }
 }
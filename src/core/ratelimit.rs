@@ -0,0 +1,407 @@
+//! Provider rate-limit reporting
+//!
+//! Each API reports what is left of its rate limit differently: Anthropic
+//! and OpenAI in response headers, Gemini only in the body of a quota error.
+//! These are normalized into a [`RemoteLimit`] that is folded into the
+//! provider's tank. When a response carries nothing usable, the tank keeps
+//! its own accounting.
+//!
+//! A refused request is classified by [`ProviderFailure`]: being rate
+//! limited (429) drains the tank until the window resets, while an
+//! overloaded provider (Anthropic's 529) is a transient failure that is
+//! resent shortly and leaves the tank alone.
+
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+use super::error::RigsError;
+use super::provider::Provider;
+use super::tank::{ReconcilePolicy, Tank};
+
+/// Token capacity a provider reported for its current window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteLimit {
+    pub remaining: u64,
+    /// When the window resets, if the provider said
+    pub reset: Option<DateTime<Utc>>,
+}
+
+impl RemoteLimit {
+    /// Read the rate-limit headers of a `provider` response
+    ///
+    /// Returns `None` when the headers are missing or malformed, or for
+    /// providers that don't send any (Gemini, Ollama).
+    pub fn from_headers(
+        provider: Provider,
+        headers: &HeaderMap,
+        now: DateTime<Utc>,
+    ) -> Option<Self> {
+        match provider {
+            Provider::Claude => anthropic(headers),
+            Provider::Codex | Provider::DeepSeek => openai(headers, now),
+            Provider::Gemini | Provider::Ollama => None,
+        }
+    }
+
+    /// Read a Gemini `RESOURCE_EXHAUSTED` error body
+    ///
+    /// The quota is used up, so `remaining` is 0; the reset comes from the
+    /// `RetryInfo` delay when there is one.
+    pub fn from_gemini_error(body: &str, now: DateTime<Utc>) -> Option<Self> {
+        let body: serde_json::Value = serde_json::from_str(body).ok()?;
+        let error = body.get("error")?;
+        if error.get("status")?.as_str()? != "RESOURCE_EXHAUSTED" {
+            return None;
+        }
+        let reset = error
+            .get("details")
+            .and_then(|d| d.as_array())
+            .into_iter()
+            .flatten()
+            .find(|d| {
+                d.get("@type")
+                    .and_then(|t| t.as_str())
+                    .is_some_and(|t| t.ends_with("google.rpc.RetryInfo"))
+            })
+            .and_then(|d| d.get("retryDelay")?.as_str())
+            .and_then(parse_duration)
+            .map(|delay| now + delay);
+        Some(Self {
+            remaining: 0,
+            reset,
+        })
+    }
+
+    /// Fold this report into `tank` according to its reconcile policy
+    pub fn apply(&self, tank: &mut Tank, policy: ReconcilePolicy) {
+        tank.merge_remote(self.remaining, self.reset, policy);
+    }
+}
+
+/// Anthropic's non-standard "overloaded" status
+const OVERLOADED: u16 = 529;
+
+/// An unsuccessful provider response, classified
+#[derive(Debug)]
+pub struct ProviderFailure {
+    pub error: RigsError,
+    /// What the tank should be told, only set when our quota ran out
+    pub limit: Option<RemoteLimit>,
+}
+
+impl ProviderFailure {
+    /// Classify a non-2xx response from `provider`
+    ///
+    /// 529, or any 5xx whose body is an `overloaded_error`, becomes
+    /// [`RigsError::ProviderOverloaded`]. 429 becomes
+    /// [`RigsError::ProviderRateLimited`] with the tank drained to 0 until
+    /// the reported reset. Other 5xx are [`RigsError::ProviderUnavailable`]
+    /// and everything else a plain API error.
+    pub fn from_response(
+        provider: Provider,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &str,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let message = format!("{}: {}", status, body.trim());
+        if status.as_u16() == OVERLOADED
+            || (status.is_server_error() && body.contains("overloaded_error"))
+        {
+            return Self {
+                error: RigsError::ProviderOverloaded(provider, message),
+                limit: None,
+            };
+        }
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let reported = RemoteLimit::from_gemini_error(body, now)
+                .or_else(|| RemoteLimit::from_headers(provider, headers, now));
+            let reset = reported
+                .and_then(|l| l.reset)
+                .or_else(|| retry_after(headers, now));
+            return Self {
+                error: RigsError::ProviderRateLimited { provider, reset },
+                limit: Some(RemoteLimit {
+                    remaining: 0,
+                    reset,
+                }),
+            };
+        }
+        let error = if status.is_server_error() {
+            RigsError::ProviderUnavailable(provider, message)
+        } else {
+            RigsError::ProviderApiError(provider, message)
+        };
+        Self { error, limit: None }
+    }
+}
+
+/// `retry-after` given in seconds
+fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let seconds: i64 = header(headers, "retry-after")?.parse().ok()?;
+    Some(now + Duration::seconds(seconds))
+}
+
+/// `anthropic-ratelimit-tokens-*`, or the input-token limit when only the
+/// split input/output headers are sent
+fn anthropic(headers: &HeaderMap) -> Option<RemoteLimit> {
+    let (remaining, reset) = ["tokens", "input-tokens"].into_iter().find_map(|kind| {
+        let remaining = header(headers, &format!("anthropic-ratelimit-{}-remaining", kind))?;
+        let reset = header(headers, &format!("anthropic-ratelimit-{}-reset", kind));
+        Some((remaining, reset))
+    })?;
+    Some(RemoteLimit {
+        remaining: remaining.parse().ok()?,
+        reset: reset
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.with_timezone(&Utc)),
+    })
+}
+
+/// `x-ratelimit-remaining-tokens`, with the reset given as a delay ("6m0s")
+fn openai(headers: &HeaderMap, now: DateTime<Utc>) -> Option<RemoteLimit> {
+    Some(RemoteLimit {
+        remaining: header(headers, "x-ratelimit-remaining-tokens")?
+            .parse()
+            .ok()?,
+        reset: header(headers, "x-ratelimit-reset-tokens")
+            .and_then(parse_duration)
+            .map(|delay| now + delay),
+    })
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name)?.to_str().ok().map(str::trim)
+}
+
+/// Parse Go-style durations as sent by OpenAI and Gemini: "1h2m3.5s", "20ms"
+fn parse_duration(s: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let value: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        total += value * seconds;
+        rest = &rest[unit_len..];
+    }
+    Some(Duration::milliseconds((total * 1000.0).round() as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect()
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_anthropic_headers() {
+        let captured = headers(&[
+            ("anthropic-ratelimit-requests-limit", "4000"),
+            ("anthropic-ratelimit-requests-remaining", "3999"),
+            ("anthropic-ratelimit-requests-reset", "2026-03-01T12:00:01Z"),
+            ("anthropic-ratelimit-tokens-limit", "400000"),
+            ("anthropic-ratelimit-tokens-remaining", "381000"),
+            ("anthropic-ratelimit-tokens-reset", "2026-03-01T12:00:45Z"),
+        ]);
+        assert_eq!(
+            RemoteLimit::from_headers(Provider::Claude, &captured, now()),
+            Some(RemoteLimit {
+                remaining: 381_000,
+                reset: Some(now() + Duration::seconds(45)),
+            })
+        );
+
+        let split = headers(&[
+            ("anthropic-ratelimit-input-tokens-remaining", "79000"),
+            (
+                "anthropic-ratelimit-input-tokens-reset",
+                "2026-03-01T12:01:00Z",
+            ),
+            ("anthropic-ratelimit-output-tokens-remaining", "16000"),
+        ]);
+        assert_eq!(
+            RemoteLimit::from_headers(Provider::Claude, &split, now()).map(|l| l.remaining),
+            Some(79_000)
+        );
+    }
+
+    #[test]
+    fn test_openai_headers() {
+        let captured = headers(&[
+            ("x-ratelimit-limit-requests", "10000"),
+            ("x-ratelimit-limit-tokens", "2000000"),
+            ("x-ratelimit-remaining-requests", "9999"),
+            ("x-ratelimit-remaining-tokens", "1999950"),
+            ("x-ratelimit-reset-requests", "6ms"),
+            ("x-ratelimit-reset-tokens", "1m30.5s"),
+        ]);
+        assert_eq!(
+            RemoteLimit::from_headers(Provider::Codex, &captured, now()),
+            Some(RemoteLimit {
+                remaining: 1_999_950,
+                reset: Some(now() + Duration::milliseconds(90_500)),
+            })
+        );
+    }
+
+    #[test]
+    fn test_gemini_quota_error() {
+        let body = r#"{
+          "error": {
+            "code": 429,
+            "message": "You exceeded your current quota.",
+            "status": "RESOURCE_EXHAUSTED",
+            "details": [
+              {
+                "@type": "type.googleapis.com/google.rpc.QuotaFailure",
+                "violations": [{"quotaMetric": "generativelanguage.googleapis.com/generate_content_paid_tier_input_token_count"}]
+              },
+              {"@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "34s"}
+            ]
+          }
+        }"#;
+        assert_eq!(
+            RemoteLimit::from_gemini_error(body, now()),
+            Some(RemoteLimit {
+                remaining: 0,
+                reset: Some(now() + Duration::seconds(34)),
+            })
+        );
+        let other = r#"{"error": {"code": 400, "status": "INVALID_ARGUMENT"}}"#;
+        assert_eq!(RemoteLimit::from_gemini_error(other, now()), None);
+    }
+
+    #[test]
+    fn test_missing_headers_keep_local_accounting() {
+        let bare = headers(&[("content-type", "application/json")]);
+        assert_eq!(
+            RemoteLimit::from_headers(Provider::Claude, &bare, now()),
+            None
+        );
+        assert_eq!(
+            RemoteLimit::from_headers(Provider::Codex, &bare, now()),
+            None
+        );
+        let garbled = headers(&[("x-ratelimit-remaining-tokens", "lots")]);
+        assert_eq!(
+            RemoteLimit::from_headers(Provider::Codex, &garbled, now()),
+            None
+        );
+
+        let mut tank = Tank::new(Provider::Claude, 10_000, 5);
+        tank.remaining = 6_000;
+        RemoteLimit {
+            remaining: 4_000,
+            reset: None,
+        }
+        .apply(&mut tank, ReconcilePolicy::Min);
+        assert_eq!(tank.remaining, 4_000);
+    }
+
+    #[test]
+    fn test_overloaded_is_transient_and_leaves_the_tank_alone() {
+        let body = r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        let failure = ProviderFailure::from_response(
+            Provider::Claude,
+            StatusCode::from_u16(529).unwrap(),
+            &headers(&[]),
+            body,
+            now(),
+        );
+        assert!(matches!(failure.error, RigsError::ProviderOverloaded(..)));
+        assert!(failure.error.is_transient());
+        assert!(!failure.error.is_rate_limit());
+        assert_eq!(
+            failure.error.suggested_wait(),
+            Some(std::time::Duration::from_secs(5))
+        );
+        assert_eq!(failure.limit, None);
+
+        // 503 carrying the same body is the same thing
+        let failure = ProviderFailure::from_response(
+            Provider::Claude,
+            StatusCode::SERVICE_UNAVAILABLE,
+            &headers(&[]),
+            body,
+            now(),
+        );
+        assert!(matches!(failure.error, RigsError::ProviderOverloaded(..)));
+
+        let mut tank = Tank::new(Provider::Claude, 10_000, 5);
+        tank.remaining = 6_000;
+        if let Some(limit) = failure.limit {
+            limit.apply(&mut tank, ReconcilePolicy::Min);
+        }
+        assert_eq!(tank.remaining, 6_000);
+    }
+
+    #[test]
+    fn test_rate_limited_drains_the_tank_until_reset() {
+        let failure = ProviderFailure::from_response(
+            Provider::Codex,
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers(&[("retry-after", "20")]),
+            "",
+            now(),
+        );
+        let reset = Some(now() + Duration::seconds(20));
+        assert!(matches!(
+            failure.error,
+            RigsError::ProviderRateLimited { reset: r, .. } if r == reset
+        ));
+        assert!(!failure.error.is_transient());
+        assert!(failure.error.is_rate_limit());
+        assert_eq!(
+            failure.limit,
+            Some(RemoteLimit {
+                remaining: 0,
+                reset,
+            })
+        );
+
+        let mut tank = Tank::new(Provider::Codex, 10_000, 5);
+        failure
+            .limit
+            .unwrap()
+            .apply(&mut tank, ReconcilePolicy::Min);
+        assert_eq!(tank.remaining, 0);
+
+        // Plain 5xx stays a generic transient failure
+        let failure = ProviderFailure::from_response(
+            Provider::Codex,
+            StatusCode::BAD_GATEWAY,
+            &headers(&[]),
+            "",
+            now(),
+        );
+        assert!(matches!(failure.error, RigsError::ProviderUnavailable(..)));
+        assert_eq!(failure.limit, None);
+    }
+}
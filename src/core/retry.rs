@@ -0,0 +1,238 @@
+//! Retrying operations that fail with recoverable errors
+//!
+//! [`with_backoff`] resends an operation while it fails with an error for
+//! which [`RigsError::is_recoverable`] holds, waiting longer each time.
+//! [`RecoveringExecutor`] applies it to every request an executor sends.
+
+use async_trait::async_trait;
+use rand::Rng;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+use super::error::{Result, RigsError};
+use super::executor::{ExecutionOutput, ExecutionRequest, Executor};
+
+/// How long to wait between attempts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    /// First wait for errors without a [`RigsError::suggested_wait`]
+    pub base: Duration,
+    /// Longest single wait, before and after jitter
+    pub cap: Duration,
+    /// Randomly lengthen or shorten each wait by up to this percentage
+    pub jitter_pct: u8,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_secs(1),
+            cap: Duration::from_secs(60),
+            jitter_pct: 20,
+        }
+    }
+}
+
+impl Backoff {
+    /// Wait after the `retry`th failure (0 for the first), which was `error`
+    ///
+    /// Starts from the error's suggested wait, or `base`, and doubles with
+    /// each retry up to `cap`.
+    pub fn delay(&self, error: &RigsError, retry: u32, rng: &mut impl Rng) -> Duration {
+        let start = error.suggested_wait().unwrap_or(self.base);
+        let grown = start
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.cap);
+        let spread = f64::from(self.jitter_pct.min(100)) / 100.0;
+        if spread == 0.0 {
+            return grown;
+        }
+        grown
+            .mul_f64(1.0 + rng.gen_range(-spread..=spread))
+            .min(self.cap)
+    }
+}
+
+/// Run `op` up to `attempts` times, sleeping with [`Backoff::default`]
+/// between recoverable failures
+///
+/// Any other error, or the last one, is returned as is.
+pub async fn with_backoff<F, Fut, T>(attempts: u32, op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    with_backoff_using(attempts, &Backoff::default(), tokio::time::sleep, op).await
+}
+
+/// Like [`with_backoff`], with the waits given by `backoff` and taken by `sleep`
+pub async fn with_backoff_using<F, Fut, T, S, SleepFut>(
+    attempts: u32,
+    backoff: &Backoff,
+    mut sleep: S,
+    mut op: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+    S: FnMut(Duration) -> SleepFut,
+    SleepFut: Future<Output = ()>,
+{
+    let mut retry = 0;
+    loop {
+        match op().await {
+            Err(e) if e.is_recoverable() && retry + 1 < attempts => {
+                // Not held across the await: thread_rng isn't Send
+                let delay = backoff.delay(&e, retry, &mut rand::thread_rng());
+                debug!(retry, delay_ms = delay.as_millis() as u64, error = %e, "Retrying after recoverable failure");
+                sleep(delay).await;
+                retry += 1;
+            }
+            outcome => return outcome,
+        }
+    }
+}
+
+/// Resends requests that fail with a recoverable error (rate limits,
+/// exhausted providers, Ollama down), using [`with_backoff_using`]
+///
+/// Sits outside [`super::executor::RetryingExecutor`], which handles
+/// transient blips with much shorter waits.
+pub struct RecoveringExecutor {
+    inner: Arc<dyn Executor>,
+    attempts: u32,
+    backoff: Backoff,
+}
+
+impl RecoveringExecutor {
+    /// Wrap `inner`, trying each request at most `attempts` times
+    pub fn new(inner: Arc<dyn Executor>, attempts: u32, backoff: Backoff) -> Self {
+        Self {
+            inner,
+            attempts,
+            backoff,
+        }
+    }
+}
+
+#[async_trait]
+impl Executor for RecoveringExecutor {
+    fn kind(&self) -> &'static str {
+        self.inner.kind()
+    }
+
+    async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+        with_backoff_using(self.attempts, &self.backoff, tokio::time::sleep, || {
+            self.inner.execute(request)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Provider;
+    use std::sync::Mutex;
+
+    fn no_jitter(cap_secs: u64) -> Backoff {
+        Backoff {
+            base: Duration::from_secs(1),
+            cap: Duration::from_secs(cap_secs),
+            jitter_pct: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backoff_sequence_grows_to_cap() {
+        let slept = Mutex::new(Vec::new());
+        let calls = Mutex::new(0);
+        let result: Result<()> = with_backoff_using(
+            5,
+            &no_jitter(30),
+            |d| {
+                slept.lock().unwrap().push(d.as_secs());
+                std::future::ready(())
+            },
+            || {
+                *calls.lock().unwrap() += 1;
+                std::future::ready(Err(RigsError::OllamaNotAvailable("refused".into())))
+            },
+        )
+        .await;
+
+        // Ollama being down suggests 10s, which then doubles
+        assert!(result.is_err());
+        assert_eq!(*calls.lock().unwrap(), 5);
+        assert_eq!(*slept.lock().unwrap(), vec![10, 20, 30, 30]);
+    }
+
+    #[tokio::test]
+    async fn test_recovers_after_rate_limit() {
+        let slept = Mutex::new(Vec::new());
+        let calls = Mutex::new(0);
+        let result = with_backoff_using(
+            3,
+            &no_jitter(60),
+            |d| {
+                slept.lock().unwrap().push(d.as_secs());
+                std::future::ready(())
+            },
+            || {
+                let mut calls = calls.lock().unwrap();
+                *calls += 1;
+                std::future::ready(if *calls == 1 {
+                    Err(RigsError::ProviderRateLimited {
+                        provider: Provider::Codex,
+                        reset: None,
+                    })
+                } else {
+                    Ok(*calls)
+                })
+            },
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(*slept.lock().unwrap(), vec![60]);
+    }
+
+    #[tokio::test]
+    async fn test_unrecoverable_error_short_circuits() {
+        let calls = Mutex::new(0);
+        let result: Result<()> = with_backoff_using(
+            5,
+            &no_jitter(60),
+            |_| async { panic!("must not sleep") },
+            || {
+                *calls.lock().unwrap() += 1;
+                std::future::ready(Err(RigsError::ProviderApiError(
+                    Provider::Claude,
+                    "bad request".into(),
+                )))
+            },
+        )
+        .await;
+
+        assert!(matches!(result, Err(RigsError::ProviderApiError(..))));
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_jitter_stays_within_band_and_cap() {
+        let backoff = Backoff {
+            base: Duration::from_secs(10),
+            cap: Duration::from_secs(15),
+            jitter_pct: 50,
+        };
+        let error = RigsError::Other("x".into());
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let first = backoff.delay(&error, 0, &mut rng);
+            assert!(first >= Duration::from_secs(5) && first <= Duration::from_secs(15));
+            assert!(backoff.delay(&error, 3, &mut rng) <= backoff.cap);
+        }
+    }
+}
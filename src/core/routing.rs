@@ -0,0 +1,509 @@
+//! Provider routing
+//!
+//! Ranks the providers that can take a bead according to the configured
+//! routing strategy. The [`Router`] also weighs in each provider's tank, so
+//! a bead goes where there is capacity for it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::bead::{Bead, TaskType};
+use super::error::{Result, RigsError};
+use super::pricing::PricingTable;
+use super::provider::Provider;
+use super::tank::{Tank, TankHealth};
+use crate::config::RoutingConfig;
+
+/// Latency assumed for a provider with no observations yet
+///
+/// Every provider starts equal, so affinity order decides until real
+/// samples accumulate.
+pub const NEUTRAL_LATENCY_MS: f64 = 30_000.0;
+
+/// Weight of the newest sample in the rolling average
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Observed execution latency for one provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderLatency {
+    pub provider: Provider,
+    /// Exponentially weighted moving average, in milliseconds
+    pub avg_ms: f64,
+    /// Number of executions observed
+    pub samples: u64,
+    /// When the average was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProviderLatency {
+    /// Neutral starting point for a provider without observations
+    pub fn neutral(provider: Provider) -> Self {
+        Self {
+            provider,
+            avg_ms: NEUTRAL_LATENCY_MS,
+            samples: 0,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Fold a new observation into the rolling average
+    ///
+    /// The first sample replaces the neutral seed outright.
+    pub fn record(&mut self, elapsed: Duration) {
+        let ms = elapsed.as_secs_f64() * 1000.0;
+        self.avg_ms = if self.samples == 0 {
+            ms
+        } else {
+            LATENCY_SMOOTHING * ms + (1.0 - LATENCY_SMOOTHING) * self.avg_ms
+        };
+        self.samples += 1;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Recent latency for every provider
+#[derive(Debug, Clone, Default)]
+pub struct LatencyTable {
+    entries: HashMap<Provider, ProviderLatency>,
+}
+
+impl LatencyTable {
+    pub fn new(entries: impl IntoIterator<Item = ProviderLatency>) -> Self {
+        Self {
+            entries: entries.into_iter().map(|l| (l.provider, l)).collect(),
+        }
+    }
+
+    /// Current average for a provider, or the neutral seed
+    pub fn average_ms(&self, provider: Provider) -> f64 {
+        self.entries
+            .get(&provider)
+            .map_or(NEUTRAL_LATENCY_MS, |l| l.avg_ms)
+    }
+
+    /// Record an observation and return the updated entry
+    pub fn record(&mut self, provider: Provider, elapsed: Duration) -> &ProviderLatency {
+        let entry = self
+            .entries
+            .entry(provider)
+            .or_insert_with(|| ProviderLatency::neutral(provider));
+        entry.record(elapsed);
+        entry
+    }
+}
+
+/// How to choose between providers that can take a bead
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoutingStrategy {
+    /// Follow task-type affinity (and the bead's preferred provider)
+    #[default]
+    Balanced,
+    /// Prefer whichever provider has been fastest recently
+    Fastest,
+    /// Prefer whichever provider costs least per token
+    Cheapest,
+}
+
+impl RoutingStrategy {
+    /// Parse `routing.strategy`; unknown values fall back to balanced
+    pub fn from_config(strategy: &str) -> Self {
+        match strategy {
+            "fastest" => RoutingStrategy::Fastest,
+            "cheapest" => RoutingStrategy::Cheapest,
+            _ => RoutingStrategy::Balanced,
+        }
+    }
+}
+
+/// Order the viable providers for a bead, best first
+///
+/// Providers whose context window is smaller than the bead's estimate are
+/// dropped. Balanced puts the bead's preferred provider first, then follows task
+/// affinity. Fastest sorts that order by recent latency, so affinity only
+/// breaks ties (e.g. before any latency has been observed).
+pub fn rank_providers(
+    bead: &Bead,
+    viable: &[Provider],
+    strategy: RoutingStrategy,
+    latency: &LatencyTable,
+) -> Vec<Provider> {
+    let mut ranked: Vec<Provider> = bead
+        .preferred_provider
+        .into_iter()
+        .chain(
+            bead.task_type
+                .provider_affinities()
+                .into_iter()
+                .map(|(p, _)| p),
+        )
+        .chain(viable.iter().copied())
+        .filter(|p| viable.contains(p))
+        .filter(|p| p.capabilities().fits_context(bead.estimated_tokens))
+        .fold(Vec::new(), |mut acc, p| {
+            if !acc.contains(&p) {
+                acc.push(p);
+            }
+            acc
+        });
+
+    if strategy == RoutingStrategy::Fastest {
+        ranked.sort_by(|a, b| latency.average_ms(*a).total_cmp(&latency.average_ms(*b)));
+    }
+    ranked
+}
+
+/// Affinity bonus for a bead's preferred provider
+///
+/// Enough to win over any affinity weight, but not over tank health.
+const PREFERRED_BONUS: f32 = 1.0;
+
+/// Picks a provider for each bead from task affinity, tank health and the
+/// routing strategy
+#[derive(Debug, Clone, Default)]
+pub struct Router {
+    strategy: RoutingStrategy,
+    /// `routing.affinity` overrides of [`TaskType::provider_affinities`]
+    affinities: HashMap<TaskType, Vec<(Provider, f32)>>,
+    latency: LatencyTable,
+    pricing: PricingTable,
+}
+
+impl Router {
+    pub fn new(strategy: RoutingStrategy) -> Self {
+        Self {
+            strategy,
+            ..Self::default()
+        }
+    }
+
+    /// Strategy and affinity overrides from `[routing]`, prices from `[pricing]`
+    pub fn from_config(routing: &RoutingConfig, pricing: &HashMap<Provider, f64>) -> Result<Self> {
+        Ok(Self {
+            strategy: RoutingStrategy::from_config(&routing.strategy),
+            affinities: routing.affinities()?,
+            latency: LatencyTable::default(),
+            pricing: PricingTable::with_overrides(pricing),
+        })
+    }
+
+    /// Recent latency, for the fastest strategy
+    pub fn with_latency(mut self, latency: LatencyTable) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// The provider `bead` should run on, given the current `tanks`
+    ///
+    /// Candidates are the task type's affinities plus the bead's preferred
+    /// provider, which gets a bonus but can't outrank a healthier tank.
+    /// Providers whose tank can't cover the estimate are skipped, and red
+    /// tanks are only used when nothing else is left. Among the rest,
+    /// balanced follows (health-weighted) affinity, while fastest and
+    /// cheapest use affinity only to break ties. Providers without a tank in
+    /// `tanks` count as green.
+    ///
+    /// Fails with [`RigsError::AllProvidersExhausted`], carrying the soonest
+    /// window end among the skipped tanks, when no candidate is usable.
+    pub fn select(&self, bead: &Bead, tanks: &[Tank]) -> Result<Provider> {
+        let affinities = self
+            .affinities
+            .get(&bead.task_type)
+            .cloned()
+            .unwrap_or_else(|| bead.task_type.provider_affinities());
+        let mut weights: Vec<(Provider, f32)> = Vec::new();
+        for (provider, weight) in bead
+            .preferred_provider
+            .map(|p| (p, 0.0))
+            .into_iter()
+            .chain(affinities)
+        {
+            if !weights.iter().any(|(p, _)| *p == provider) {
+                weights.push((provider, weight));
+            }
+        }
+
+        let mut soonest_reset: Option<DateTime<Utc>> = None;
+        let mut candidates: Vec<(Provider, TankHealth, f32)> = Vec::new();
+        for (provider, weight) in weights {
+            if bead.excluded_providers.contains(&provider)
+                || !provider.capabilities().fits_context(bead.estimated_tokens)
+            {
+                continue;
+            }
+            let tank = tanks.iter().find(|t| t.provider == provider);
+            if let Some(tank) = tank.filter(|t| !t.can_consume(bead.estimated_tokens)) {
+                soonest_reset =
+                    Some(soonest_reset.map_or(tank.window_end, |r| r.min(tank.window_end)));
+                continue;
+            }
+            let health = tank.map_or(TankHealth::Green, |t| t.health);
+            let bonus = if bead.preferred_provider == Some(provider) {
+                PREFERRED_BONUS
+            } else {
+                0.0
+            };
+            let factor = match health {
+                TankHealth::Green => 1.0,
+                TankHealth::Yellow => 0.6,
+                TankHealth::Red | TankHealth::Empty => 0.1,
+            };
+            candidates.push((provider, health, (weight + bonus) * factor));
+        }
+
+        let red = |health: TankHealth| matches!(health, TankHealth::Red | TankHealth::Empty);
+        candidates.sort_by(|a, b| {
+            red(a.1)
+                .cmp(&red(b.1))
+                .then_with(|| match self.strategy {
+                    RoutingStrategy::Balanced => std::cmp::Ordering::Equal,
+                    RoutingStrategy::Fastest => self
+                        .latency
+                        .average_ms(a.0)
+                        .total_cmp(&self.latency.average_ms(b.0)),
+                    RoutingStrategy::Cheapest => self
+                        .pricing
+                        .per_million(a.0)
+                        .total_cmp(&self.pricing.per_million(b.0)),
+                })
+                .then_with(|| b.2.total_cmp(&a.2))
+        });
+        candidates
+            .first()
+            .map(|(provider, _, _)| *provider)
+            .ok_or_else(|| RigsError::AllProvidersExhausted(soonest_reset.unwrap_or_else(Utc::now)))
+    }
+}
+
+/// Order providers for a bead by an explicit list instead of affinity
+///
+/// Used for a one-off `--provider-order`. Providers the bead has excluded
+/// after repeated failures, or whose context window is too small, are
+/// skipped; disabled or exhausted ones should already be left out of `order`.
+pub fn rank_by_order(bead: &Bead, order: &[Provider]) -> Vec<Provider> {
+    order
+        .iter()
+        .copied()
+        .filter(|p| !bead.excluded_providers.contains(p))
+        .filter(|p| p.capabilities().fits_context(bead.estimated_tokens))
+        .fold(Vec::new(), |mut acc, p| {
+            if !acc.contains(&p) {
+                acc.push(p);
+            }
+            acc
+        })
+}
+
+/// The provider a bead runs on when remote execution is forbidden
+///
+/// Ollama is the only candidate, so a bead it can't take is an error
+/// rather than something to route elsewhere.
+pub fn route_local(bead: &Bead) -> Result<Provider> {
+    let capabilities = Provider::Ollama.capabilities();
+    if !capabilities.fits_context(bead.estimated_tokens) {
+        return Err(RigsError::NotServableLocally {
+            bead_id: bead.id.clone(),
+            reason: format!(
+                "~{} tokens exceeds Ollama's context window of {} tokens; split the bead",
+                bead.estimated_tokens, capabilities.max_context
+            ),
+        });
+    }
+    Ok(Provider::Ollama)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TaskType;
+
+    const EXECUTION: [Provider; 3] = [Provider::Claude, Provider::Codex, Provider::Gemini];
+
+    #[test]
+    fn test_rolling_average() {
+        let mut latency = ProviderLatency::neutral(Provider::Claude);
+        latency.record(Duration::from_millis(1_000));
+        assert_eq!(latency.avg_ms, 1_000.0);
+
+        latency.record(Duration::from_millis(2_000));
+        assert!((latency.avg_ms - 1_200.0).abs() < 1e-9);
+        assert_eq!(latency.samples, 2);
+    }
+
+    #[test]
+    fn test_neutral_latency_keeps_affinity_order() {
+        let bead = Bead::new("Review", "review", TaskType::Review);
+        let ranked = rank_providers(
+            &bead,
+            &EXECUTION,
+            RoutingStrategy::Fastest,
+            &LatencyTable::default(),
+        );
+        assert_eq!(ranked[0], Provider::Codex);
+    }
+
+    #[test]
+    fn test_fastest_beats_slower_preferred_provider() {
+        let bead =
+            Bead::new("Parser", "parser", TaskType::Implementation).with_provider(Provider::Claude);
+        let mut latency = LatencyTable::default();
+        latency.record(Provider::Claude, Duration::from_secs(40));
+        latency.record(Provider::Codex, Duration::from_secs(5));
+        latency.record(Provider::Gemini, Duration::from_secs(12));
+
+        let balanced = rank_providers(&bead, &EXECUTION, RoutingStrategy::Balanced, &latency);
+        assert_eq!(balanced[0], Provider::Claude);
+
+        let fastest = rank_providers(&bead, &EXECUTION, RoutingStrategy::Fastest, &latency);
+        assert_eq!(
+            fastest,
+            vec![Provider::Codex, Provider::Gemini, Provider::Claude]
+        );
+    }
+
+    #[test]
+    fn test_only_viable_providers_ranked() {
+        let bead =
+            Bead::new("Parser", "parser", TaskType::Implementation).with_provider(Provider::Claude);
+        let ranked = rank_providers(
+            &bead,
+            &[Provider::Gemini],
+            RoutingStrategy::Balanced,
+            &LatencyTable::default(),
+        );
+        assert_eq!(ranked, vec![Provider::Gemini]);
+    }
+
+    #[test]
+    fn test_large_context_bead_skips_small_providers() {
+        let bead = Bead::new("Audit", "audit the monorepo", TaskType::Review)
+            .with_provider(Provider::Codex)
+            .with_estimate(400_000);
+        let ranked = rank_providers(
+            &bead,
+            &[
+                Provider::Claude,
+                Provider::Codex,
+                Provider::Gemini,
+                Provider::Ollama,
+            ],
+            RoutingStrategy::Balanced,
+            &LatencyTable::default(),
+        );
+        assert_eq!(ranked, vec![Provider::Gemini]);
+    }
+
+    #[test]
+    fn test_explicit_order_overrides_affinity() {
+        // Review's affinity puts Codex first
+        let mut bead = Bead::new("Review", "review", TaskType::Review);
+        let order = [Provider::Gemini, Provider::Claude, Provider::Codex];
+        assert_eq!(rank_by_order(&bead, &order), order.to_vec());
+
+        bead.excluded_providers.push(Provider::Gemini);
+        assert_eq!(
+            rank_by_order(&bead, &order),
+            vec![Provider::Claude, Provider::Codex]
+        );
+    }
+
+    #[test]
+    fn test_local_only_routes_to_ollama() {
+        let mut config = crate::config::Config::default();
+        config.general.local_only = true;
+        let viable: Vec<Provider> = Provider::all()
+            .filter(|&p| config.is_provider_enabled(p))
+            .collect();
+        assert_eq!(viable, vec![Provider::Ollama]);
+
+        let bead =
+            Bead::new("Parser", "parser", TaskType::Implementation).with_provider(Provider::Claude);
+        let ranked = rank_providers(
+            &bead,
+            &viable,
+            RoutingStrategy::Balanced,
+            &LatencyTable::default(),
+        );
+        assert_eq!(ranked, vec![Provider::Ollama]);
+        assert_eq!(route_local(&bead).unwrap(), Provider::Ollama);
+
+        let huge = bead.with_estimate(1_000_000);
+        assert!(matches!(
+            route_local(&huge),
+            Err(RigsError::NotServableLocally { .. })
+        ));
+    }
+
+    fn tank(provider: Provider, remaining: u64) -> Tank {
+        let mut tank = Tank::new(provider, 100_000, 5);
+        tank.update_remaining(remaining);
+        tank
+    }
+
+    #[test]
+    fn test_empty_preferred_provider_falls_to_next_affinity() {
+        let bead = Bead::new("Parser", "parser", TaskType::Review)
+            .with_provider(Provider::Claude)
+            .with_estimate(5_000);
+        let router = Router::new(RoutingStrategy::Balanced);
+
+        let tanks = [
+            tank(Provider::Claude, 90_000),
+            tank(Provider::Codex, 90_000),
+        ];
+        assert_eq!(router.select(&bead, &tanks).unwrap(), Provider::Claude);
+
+        let tanks = [tank(Provider::Claude, 0), tank(Provider::Codex, 90_000)];
+        assert_eq!(router.select(&bead, &tanks).unwrap(), Provider::Codex);
+    }
+
+    #[test]
+    fn test_red_tanks_only_used_as_last_resort() {
+        let bead = Bead::new("Parser", "parser", TaskType::Implementation).with_estimate(5_000);
+        let router = Router::new(RoutingStrategy::Balanced);
+
+        let tanks = [
+            tank(Provider::Claude, 10_000),
+            tank(Provider::Codex, 40_000),
+            tank(Provider::Gemini, 90_000),
+        ];
+        assert_eq!(router.select(&bead, &tanks).unwrap(), Provider::Gemini);
+
+        let tanks = [
+            tank(Provider::Claude, 10_000),
+            tank(Provider::Codex, 0),
+            tank(Provider::Gemini, 0),
+        ];
+        assert_eq!(router.select(&bead, &tanks).unwrap(), Provider::Claude);
+    }
+
+    #[test]
+    fn test_cheapest_prefers_lowest_price() {
+        let bead = Bead::new("Parser", "parser", TaskType::Implementation).with_estimate(5_000);
+        let tanks = [tank(Provider::Claude, 90_000)];
+
+        let cheapest = Router::new(RoutingStrategy::Cheapest);
+        assert_eq!(cheapest.select(&bead, &tanks).unwrap(), Provider::Codex);
+
+        let balanced = Router::new(RoutingStrategy::Balanced);
+        assert_eq!(balanced.select(&bead, &tanks).unwrap(), Provider::Claude);
+    }
+
+    #[test]
+    fn test_all_exhausted_reports_soonest_reset() {
+        let bead = Bead::new("Parser", "parser", TaskType::Implementation).with_estimate(5_000);
+        let mut tanks = [
+            tank(Provider::Claude, 0),
+            tank(Provider::Codex, 1_000),
+            tank(Provider::Gemini, 0),
+        ];
+        let soonest = Utc::now() + chrono::Duration::minutes(20);
+        tanks[1].window_end = soonest;
+
+        let err = Router::new(RoutingStrategy::Balanced)
+            .select(&bead, &tanks)
+            .unwrap_err();
+        assert!(matches!(err, RigsError::AllProvidersExhausted(at) if at == soonest));
+    }
+}
@@ -0,0 +1,300 @@
+//! Dispatch order preview
+//!
+//! Replays the Foreman's scheduling rules against a snapshot of pending
+//! beads without executing anything: beads run in waves as their
+//! dependencies complete, in queue order within each wave. Beads whose
+//! provider tank can't cover their estimate are projected as deferred, and
+//! beads whose dependencies can never complete are reported as blocked.
+//! [`forecast_completion`] builds on the same rules to estimate when a set
+//! of beads will have drained through the tanks.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+
+use super::bead::{Bead, BeadId};
+use super::provider::Provider;
+use super::tank::Tank;
+
+/// A bead the Foreman is expected to dispatch
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedDispatch {
+    /// 1-based `run_once` pass the bead is dispatched in
+    pub wave: usize,
+    pub bead_id: BeadId,
+    pub title: String,
+    pub provider: Provider,
+    pub estimated_tokens: u64,
+}
+
+/// A bead that is expected to be held back
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeldBead {
+    pub bead_id: BeadId,
+    pub title: String,
+    pub reason: String,
+}
+
+/// Projected outcome of draining the current queue
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DispatchPlan {
+    /// Beads in dispatch order
+    pub dispatched: Vec<PlannedDispatch>,
+    /// Beads a tank can't cover when their turn comes
+    pub deferred: Vec<HeldBead>,
+    /// Beads whose dependencies will never complete (missing, failed, or cyclic)
+    pub blocked: Vec<HeldBead>,
+}
+
+impl DispatchPlan {
+    /// Bead ids in the order they'd be dispatched
+    pub fn order(&self) -> Vec<BeadId> {
+        self.dispatched.iter().map(|d| d.bead_id.clone()).collect()
+    }
+}
+
+/// Preview the dispatch order for `pending` (already in queue order)
+///
+/// Every dispatched bead is assumed to succeed, and draws its estimate from
+/// its provider's tank when one is given.
+pub fn plan_dispatch(
+    pending: &[Bead],
+    completed: &HashSet<BeadId>,
+    tanks: &[Tank],
+) -> DispatchPlan {
+    let mut plan = DispatchPlan::default();
+    let mut done = completed.clone();
+    let mut tanks: HashMap<Provider, Tank> =
+        tanks.iter().map(|t| (t.provider, t.clone())).collect();
+    let mut waiting: Vec<&Bead> = pending.iter().collect();
+    let mut wave = 0;
+
+    loop {
+        let (ready, rest): (Vec<&Bead>, Vec<&Bead>) =
+            waiting.into_iter().partition(|b| b.dependencies_met(&done));
+        waiting = rest;
+        if ready.is_empty() {
+            break;
+        }
+        wave += 1;
+
+        let mut finished = Vec::new();
+        for bead in ready {
+            let provider = bead.dispatch_provider();
+            if let Some(tank) = tanks.get_mut(&provider) {
+                if tank.consume(bead.estimated_tokens).is_err() {
+                    plan.deferred.push(HeldBead {
+                        bead_id: bead.id.clone(),
+                        title: bead.title.clone(),
+                        reason: format!(
+                            "{} tank has {} tokens left, needs {}",
+                            provider, tank.remaining, bead.estimated_tokens
+                        ),
+                    });
+                    continue;
+                }
+            }
+            plan.dispatched.push(PlannedDispatch {
+                wave,
+                bead_id: bead.id.clone(),
+                title: bead.title.clone(),
+                provider,
+                estimated_tokens: bead.estimated_tokens,
+            });
+            finished.push(bead.id.clone());
+        }
+        // A pass waits for all of its beads before the next one starts
+        done.extend(finished);
+    }
+
+    let deferred: HashSet<&BeadId> = plan.deferred.iter().map(|h| &h.bead_id).collect();
+    for bead in waiting {
+        let unmet: Vec<&str> = bead
+            .dependencies
+            .iter()
+            .filter(|dep| !done.contains(*dep))
+            .map(|dep| dep.as_str())
+            .collect();
+        let reason = if bead.dependencies.iter().any(|d| deferred.contains(d)) {
+            format!("waiting on deferred {}", unmet.join(", "))
+        } else {
+            format!("waiting on {}", unmet.join(", "))
+        };
+        plan.blocked.push(HeldBead {
+            bead_id: bead.id.clone(),
+            title: bead.title.clone(),
+            reason,
+        });
+    }
+
+    plan
+}
+
+/// When a set of remaining beads is expected to finish
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletionForecast {
+    /// Nothing left to run
+    Finished,
+    /// Every bead fits its tank by this time
+    At(DateTime<Utc>),
+    /// Some bead can never run
+    Blocked(String),
+}
+
+impl CompletionForecast {
+    pub fn eta(&self) -> Option<DateTime<Utc>> {
+        match self {
+            CompletionForecast::At(at) => Some(*at),
+            _ => None,
+        }
+    }
+}
+
+/// Estimate when `remaining` will have been dispatched
+///
+/// Each provider's share of the estimates is drawn from its tank, rolling
+/// into later windows as the tank refills. Execution time is not modelled,
+/// so the result is the earliest the tanks allow rather than a promise.
+/// Providers without a tank (or with an unlimited one) never hold work back.
+pub fn forecast_completion(
+    remaining: &[Bead],
+    completed: &HashSet<BeadId>,
+    tanks: &[Tank],
+    now: DateTime<Utc>,
+) -> CompletionForecast {
+    if remaining.is_empty() {
+        return CompletionForecast::Finished;
+    }
+
+    // Token limits are handled below; here only dependencies can block
+    let plan = plan_dispatch(remaining, completed, &[]);
+    if let Some(held) = plan.blocked.first() {
+        return CompletionForecast::Blocked(format!("{} is {}", held.bead_id, held.reason));
+    }
+
+    let mut needed: HashMap<Provider, u64> = HashMap::new();
+    for bead in remaining {
+        let provider = bead.dispatch_provider();
+        if let Some(tank) = tanks.iter().find(|t| t.provider == provider) {
+            if !tank.is_unlimited() && bead.estimated_tokens > tank.capacity {
+                return CompletionForecast::Blocked(format!(
+                    "{} needs {} tokens, more than the {} tank holds",
+                    bead.id, bead.estimated_tokens, provider
+                ));
+            }
+        }
+        *needed.entry(provider).or_default() += bead.estimated_tokens;
+    }
+
+    let mut eta = now;
+    for (provider, tokens) in needed {
+        if let Some(tank) = tanks.iter().find(|t| t.provider == provider) {
+            eta = eta.max(tank_drained_at(tank, tokens, now));
+        }
+    }
+    CompletionForecast::At(eta)
+}
+
+/// When `tank` will have let `tokens` through, refilling at each reset
+fn tank_drained_at(tank: &Tank, tokens: u64, now: DateTime<Utc>) -> DateTime<Utc> {
+    if tank.is_unlimited() {
+        return now;
+    }
+    let window = tank.window_end - tank.window_start;
+    // A window that has already ended resets on the next request
+    let (remaining, window_end) = if tank.window_end <= now {
+        (tank.capacity, now + window)
+    } else {
+        (tank.remaining, tank.window_end)
+    };
+    if tokens <= remaining {
+        return now;
+    }
+
+    let windows = (tokens - remaining).div_ceil(tank.capacity);
+    window_end + window * (windows as i32 - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::bead::{Priority, TaskType};
+
+    #[test]
+    fn test_waves_follow_dependencies() {
+        let research = Bead::new("Research", "r", TaskType::Research);
+        let design =
+            Bead::new("Design", "d", TaskType::Design).with_dependencies(vec![research.id.clone()]);
+        let docs = Bead::new("Docs", "d", TaskType::Documentation).with_priority(Priority::High);
+        let pending = vec![docs.clone(), research.clone(), design.clone()];
+
+        let plan = plan_dispatch(&pending, &HashSet::new(), &[]);
+        assert_eq!(plan.order(), vec![docs.id, research.id, design.id]);
+        assert_eq!(plan.dispatched[2].wave, 2);
+        assert!(plan.blocked.is_empty());
+    }
+
+    #[test]
+    fn test_deferrals_and_missing_dependencies() {
+        let big = Bead::new("Big", "b", TaskType::Implementation).with_estimate(5_000);
+        let after_big =
+            Bead::new("After", "a", TaskType::Review).with_dependencies(vec![big.id.clone()]);
+        let orphan =
+            Bead::new("Orphan", "o", TaskType::Review).with_dependencies(vec![BeadId::new()]);
+        let tank = Tank::new(Provider::Claude, 1_000, 5);
+
+        let plan = plan_dispatch(&[big.clone(), after_big, orphan], &HashSet::new(), &[tank]);
+        assert!(plan.dispatched.is_empty());
+        assert_eq!(plan.deferred[0].bead_id, big.id);
+        assert!(plan.deferred[0].reason.contains("1000 tokens left"));
+        assert_eq!(plan.blocked.len(), 2);
+        assert!(plan.blocked[0].reason.starts_with("waiting on deferred"));
+    }
+
+    #[test]
+    fn test_forecast_rolls_into_later_windows() {
+        let mut tank = Tank::new(Provider::Claude, 1_000, 5);
+        tank.remaining = 200;
+        let now = tank.window_start;
+        let first = Bead::new("First", "f", TaskType::Implementation)
+            .with_provider(Provider::Claude)
+            .with_estimate(700);
+        let second = Bead::new("Second", "s", TaskType::Implementation)
+            .with_provider(Provider::Claude)
+            .with_estimate(900);
+        let done = HashSet::new();
+        let window_end = tank.window_end;
+        let tanks = [tank];
+
+        let small = forecast_completion(&[first.clone().with_estimate(100)], &done, &tanks, now);
+        assert_eq!(small, CompletionForecast::At(now));
+
+        // 1400 tokens past the 200 left: this window's reset plus one more
+        let forecast = forecast_completion(&[first, second], &done, &tanks, now);
+        assert_eq!(
+            forecast.eta(),
+            Some(window_end + chrono::Duration::hours(5))
+        );
+        assert_eq!(
+            forecast_completion(&[], &done, &tanks, now),
+            CompletionForecast::Finished
+        );
+    }
+
+    #[test]
+    fn test_forecast_blocked() {
+        let tanks = [Tank::new(Provider::Claude, 1_000, 5)];
+        let now = tanks[0].window_start;
+        let huge = Bead::new("Huge", "h", TaskType::Implementation)
+            .with_provider(Provider::Claude)
+            .with_estimate(5_000);
+        let forecast = forecast_completion(&[huge], &HashSet::new(), &tanks, now);
+        assert!(matches!(forecast, CompletionForecast::Blocked(ref r) if r.contains("more than")));
+
+        let orphan =
+            Bead::new("Orphan", "o", TaskType::Review).with_dependencies(vec![BeadId::new()]);
+        let forecast = forecast_completion(&[orphan], &HashSet::new(), &tanks, now);
+        assert_eq!(forecast.eta(), None);
+        assert!(matches!(forecast, CompletionForecast::Blocked(ref r) if r.contains("waiting on")));
+    }
+}
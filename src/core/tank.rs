@@ -2,16 +2,77 @@
 //!
 //! A Tank tracks the current rate limit state for a single provider.
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
-use super::provider::Provider;
+use super::provider::{Provider, ProviderLimits, WeeklyReset};
+
+/// Current version of the persisted tank shape
+///
+/// Bump this whenever fields are added to [`Tank`] so that rows written by an
+/// older binary can be recognised and upgraded on read.
+pub const TANK_SCHEMA_VERSION: u32 = 6;
+
+/// How to reconcile local accounting with what a provider reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconcilePolicy {
+    /// Take the more pessimistic value: lower remaining, later reset
+    #[default]
+    Min,
+    /// Take the provider's values
+    TrustRemote,
+    /// Ignore the provider and keep local accounting
+    TrustLocal,
+}
+
+/// How a tank's capacity comes back after it is consumed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowKind {
+    /// Everything comes back at once when the window ends
+    #[default]
+    Fixed,
+    /// Each consumption comes back one window length after it was made
+    Rolling,
+}
+
+/// Tokens taken from a rolling tank, kept until they age out of the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Consumption {
+    pub at: DateTime<Utc>,
+    pub tokens: u64,
+}
+
+/// One consumption from a tank, as kept in the usage history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageEvent {
+    pub provider: Provider,
+    pub tokens: u64,
+    pub at: DateTime<Utc>,
+    /// Tokens left in the window after this consumption
+    pub remaining: u64,
+}
+
+impl UsageEvent {
+    /// Record `tokens` just consumed from `tank`
+    pub fn new(tank: &Tank, tokens: u64) -> Self {
+        Self {
+            provider: tank.provider,
+            tokens,
+            at: tank.last_request.unwrap_or(tank.updated_at),
+            remaining: tank.remaining,
+        }
+    }
+}
 
 /// Health level of a tank based on remaining capacity
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TankHealth {
     /// >50% capacity remaining
+    #[default]
     Green,
     /// 20-50% capacity remaining
     Yellow,
@@ -28,7 +89,7 @@ impl TankHealth {
             TankHealth::Empty
         } else if ratio < red_threshold {
             TankHealth::Red
-        } else if ratio < yellow_threshold {
+        } else if ratio <= yellow_threshold {
             TankHealth::Yellow
         } else {
             TankHealth::Green
@@ -57,8 +118,15 @@ impl TankHealth {
 }
 
 /// Rate limit state for a provider
+///
+/// Fields added after the first release carry `#[serde(default)]` so that
+/// tanks persisted by older versions still deserialize; call [`Tank::upgrade`]
+/// (or load through [`Tank::from_json`]) to bring them up to date.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tank {
+    /// Version of the persisted shape (0 = written before versioning)
+    #[serde(default)]
+    pub schema_version: u32,
     /// Provider this tank belongs to
     pub provider: Provider,
     /// Maximum tokens in window
@@ -70,15 +138,73 @@ pub struct Tank {
     /// When current window ends (reset time)
     pub window_end: DateTime<Utc>,
     /// Current health level
+    #[serde(default)]
     pub health: TankHealth,
     /// Last API request time
+    #[serde(default)]
     pub last_request: Option<DateTime<Utc>>,
     /// Requests made in current window
+    #[serde(default)]
     pub requests_this_window: u32,
     /// Tokens consumed in current window
+    #[serde(default)]
     pub tokens_this_window: u64,
+    /// Tokens consumed since the last weekly reset
+    #[serde(default)]
+    pub weekly_used: u64,
+    /// When the weekly cap next resets (unset until first rolled)
+    #[serde(default)]
+    pub weekly_reset_at: Option<DateTime<Utc>>,
     /// When this tank state was last updated
+    #[serde(default = "Utc::now")]
     pub updated_at: DateTime<Utc>,
+    /// Whether capacity refills all at once or ages back in
+    #[serde(default)]
+    pub window_kind: WindowKind,
+    /// Consumption still inside the trailing window, oldest first (rolling
+    /// tanks only)
+    #[serde(default)]
+    pub consumption: VecDeque<Consumption>,
+    /// Tokens consumed since the last UTC midnight
+    #[serde(default)]
+    pub tokens_today: u64,
+    /// When `tokens_today` next resets (unset until first rolled)
+    #[serde(default)]
+    pub daily_reset_at: Option<DateTime<Utc>>,
+    /// Most tokens a day may use, whatever the window allows
+    #[serde(default)]
+    pub daily_cap: Option<u64>,
+    /// Most tokens a week may use (counted in `weekly_used`)
+    #[serde(default)]
+    pub weekly_cap: Option<u64>,
+    /// When the weekly cap resets
+    #[serde(default)]
+    pub weekly_reset: WeeklyReset,
+    /// Most requests a minute may make
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Recent request times, oldest first, holding at most
+    /// `requests_per_minute` entries
+    #[serde(default)]
+    pub recent_requests: VecDeque<DateTime<Utc>>,
+    /// Tokens added to `capacity` by top-ups, taken back off when the
+    /// window resets
+    #[serde(default)]
+    pub granted: u64,
+    /// Capacity fraction below which health turns yellow
+    #[serde(default = "default_threshold_yellow")]
+    pub threshold_yellow: f32,
+    /// Capacity fraction below which health turns red
+    #[serde(default = "default_threshold_red")]
+    pub threshold_red: f32,
+}
+
+fn default_threshold_yellow() -> f32 {
+    0.5
+}
+
+fn default_threshold_red() -> f32 {
+    0.2
 }
 
 impl Tank {
@@ -88,6 +214,7 @@ impl Tank {
         let window_end = now + Duration::hours(window_hours as i64);
 
         Self {
+            schema_version: TANK_SCHEMA_VERSION,
             provider,
             capacity,
             remaining: capacity,
@@ -97,10 +224,81 @@ impl Tank {
             last_request: None,
             requests_this_window: 0,
             tokens_this_window: 0,
+            weekly_used: 0,
+            weekly_reset_at: None,
             updated_at: now,
+            window_kind: WindowKind::Fixed,
+            consumption: VecDeque::new(),
+            tokens_today: 0,
+            daily_reset_at: None,
+            daily_cap: None,
+            weekly_cap: None,
+            weekly_reset: WeeklyReset::default(),
+            requests_per_minute: None,
+            recent_requests: VecDeque::new(),
+            granted: 0,
+            threshold_yellow: default_threshold_yellow(),
+            threshold_red: default_threshold_red(),
         }
     }
 
+    /// Enforce the request rate and daily and weekly caps from `limits`
+    pub fn with_limits(mut self, limits: &ProviderLimits) -> Self {
+        self.requests_per_minute = limits.requests_per_minute;
+        self.daily_cap = limits.daily_cap;
+        self.weekly_cap = limits.weekly_cap;
+        self.weekly_reset = limits.weekly_reset;
+        self
+    }
+
+    /// Judge health against the provider's configured thresholds
+    pub fn with_thresholds(mut self, yellow: f32, red: f32) -> Self {
+        self.threshold_yellow = yellow;
+        self.threshold_red = red;
+        self.recalculate_health(yellow, red);
+        self
+    }
+
+    /// Create a full tank whose consumption ages out over a trailing window
+    ///
+    /// While nothing is consumed the window starts now; after that it
+    /// starts at the oldest consumption still inside it, so `window_end` is
+    /// when capacity next starts coming back.
+    pub fn rolling(provider: Provider, capacity: u64, window_hours: u32) -> Self {
+        Self {
+            window_kind: WindowKind::Rolling,
+            ..Self::new(provider, capacity, window_hours)
+        }
+    }
+
+    /// Deserialize a persisted tank, upgrading older shapes to the current one
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let mut tank: Tank = serde_json::from_str(json)?;
+        tank.upgrade();
+        Ok(tank)
+    }
+
+    /// Bring a tank loaded from an older schema version up to date
+    ///
+    /// Missing fields have already been defaulted by serde; this fixes up
+    /// anything derived from them and stamps the current version.
+    /// Returns `true` if the tank was changed.
+    pub fn upgrade(&mut self) -> bool {
+        if self.schema_version >= TANK_SCHEMA_VERSION {
+            return false;
+        }
+
+        self.remaining = self.remaining.min(self.capacity);
+        self.recalculate_health(self.threshold_yellow, self.threshold_red);
+        self.schema_version = TANK_SCHEMA_VERSION;
+        true
+    }
+
+    /// Whether this tank has no meaningful limit (e.g. local Ollama)
+    pub fn is_unlimited(&self) -> bool {
+        self.capacity == u64::MAX
+    }
+
     /// Get current capacity ratio (0.0 to 1.0)
     pub fn capacity_ratio(&self) -> f32 {
         if self.capacity == 0 {
@@ -119,58 +317,317 @@ impl Tank {
         }
     }
 
+    /// Tokens consumed per hour so far this window
+    pub fn burn_rate_per_hour(&self) -> f32 {
+        self.burn_rate_per_hour_at(Utc::now())
+    }
+
+    /// [`Tank::burn_rate_per_hour`] as of `now`
+    ///
+    /// Zero until a minute of the window has passed, so a freshly reset
+    /// window doesn't extrapolate from a single request.
+    pub fn burn_rate_per_hour_at(&self, now: DateTime<Utc>) -> f32 {
+        let elapsed = now.min(self.window_end) - self.window_start;
+        if elapsed < Duration::minutes(1) {
+            return 0.0;
+        }
+        self.tokens_this_window as f32 * 3600.0 / elapsed.num_seconds() as f32
+    }
+
+    /// When `remaining` will hit zero at the current burn rate
+    pub fn projected_exhaustion(&self) -> Option<DateTime<Utc>> {
+        self.projected_exhaustion_at(Utc::now())
+    }
+
+    /// [`Tank::projected_exhaustion`] as of `now`
+    ///
+    /// `None` if nothing is being burned, the tank is unlimited, or the
+    /// projection is more than a year out.
+    pub fn projected_exhaustion_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let rate = self.burn_rate_per_hour_at(now);
+        if rate <= 0.0 || self.is_unlimited() {
+            return None;
+        }
+        let seconds = self.remaining as f64 / rate as f64 * 3600.0;
+        if !seconds.is_finite() || seconds > Duration::days(365).num_seconds() as f64 {
+            return None;
+        }
+        Some(now + Duration::seconds(seconds as i64))
+    }
+
+    /// Length of the tank's window in whole hours
+    pub fn window_hours(&self) -> u32 {
+        (self.window_end - self.window_start).num_hours().max(0) as u32
+    }
+
     /// Check if window has reset and needs refresh
+    ///
+    /// For a rolling tank this means some consumption has aged out.
     pub fn needs_refresh(&self) -> bool {
-        Utc::now() >= self.window_end
+        self.needs_refresh_at(Utc::now())
+    }
+
+    /// [`Tank::needs_refresh`] as of `now`
+    pub fn needs_refresh_at(&self, now: DateTime<Utc>) -> bool {
+        now >= self.window_end
     }
 
     /// Check if we can consume the given number of tokens
+    ///
+    /// Rolling tanks only count consumption as aged out once refreshed (see
+    /// [`Tank::reset_window`]); [`Tank::consume_at`] refreshes first.
+    ///
+    /// Daily and weekly caps count usage since they were last rolled; see
+    /// [`Tank::roll_daily`] and [`Tank::roll_weekly`].
     pub fn can_consume(&self, tokens: u64) -> bool {
-        self.remaining >= tokens && self.health != TankHealth::Empty
+        self.shortfall(tokens).is_none()
+    }
+
+    /// The limit `tokens` would break, with how much it still allows
+    fn shortfall(&self, tokens: u64) -> Option<(CapacityLimit, u64)> {
+        if self.remaining < tokens || self.health == TankHealth::Empty {
+            return Some((CapacityLimit::Window, self.remaining));
+        }
+        let caps = [
+            (CapacityLimit::Daily, self.daily_cap, self.tokens_today),
+            (CapacityLimit::Weekly, self.weekly_cap, self.weekly_used),
+        ];
+        caps.into_iter().find_map(|(limit, cap, used)| {
+            let left = cap?.saturating_sub(used);
+            (left < tokens).then_some((limit, left))
+        })
+    }
+
+    /// Requests made in the minute before `now`, oldest first
+    fn requests_in_last_minute(&self, now: DateTime<Utc>) -> impl Iterator<Item = &DateTime<Utc>> {
+        let since = now - Duration::minutes(1);
+        self.recent_requests.iter().filter(move |&&at| at > since)
+    }
+
+    /// Check if the requests-per-minute limit leaves room for a request
+    pub fn can_make_request(&self, now: DateTime<Utc>) -> bool {
+        self.time_until_next_request_slot(now).is_zero()
+    }
+
+    /// How long until the requests-per-minute limit allows another request
+    ///
+    /// Zero if a request can be made now.
+    pub fn time_until_next_request_slot(&self, now: DateTime<Utc>) -> Duration {
+        let Some(rpm) = self.requests_per_minute else {
+            return Duration::zero();
+        };
+        let recent: Vec<_> = self.requests_in_last_minute(now).collect();
+        match recent.len().checked_sub(rpm as usize) {
+            Some(excess) => *recent[excess] + Duration::minutes(1) - now,
+            None => Duration::zero(),
+        }
     }
 
     /// Consume tokens from the tank
     pub fn consume(&mut self, tokens: u64) -> Result<(), InsufficientCapacity> {
-        if !self.can_consume(tokens) {
+        self.consume_at(tokens, Utc::now())
+    }
+
+    /// Consume tokens from the tank as of `now`
+    pub fn consume_at(
+        &mut self,
+        tokens: u64,
+        now: DateTime<Utc>,
+    ) -> Result<(), InsufficientCapacity> {
+        if self.window_kind == WindowKind::Rolling {
+            self.age_out(now);
+        }
+        if self.daily_cap.is_some() {
+            self.roll_daily(now);
+        }
+        if self.weekly_cap.is_some() {
+            let reset = self.weekly_reset;
+            self.roll_weekly(&reset, now);
+        }
+        if let Some((limit, available)) = self.shortfall(tokens) {
             return Err(InsufficientCapacity {
                 requested: tokens,
-                available: self.remaining,
+                available,
                 provider: self.provider,
+                limit,
+            });
+        }
+        if !self.can_make_request(now) {
+            return Err(InsufficientCapacity {
+                requested: tokens,
+                available: 0,
+                provider: self.provider,
+                limit: CapacityLimit::RequestsPerMinute,
             });
         }
 
+        if self.window_kind == WindowKind::Rolling {
+            if self.consumption.is_empty() {
+                let span = self.window_end - self.window_start;
+                self.window_start = now;
+                self.window_end = now + span;
+            }
+            self.consumption.push_back(Consumption { at: now, tokens });
+        }
         self.remaining = self.remaining.saturating_sub(tokens);
         self.tokens_this_window += tokens;
+        self.weekly_used += tokens;
+        self.tokens_today += tokens;
         self.requests_this_window += 1;
-        self.last_request = Some(Utc::now());
-        self.recalculate_health(0.5, 0.2); // Default thresholds
-        self.updated_at = Utc::now();
+        self.last_request = Some(now);
+        if let Some(rpm) = self.requests_per_minute {
+            while self.recent_requests.len() >= rpm.max(1) as usize {
+                self.recent_requests.pop_front();
+            }
+            self.recent_requests.push_back(now);
+        }
+        self.recalculate_health(self.threshold_yellow, self.threshold_red);
+        self.updated_at = now;
 
         Ok(())
     }
 
     /// Reset the window (call when window_end is reached)
+    ///
+    /// A rolling tank only gets back what has aged out of the trailing
+    /// `window_hours`. Either kind gives up any capacity granted by
+    /// [`Tank::top_up`].
     pub fn reset_window(&mut self, window_hours: u32) {
-        let now = Utc::now();
-        self.window_start = now;
-        self.window_end = now + Duration::hours(window_hours as i64);
-        self.remaining = self.capacity;
-        self.requests_this_window = 0;
-        self.tokens_this_window = 0;
-        self.recalculate_health(0.5, 0.2);
+        self.reset_window_at(window_hours, Utc::now());
+    }
+
+    /// [`Tank::reset_window`] as of `now`
+    pub fn reset_window_at(&mut self, window_hours: u32, now: DateTime<Utc>) {
+        let span = Duration::hours(window_hours as i64);
+        // A top-up only lasts for the window it was bought in
+        self.capacity = self.capacity.saturating_sub(self.granted);
+        self.granted = 0;
+        match self.window_kind {
+            WindowKind::Fixed => {
+                self.window_start = now;
+                self.window_end = now + span;
+                self.remaining = self.capacity;
+                self.requests_this_window = 0;
+                self.tokens_this_window = 0;
+                self.recalculate_health(self.threshold_yellow, self.threshold_red);
+            }
+            WindowKind::Rolling => {
+                self.window_end = self.window_start + span;
+                self.age_out(now);
+            }
+        }
         self.updated_at = now;
     }
 
+    /// Drop rolling consumption older than the window and recount what's left
+    fn age_out(&mut self, now: DateTime<Utc>) {
+        let span = self.window_end - self.window_start;
+        while self.consumption.front().is_some_and(|c| c.at + span <= now) {
+            self.consumption.pop_front();
+        }
+        self.window_start = self.consumption.front().map_or(now, |c| c.at);
+        self.window_end = self.window_start + span;
+        self.tokens_this_window = self.consumption.iter().map(|c| c.tokens).sum();
+        self.requests_this_window = self.consumption.len() as u32;
+        self.remaining = self.capacity.saturating_sub(self.tokens_this_window);
+        self.recalculate_health(self.threshold_yellow, self.threshold_red);
+    }
+
+    /// Add purchased capacity to the current window
+    ///
+    /// Raises both capacity and remaining by `tokens`, so the health ratio
+    /// reflects the larger budget; the window itself is unchanged. The grant
+    /// is kept in `granted` and dropped again by [`Tank::reset_window`].
+    /// Unlimited tanks are left alone.
+    pub fn top_up(&mut self, tokens: u64) {
+        if self.is_unlimited() {
+            return;
+        }
+        self.capacity = self.capacity.saturating_add(tokens);
+        self.remaining = self.remaining.saturating_add(tokens);
+        self.granted = self.granted.saturating_add(tokens);
+        self.recalculate_health(self.threshold_yellow, self.threshold_red);
+        self.updated_at = Utc::now();
+    }
+
+    /// Start a new day if UTC midnight has passed
+    ///
+    /// Returns `true` if the day's usage was cleared.
+    pub fn roll_daily(&mut self, now: DateTime<Utc>) -> bool {
+        match self.daily_reset_at {
+            Some(at) if now < at => false,
+            previous => {
+                let tomorrow = now.date_naive() + Duration::days(1);
+                self.daily_reset_at = Some(tomorrow.and_time(NaiveTime::MIN).and_utc());
+                if previous.is_some() {
+                    self.tokens_today = 0;
+                }
+                previous.is_some()
+            }
+        }
+    }
+
+    /// Start a new weekly period if the provider's reset point has passed
+    ///
+    /// Resets land on the configured anchor rather than seven days after
+    /// the previous one started. Returns `true` if weekly usage was cleared.
+    pub fn roll_weekly(&mut self, reset: &WeeklyReset, now: DateTime<Utc>) -> bool {
+        match self.weekly_reset_at {
+            Some(at) if now < at => false,
+            previous => {
+                self.weekly_reset_at = Some(reset.next_after(now));
+                if previous.is_some() {
+                    self.weekly_used = 0;
+                }
+                previous.is_some()
+            }
+        }
+    }
+
     /// Update remaining capacity (e.g., from API response)
-    pub fn update_remaining(&mut self, remaining: u64, yellow: f32, red: f32) {
+    pub fn update_remaining(&mut self, remaining: u64) {
         self.remaining = remaining.min(self.capacity);
-        self.recalculate_health(yellow, red);
+        self.recalculate_health(self.threshold_yellow, self.threshold_red);
+        self.updated_at = Utc::now();
+    }
+
+    /// Fold provider-reported capacity (e.g. from rate limit headers) into
+    /// local accounting
+    ///
+    /// Headers can run ahead of requests still in flight, so trusting them
+    /// outright may overstate capacity; [`ReconcilePolicy::Min`] keeps
+    /// whichever view is more conservative. A `reset` in the past is ignored.
+    pub fn merge_remote(
+        &mut self,
+        remaining: u64,
+        reset: Option<DateTime<Utc>>,
+        policy: ReconcilePolicy,
+    ) {
+        let remaining = remaining.min(self.capacity);
+        let reset = reset.filter(|at| *at > Utc::now());
+        match policy {
+            ReconcilePolicy::Min => {
+                self.remaining = self.remaining.min(remaining);
+                if let Some(at) = reset {
+                    self.window_end = self.window_end.max(at);
+                }
+            }
+            ReconcilePolicy::TrustRemote => {
+                self.remaining = remaining;
+                if let Some(at) = reset {
+                    self.window_end = at;
+                }
+            }
+            ReconcilePolicy::TrustLocal => return,
+        }
+        self.recalculate_health(self.threshold_yellow, self.threshold_red);
         self.updated_at = Utc::now();
     }
 
     /// Recalculate health based on current ratio
     fn recalculate_health(&mut self, yellow_threshold: f32, red_threshold: f32) {
-        self.health = TankHealth::from_ratio(self.capacity_ratio(), yellow_threshold, red_threshold);
+        self.health =
+            TankHealth::from_ratio(self.capacity_ratio(), yellow_threshold, red_threshold);
     }
 
     /// Generate a progress bar string (for display)
@@ -195,20 +652,46 @@ impl Tank {
     }
 }
 
+/// Which limit stopped a consumption
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapacityLimit {
+    /// The current rate limit window
+    Window,
+    /// The provider's daily cap
+    Daily,
+    /// The provider's weekly cap
+    Weekly,
+    /// The provider's requests-per-minute limit
+    RequestsPerMinute,
+}
+
 /// Error when trying to consume more tokens than available
 #[derive(Debug, Clone)]
 pub struct InsufficientCapacity {
     pub requested: u64,
     pub available: u64,
     pub provider: Provider,
+    pub limit: CapacityLimit,
 }
 
 impl std::fmt::Display for InsufficientCapacity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let left = match self.limit {
+            CapacityLimit::Window => "available",
+            CapacityLimit::Daily => "left under the daily cap",
+            CapacityLimit::Weekly => "left under the weekly cap",
+            CapacityLimit::RequestsPerMinute => {
+                return write!(
+                    f,
+                    "Insufficient capacity on {}: requests-per-minute limit reached",
+                    self.provider
+                );
+            }
+        };
         write!(
             f,
-            "Insufficient capacity on {}: requested {} tokens, only {} available",
-            self.provider, self.requested, self.available
+            "Insufficient capacity on {}: requested {} tokens, only {} {}",
+            self.provider, self.requested, self.available, left
         )
     }
 }
@@ -218,6 +701,7 @@ impl std::error::Error for InsufficientCapacity {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::provider::ProviderConfig;
 
     #[test]
     fn test_tank_creation() {
@@ -227,22 +711,127 @@ mod tests {
         assert_eq!(tank.health, TankHealth::Green);
     }
 
+    /// Local says 60k left resetting in 1h; the provider says 80k, resetting in 2h
+    fn conflicting() -> (Tank, u64, DateTime<Utc>) {
+        let mut tank = Tank::new(Provider::Claude, 100_000, 1);
+        tank.consume(40_000).unwrap();
+        (tank, 80_000, Utc::now() + Duration::hours(2))
+    }
+
+    #[test]
+    fn test_merge_remote_min() {
+        let (mut tank, remote, reset) = conflicting();
+        tank.merge_remote(remote, Some(reset), ReconcilePolicy::Min);
+        assert_eq!(tank.remaining, 60_000);
+        assert_eq!(tank.window_end, reset);
+
+        tank.merge_remote(10_000, None, ReconcilePolicy::Min);
+        assert_eq!(tank.remaining, 10_000);
+        assert_eq!(tank.health, TankHealth::Red);
+    }
+
+    #[test]
+    fn test_merge_remote_trust_remote() {
+        let (mut tank, remote, reset) = conflicting();
+        tank.merge_remote(remote, Some(reset), ReconcilePolicy::TrustRemote);
+        assert_eq!(tank.remaining, 80_000);
+        assert_eq!(tank.window_end, reset);
+        assert_eq!(tank.health, TankHealth::Green);
+
+        // Never more than the tank holds, and stale resets are ignored
+        tank.merge_remote(
+            500_000,
+            Some(Utc::now() - Duration::hours(1)),
+            ReconcilePolicy::TrustRemote,
+        );
+        assert_eq!(tank.remaining, 100_000);
+        assert_eq!(tank.window_end, reset);
+    }
+
+    #[test]
+    fn test_merge_remote_trust_local() {
+        let (mut tank, remote, reset) = conflicting();
+        let window_end = tank.window_end;
+        tank.merge_remote(remote, Some(reset), ReconcilePolicy::TrustLocal);
+        assert_eq!(tank.remaining, 60_000);
+        assert_eq!(tank.window_end, window_end);
+    }
+
+    #[test]
+    fn test_consumption_uses_configured_thresholds() {
+        let mut tank = Tank::new(Provider::Codex, 100_000, 5).with_thresholds(0.3, 0.1);
+        tank.consume(60_000).unwrap();
+        assert_eq!(tank.health, TankHealth::Green);
+        tank.consume(15_000).unwrap();
+        assert_eq!(tank.health, TankHealth::Yellow);
+        tank.consume(20_000).unwrap();
+        assert_eq!(tank.health, TankHealth::Red);
+
+        // Persisted with the tank, so recalculating after a reload agrees
+        let mut reloaded = Tank::from_json(&serde_json::to_string(&tank).unwrap()).unwrap();
+        reloaded.merge_remote(8_000, None, ReconcilePolicy::TrustRemote);
+        assert_eq!(reloaded.health, TankHealth::Red);
+        reloaded.merge_remote(25_000, None, ReconcilePolicy::TrustRemote);
+        assert_eq!(reloaded.health, TankHealth::Yellow);
+    }
+
     #[test]
     fn test_tank_consumption() {
         let mut tank = Tank::new(Provider::Claude, 100_000, 5);
-        
+
         assert!(tank.consume(50_000).is_ok());
         assert_eq!(tank.remaining, 50_000);
         assert_eq!(tank.health, TankHealth::Yellow);
-        
+
         assert!(tank.consume(40_000).is_ok());
         assert_eq!(tank.remaining, 10_000);
         assert_eq!(tank.health, TankHealth::Red);
-        
+
         // Can't consume more than available
         assert!(tank.consume(20_000).is_err());
     }
 
+    #[test]
+    fn test_top_up_raises_capacity_and_recomputes_health() {
+        let mut tank = Tank::new(Provider::Claude, 100_000, 5);
+        tank.consume(85_000).unwrap();
+        assert_eq!(tank.health, TankHealth::Red);
+        let window_end = tank.window_end;
+
+        tank.top_up(100_000);
+        assert_eq!(tank.capacity, 200_000);
+        assert_eq!(tank.remaining, 115_000);
+        assert_eq!(tank.health, TankHealth::Green);
+        assert_eq!(tank.window_end, window_end);
+        assert_eq!(tank.tokens_this_window, 85_000);
+
+        let mut local = Tank::new(Provider::Ollama, u64::MAX, 1);
+        local.top_up(1_000);
+        assert!(local.is_unlimited());
+    }
+
+    #[test]
+    fn test_reset_drops_the_top_up() {
+        let mut tank = Tank::new(Provider::Claude, 100_000, 5);
+        tank.consume(90_000).unwrap();
+        tank.top_up(50_000);
+        assert_eq!(tank.capacity, 150_000);
+        assert_eq!(tank.granted, 50_000);
+
+        tank.reset_window(5);
+        assert_eq!(tank.capacity, 100_000);
+        assert_eq!(tank.remaining, 100_000);
+        assert_eq!(tank.granted, 0);
+        assert_eq!(tank.health, TankHealth::Green);
+
+        // A rolling tank gives the grant back once its window refreshes too
+        let mut rolling = Tank::rolling(Provider::Codex, 1_000, 1);
+        rolling.top_up(500);
+        rolling.reset_window(1);
+        assert_eq!(rolling.capacity, 1_000);
+        assert_eq!(rolling.remaining, 1_000);
+    }
+
     #[test]
     fn test_tank_health() {
         assert_eq!(TankHealth::from_ratio(0.6, 0.5, 0.2), TankHealth::Green);
@@ -251,12 +840,217 @@ mod tests {
         assert_eq!(TankHealth::from_ratio(0.0, 0.5, 0.2), TankHealth::Empty);
     }
 
+    #[test]
+    fn test_tank_health_boundaries() {
+        assert_eq!(TankHealth::from_ratio(0.5, 0.5, 0.2), TankHealth::Yellow);
+        assert_eq!(TankHealth::from_ratio(0.2, 0.5, 0.2), TankHealth::Yellow);
+        assert_eq!(TankHealth::from_ratio(0.19, 0.5, 0.2), TankHealth::Red);
+    }
+
+    #[test]
+    fn test_deserialize_legacy_tank() {
+        // Shape written before schema versioning: no version, health or counters
+        let json = r#"{
+            "provider": "claude",
+            "capacity": 1000,
+            "remaining": 400,
+            "window_start": "2026-01-18T12:00:00Z",
+            "window_end": "2026-01-18T17:00:00Z",
+            "updated_at": "2026-01-18T13:00:00Z"
+        }"#;
+
+        let tank = Tank::from_json(json).unwrap();
+        assert_eq!(tank.schema_version, TANK_SCHEMA_VERSION);
+        assert_eq!(tank.provider, Provider::Claude);
+        assert_eq!(tank.remaining, 400);
+        assert_eq!(tank.health, TankHealth::Yellow);
+        assert_eq!(tank.last_request, None);
+        assert_eq!(tank.requests_this_window, 0);
+        assert_eq!(tank.tokens_this_window, 0);
+    }
+
+    #[test]
+    fn test_current_tank_round_trip() {
+        let mut tank = Tank::new(Provider::Codex, 50_000, 5);
+        tank.consume(10_000).unwrap();
+
+        let json = serde_json::to_string(&tank).unwrap();
+        let mut parsed = Tank::from_json(&json).unwrap();
+        assert!(!parsed.upgrade());
+        assert_eq!(parsed.remaining, 40_000);
+        assert_eq!(parsed.requests_this_window, 1);
+    }
+
+    #[test]
+    fn test_weekly_usage_resets_on_anchor() {
+        let reset = WeeklyReset::default();
+        let mut tank = Tank::new(Provider::Claude, 100_000, 5);
+        let wednesday: DateTime<Utc> = "2026-01-14T10:00:00Z".parse().unwrap();
+
+        assert!(!tank.roll_weekly(&reset, wednesday));
+        assert_eq!(
+            tank.weekly_reset_at,
+            Some("2026-01-19T00:00:00Z".parse().unwrap())
+        );
+        tank.consume(30_000).unwrap();
+
+        // Sunday night is still the same week
+        assert!(!tank.roll_weekly(&reset, "2026-01-18T23:59:00Z".parse().unwrap()));
+        assert_eq!(tank.weekly_used, 30_000);
+
+        assert!(tank.roll_weekly(&reset, "2026-01-19T00:01:00Z".parse().unwrap()));
+        assert_eq!(tank.weekly_used, 0);
+        assert_eq!(
+            tank.weekly_reset_at,
+            Some("2026-01-26T00:00:00Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_rolling_capacity_recovers_gradually() {
+        let mut tank = Tank::rolling(Provider::Claude, 1_000, 5);
+        let start: DateTime<Utc> = "2026-01-18T10:00:00Z".parse().unwrap();
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+
+        tank.consume_at(400, at(0)).unwrap();
+        tank.consume_at(400, at(120)).unwrap();
+        assert_eq!(tank.remaining, 200);
+        assert_eq!(tank.window_end, at(300));
+        assert!(tank.consume_at(300, at(180)).is_err());
+        assert!(!tank.needs_refresh_at(at(299)));
+
+        // The first 400 tokens come back five hours after they were used
+        assert!(tank.needs_refresh_at(at(300)));
+        tank.reset_window_at(5, at(300));
+        assert_eq!(tank.remaining, 600);
+        assert_eq!(tank.tokens_this_window, 400);
+        assert!((tank.capacity_ratio() - 0.6).abs() < 1e-6);
+        assert!(tank.can_consume(600));
+        assert_eq!(tank.window_end, at(420));
+
+        tank.reset_window_at(5, at(420));
+        assert_eq!(tank.remaining, 1_000);
+        assert_eq!(tank.health, TankHealth::Green);
+        assert!(tank.consumption.is_empty());
+
+        // Consuming ages out on its own, without a refresh first
+        tank.consume_at(900, at(420)).unwrap();
+        tank.consume_at(500, at(720)).unwrap();
+        assert_eq!(tank.remaining, 500);
+    }
+
+    #[test]
+    fn test_fixed_window_refills_all_at_once() {
+        let mut tank = Tank::new(Provider::Claude, 1_000, 5);
+        let start = tank.window_start;
+        tank.consume_at(400, start).unwrap();
+        tank.consume_at(400, start + Duration::hours(2)).unwrap();
+        assert!(tank.consumption.is_empty());
+
+        tank.reset_window_at(5, start + Duration::hours(5));
+        assert_eq!(tank.remaining, 1_000);
+        assert_eq!(tank.window_end, start + Duration::hours(10));
+    }
+
+    #[test]
+    fn test_daily_cap_blocks_what_the_window_allows() {
+        let limits = ProviderConfig::gemini_default().limits;
+        let mut tank = Tank::new(Provider::Gemini, 2_000_000, 24).with_limits(&limits);
+        let morning: DateTime<Utc> = "2026-01-14T08:00:00Z".parse().unwrap();
+
+        tank.consume_at(900_000, morning).unwrap();
+        let err = tank
+            .consume_at(200_000, morning + Duration::hours(6))
+            .unwrap_err();
+        assert_eq!(err.limit, CapacityLimit::Daily);
+        assert_eq!(err.available, 100_000);
+        assert!(err.to_string().contains("daily cap"));
+        assert_eq!(tank.remaining, 1_100_000);
+
+        // The cap resets at UTC midnight, not 24 hours after first use
+        tank.consume_at(200_000, "2026-01-15T00:00:00Z".parse().unwrap())
+            .unwrap();
+        assert_eq!(tank.tokens_today, 200_000);
+    }
+
+    #[test]
+    fn test_weekly_cap_resets_on_anchor() {
+        let limits = ProviderConfig::claude_default().limits;
+        let mut tank = Tank::new(Provider::Claude, u64::MAX - 1, 5).with_limits(&limits);
+        let wednesday: DateTime<Utc> = "2026-01-14T10:00:00Z".parse().unwrap();
+
+        tank.consume_at(450_000, wednesday).unwrap();
+        let err = tank
+            .consume_at(60_000, wednesday + Duration::days(2))
+            .unwrap_err();
+        assert_eq!(err.limit, CapacityLimit::Weekly);
+        assert!(!tank.can_consume(60_000));
+
+        tank.consume_at(60_000, "2026-01-19T00:00:00Z".parse().unwrap())
+            .unwrap();
+        assert_eq!(tank.weekly_used, 60_000);
+    }
+
+    #[test]
+    fn test_requests_per_minute_throttle() {
+        let limits = ProviderConfig::gemini_default().limits;
+        let mut tank = Tank::new(Provider::Gemini, 1_000_000, 24).with_limits(&limits);
+        let start: DateTime<Utc> = "2026-01-14T08:00:00Z".parse().unwrap();
+
+        for i in 0..15 {
+            tank.consume_at(100, start + Duration::seconds(i * 2))
+                .unwrap();
+        }
+        let sixteenth = start + Duration::seconds(40);
+        assert!(!tank.can_make_request(sixteenth));
+        assert_eq!(
+            tank.time_until_next_request_slot(sixteenth),
+            Duration::seconds(20)
+        );
+        let err = tank.consume_at(100, sixteenth).unwrap_err();
+        assert_eq!(err.limit, CapacityLimit::RequestsPerMinute);
+        assert_eq!(tank.requests_this_window, 15);
+
+        // The first request ages out a minute after it was made
+        let later = start + Duration::minutes(1) + Duration::seconds(1);
+        assert!(tank.can_make_request(later));
+        tank.consume_at(100, later).unwrap();
+        assert_eq!(tank.recent_requests.len(), 15);
+    }
+
+    #[test]
+    fn test_projected_exhaustion_of_half_consumed_window() {
+        let start: DateTime<Utc> = "2026-01-14T08:00:00Z".parse().unwrap();
+        let mut tank = Tank::new(Provider::Claude, 1_000, 5);
+        tank.window_start = start;
+        tank.window_end = start + Duration::hours(5);
+        assert_eq!(tank.burn_rate_per_hour_at(start), 0.0);
+        assert_eq!(tank.projected_exhaustion_at(start), None);
+
+        tank.consume_at(500, start + Duration::minutes(30)).unwrap();
+        let now = start + Duration::hours(1);
+        assert_eq!(tank.burn_rate_per_hour_at(now), 500.0);
+        assert_eq!(
+            tank.projected_exhaustion_at(now),
+            Some(now + Duration::hours(1))
+        );
+
+        // A trickle that would take years projects nothing
+        let mut idle = Tank::new(Provider::Claude, u64::MAX / 2, 5);
+        idle.window_start = start;
+        idle.consume_at(1, start).unwrap();
+        assert_eq!(
+            idle.projected_exhaustion_at(start + Duration::hours(1)),
+            None
+        );
+    }
+
     #[test]
     fn test_progress_bar() {
         let mut tank = Tank::new(Provider::Claude, 100, 5);
         tank.remaining = 75;
         tank.recalculate_health(0.5, 0.2);
-        
+
         let bar = tank.progress_bar(10);
         assert!(bar.contains("75%"));
     }
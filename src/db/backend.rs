@@ -0,0 +1,156 @@
+//! Storage backends
+//!
+//! Code outside `db` reaches storage through the repository traits, so a
+//! backend is one type implementing all of them plus its own connection
+//! setup and SQL dialect. [`connect`] picks it from `[database] backend`.
+//! SQLite is the complete backend; Postgres (behind the `postgres` feature)
+//! is a stub that connects but doesn't store anything yet.
+
+use std::sync::Arc;
+
+use super::open_workspace;
+#[cfg(feature = "postgres")]
+use super::postgres::PostgresRepository;
+use super::repository::{
+    BeadRepository, CompletionRepository, LatencyRepository, SqliteRepository,
+};
+use crate::config::{Config, DatabaseBackend};
+use crate::core::{Result, RigsError};
+
+/// Everything the library needs from storage
+pub trait Storage: BeadRepository + CompletionRepository + LatencyRepository {
+    fn backend(&self) -> DatabaseBackend;
+}
+
+impl Storage for SqliteRepository {
+    fn backend(&self) -> DatabaseBackend {
+        DatabaseBackend::Sqlite
+    }
+}
+
+/// Open the storage backend named by `config`
+///
+/// SQLite goes through [`open_workspace`], so the workspace must have been
+/// initialized. Postgres needs `database.url` and a build with the
+/// `postgres` feature.
+pub async fn connect(config: &Config) -> Result<Arc<dyn Storage>> {
+    match config.database.backend {
+        DatabaseBackend::Sqlite => Ok(Arc::new(SqliteRepository::new(
+            open_workspace(config).await?,
+        ))),
+        #[cfg(feature = "postgres")]
+        DatabaseBackend::Postgres => {
+            let url = config.database.url.as_deref().ok_or_else(|| {
+                RigsError::InvalidConfig("database.url is required for postgres".into())
+            })?;
+            Ok(Arc::new(PostgresRepository::connect(url).await?))
+        }
+        #[cfg(not(feature = "postgres"))]
+        DatabaseBackend::Postgres => Err(RigsError::BackendUnsupported {
+            backend: "postgres",
+            reason: "rigs was built without the `postgres` feature".into(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::routing::ProviderLatency;
+    use crate::core::{
+        Bead, BeadRef, BeadStatus, ExecutionOutput, ExecutionRecord, Provider, TaskType,
+    };
+    use crate::db::init_pool;
+    use chrono::Utc;
+    use std::time::Duration;
+
+    /// What every backend must get right, whatever its SQL looks like
+    async fn repository_suite(storage: &dyn Storage) {
+        let mut first = Bead::new("Schema", "Design the schema", TaskType::Design);
+        first.convoy_id = Some("storage".into());
+        let mut second = Bead::new("Queries", "Write the queries", TaskType::Implementation);
+        second.convoy_id = Some("storage".into());
+        second.dependencies = vec![first.id.clone()];
+
+        let seq = storage.create(&first).await.unwrap();
+        assert_eq!(storage.create(&second).await.unwrap(), seq + 1);
+        assert_eq!(
+            storage.resolve(&BeadRef::Seq(seq)).await.unwrap().id,
+            first.id
+        );
+        assert_eq!(
+            storage.get(&second.id).await.unwrap().unwrap().dependencies,
+            vec![first.id.clone()]
+        );
+        assert_eq!(storage.get_pending_ordered().await.unwrap().len(), 2);
+        assert_eq!(storage.list_by_convoy("storage").await.unwrap().len(), 2);
+        assert_eq!(
+            storage.list_dependents(&first.id).await.unwrap()[0].id,
+            second.id
+        );
+
+        first.status = BeadStatus::Completed;
+        first.output = Some("tables".into());
+        storage.update(&first).await.unwrap();
+        let completed = storage.list_by_status(BeadStatus::Completed).await.unwrap();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].output.as_deref(), Some("tables"));
+
+        let outcome = Ok(ExecutionOutput {
+            text: "tables".into(),
+            prompt_tokens: 100,
+            completion_tokens: 50,
+            model: "sonnet".into(),
+            finish_reason: None,
+            duration: Duration::from_secs(2),
+            diagnostics: None,
+        });
+        let record =
+            ExecutionRecord::new(&first, Provider::Claude, &outcome, Duration::from_secs(2));
+        storage.record_completion(&record).await.unwrap();
+        let history = storage.list_completions(&first.id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].succeeded());
+
+        let latency = ProviderLatency {
+            provider: Provider::Codex,
+            avg_ms: 1200.0,
+            samples: 3,
+            updated_at: Utc::now(),
+        };
+        storage.upsert_latency(&latency).await.unwrap();
+        storage.upsert_latency(&latency).await.unwrap();
+        assert_eq!(storage.get_latencies().await.unwrap().len(), 1);
+
+        storage.delete(&second.id).await.unwrap();
+        assert!(storage.get(&second.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_backend_passes_the_shared_suite() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.database.path = dir.path().join("rigs.db").display().to_string();
+        assert!(matches!(
+            connect(&config).await,
+            Err(RigsError::WorkspaceNotInitialized)
+        ));
+
+        init_pool(&config.database_path()).await.unwrap();
+        let storage = connect(&config).await.unwrap();
+        assert_eq!(storage.backend(), DatabaseBackend::Sqlite);
+        repository_suite(storage.as_ref()).await;
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    #[tokio::test]
+    async fn test_postgres_needs_the_feature() {
+        let mut config = Config::default();
+        config.database.backend = DatabaseBackend::Postgres;
+        config.database.url = Some("postgres://localhost/rigs".into());
+        assert!(matches!(
+            connect(&config).await,
+            Err(RigsError::BackendUnsupported { .. })
+        ));
+    }
+}
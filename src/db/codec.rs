@@ -0,0 +1,248 @@
+//! Database forms of core enums
+//!
+//! Every enum stored in a TEXT column goes through [`DbText`], so the
+//! string written for a variant is defined in exactly one place. The forms
+//! match the enums' serde representation (lowercase variant names), which
+//! is what existing databases already hold; note that this differs from
+//! `Display` for multi-word variants (`inprogress` vs `in_progress`).
+//!
+//! [`Priority`] is stored as its integer rank instead, so the queue can be
+//! ordered by it in SQL.
+
+use crate::core::{
+    BeadStatus, ConvoyStatus, Priority, Provider, Result, RetryClass, RigsError, TankHealth,
+    TaskType,
+};
+
+/// An enum stored as TEXT
+pub trait DbText: Sized + Copy + 'static {
+    /// Every variant, for parsing and exhaustive tests
+    const VARIANTS: &'static [Self];
+
+    /// The string stored for this variant
+    fn as_db_str(&self) -> &'static str;
+
+    /// Parse a stored string back into a variant
+    fn from_db_str(s: &str) -> Result<Self> {
+        Self::VARIANTS
+            .iter()
+            .copied()
+            .find(|v| v.as_db_str() == s)
+            .ok_or_else(|| {
+                RigsError::Other(format!(
+                    "Unknown {} '{}' in database",
+                    std::any::type_name::<Self>()
+                        .rsplit("::")
+                        .next()
+                        .unwrap_or("value"),
+                    s
+                ))
+            })
+    }
+}
+
+impl DbText for BeadStatus {
+    const VARIANTS: &'static [Self] = &[
+        BeadStatus::Pending,
+        BeadStatus::Optimizing,
+        BeadStatus::Queued,
+        BeadStatus::Assigned,
+        BeadStatus::InProgress,
+        BeadStatus::Deferred,
+        BeadStatus::Reviewing,
+        BeadStatus::Completed,
+        BeadStatus::Failed,
+        BeadStatus::Cancelled,
+    ];
+
+    fn as_db_str(&self) -> &'static str {
+        match self {
+            BeadStatus::Pending => "pending",
+            BeadStatus::Optimizing => "optimizing",
+            BeadStatus::Queued => "queued",
+            BeadStatus::Assigned => "assigned",
+            BeadStatus::InProgress => "inprogress",
+            BeadStatus::Deferred => "deferred",
+            BeadStatus::Reviewing => "reviewing",
+            BeadStatus::Completed => "completed",
+            BeadStatus::Failed => "failed",
+            BeadStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl DbText for TaskType {
+    const VARIANTS: &'static [Self] = &[
+        TaskType::Implementation,
+        TaskType::Review,
+        TaskType::Research,
+        TaskType::Refactor,
+        TaskType::Test,
+        TaskType::Documentation,
+        TaskType::Debug,
+        TaskType::Design,
+    ];
+
+    fn as_db_str(&self) -> &'static str {
+        match self {
+            TaskType::Implementation => "implementation",
+            TaskType::Review => "review",
+            TaskType::Research => "research",
+            TaskType::Refactor => "refactor",
+            TaskType::Test => "test",
+            TaskType::Documentation => "documentation",
+            TaskType::Debug => "debug",
+            TaskType::Design => "design",
+        }
+    }
+}
+
+impl DbText for Provider {
+    const VARIANTS: &'static [Self] = &[
+        Provider::Claude,
+        Provider::Codex,
+        Provider::Gemini,
+        Provider::DeepSeek,
+        Provider::Ollama,
+    ];
+
+    fn as_db_str(&self) -> &'static str {
+        self.as_str()
+    }
+}
+
+impl DbText for ConvoyStatus {
+    const VARIANTS: &'static [Self] = &[
+        ConvoyStatus::Planning,
+        ConvoyStatus::Queued,
+        ConvoyStatus::InProgress,
+        ConvoyStatus::Paused,
+        ConvoyStatus::Completed,
+        ConvoyStatus::CompletedWithErrors,
+        ConvoyStatus::Failed,
+        ConvoyStatus::Archived,
+    ];
+
+    fn as_db_str(&self) -> &'static str {
+        match self {
+            ConvoyStatus::Planning => "planning",
+            ConvoyStatus::Queued => "queued",
+            ConvoyStatus::InProgress => "inprogress",
+            ConvoyStatus::Paused => "paused",
+            ConvoyStatus::Completed => "completed",
+            ConvoyStatus::CompletedWithErrors => "completedwitherrors",
+            ConvoyStatus::Failed => "failed",
+            ConvoyStatus::Archived => "archived",
+        }
+    }
+}
+
+impl DbText for RetryClass {
+    const VARIANTS: &'static [Self] = &[
+        RetryClass::Transient,
+        RetryClass::RateLimited,
+        RetryClass::Fatal,
+    ];
+
+    fn as_db_str(&self) -> &'static str {
+        match self {
+            RetryClass::Transient => "transient",
+            RetryClass::RateLimited => "rate_limited",
+            RetryClass::Fatal => "fatal",
+        }
+    }
+}
+
+impl DbText for TankHealth {
+    const VARIANTS: &'static [Self] = &[
+        TankHealth::Green,
+        TankHealth::Yellow,
+        TankHealth::Red,
+        TankHealth::Empty,
+    ];
+
+    fn as_db_str(&self) -> &'static str {
+        match self {
+            TankHealth::Green => "green",
+            TankHealth::Yellow => "yellow",
+            TankHealth::Red => "red",
+            TankHealth::Empty => "empty",
+        }
+    }
+}
+
+/// Quoted, comma-separated db forms of enum values, for `IN (...)` clauses
+pub fn sql_list<T: DbText>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|v| format!("'{}'", v.as_db_str()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Integer rank stored for a priority (higher runs first)
+pub fn priority_to_db(priority: Priority) -> i64 {
+    priority as i64
+}
+
+/// Read back a stored rank, clamping out-of-range values
+pub fn priority_from_db(value: i64) -> Priority {
+    match value {
+        i64::MIN..=0 => Priority::Low,
+        1 => Priority::Normal,
+        2 => Priority::High,
+        _ => Priority::Critical,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+    use serde::Serialize;
+
+    /// Every variant round-trips and matches its serde form
+    fn assert_round_trips<T>(all: &[T])
+    where
+        T: DbText + Serialize + PartialEq + std::fmt::Debug,
+    {
+        assert_eq!(
+            T::VARIANTS.len(),
+            all.len(),
+            "VARIANTS is missing a variant"
+        );
+        for variant in all {
+            let stored = variant.as_db_str();
+            assert_eq!(T::from_db_str(stored).unwrap(), *variant);
+            assert_eq!(serde_json::to_value(variant).unwrap(), stored);
+        }
+    }
+
+    #[test]
+    fn test_variants_round_trip() {
+        assert_round_trips(BeadStatus::value_variants());
+        assert_round_trips(TaskType::value_variants());
+        assert_round_trips(&Provider::all().collect::<Vec<_>>());
+        assert_round_trips(ConvoyStatus::VARIANTS);
+        assert_round_trips(RetryClass::VARIANTS);
+        assert_round_trips(TankHealth::VARIANTS);
+        assert!(BeadStatus::from_db_str("in_progress").is_err());
+    }
+
+    #[test]
+    fn test_priority_round_trip() {
+        for priority in Priority::value_variants() {
+            assert_eq!(priority_from_db(priority_to_db(*priority)), *priority);
+        }
+        assert_eq!(priority_from_db(-3), Priority::Low);
+        assert_eq!(priority_from_db(9), Priority::Critical);
+    }
+
+    #[test]
+    fn test_sql_list() {
+        assert_eq!(
+            sql_list(&[BeadStatus::Completed, BeadStatus::InProgress]),
+            "'completed', 'inprogress'"
+        );
+    }
+}
@@ -1,25 +1,129 @@
 //! Database operations and repository implementations
 
+pub mod backend;
+pub mod codec;
+#[cfg(feature = "postgres")]
+pub mod postgres;
 pub mod repository;
 
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use std::path::Path;
 
-use crate::core::Result;
+use crate::config::{Config, DatabaseBackend};
+use crate::core::{Result, RigsError};
+
+/// Tables every repository expects the migrations to have created
+const EXPECTED_TABLES: &[&str] = &[
+    "beads",
+    "tanks",
+    "convoys",
+    "completions",
+    "optimization_traces",
+    "config",
+    "provider_latency",
+    "tank_usage",
+    "foreman_state",
+];
 
 /// Initialize the database connection pool
 pub async fn init_pool(db_path: &Path) -> Result<SqlitePool> {
     let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
-    
+
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
         .connect(&db_url)
         .await?;
-    
+
     // Run migrations
-    sqlx::migrate!("./migrations")
-        .run(&pool)
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    verify_schema(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Open the configured workspace database
+///
+/// Fails with [`RigsError::WorkspaceNotInitialized`] rather than creating an
+/// empty database somewhere `rigs init` never ran. The CLI still needs the
+/// SQLite backend; other backends are only reachable through
+/// [`backend::connect`].
+pub async fn open_workspace(config: &Config) -> Result<SqlitePool> {
+    if config.database.backend != DatabaseBackend::Sqlite {
+        return Err(RigsError::BackendUnsupported {
+            backend: "postgres",
+            reason: "the rigs CLI only runs on sqlite so far".into(),
+        });
+    }
+    let db_path = config.database_path();
+    if !db_path.exists() {
+        return Err(RigsError::WorkspaceNotInitialized);
+    }
+    init_pool(&db_path).await
+}
+
+/// Initialize an in-memory database (tests, benchmarks, ephemeral runs)
+///
+/// Uses a single long-lived connection, since every SQLite `:memory:`
+/// connection would otherwise see its own empty database.
+pub async fn init_memory_pool() -> Result<SqlitePool> {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
         .await?;
-    
+
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    verify_schema(&pool).await?;
+
     Ok(pool)
 }
+
+/// Check that migrations actually created the schema
+///
+/// `migrate!` embeds whatever `./migrations` held at build time, so a build
+/// without it migrates nothing and every query later fails with a bare
+/// "no such table". Fails with [`RigsError::SchemaMissing`] instead.
+pub async fn verify_schema(pool: &SqlitePool) -> Result<()> {
+    let present: Vec<String> =
+        sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table'")
+            .fetch_all(pool)
+            .await?;
+    let missing: Vec<String> = EXPECTED_TABLES
+        .iter()
+        .filter(|table| !present.iter().any(|name| name == *table))
+        .map(|table| table.to_string())
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(RigsError::SchemaMissing(missing))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unmigrated_pool_reports_missing_schema() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        let err = verify_schema(&pool).await.unwrap_err();
+        match &err {
+            RigsError::SchemaMissing(tables) => {
+                assert_eq!(tables.len(), EXPECTED_TABLES.len());
+                assert!(tables.contains(&"beads".to_string()));
+            }
+            other => panic!("expected SchemaMissing, got {:?}", other),
+        }
+        assert!(err.to_string().contains("./migrations"));
+
+        let migrated = init_memory_pool().await.unwrap();
+        assert!(verify_schema(&migrated).await.is_ok());
+    }
+}
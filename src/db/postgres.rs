@@ -0,0 +1,123 @@
+//! PostgreSQL storage backend (stub)
+//!
+//! Connects, but every repository operation reports
+//! [`RigsError::BackendUnsupported`] until the Postgres schema and queries
+//! are written. It exists to keep the [`Storage`] seam honest.
+
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+
+use super::backend::Storage;
+use super::repository::{BeadRepository, CompletionRepository, LatencyRepository};
+use crate::config::DatabaseBackend;
+use crate::core::routing::ProviderLatency;
+use crate::core::{Bead, BeadId, BeadStatus, ExecutionRecord, Result, RigsError};
+
+/// PostgreSQL implementation of repositories
+pub struct PostgresRepository {
+    pool: PgPool,
+}
+
+impl PostgresRepository {
+    /// Connect to the server at `url`
+    // TODO: Run Postgres migrations once the schema is ported
+    pub async fn connect(url: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new().max_connections(5).connect(url).await?;
+        Ok(Self { pool })
+    }
+
+    /// Access the underlying connection pool
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+fn unsupported<T>(operation: &str) -> Result<T> {
+    Err(RigsError::BackendUnsupported {
+        backend: "postgres",
+        reason: format!("{} is not implemented yet", operation),
+    })
+}
+
+impl Storage for PostgresRepository {
+    fn backend(&self) -> DatabaseBackend {
+        DatabaseBackend::Postgres
+    }
+}
+
+#[async_trait]
+impl BeadRepository for PostgresRepository {
+    async fn create(&self, _bead: &Bead) -> Result<u64> {
+        unsupported("creating beads")
+    }
+
+    async fn get(&self, _id: &BeadId) -> Result<Option<Bead>> {
+        unsupported("reading beads")
+    }
+
+    async fn get_by_seq(&self, _seq: u64) -> Result<Option<Bead>> {
+        unsupported("reading beads")
+    }
+
+    async fn update(&self, _bead: &Bead) -> Result<()> {
+        unsupported("updating beads")
+    }
+
+    async fn delete(&self, _id: &BeadId) -> Result<()> {
+        unsupported("deleting beads")
+    }
+
+    async fn list_by_status(&self, _status: BeadStatus) -> Result<Vec<Bead>> {
+        unsupported("listing beads")
+    }
+
+    async fn list_by_convoy(&self, _convoy_id: &str) -> Result<Vec<Bead>> {
+        unsupported("listing beads")
+    }
+
+    async fn get_pending_ordered(&self) -> Result<Vec<Bead>> {
+        unsupported("listing beads")
+    }
+
+    async fn get_deferred_ready(&self) -> Result<Vec<Bead>> {
+        unsupported("listing beads")
+    }
+
+    async fn list_dead_letter(&self) -> Result<Vec<Bead>> {
+        unsupported("listing beads")
+    }
+
+    async fn list_dependents(&self, _id: &BeadId) -> Result<Vec<Bead>> {
+        unsupported("listing beads")
+    }
+
+    async fn request_cancel(&self, _id: &BeadId) -> Result<()> {
+        unsupported("cancelling beads")
+    }
+
+    async fn cancel_requested(&self, _id: &BeadId) -> Result<bool> {
+        unsupported("cancelling beads")
+    }
+}
+
+#[async_trait]
+impl LatencyRepository for PostgresRepository {
+    async fn get_latencies(&self) -> Result<Vec<ProviderLatency>> {
+        unsupported("reading provider latency")
+    }
+
+    async fn upsert_latency(&self, _latency: &ProviderLatency) -> Result<()> {
+        unsupported("recording provider latency")
+    }
+}
+
+#[async_trait]
+impl CompletionRepository for PostgresRepository {
+    async fn record_completion(&self, _record: &ExecutionRecord) -> Result<()> {
+        unsupported("recording completions")
+    }
+
+    async fn list_completions(&self, _bead_id: &BeadId) -> Result<Vec<ExecutionRecord>> {
+        unsupported("reading completions")
+    }
+}
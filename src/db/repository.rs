@@ -1,21 +1,60 @@
 //! Repository implementations for database operations
 
 use async_trait::async_trait;
-use sqlx::SqlitePool;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::collections::{BTreeMap, HashMap};
 
-use crate::core::{Bead, BeadId, BeadStatus, Convoy, Provider, Result, Tank};
+use super::codec::{priority_from_db, priority_to_db, sql_list, DbText};
+use crate::core::error::ResultExt;
+use crate::core::gate::review_convoy;
+use crate::core::routing::ProviderLatency;
+use crate::core::{
+    Bead, BeadId, BeadRef, BeadStatus, Convoy, ConvoyId, ConvoyStatus, ExecutionDiagnostics,
+    ExecutionRecord, Provider, QualityGate, Reschedule, Result, RetryClass, RigsError, Tank,
+    TankHealth, TaskType, UsageEvent,
+};
 
 /// Repository for bead operations
 #[async_trait]
 pub trait BeadRepository: Send + Sync {
-    async fn create(&self, bead: &Bead) -> Result<()>;
+    /// Store a new bead, returning the sequence number it was given
+    async fn create(&self, bead: &Bead) -> Result<u64>;
     async fn get(&self, id: &BeadId) -> Result<Option<Bead>>;
+    async fn get_by_seq(&self, seq: u64) -> Result<Option<Bead>>;
     async fn update(&self, bead: &Bead) -> Result<()>;
     async fn delete(&self, id: &BeadId) -> Result<()>;
     async fn list_by_status(&self, status: BeadStatus) -> Result<Vec<Bead>>;
     async fn list_by_convoy(&self, convoy_id: &str) -> Result<Vec<Bead>>;
     async fn get_pending_ordered(&self) -> Result<Vec<Bead>>;
     async fn get_deferred_ready(&self) -> Result<Vec<Bead>>;
+    /// Failed beads with no retry budget left, most recent failure first
+    async fn list_dead_letter(&self) -> Result<Vec<Bead>>;
+    /// Beads that list `id` among their direct dependencies, oldest first
+    async fn list_dependents(&self, id: &BeadId) -> Result<Vec<Bead>>;
+
+    /// Ask the foreman executing bead `id` to cancel it at its next checkpoint
+    ///
+    /// Kept outside the bead's own columns, so the foreman saving its copy
+    /// of the bead can't clear it.
+    async fn request_cancel(&self, id: &BeadId) -> Result<()>;
+    async fn cancel_requested(&self, id: &BeadId) -> Result<bool>;
+
+    /// Look up a bead by id or `#seq`
+    async fn resolve(&self, bead: &BeadRef) -> Result<Bead> {
+        match bead {
+            BeadRef::Id(id) => self
+                .get(id)
+                .await?
+                .ok_or_else(|| RigsError::BeadNotFound(id.clone())),
+            BeadRef::Seq(seq) => self
+                .get_by_seq(*seq)
+                .await?
+                .ok_or(RigsError::BeadSeqNotFound(*seq)),
+        }
+    }
 }
 
 /// Repository for tank operations
@@ -33,6 +72,42 @@ pub trait ConvoyRepository: Send + Sync {
     async fn get(&self, id: &str) -> Result<Option<Convoy>>;
     async fn update(&self, convoy: &Convoy) -> Result<()>;
     async fn list_active(&self) -> Result<Vec<Convoy>>;
+    /// Every convoy, archived ones included, oldest first
+    async fn list_all(&self) -> Result<Vec<Convoy>>;
+}
+
+/// Repository for observed provider latency
+#[async_trait]
+pub trait LatencyRepository: Send + Sync {
+    async fn get_latencies(&self) -> Result<Vec<ProviderLatency>>;
+    async fn upsert_latency(&self, latency: &ProviderLatency) -> Result<()>;
+}
+
+/// Repository for the execution history (one record per attempt)
+#[async_trait]
+pub trait CompletionRepository: Send + Sync {
+    async fn record_completion(&self, record: &ExecutionRecord) -> Result<()>;
+    /// A bead's attempts, oldest first
+    async fn list_completions(&self, bead_id: &BeadId) -> Result<Vec<ExecutionRecord>>;
+}
+
+/// Repository for the foreman's durable settings (the `foreman_state` row)
+#[async_trait]
+pub trait ForemanStateRepository: Send + Sync {
+    async fn is_paused(&self) -> Result<bool>;
+    async fn set_paused(&self, paused: bool) -> Result<()>;
+}
+
+/// Repository for the token consumption history
+#[async_trait]
+pub trait UsageRepository: Send + Sync {
+    async fn record_usage(&self, event: &UsageEvent) -> Result<()>;
+    /// Consumption since `since`, optionally for one provider, oldest first
+    async fn list_usage(
+        &self,
+        provider: Option<Provider>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<UsageEvent>>;
 }
 
 /// SQLite implementation of repositories
@@ -44,6 +119,1974 @@ impl SqliteRepository {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
     }
+
+    /// Access the underlying connection pool
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Delete terminal beads and execution history older than `cutoff`
+    ///
+    /// Beads belonging to a convoy that is still running are kept so the
+    /// convoy never loses members; their history is kept with them. With
+    /// `dry_run` the deletions are rolled back and only the counts returned.
+    pub async fn prune(&self, cutoff: DateTime<Utc>, dry_run: bool) -> Result<PruneReport> {
+        let terminal_beads = sql_list(&[
+            BeadStatus::Completed,
+            BeadStatus::Failed,
+            BeadStatus::Cancelled,
+        ]);
+        let terminal_convoys = sql_list(&[
+            ConvoyStatus::Completed,
+            ConvoyStatus::CompletedWithErrors,
+            ConvoyStatus::Failed,
+            ConvoyStatus::Archived,
+        ]);
+        let live_convoys = format!(
+            "SELECT id FROM convoys WHERE status NOT IN ({})",
+            terminal_convoys
+        );
+        let old_terminal = format!(
+            "status IN ({}) AND COALESCE(completed_at, created_at) < ?1",
+            terminal_beads
+        );
+        let prunable = format!(
+            "SELECT id FROM beads WHERE {} \
+             AND (convoy_id IS NULL OR convoy_id NOT IN ({}))",
+            old_terminal, live_convoys
+        );
+        let cutoff = format_timestamp(&cutoff);
+
+        let mut tx = self.pool.begin().await.context("starting prune")?;
+
+        let protected: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM beads WHERE {} AND convoy_id IN ({})",
+            old_terminal, live_convoys
+        ))
+        .bind(&cutoff)
+        .fetch_one(&mut *tx)
+        .await
+        .context("counting beads kept for live convoys")?;
+
+        let completions = sqlx::query(&format!(
+            "DELETE FROM completions WHERE bead_id IN ({}) \
+             OR (completed_at < ?1 AND bead_id NOT IN \
+                 (SELECT id FROM beads WHERE convoy_id IN ({})))",
+            prunable, live_convoys
+        ))
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await
+        .context("pruning execution history")?
+        .rows_affected();
+
+        let beads = sqlx::query(&format!("DELETE FROM beads WHERE id IN ({})", prunable))
+            .bind(&cutoff)
+            .execute(&mut *tx)
+            .await
+            .context("pruning beads")?
+            .rows_affected();
+
+        if dry_run {
+            tx.rollback().await.context("rolling back dry-run prune")?;
+        } else {
+            tx.commit().await.context("committing prune")?;
+        }
+
+        Ok(PruneReport {
+            beads,
+            completions,
+            protected: protected as u64,
+        })
+    }
+
+    /// Recompute the stored status of every active convoy from its beads
+    ///
+    /// A crash between a bead finishing and its convoy being updated leaves
+    /// the convoy's stored status behind. With a `gate`, convoys that turn
+    /// out complete also get their convoy-level review (see
+    /// [`review_convoy`]). Returns the corrections made.
+    pub async fn reconcile_convoys(
+        &self,
+        gate: Option<&dyn QualityGate>,
+    ) -> Result<Vec<ConvoyCorrection>> {
+        let terminal = sql_list(&[
+            ConvoyStatus::Completed,
+            ConvoyStatus::CompletedWithErrors,
+            ConvoyStatus::Failed,
+            ConvoyStatus::Archived,
+        ]);
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM convoys WHERE status NOT IN ({}) ORDER BY created_at ASC",
+            CONVOY_COLUMNS, terminal
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .context("listing active convoys")?;
+
+        let mut corrections = Vec::new();
+        for row in rows {
+            let mut convoy = convoy_from_row(&row)?;
+            let stored = convoy.status;
+            let beads = self.list_by_convoy(&convoy.id).await?;
+            convoy.beads = beads.iter().map(|b| b.id.clone()).collect();
+            convoy.refresh_status(&beads.iter().map(|b| (b.id.clone(), b.status)).collect());
+            if let Some(gate) = gate {
+                review_convoy(&mut convoy, &beads, gate).await?;
+            }
+            if convoy.status == stored {
+                continue;
+            }
+
+            sqlx::query(
+                "UPDATE convoys SET status = ?, completed_at = ?, metadata = ? WHERE id = ?",
+            )
+            .bind(convoy.status.as_db_str())
+            .bind(convoy.completed_at.as_ref().map(format_timestamp))
+            .bind(serde_json::to_string(&convoy.metadata)?)
+            .bind(&convoy.id)
+            .execute(&self.pool)
+            .await
+            .context(format!("updating status of convoy {}", convoy.id))?;
+            corrections.push(ConvoyCorrection {
+                convoy_id: convoy.id,
+                from: stored,
+                to: convoy.status,
+            });
+        }
+        Ok(corrections)
+    }
+
+    /// Reschedule every unfinished bead of a convoy in one transaction
+    ///
+    /// See [`Bead::reschedule`]; returns the beads that changed.
+    pub async fn reschedule_convoy(
+        &self,
+        convoy_id: &str,
+        change: Reschedule,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Bead>> {
+        let finished = sql_list(&[
+            BeadStatus::Completed,
+            BeadStatus::Failed,
+            BeadStatus::Cancelled,
+        ]);
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context(format!("starting reschedule of convoy {}", convoy_id))?;
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM beads WHERE convoy_id = ? AND status NOT IN ({}) \
+             ORDER BY created_at ASC",
+            BEAD_COLUMNS, finished
+        ))
+        .bind(convoy_id)
+        .fetch_all(&mut *tx)
+        .await
+        .context(format!("listing beads of convoy {}", convoy_id))?;
+
+        let mut changed = Vec::new();
+        for row in &rows {
+            let mut bead = bead_from_row(row)?;
+            if !bead.reschedule(change, now) {
+                continue;
+            }
+            sqlx::query("UPDATE beads SET deadline = ?, deferred_until = ? WHERE id = ?")
+                .bind(bead.deadline.as_ref().map(format_timestamp))
+                .bind(bead.deferred_until.as_ref().map(format_timestamp))
+                .bind(bead.id.as_str())
+                .execute(&mut *tx)
+                .await
+                .context(format!("rescheduling bead {}", bead.id))?;
+            changed.push(bead);
+        }
+        tx.commit()
+            .await
+            .context(format!("committing reschedule of convoy {}", convoy_id))?;
+        Ok(changed)
+    }
+
+    /// Store a new convoy and its beads in one transaction
+    ///
+    /// Either all of them are stored or, if any insert fails, none are.
+    pub async fn create_convoy_with_beads(&self, convoy: &Convoy, beads: &[Bead]) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context(format!("starting creation of convoy {}", convoy.id))?;
+        bind_convoy(sqlx::query(&insert_convoy_sql()), convoy)?
+            .execute(&mut *tx)
+            .await
+            .context(format!("creating convoy {}", convoy.id))?;
+        let sql = insert_bead_sql();
+        for bead in beads {
+            bind_bead(sqlx::query(&sql), bead)?
+                .fetch_one(&mut *tx)
+                .await
+                .context(format!("creating bead {}", bead.id))?;
+        }
+        tx.commit()
+            .await
+            .context(format!("committing creation of convoy {}", convoy.id))?;
+        Ok(())
+    }
+
+    /// Save the merge of `source` into `target` in one transaction
+    ///
+    /// Writes the `moved` beads and both convoys; if any write fails, none
+    /// of them are kept.
+    pub async fn save_convoy_merge(
+        &self,
+        source: &Convoy,
+        target: &Convoy,
+        moved: &[Bead],
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context(format!("starting merge of convoy {}", source.id))?;
+        for bead in moved {
+            let result = bind_bead(sqlx::query(UPDATE_BEAD_SQL), bead)?
+                .bind(bead.id.as_str())
+                .execute(&mut *tx)
+                .await
+                .context(format!("updating bead {}", bead.id))?;
+            if result.rows_affected() == 0 {
+                return Err(RigsError::BeadNotFound(bead.id.clone()));
+            }
+        }
+        for convoy in [source, target] {
+            let result = bind_convoy(sqlx::query(UPDATE_CONVOY_SQL), convoy)?
+                .bind(&convoy.id)
+                .execute(&mut *tx)
+                .await
+                .context(format!("updating convoy {}", convoy.id))?;
+            if result.rows_affected() == 0 {
+                return Err(RigsError::ConvoyNotFound(convoy.id.clone()));
+            }
+        }
+        tx.commit()
+            .await
+            .context(format!("committing merge of convoy {}", source.id))?;
+        Ok(())
+    }
+
+    /// Each stored convoy's direct prerequisite convoys
+    pub async fn convoy_dependencies(&self) -> Result<HashMap<ConvoyId, Vec<ConvoyId>>> {
+        let rows = sqlx::query("SELECT id, depends_on FROM convoys")
+            .fetch_all(&self.pool)
+            .await
+            .context("reading convoy dependencies")?;
+        rows.iter()
+            .map(|row| {
+                let depends_on: String = row.try_get("depends_on")?;
+                Ok((row.try_get("id")?, serde_json::from_str(&depends_on)?))
+            })
+            .collect()
+    }
+
+    /// One page of the beads matching `filter`, in its sort order
+    pub async fn list(&self, filter: &BeadFilter) -> Result<Vec<Bead>> {
+        let sql = format!(
+            "SELECT {} FROM beads WHERE (?1 IS NULL OR status = ?1) \
+             AND (?2 IS NULL OR convoy_id = ?2) ORDER BY {} LIMIT ?3 OFFSET ?4",
+            BEAD_COLUMNS,
+            filter.sort.order_by()
+        );
+        let rows = sqlx::query(&sql)
+            .bind(filter.status.map(|s| s.as_db_str()))
+            .bind(filter.convoy_id.as_deref())
+            .bind(filter.limit as i64)
+            .bind(filter.offset as i64)
+            .fetch_all(&self.pool)
+            .await
+            .context("listing beads")?;
+        rows.iter().map(bead_from_row).collect()
+    }
+
+    /// Ids of every convoy, oldest first
+    pub async fn convoy_ids(&self) -> Result<Vec<String>> {
+        let ids = sqlx::query_scalar("SELECT id FROM convoys ORDER BY created_at ASC")
+            .fetch_all(&self.pool)
+            .await
+            .context("listing convoy ids")?;
+        Ok(ids)
+    }
+
+    /// Aggregate counts and token metrics over the whole workspace
+    pub async fn stats(&self) -> Result<WorkspaceStats> {
+        let beads_by_status = self
+            .count_by("SELECT status, COUNT(*) FROM beads GROUP BY status")
+            .await?;
+        let beads_by_type = self
+            .count_by("SELECT task_type, COUNT(*) FROM beads GROUP BY task_type")
+            .await?;
+        let convoys_by_status = self
+            .count_by("SELECT status, COUNT(*) FROM convoys GROUP BY status")
+            .await?;
+
+        let completed = beads_by_status
+            .get(BeadStatus::Completed.as_db_str())
+            .copied()
+            .unwrap_or(0);
+        let finished = [
+            BeadStatus::Completed,
+            BeadStatus::Failed,
+            BeadStatus::Cancelled,
+        ]
+        .iter()
+        .map(|s| beads_by_status.get(s.as_db_str()).copied().unwrap_or(0))
+        .sum::<u64>();
+        let completion_rate = (finished > 0).then(|| completed as f64 / finished as f64);
+
+        let avg_tokens_by_type = sqlx::query_as::<_, (String, f64)>(
+            "SELECT task_type, AVG(actual_tokens) FROM beads \
+             WHERE actual_tokens IS NOT NULL GROUP BY task_type",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("averaging tokens per task type")?
+        .into_iter()
+        .collect();
+
+        // Per bead, the smaller of estimate and actual over the larger:
+        // 1.0 is a perfect estimate, over- and under-estimates count alike
+        let estimation_accuracy: Option<f64> = sqlx::query_scalar(
+            "SELECT AVG(MIN(estimated_tokens, actual_tokens) * 1.0 \
+                        / MAX(estimated_tokens, actual_tokens)) \
+             FROM beads WHERE actual_tokens > 0 AND estimated_tokens > 0",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("measuring estimation accuracy")?;
+
+        let provider_tokens: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT assigned_provider, SUM(actual_tokens) FROM beads \
+             WHERE assigned_provider IS NOT NULL AND actual_tokens IS NOT NULL \
+             GROUP BY assigned_provider",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("summing tokens per provider")?;
+        let total_tokens: i64 = provider_tokens.iter().map(|(_, t)| t).sum();
+        let provider_share = provider_tokens
+            .into_iter()
+            .filter(|_| total_tokens > 0)
+            .map(|(provider, tokens)| (provider, tokens as f64 / total_tokens as f64))
+            .collect();
+
+        Ok(WorkspaceStats {
+            beads_by_status,
+            beads_by_type,
+            convoys_by_status,
+            completion_rate,
+            avg_tokens_by_type,
+            estimation_accuracy,
+            provider_share,
+        })
+    }
+
+    async fn count_by(&self, sql: &str) -> Result<BTreeMap<String, u64>> {
+        Ok(sqlx::query_as::<_, (String, i64)>(sql)
+            .fetch_all(&self.pool)
+            .await
+            .context("computing workspace stats")?
+            .into_iter()
+            .map(|(key, count)| (key, count as u64))
+            .collect())
+    }
+}
+
+/// Which beads [`SqliteRepository::list`] returns, and in what order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeadFilter {
+    pub status: Option<BeadStatus>,
+    pub convoy_id: Option<String>,
+    pub limit: u32,
+    /// Matching beads to skip before the page starts
+    pub offset: u32,
+    pub sort: BeadSort,
+}
+
+impl Default for BeadFilter {
+    fn default() -> Self {
+        Self {
+            status: None,
+            convoy_id: None,
+            limit: 20,
+            offset: 0,
+            sort: BeadSort::default(),
+        }
+    }
+}
+
+/// Order of a bead listing; ties go to the newest bead
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum BeadSort {
+    /// Most urgent first
+    Priority,
+    /// Newest first
+    #[default]
+    Created,
+    /// Lifecycle order, pending first
+    Status,
+}
+
+impl BeadSort {
+    fn order_by(self) -> String {
+        match self {
+            BeadSort::Priority => "priority DESC, created_at DESC".to_string(),
+            BeadSort::Created => "created_at DESC".to_string(),
+            BeadSort::Status => {
+                let ranks: String = BeadStatus::VARIANTS
+                    .iter()
+                    .enumerate()
+                    .map(|(rank, status)| format!(" WHEN '{}' THEN {}", status.as_db_str(), rank))
+                    .collect();
+                format!("CASE status{} END, created_at DESC", ranks)
+            }
+        }
+    }
+}
+
+/// Workspace-wide aggregates reported by `rigs stats`
+///
+/// Keys are the stored forms of statuses, task types and providers (e.g.
+/// "inprogress", "implementation", "claude").
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WorkspaceStats {
+    pub beads_by_status: BTreeMap<String, u64>,
+    pub beads_by_type: BTreeMap<String, u64>,
+    pub convoys_by_status: BTreeMap<String, u64>,
+    /// Completed beads as a fraction of finished (completed, failed or
+    /// cancelled) ones; `None` before anything finished
+    pub completion_rate: Option<f64>,
+    /// Mean actual tokens per executed bead
+    pub avg_tokens_by_type: BTreeMap<String, f64>,
+    /// Mean of min(estimate, actual) / max(estimate, actual); 1.0 is perfect
+    pub estimation_accuracy: Option<f64>,
+    /// Each provider's fraction of all tokens used
+    pub provider_share: BTreeMap<String, f64>,
+}
+
+/// A convoy status fixed by [`SqliteRepository::reconcile_convoys`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvoyCorrection {
+    pub convoy_id: String,
+    pub from: ConvoyStatus,
+    pub to: ConvoyStatus,
+}
+
+/// What [`SqliteRepository::prune`] removed (or would remove)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Terminal beads deleted
+    pub beads: u64,
+    /// Execution history rows deleted
+    pub completions: u64,
+    /// Old terminal beads kept because their convoy is still running
+    pub protected: u64,
+}
+
+const BEAD_COLUMNS: &str = "id, title, description, task_type, priority, status, \
+    estimated_tokens, actual_tokens, preferred_provider, assigned_provider, \
+    acceptance_criteria, dependencies, convoy_id, created_at, started_at, completed_at, \
+    deferred_until, optimized_prompt, output, error, provenance, context, retry_count, \
+    max_retries, provider_failures, excluded_providers, deadline, error_class, seq";
+
+#[async_trait]
+impl BeadRepository for SqliteRepository {
+    async fn create(&self, bead: &Bead) -> Result<u64> {
+        let sql = insert_bead_sql();
+        let seq: i64 = bind_bead(sqlx::query(&sql), bead)?
+            .fetch_one(&self.pool)
+            .await
+            .context(format!("creating bead {}", bead.id))?
+            .try_get("seq")?;
+        Ok(seq as u64)
+    }
+
+    async fn get_by_seq(&self, seq: u64) -> Result<Option<Bead>> {
+        let sql = format!("SELECT {} FROM beads WHERE seq = ?", BEAD_COLUMNS);
+        let row = sqlx::query(&sql)
+            .bind(seq as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .context(format!("loading bead #{}", seq))?;
+        row.as_ref().map(bead_from_row).transpose()
+    }
+
+    async fn get(&self, id: &BeadId) -> Result<Option<Bead>> {
+        let sql = format!("SELECT {} FROM beads WHERE id = ?", BEAD_COLUMNS);
+        let row = sqlx::query(&sql)
+            .bind(id.as_str())
+            .fetch_optional(&self.pool)
+            .await
+            .context(format!("loading bead {}", id))?;
+        row.as_ref().map(bead_from_row).transpose()
+    }
+
+    async fn update(&self, bead: &Bead) -> Result<()> {
+        let result = bind_bead(sqlx::query(UPDATE_BEAD_SQL), bead)?
+            .bind(bead.id.as_str())
+            .execute(&self.pool)
+            .await
+            .context(format!("updating bead {}", bead.id))?;
+        if result.rows_affected() == 0 {
+            return Err(RigsError::BeadNotFound(bead.id.clone()));
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, id: &BeadId) -> Result<()> {
+        sqlx::query("DELETE FROM beads WHERE id = ?")
+            .bind(id.as_str())
+            .execute(&self.pool)
+            .await
+            .context(format!("deleting bead {}", id))?;
+        Ok(())
+    }
+
+    async fn list_by_status(&self, status: BeadStatus) -> Result<Vec<Bead>> {
+        let sql = format!(
+            "SELECT {} FROM beads WHERE status = ? ORDER BY created_at ASC",
+            BEAD_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(status.as_db_str())
+            .fetch_all(&self.pool)
+            .await
+            .context(format!("listing {} beads", status.as_db_str()))?;
+        rows.iter().map(bead_from_row).collect()
+    }
+
+    async fn list_by_convoy(&self, convoy_id: &str) -> Result<Vec<Bead>> {
+        let sql = format!(
+            "SELECT {} FROM beads WHERE convoy_id = ? ORDER BY created_at ASC",
+            BEAD_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(convoy_id)
+            .fetch_all(&self.pool)
+            .await
+            .context(format!("listing beads of convoy {}", convoy_id))?;
+        rows.iter().map(bead_from_row).collect()
+    }
+
+    /// Beads waiting to run (pending or requeued), highest priority first
+    async fn get_pending_ordered(&self) -> Result<Vec<Bead>> {
+        let sql = format!(
+            "SELECT {} FROM beads WHERE status IN (?, ?) \
+             ORDER BY priority DESC, created_at ASC",
+            BEAD_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(BeadStatus::Pending.as_db_str())
+            .bind(BeadStatus::Queued.as_db_str())
+            .fetch_all(&self.pool)
+            .await
+            .context("listing pending beads")?;
+        rows.iter().map(bead_from_row).collect()
+    }
+
+    async fn get_deferred_ready(&self) -> Result<Vec<Bead>> {
+        let sql = format!(
+            "SELECT {} FROM beads WHERE status = ? AND deferred_until <= ? \
+             ORDER BY priority DESC, created_at ASC",
+            BEAD_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(BeadStatus::Deferred.as_db_str())
+            .bind(format_timestamp(&Utc::now()))
+            .fetch_all(&self.pool)
+            .await
+            .context("listing deferred beads that are ready")?;
+        rows.iter().map(bead_from_row).collect()
+    }
+
+    async fn list_dead_letter(&self) -> Result<Vec<Bead>> {
+        let sql = format!(
+            "SELECT {} FROM beads WHERE status = ? AND retry_count >= max_retries \
+             ORDER BY completed_at DESC",
+            BEAD_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(BeadStatus::Failed.as_db_str())
+            .fetch_all(&self.pool)
+            .await
+            .context("listing dead-lettered beads")?;
+        rows.iter().map(bead_from_row).collect()
+    }
+
+    async fn list_dependents(&self, id: &BeadId) -> Result<Vec<Bead>> {
+        let sql = format!(
+            "SELECT {} FROM beads WHERE EXISTS \
+             (SELECT 1 FROM json_each(beads.dependencies) WHERE value = ?) \
+             ORDER BY created_at ASC",
+            BEAD_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(id.as_str())
+            .fetch_all(&self.pool)
+            .await
+            .context(format!("listing dependents of bead {}", id))?;
+        rows.iter().map(bead_from_row).collect()
+    }
+
+    async fn request_cancel(&self, id: &BeadId) -> Result<()> {
+        let result = sqlx::query("UPDATE beads SET cancel_requested = 1 WHERE id = ?")
+            .bind(id.as_str())
+            .execute(&self.pool)
+            .await
+            .context(format!("requesting cancellation of bead {}", id))?;
+        if result.rows_affected() == 0 {
+            return Err(RigsError::BeadNotFound(id.clone()));
+        }
+        Ok(())
+    }
+
+    async fn cancel_requested(&self, id: &BeadId) -> Result<bool> {
+        let requested: Option<bool> =
+            sqlx::query_scalar("SELECT cancel_requested FROM beads WHERE id = ?")
+                .bind(id.as_str())
+                .fetch_optional(&self.pool)
+                .await
+                .context(format!("checking cancellation of bead {}", id))?;
+        Ok(requested.unwrap_or(false))
+    }
 }
 
-// TODO: Implement all repository traits for SqliteRepository
+#[async_trait]
+impl LatencyRepository for SqliteRepository {
+    async fn get_latencies(&self) -> Result<Vec<ProviderLatency>> {
+        let rows =
+            sqlx::query("SELECT provider, avg_ms, samples, updated_at FROM provider_latency")
+                .fetch_all(&self.pool)
+                .await
+                .context("loading provider latencies")?;
+        rows.iter()
+            .map(|row| {
+                Ok(ProviderLatency {
+                    provider: Provider::from_db_str(&row.try_get::<String, _>("provider")?)?,
+                    avg_ms: row.try_get("avg_ms")?,
+                    samples: row.try_get::<i64, _>("samples")? as u64,
+                    updated_at: parse_timestamp(&row.try_get::<String, _>("updated_at")?)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn upsert_latency(&self, latency: &ProviderLatency) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO provider_latency (provider, avg_ms, samples, updated_at) \
+             VALUES (?, ?, ?, ?) \
+             ON CONFLICT(provider) DO UPDATE SET \
+             avg_ms = excluded.avg_ms, samples = excluded.samples, updated_at = excluded.updated_at",
+        )
+        .bind(latency.provider.as_db_str())
+        .bind(latency.avg_ms)
+        .bind(latency.samples as i64)
+        .bind(format_timestamp(&latency.updated_at))
+        .execute(&self.pool)
+        .await
+        .context(format!("saving latency of {}", latency.provider))?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CompletionRepository for SqliteRepository {
+    async fn record_completion(&self, record: &ExecutionRecord) -> Result<()> {
+        let diagnostics = record.diagnostics.as_ref();
+        sqlx::query(
+            "INSERT INTO completions (id, bead_id, provider, estimated_tokens, actual_tokens, \
+             duration_ms, success, error_message, completed_at, command, exit_code, stdout, stderr) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(record.bead_id.as_str())
+        .bind(record.provider.as_db_str())
+        .bind(record.estimated_tokens as i64)
+        .bind(record.actual_tokens as i64)
+        .bind(record.duration.as_millis() as i64)
+        .bind(record.succeeded())
+        .bind(record.error.as_deref())
+        .bind(format_timestamp(&record.completed_at))
+        .bind(diagnostics.map(|d| d.command.as_str()))
+        .bind(diagnostics.and_then(|d| d.exit_code))
+        .bind(diagnostics.map(|d| d.stdout.as_str()))
+        .bind(diagnostics.map(|d| d.stderr.as_str()))
+        .execute(&self.pool)
+        .await
+        .context(format!("recording an attempt of bead {}", record.bead_id))?;
+        Ok(())
+    }
+
+    async fn list_completions(&self, bead_id: &BeadId) -> Result<Vec<ExecutionRecord>> {
+        let rows = sqlx::query(
+            "SELECT provider, estimated_tokens, actual_tokens, duration_ms, error_message, \
+             completed_at, command, exit_code, stdout, stderr \
+             FROM completions WHERE bead_id = ? ORDER BY completed_at, rowid",
+        )
+        .bind(bead_id.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .context(format!("loading attempts of bead {}", bead_id))?;
+        rows.iter()
+            .map(|row| {
+                let diagnostics = row
+                    .try_get::<Option<String>, _>("command")?
+                    .map(|command| -> Result<_> {
+                        Ok(ExecutionDiagnostics {
+                            command,
+                            exit_code: row.try_get("exit_code")?,
+                            stdout: row
+                                .try_get::<Option<String>, _>("stdout")?
+                                .unwrap_or_default(),
+                            stderr: row
+                                .try_get::<Option<String>, _>("stderr")?
+                                .unwrap_or_default(),
+                        })
+                    })
+                    .transpose()?;
+                Ok(ExecutionRecord {
+                    bead_id: bead_id.clone(),
+                    provider: Provider::from_db_str(&row.try_get::<String, _>("provider")?)?,
+                    estimated_tokens: row.try_get::<i64, _>("estimated_tokens")? as u64,
+                    actual_tokens: row.try_get::<i64, _>("actual_tokens")? as u64,
+                    duration: std::time::Duration::from_millis(
+                        row.try_get::<i64, _>("duration_ms")? as u64,
+                    ),
+                    error: row.try_get("error_message")?,
+                    diagnostics,
+                    completed_at: parse_timestamp(&row.try_get::<String, _>("completed_at")?)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ForemanStateRepository for SqliteRepository {
+    async fn is_paused(&self) -> Result<bool> {
+        let paused: Option<bool> =
+            sqlx::query_scalar("SELECT paused FROM foreman_state WHERE id = 1")
+                .fetch_optional(&self.pool)
+                .await
+                .context("loading foreman state")?;
+        Ok(paused.unwrap_or(false))
+    }
+
+    async fn set_paused(&self, paused: bool) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO foreman_state (id, paused, updated_at) VALUES (1, ?, ?) \
+             ON CONFLICT(id) DO UPDATE SET paused = excluded.paused, updated_at = excluded.updated_at",
+        )
+        .bind(paused)
+        .bind(format_timestamp(&Utc::now()))
+        .execute(&self.pool)
+        .await
+        .context(if paused { "pausing the foreman" } else { "resuming the foreman" })?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UsageRepository for SqliteRepository {
+    async fn record_usage(&self, event: &UsageEvent) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO tank_usage (provider, tokens, remaining, recorded_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(event.provider.as_db_str())
+        .bind(event.tokens as i64)
+        .bind(event.remaining as i64)
+        .bind(format_timestamp(&event.at))
+        .execute(&self.pool)
+        .await
+        .context(format!("recording {} usage", event.provider))?;
+        Ok(())
+    }
+
+    async fn list_usage(
+        &self,
+        provider: Option<Provider>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<UsageEvent>> {
+        let rows = sqlx::query(
+            "SELECT provider, tokens, remaining, recorded_at FROM tank_usage \
+             WHERE recorded_at >= ?1 AND (?2 IS NULL OR provider = ?2) \
+             ORDER BY recorded_at, id",
+        )
+        .bind(format_timestamp(&since))
+        .bind(provider.map(|p| p.as_db_str()))
+        .fetch_all(&self.pool)
+        .await
+        .context("loading tank usage")?;
+        rows.iter()
+            .map(|row| {
+                Ok(UsageEvent {
+                    provider: Provider::from_db_str(&row.try_get::<String, _>("provider")?)?,
+                    tokens: row.try_get::<i64, _>("tokens")? as u64,
+                    at: parse_timestamp(&row.try_get::<String, _>("recorded_at")?)?,
+                    remaining: row.try_get::<i64, _>("remaining")? as u64,
+                })
+            })
+            .collect()
+    }
+}
+
+const CONVOY_COLUMNS: &str = "id, name, goal, beads, status, created_at, completed_at, \
+    metadata, depends_on, acceptance_criteria";
+
+#[async_trait]
+impl ConvoyRepository for SqliteRepository {
+    async fn create(&self, convoy: &Convoy) -> Result<()> {
+        bind_convoy(sqlx::query(&insert_convoy_sql()), convoy)?
+            .execute(&self.pool)
+            .await
+            .context(format!("creating convoy {}", convoy.id))?;
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Convoy>> {
+        let sql = format!("SELECT {} FROM convoys WHERE id = ?", CONVOY_COLUMNS);
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .context(format!("loading convoy {}", id))?;
+        row.as_ref().map(convoy_from_row).transpose()
+    }
+
+    async fn update(&self, convoy: &Convoy) -> Result<()> {
+        let result = bind_convoy(sqlx::query(UPDATE_CONVOY_SQL), convoy)?
+            .bind(&convoy.id)
+            .execute(&self.pool)
+            .await
+            .context(format!("updating convoy {}", convoy.id))?;
+        if result.rows_affected() == 0 {
+            return Err(RigsError::ConvoyNotFound(convoy.id.clone()));
+        }
+        Ok(())
+    }
+
+    async fn list_active(&self) -> Result<Vec<Convoy>> {
+        let terminal = sql_list(&[
+            ConvoyStatus::Completed,
+            ConvoyStatus::CompletedWithErrors,
+            ConvoyStatus::Failed,
+            ConvoyStatus::Archived,
+        ]);
+        let sql = format!(
+            "SELECT {} FROM convoys WHERE status NOT IN ({}) ORDER BY created_at ASC",
+            CONVOY_COLUMNS, terminal
+        );
+        let rows = sqlx::query(&sql)
+            .fetch_all(&self.pool)
+            .await
+            .context("listing active convoys")?;
+        rows.iter().map(convoy_from_row).collect()
+    }
+
+    async fn list_all(&self) -> Result<Vec<Convoy>> {
+        let sql = format!(
+            "SELECT {} FROM convoys ORDER BY created_at ASC",
+            CONVOY_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .fetch_all(&self.pool)
+            .await
+            .context("listing convoys")?;
+        rows.iter().map(convoy_from_row).collect()
+    }
+}
+
+/// Tank fields with a column of their own, named as both column and serde
+/// field; the rest of the tank is kept as JSON in `state`
+const TANK_COLUMNS: &[&str] = &[
+    "provider",
+    "capacity",
+    "remaining",
+    "window_start",
+    "window_end",
+    "health",
+    "last_request",
+    "requests_this_window",
+    "tokens_this_window",
+    "updated_at",
+    "schema_version",
+];
+
+#[async_trait]
+impl TankRepository for SqliteRepository {
+    async fn get(&self, provider: Provider) -> Result<Option<Tank>> {
+        let sql = format!(
+            "SELECT {}, state FROM tanks WHERE provider = ?",
+            TANK_COLUMNS.join(", ")
+        );
+        let row = sqlx::query(&sql)
+            .bind(provider.as_db_str())
+            .fetch_optional(&self.pool)
+            .await
+            .context(format!("loading tank {}", provider))?;
+        row.as_ref().map(tank_from_row).transpose()
+    }
+
+    async fn get_all(&self) -> Result<Vec<Tank>> {
+        let sql = format!(
+            "SELECT {}, state FROM tanks ORDER BY provider",
+            TANK_COLUMNS.join(", ")
+        );
+        let rows = sqlx::query(&sql)
+            .fetch_all(&self.pool)
+            .await
+            .context("listing tanks")?;
+        rows.iter().map(tank_from_row).collect()
+    }
+
+    async fn upsert(&self, tank: &Tank) -> Result<()> {
+        let updates: Vec<String> = TANK_COLUMNS[1..]
+            .iter()
+            .chain(&["state"])
+            .map(|column| format!("{0} = excluded.{0}", column))
+            .collect();
+        let sql = format!(
+            "INSERT INTO tanks ({}, state) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT(provider) DO UPDATE SET {}",
+            TANK_COLUMNS.join(", "),
+            updates.join(", ")
+        );
+        let mut state = serde_json::to_value(tank)?;
+        if let Some(fields) = state.as_object_mut() {
+            fields.retain(|key, _| !TANK_COLUMNS.contains(&key.as_str()));
+        }
+        sqlx::query(&sql)
+            .bind(tank.provider.as_db_str())
+            .bind(tank.capacity as i64)
+            .bind(tank.remaining as i64)
+            .bind(format_timestamp(&tank.window_start))
+            .bind(format_timestamp(&tank.window_end))
+            .bind(tank.health.as_db_str())
+            .bind(tank.last_request.as_ref().map(format_timestamp))
+            .bind(tank.requests_this_window as i64)
+            .bind(tank.tokens_this_window as i64)
+            .bind(format_timestamp(&tank.updated_at))
+            .bind(tank.schema_version as i64)
+            .bind(state.to_string())
+            .execute(&self.pool)
+            .await
+            .context(format!("saving tank {}", tank.provider))?;
+        Ok(())
+    }
+}
+
+type SqliteQuery<'q> = sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>;
+
+/// Bind every bead column, in `BEAD_COLUMNS` order
+fn bind_bead<'q>(query: SqliteQuery<'q>, bead: &Bead) -> Result<SqliteQuery<'q>> {
+    Ok(query
+        .bind(bead.id.as_str().to_string())
+        .bind(bead.title.clone())
+        .bind(bead.description.clone())
+        .bind(bead.task_type.as_db_str())
+        .bind(priority_to_db(bead.priority))
+        .bind(bead.status.as_db_str())
+        .bind(bead.estimated_tokens as i64)
+        .bind(bead.actual_tokens.map(|t| t as i64))
+        .bind(bead.preferred_provider.map(|p| p.as_db_str()))
+        .bind(bead.assigned_provider.map(|p| p.as_db_str()))
+        .bind(serde_json::to_string(&bead.acceptance_criteria)?)
+        .bind(serde_json::to_string(&bead.dependencies)?)
+        .bind(bead.convoy_id.clone())
+        .bind(format_timestamp(&bead.created_at))
+        .bind(bead.started_at.as_ref().map(format_timestamp))
+        .bind(bead.completed_at.as_ref().map(format_timestamp))
+        .bind(bead.deferred_until.as_ref().map(format_timestamp))
+        .bind(bead.optimized_prompt.clone())
+        .bind(bead.output.clone())
+        .bind(bead.error.clone())
+        .bind(
+            bead.provenance
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        )
+        .bind(serde_json::to_string(&bead.context)?)
+        .bind(bead.retry_count as i64)
+        .bind(bead.max_retries as i64)
+        .bind(bead.provider_failures as i64)
+        .bind(serde_json::to_string(&bead.excluded_providers)?)
+        .bind(bead.deadline.as_ref().map(format_timestamp))
+        .bind(bead.error_class.map(|c| c.as_db_str())))
+}
+
+/// INSERT for one bead, returning the `seq` it was numbered with
+fn insert_bead_sql() -> String {
+    // Numbering in the INSERT itself keeps it atomic: SQLite runs one
+    // write at a time, so concurrent creates can't share or skip a number.
+    // The number comes from the `bead_seq` counter (advanced by a trigger),
+    // not MAX(seq), so a deleted bead's number is never handed out again
+    format!(
+        "INSERT INTO beads ({}) VALUES \
+         (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, \
+         (SELECT last + 1 FROM bead_seq WHERE id = 1)) RETURNING seq",
+        BEAD_COLUMNS
+    )
+}
+
+/// UPDATE of every bead column, followed by the id of the bead to update
+const UPDATE_BEAD_SQL: &str = "UPDATE beads SET id = ?, title = ?, description = ?, \
+    task_type = ?, priority = ?, status = ?, estimated_tokens = ?, actual_tokens = ?, \
+    preferred_provider = ?, assigned_provider = ?, acceptance_criteria = ?, dependencies = ?, \
+    convoy_id = ?, created_at = ?, started_at = ?, completed_at = ?, deferred_until = ?, \
+    optimized_prompt = ?, output = ?, error = ?, provenance = ?, context = ?, retry_count = ?, \
+    max_retries = ?, provider_failures = ?, excluded_providers = ?, deadline = ?, \
+    error_class = ? WHERE id = ?";
+
+/// UPDATE of every convoy column, followed by the id of the convoy to update
+const UPDATE_CONVOY_SQL: &str = "UPDATE convoys SET id = ?, name = ?, goal = ?, beads = ?, \
+    status = ?, created_at = ?, completed_at = ?, metadata = ?, depends_on = ?, \
+    acceptance_criteria = ? WHERE id = ?";
+
+/// INSERT for one convoy
+fn insert_convoy_sql() -> String {
+    format!(
+        "INSERT INTO convoys ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        CONVOY_COLUMNS
+    )
+}
+
+/// Bind every convoy column, in `CONVOY_COLUMNS` order
+fn bind_convoy<'q>(query: SqliteQuery<'q>, convoy: &Convoy) -> Result<SqliteQuery<'q>> {
+    Ok(query
+        .bind(convoy.id.clone())
+        .bind(convoy.name.clone())
+        .bind(convoy.goal.clone())
+        .bind(serde_json::to_string(&convoy.beads)?)
+        .bind(convoy.status.as_db_str())
+        .bind(format_timestamp(&convoy.created_at))
+        .bind(convoy.completed_at.as_ref().map(format_timestamp))
+        .bind(serde_json::to_string(&convoy.metadata)?)
+        .bind(serde_json::to_string(&convoy.depends_on)?)
+        .bind(serde_json::to_string(&convoy.acceptance_criteria)?))
+}
+
+fn convoy_from_row(row: &SqliteRow) -> Result<Convoy> {
+    Ok(Convoy {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        goal: row.try_get("goal")?,
+        beads: serde_json::from_str(&row.try_get::<String, _>("beads")?)?,
+        status: ConvoyStatus::from_db_str(&row.try_get::<String, _>("status")?)?,
+        created_at: parse_timestamp(&row.try_get::<String, _>("created_at")?)?,
+        completed_at: parse_optional_timestamp(row.try_get("completed_at")?)?,
+        metadata: serde_json::from_str(&row.try_get::<String, _>("metadata")?)?,
+        depends_on: serde_json::from_str(&row.try_get::<String, _>("depends_on")?)?,
+        acceptance_criteria: serde_json::from_str(
+            &row.try_get::<String, _>("acceptance_criteria")?,
+        )?,
+    })
+}
+
+/// Rebuild a tank from its columns and `state`, upgrading older rows
+fn tank_from_row(row: &SqliteRow) -> Result<Tank> {
+    let state: Option<String> = row.try_get("state")?;
+    let mut fields: serde_json::Map<String, serde_json::Value> = match state {
+        Some(state) => serde_json::from_str(&state)?,
+        None => serde_json::Map::new(),
+    };
+    let columns = [
+        serde_json::to_value(Provider::from_db_str(
+            &row.try_get::<String, _>("provider")?,
+        )?)?,
+        (row.try_get::<i64, _>("capacity")? as u64).into(),
+        (row.try_get::<i64, _>("remaining")? as u64).into(),
+        serde_json::to_value(parse_timestamp(&row.try_get::<String, _>("window_start")?)?)?,
+        serde_json::to_value(parse_timestamp(&row.try_get::<String, _>("window_end")?)?)?,
+        serde_json::to_value(TankHealth::from_db_str(
+            &row.try_get::<String, _>("health")?,
+        )?)?,
+        serde_json::to_value(parse_optional_timestamp(row.try_get("last_request")?)?)?,
+        row.try_get::<i64, _>("requests_this_window")?.into(),
+        row.try_get::<i64, _>("tokens_this_window")?.into(),
+        serde_json::to_value(parse_timestamp(&row.try_get::<String, _>("updated_at")?)?)?,
+        row.try_get::<i64, _>("schema_version")?.into(),
+    ];
+    for (name, value) in TANK_COLUMNS.iter().zip(columns) {
+        fields.insert(name.to_string(), value);
+    }
+    let mut tank: Tank = serde_json::from_value(serde_json::Value::Object(fields))?;
+    tank.upgrade();
+    Ok(tank)
+}
+
+fn bead_from_row(row: &SqliteRow) -> Result<Bead> {
+    let id: String = row.try_get("id")?;
+    let provenance: Option<String> = row.try_get("provenance")?;
+
+    Ok(Bead {
+        id: BeadId::parse(&id)?,
+        seq: row.try_get::<Option<i64>, _>("seq")?.map(|s| s as u64),
+        title: row.try_get("title")?,
+        description: row.try_get("description")?,
+        task_type: TaskType::from_db_str(&row.try_get::<String, _>("task_type")?)?,
+        priority: priority_from_db(row.try_get("priority")?),
+        status: BeadStatus::from_db_str(&row.try_get::<String, _>("status")?)?,
+        estimated_tokens: row.try_get::<i64, _>("estimated_tokens")? as u64,
+        actual_tokens: row
+            .try_get::<Option<i64>, _>("actual_tokens")?
+            .map(|t| t as u64),
+        preferred_provider: row
+            .try_get::<Option<String>, _>("preferred_provider")?
+            .map(|p| Provider::from_db_str(&p))
+            .transpose()?,
+        assigned_provider: row
+            .try_get::<Option<String>, _>("assigned_provider")?
+            .map(|p| Provider::from_db_str(&p))
+            .transpose()?,
+        acceptance_criteria: serde_json::from_str(
+            &row.try_get::<String, _>("acceptance_criteria")?,
+        )?,
+        dependencies: serde_json::from_str(&row.try_get::<String, _>("dependencies")?)?,
+        convoy_id: row.try_get("convoy_id")?,
+        retry_count: row.try_get::<i64, _>("retry_count")? as u32,
+        max_retries: row.try_get::<i64, _>("max_retries")? as u32,
+        provider_failures: row.try_get::<i64, _>("provider_failures")? as u32,
+        excluded_providers: serde_json::from_str(&row.try_get::<String, _>("excluded_providers")?)?,
+        context: serde_json::from_str(&row.try_get::<String, _>("context")?)?,
+        created_at: parse_timestamp(&row.try_get::<String, _>("created_at")?)?,
+        started_at: parse_optional_timestamp(row.try_get("started_at")?)?,
+        completed_at: parse_optional_timestamp(row.try_get("completed_at")?)?,
+        deferred_until: parse_optional_timestamp(row.try_get("deferred_until")?)?,
+        deadline: parse_optional_timestamp(row.try_get("deadline")?)?,
+        optimized_prompt: row.try_get("optimized_prompt")?,
+        output: row.try_get("output")?,
+        error: row.try_get("error")?,
+        error_class: row
+            .try_get::<Option<String>, _>("error_class")?
+            .map(|c| RetryClass::from_db_str(&c))
+            .transpose()?,
+        provenance: provenance.map(|p| serde_json::from_str(&p)).transpose()?,
+    })
+}
+
+/// Format timestamps with fixed precision so they sort correctly as TEXT
+fn format_timestamp(ts: &DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn parse_timestamp(s: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| RigsError::Other(format!("Invalid timestamp '{}': {}", s, e)))
+}
+
+fn parse_optional_timestamp(s: Option<String>) -> Result<Option<DateTime<Utc>>> {
+    s.as_deref().map(parse_timestamp).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Priority, Provenance, Verdict};
+    use crate::db::init_memory_pool;
+
+    async fn repo() -> SqliteRepository {
+        SqliteRepository::new(init_memory_pool().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_bead_round_trip() {
+        let repo = repo().await;
+        let dep = BeadId::new();
+        let mut bead = Bead::new("Parser", "Write a parser", TaskType::Implementation)
+            .with_priority(Priority::High)
+            .with_provider(Provider::Claude)
+            .with_criteria(vec!["Handles nesting".into()])
+            .with_dependencies(vec![dep.clone()])
+            .with_estimate(4_000);
+        bead.status = BeadStatus::InProgress;
+        bead.provenance = Some(Provenance::new(Provider::Claude, "sonnet", "prompt", "cli"));
+
+        BeadRepository::create(&repo, &bead).await.unwrap();
+        let loaded = BeadRepository::get(&repo, &bead.id).await.unwrap().unwrap();
+
+        assert_eq!(loaded.id, bead.id);
+        assert_eq!(loaded.priority, Priority::High);
+        assert_eq!(loaded.status, BeadStatus::InProgress);
+        assert_eq!(loaded.preferred_provider, Some(Provider::Claude));
+        assert_eq!(
+            loaded.acceptance_criteria,
+            vec!["Handles nesting".to_string()]
+        );
+        assert_eq!(loaded.dependencies, vec![dep]);
+        assert_eq!(loaded.estimated_tokens, 4_000);
+        assert_eq!(loaded.provenance, bead.provenance);
+        assert_eq!(
+            format_timestamp(&loaded.created_at),
+            format_timestamp(&bead.created_at)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_database_errors_name_the_operation() {
+        let repo = repo().await;
+        let bead = Bead::new("Dup", "dup", TaskType::Test);
+        BeadRepository::create(&repo, &bead).await.unwrap();
+
+        let err = BeadRepository::create(&repo, &bead).await.unwrap_err();
+        assert!(matches!(err, RigsError::DatabaseContext { .. }));
+        let message = err.to_string();
+        assert!(
+            message.starts_with(&format!("Database error while creating bead {}: ", bead.id)),
+            "{}",
+            message
+        );
+        assert!(message.contains("UNIQUE constraint failed"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_reschedule_convoy_shifts_deadlines() {
+        use chrono::{Duration, SubsecRound};
+
+        let repo = repo().await;
+        // Stored timestamps keep microseconds
+        let now = Utc::now().trunc_subsecs(6);
+        let mut soon = Bead::new("Soon", "soon", TaskType::Test);
+        soon.convoy_id = Some("cv-release".into());
+        soon.deadline = Some(now + Duration::days(1));
+        let mut deferred = Bead::new("Deferred", "deferred", TaskType::Test);
+        deferred.convoy_id = Some("cv-release".into());
+        deferred.status = BeadStatus::Deferred;
+        deferred.deadline = Some(now + Duration::days(3));
+        deferred.deferred_until = Some(now + Duration::hours(6));
+        let mut done = Bead::new("Done", "done", TaskType::Test);
+        done.convoy_id = Some("cv-release".into());
+        done.status = BeadStatus::Completed;
+        done.deadline = Some(now + Duration::days(1));
+        let mut other = Bead::new("Other", "other", TaskType::Test);
+        other.deadline = Some(now + Duration::days(1));
+        for bead in [&soon, &deferred, &done, &other] {
+            BeadRepository::create(&repo, bead).await.unwrap();
+        }
+
+        let changed = repo
+            .reschedule_convoy("cv-release", Reschedule::Shift(Duration::days(2)), now)
+            .await
+            .unwrap();
+        assert_eq!(changed.len(), 2);
+
+        let stored = |bead: &Bead| {
+            let repo = &repo;
+            let id = bead.id.clone();
+            async move { BeadRepository::get(repo, &id).await.unwrap().unwrap() }
+        };
+        let shifted = |at: Option<DateTime<Utc>>| at.map(|at| at + Duration::days(2));
+        assert_eq!(stored(&soon).await.deadline, shifted(soon.deadline));
+        let rescheduled = stored(&deferred).await;
+        assert_eq!(rescheduled.deadline, shifted(deferred.deadline));
+        assert_eq!(rescheduled.deferred_until, shifted(deferred.deferred_until));
+        assert_eq!(stored(&done).await.deadline, done.deadline);
+        assert_eq!(stored(&other).await.deadline, other.deadline);
+    }
+
+    #[tokio::test]
+    async fn test_bead_update_and_delete() {
+        let repo = repo().await;
+        let mut bead = Bead::new("Docs", "Write docs", TaskType::Documentation);
+        BeadRepository::create(&repo, &bead).await.unwrap();
+
+        bead.status = BeadStatus::Completed;
+        bead.output = Some("done".into());
+        bead.retry_count = 2;
+        BeadRepository::update(&repo, &bead).await.unwrap();
+
+        let loaded = BeadRepository::get(&repo, &bead.id).await.unwrap().unwrap();
+        assert_eq!(loaded.status, BeadStatus::Completed);
+        assert_eq!(loaded.retry_count, 2);
+        assert_eq!(loaded.output.as_deref(), Some("done"));
+
+        BeadRepository::delete(&repo, &bead.id).await.unwrap();
+        assert!(BeadRepository::get(&repo, &bead.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            BeadRepository::update(&repo, &bead).await,
+            Err(RigsError::BeadNotFound(_))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_seq_assigned_without_gaps_under_concurrency() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = std::sync::Arc::new(SqliteRepository::new(
+            crate::db::init_pool(&dir.path().join("rigs.db"))
+                .await
+                .unwrap(),
+        ));
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for i in 0..20 {
+            let repo = repo.clone();
+            tasks.spawn(async move {
+                let bead = Bead::new(format!("Bead {}", i), "work", TaskType::Test);
+                let seq = BeadRepository::create(repo.as_ref(), &bead).await.unwrap();
+                (seq, bead.id)
+            });
+        }
+        let mut created = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            created.push(joined.unwrap());
+        }
+        created.sort_by_key(|(seq, _)| *seq);
+        let seqs: Vec<u64> = created.iter().map(|(seq, _)| *seq).collect();
+        assert_eq!(seqs, (1..=20).collect::<Vec<_>>());
+
+        let (seq, id) = &created[6];
+        let by_seq = repo
+            .resolve(&format!("#{}", seq).parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(&by_seq.id, id);
+        assert_eq!(by_seq.seq, Some(7));
+        let by_id = repo.resolve(&BeadRef::Id(id.clone())).await.unwrap();
+        assert_eq!(by_id.seq, Some(7));
+        assert!(matches!(
+            repo.resolve(&BeadRef::Seq(21)).await,
+            Err(RigsError::BeadSeqNotFound(21))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_convoy_merge_saves_nothing() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let mut source = Convoy::new("Source");
+        ConvoyRepository::create(&repo, &source).await.unwrap();
+        let mut bead = Bead::new("Moved", "work", TaskType::Test);
+        bead.convoy_id = Some(source.id.clone());
+        BeadRepository::create(&repo, &bead).await.unwrap();
+
+        // The target was never stored, so its write fails after the others
+        let target = Convoy::new("Target");
+        bead.convoy_id = Some(target.id.clone());
+        source.status = ConvoyStatus::Archived;
+        assert!(matches!(
+            repo.save_convoy_merge(&source, &target, std::slice::from_ref(&bead))
+                .await,
+            Err(RigsError::ConvoyNotFound(_))
+        ));
+
+        let stored = BeadRepository::get(&repo, &bead.id).await.unwrap().unwrap();
+        assert_eq!(stored.convoy_id.as_deref(), Some(source.id.as_str()));
+        let stored = ConvoyRepository::get(&repo, &source.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(stored.status, ConvoyStatus::Archived);
+    }
+
+    #[tokio::test]
+    async fn test_seq_not_reused_after_deleting_the_newest_bead() {
+        let repo = SqliteRepository::new(init_memory_pool().await.unwrap());
+        let first = Bead::new("First", "work", TaskType::Test);
+        let second = Bead::new("Second", "work", TaskType::Test);
+        assert_eq!(BeadRepository::create(&repo, &first).await.unwrap(), 1);
+        assert_eq!(BeadRepository::create(&repo, &second).await.unwrap(), 2);
+
+        repo.delete(&second.id).await.unwrap();
+        let third = Bead::new("Third", "work", TaskType::Test);
+        assert_eq!(BeadRepository::create(&repo, &third).await.unwrap(), 3);
+        assert!(matches!(
+            repo.resolve(&BeadRef::Seq(2)).await,
+            Err(RigsError::BeadSeqNotFound(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pending_ordered_by_priority() {
+        let repo = repo().await;
+        let low = Bead::new("Low", "low", TaskType::Research).with_priority(Priority::Low);
+        let critical =
+            Bead::new("Critical", "critical", TaskType::Debug).with_priority(Priority::Critical);
+        let mut done = Bead::new("Done", "done", TaskType::Test);
+        done.status = BeadStatus::Completed;
+
+        for bead in [&low, &critical, &done] {
+            BeadRepository::create(&repo, bead).await.unwrap();
+        }
+
+        let mut retried = Bead::new("Retried", "retried", TaskType::Test);
+        retried.status = BeadStatus::Queued;
+        BeadRepository::create(&repo, &retried).await.unwrap();
+
+        let pending = repo.get_pending_ordered().await.unwrap();
+        let ids: Vec<_> = pending.iter().map(|b| b.id.clone()).collect();
+        assert_eq!(ids, vec![critical.id, retried.id, low.id]);
+
+        let completed = repo.list_by_status(BeadStatus::Completed).await.unwrap();
+        assert_eq!(completed.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_filters_sorts_and_pages() {
+        let repo = repo().await;
+        let start = Utc::now();
+        let mut beads = Vec::new();
+        for (i, (priority, status, convoy)) in [
+            (Priority::Low, BeadStatus::Completed, Some("toml")),
+            (Priority::Critical, BeadStatus::Pending, Some("toml")),
+            (Priority::Normal, BeadStatus::Pending, None),
+            (Priority::High, BeadStatus::Failed, Some("toml")),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut bead =
+                Bead::new(format!("B{}", i), "b", TaskType::Test).with_priority(priority);
+            bead.status = status;
+            bead.convoy_id = convoy.map(Into::into);
+            bead.created_at = start + chrono::Duration::seconds(i as i64);
+            BeadRepository::create(&repo, &bead).await.unwrap();
+            beads.push(bead.id);
+        }
+        let listed = |filter: BeadFilter| {
+            let repo = &repo;
+            async move {
+                repo.list(&filter)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|b| b.id)
+                    .collect::<Vec<_>>()
+            }
+        };
+        let ids = |order: &[usize]| order.iter().map(|&i| beads[i].clone()).collect::<Vec<_>>();
+
+        assert_eq!(listed(BeadFilter::default()).await, ids(&[3, 2, 1, 0]));
+        let pending = BeadFilter {
+            status: Some(BeadStatus::Pending),
+            ..BeadFilter::default()
+        };
+        assert_eq!(listed(pending.clone()).await, ids(&[2, 1]));
+        let toml = BeadFilter {
+            convoy_id: Some("toml".into()),
+            ..BeadFilter::default()
+        };
+        assert_eq!(listed(toml.clone()).await, ids(&[3, 1, 0]));
+        assert_eq!(
+            listed(BeadFilter {
+                convoy_id: Some("toml".into()),
+                ..pending
+            })
+            .await,
+            ids(&[1])
+        );
+
+        let by_priority = BeadFilter {
+            sort: BeadSort::Priority,
+            ..BeadFilter::default()
+        };
+        assert_eq!(listed(by_priority.clone()).await, ids(&[1, 3, 2, 0]));
+        let by_status = BeadFilter {
+            sort: BeadSort::Status,
+            ..toml
+        };
+        assert_eq!(listed(by_status).await, ids(&[1, 0, 3]));
+
+        assert_eq!(
+            listed(BeadFilter {
+                limit: 2,
+                offset: 1,
+                ..by_priority.clone()
+            })
+            .await,
+            ids(&[3, 2])
+        );
+        assert!(listed(BeadFilter {
+            offset: 4,
+            ..by_priority
+        })
+        .await
+        .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deferred_ready() {
+        let repo = repo().await;
+        let mut ready = Bead::new("Ready", "ready", TaskType::Review);
+        ready.status = BeadStatus::Deferred;
+        ready.deferred_until = Some(Utc::now() - chrono::Duration::minutes(1));
+        let mut waiting = Bead::new("Waiting", "waiting", TaskType::Review);
+        waiting.status = BeadStatus::Deferred;
+        waiting.deferred_until = Some(Utc::now() + chrono::Duration::hours(1));
+
+        BeadRepository::create(&repo, &ready).await.unwrap();
+        BeadRepository::create(&repo, &waiting).await.unwrap();
+
+        let beads = repo.get_deferred_ready().await.unwrap();
+        assert_eq!(beads.len(), 1);
+        assert_eq!(beads[0].id, ready.id);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_list_and_requeue() {
+        let repo = repo().await;
+        let mut exhausted = Bead::new("Exhausted", "exhausted", TaskType::Test);
+        exhausted.status = BeadStatus::Failed;
+        exhausted.retry_count = exhausted.max_retries;
+        exhausted.error = Some("provider timeout".into());
+        let mut retryable = Bead::new("Retryable", "retryable", TaskType::Test);
+        retryable.status = BeadStatus::Failed;
+        let done = Bead::new("Done", "done", TaskType::Test);
+
+        for bead in [&exhausted, &retryable, &done] {
+            BeadRepository::create(&repo, bead).await.unwrap();
+        }
+
+        let dead = repo.list_dead_letter().await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].id, exhausted.id);
+        assert_eq!(dead[0].error.as_deref(), Some("provider timeout"));
+
+        let mut bead = dead.into_iter().next().unwrap();
+        assert!(bead.requeue_dead_letter());
+        BeadRepository::update(&repo, &bead).await.unwrap();
+
+        assert!(repo.list_dead_letter().await.unwrap().is_empty());
+        let pending = repo.get_pending_ordered().await.unwrap();
+        assert_eq!(pending.len(), 2);
+        assert!(pending
+            .iter()
+            .any(|b| b.id == exhausted.id && b.retry_count == 0));
+    }
+
+    #[tokio::test]
+    async fn test_stats_aggregates() {
+        let repo = repo().await;
+        let seeds = [
+            (
+                TaskType::Implementation,
+                BeadStatus::Completed,
+                Provider::Claude,
+                1_000,
+                Some(1_000),
+            ),
+            (
+                TaskType::Implementation,
+                BeadStatus::Completed,
+                Provider::Claude,
+                1_000,
+                Some(2_000),
+            ),
+            (
+                TaskType::Review,
+                BeadStatus::Completed,
+                Provider::Codex,
+                500,
+                Some(1_000),
+            ),
+            (
+                TaskType::Review,
+                BeadStatus::Failed,
+                Provider::Codex,
+                500,
+                None,
+            ),
+            (
+                TaskType::Research,
+                BeadStatus::Pending,
+                Provider::Gemini,
+                800,
+                None,
+            ),
+        ];
+        for (task_type, status, provider, estimate, actual) in seeds {
+            let mut bead = Bead::new("Seed", "seed", task_type).with_estimate(estimate);
+            bead.status = status;
+            bead.actual_tokens = actual;
+            bead.assigned_provider = actual.map(|_| provider);
+            BeadRepository::create(&repo, &bead).await.unwrap();
+        }
+        insert_convoy(&repo, "c1", ConvoyStatus::InProgress.as_db_str()).await;
+        insert_convoy(&repo, "c2", ConvoyStatus::Completed.as_db_str()).await;
+        insert_convoy(&repo, "c3", ConvoyStatus::Completed.as_db_str()).await;
+
+        let stats = repo.stats().await.unwrap();
+        assert_eq!(stats.beads_by_status["completed"], 3);
+        assert_eq!(stats.beads_by_status["pending"], 1);
+        assert_eq!(stats.beads_by_type["implementation"], 2);
+        assert_eq!(stats.convoys_by_status["completed"], 2);
+        assert_eq!(stats.convoys_by_status["inprogress"], 1);
+        // 3 of the 4 finished beads succeeded; the pending one doesn't count
+        assert_eq!(stats.completion_rate, Some(0.75));
+        assert_eq!(stats.avg_tokens_by_type["implementation"], 1_500.0);
+        assert_eq!(stats.avg_tokens_by_type["review"], 1_000.0);
+        assert!(!stats.avg_tokens_by_type.contains_key("research"));
+        // (1.0 + 0.5 + 0.5) / 3
+        assert!((stats.estimation_accuracy.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.provider_share["claude"], 0.75);
+        assert_eq!(stats.provider_share["codex"], 0.25);
+
+        let empty = SqliteRepository::new(init_memory_pool().await.unwrap())
+            .stats()
+            .await
+            .unwrap();
+        assert_eq!(empty.completion_rate, None);
+        assert!(empty.provider_share.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tank_upsert_updates_in_place() {
+        let repo = repo().await;
+        let mut claude = Tank::new(Provider::Claude, 100_000, 5);
+        claude.consume(30_000).unwrap();
+        claude.weekly_used = 30_000;
+        claude.weekly_reset_at = Some(claude.window_end + chrono::Duration::days(7));
+        TankRepository::upsert(&repo, &claude).await.unwrap();
+        TankRepository::upsert(&repo, &Tank::new(Provider::Ollama, u64::MAX, 1))
+            .await
+            .unwrap();
+
+        claude.consume(40_000).unwrap();
+        claude.weekly_used = 70_000;
+        claude.window_end += chrono::Duration::hours(1);
+        TankRepository::upsert(&repo, &claude).await.unwrap();
+
+        let tanks = repo.get_all().await.unwrap();
+        assert_eq!(tanks.len(), 2);
+        let stored = TankRepository::get(&repo, Provider::Claude)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.remaining, 30_000);
+        assert_eq!(stored.health, claude.health);
+        assert_eq!(stored.requests_this_window, 2);
+        assert_eq!(stored.tokens_this_window, 70_000);
+        assert_eq!(
+            stored.window_end.timestamp_micros(),
+            claude.window_end.timestamp_micros()
+        );
+        assert_eq!(stored.weekly_used, 70_000);
+        assert_eq!(
+            stored.weekly_reset_at.map(|t| t.timestamp_micros()),
+            claude.weekly_reset_at.map(|t| t.timestamp_micros())
+        );
+
+        let ollama = TankRepository::get(&repo, Provider::Ollama)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(ollama.is_unlimited());
+        assert!(TankRepository::get(&repo, Provider::Gemini)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_latency_round_trip() {
+        let repo = repo().await;
+        assert!(repo.get_latencies().await.unwrap().is_empty());
+
+        let mut latency = ProviderLatency::neutral(Provider::Codex);
+        latency.record(std::time::Duration::from_millis(1_500));
+        repo.upsert_latency(&latency).await.unwrap();
+        latency.record(std::time::Duration::from_millis(500));
+        repo.upsert_latency(&latency).await.unwrap();
+
+        let stored = repo.get_latencies().await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].provider, Provider::Codex);
+        assert_eq!(stored[0].samples, 2);
+        assert!((stored[0].avg_ms - latency.avg_ms).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_list_usage_filters_by_provider_and_time() {
+        let repo = repo().await;
+        let start: DateTime<Utc> = "2026-01-14T08:00:00Z".parse().unwrap();
+        let events = [
+            (Provider::Claude, 0, 1_000, 9_000),
+            (Provider::Gemini, 30, 2_000, 98_000),
+            (Provider::Claude, 90, 3_000, 6_000),
+            (Provider::Claude, 150, 500, 5_500),
+        ];
+        for (provider, minutes, tokens, remaining) in events {
+            repo.record_usage(&UsageEvent {
+                provider,
+                tokens,
+                at: start + chrono::Duration::minutes(minutes),
+                remaining,
+            })
+            .await
+            .unwrap();
+        }
+
+        let since = start + chrono::Duration::minutes(30);
+        let all = repo.list_usage(None, since).await.unwrap();
+        assert_eq!(
+            all.iter().map(|e| e.tokens).collect::<Vec<_>>(),
+            vec![2_000, 3_000, 500]
+        );
+
+        let claude = repo
+            .list_usage(Some(Provider::Claude), since)
+            .await
+            .unwrap();
+        assert_eq!(claude.len(), 2);
+        assert_eq!(claude[0].remaining, 6_000);
+        assert_eq!(claude[1].at, start + chrono::Duration::minutes(150));
+    }
+
+    #[tokio::test]
+    async fn test_convoy_round_trip_keeps_bead_order() {
+        let repo = repo().await;
+        let beads: Vec<BeadId> = (0..3).map(|_| BeadId::new()).collect();
+        let mut convoy = Convoy::from_goal("auth", "Add OAuth2 login", beads.clone());
+        convoy.metadata.insert("owner".into(), "platform".into());
+        convoy.depends_on = vec!["schema".into()];
+        ConvoyRepository::create(&repo, &convoy).await.unwrap();
+
+        let stored = ConvoyRepository::get(&repo, &convoy.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.beads, beads);
+        assert_eq!(stored.goal.as_deref(), Some("Add OAuth2 login"));
+        assert_eq!(
+            stored.metadata.get("owner").map(String::as_str),
+            Some("platform")
+        );
+        assert_eq!(stored.depends_on, vec!["schema".to_string()]);
+        assert_eq!(stored.status, ConvoyStatus::Queued);
+
+        convoy.beads = vec![beads[2].clone(), beads[0].clone()];
+        convoy.status = ConvoyStatus::InProgress;
+        ConvoyRepository::update(&repo, &convoy).await.unwrap();
+        let stored = ConvoyRepository::get(&repo, &convoy.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.beads, vec![beads[2].clone(), beads[0].clone()]);
+
+        let mut done = Convoy::new("done");
+        done.status = ConvoyStatus::Completed;
+        ConvoyRepository::create(&repo, &done).await.unwrap();
+        let active = repo.list_active().await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].id, convoy.id);
+        let all = repo.list_all().await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].name, "done");
+        // Convoys without beads are listed too
+        assert_eq!(
+            repo.convoy_ids().await.unwrap(),
+            vec![convoy.id.clone(), done.id.clone()]
+        );
+
+        assert!(ConvoyRepository::get(&repo, "missing")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            ConvoyRepository::update(&repo, &Convoy::new("missing")).await,
+            Err(RigsError::ConvoyNotFound(_))
+        ));
+    }
+
+    async fn insert_convoy(repo: &SqliteRepository, id: &str, status: &str) {
+        sqlx::query("INSERT INTO convoys (id, name, status, created_at) VALUES (?, ?, ?, ?)")
+            .bind(id)
+            .bind(id)
+            .bind(status)
+            .bind(format_timestamp(&Utc::now()))
+            .execute(repo.pool())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_fixes_drifted_convoy_status() {
+        let repo = repo().await;
+        insert_convoy(&repo, "crashed", "inprogress").await;
+        insert_convoy(&repo, "fine", "inprogress").await;
+        insert_convoy(&repo, "archived", "archived").await;
+        for (convoy, status) in [
+            ("crashed", BeadStatus::Completed),
+            ("crashed", BeadStatus::Failed),
+            ("fine", BeadStatus::Completed),
+            ("fine", BeadStatus::Pending),
+            ("archived", BeadStatus::Pending),
+        ] {
+            let mut bead = Bead::new("Step", "step", TaskType::Test);
+            bead.convoy_id = Some(convoy.into());
+            bead.status = status;
+            BeadRepository::create(&repo, &bead).await.unwrap();
+        }
+
+        let corrections = repo.reconcile_convoys(None).await.unwrap();
+        assert_eq!(
+            corrections,
+            vec![ConvoyCorrection {
+                convoy_id: "crashed".into(),
+                from: ConvoyStatus::InProgress,
+                to: ConvoyStatus::CompletedWithErrors,
+            }]
+        );
+        let (status, completed_at): (String, Option<String>) =
+            sqlx::query_as("SELECT status, completed_at FROM convoys WHERE id = 'crashed'")
+                .fetch_one(repo.pool())
+                .await
+                .unwrap();
+        assert_eq!(status, "completedwitherrors");
+        assert!(completed_at.is_some());
+
+        assert!(repo.reconcile_convoys(None).await.unwrap().is_empty());
+    }
+
+    struct RejectingGate;
+
+    #[async_trait]
+    impl QualityGate for RejectingGate {
+        async fn review(&self, _criteria: &[String], _output: &str) -> Result<Verdict> {
+            Ok(Verdict {
+                passed: false,
+                reason: "integration tests fail".into(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_runs_the_convoy_gate() {
+        let repo = repo().await;
+        insert_convoy(&repo, "gated", "inprogress").await;
+        insert_convoy(&repo, "ungated", "inprogress").await;
+        sqlx::query("UPDATE convoys SET acceptance_criteria = ? WHERE id = 'gated'")
+            .bind(r#"["All tests pass"]"#)
+            .execute(repo.pool())
+            .await
+            .unwrap();
+        for convoy in ["gated", "gated", "ungated"] {
+            let mut bead = Bead::new("Step", "step", TaskType::Test);
+            bead.convoy_id = Some(convoy.into());
+            bead.status = BeadStatus::Completed;
+            bead.output = Some("ok".into());
+            BeadRepository::create(&repo, &bead).await.unwrap();
+        }
+
+        let corrections = repo.reconcile_convoys(Some(&RejectingGate)).await.unwrap();
+        let mut statuses: Vec<_> = corrections
+            .iter()
+            .map(|c| (c.convoy_id.as_str(), c.to))
+            .collect();
+        statuses.sort_by_key(|(id, _)| *id);
+        assert_eq!(
+            statuses,
+            vec![
+                ("gated", ConvoyStatus::CompletedWithErrors),
+                ("ungated", ConvoyStatus::Completed),
+            ]
+        );
+        let (metadata,): (String,) =
+            sqlx::query_as("SELECT metadata FROM convoys WHERE id = 'gated'")
+                .fetch_one(repo.pool())
+                .await
+                .unwrap();
+        assert!(metadata.contains("integration tests fail"));
+    }
+
+    async fn insert_completion(repo: &SqliteRepository, bead: &Bead, at: DateTime<Utc>) {
+        sqlx::query(
+            "INSERT INTO completions (id, bead_id, provider, estimated_tokens, actual_tokens, \
+             duration_ms, success, completed_at) VALUES (?, ?, 'claude', 0, 0, 0, 1, ?)",
+        )
+        .bind(format!("c-{}", bead.id))
+        .bind(bead.id.as_str())
+        .bind(format_timestamp(&at))
+        .execute(repo.pool())
+        .await
+        .unwrap();
+    }
+
+    fn finished(title: &str, days_ago: i64) -> Bead {
+        let mut bead = Bead::new(title, title, TaskType::Test);
+        bead.status = BeadStatus::Completed;
+        bead.completed_at = Some(Utc::now() - chrono::Duration::days(days_ago));
+        bead
+    }
+
+    #[tokio::test]
+    async fn test_prune_age_cutoff() {
+        let repo = repo().await;
+        let old = finished("Old", 45);
+        let recent = finished("Recent", 5);
+        let mut running = Bead::new("Running", "running", TaskType::Test);
+        running.created_at = Utc::now() - chrono::Duration::days(60);
+        running.status = BeadStatus::InProgress;
+
+        for bead in [&old, &recent, &running] {
+            BeadRepository::create(&repo, bead).await.unwrap();
+        }
+        insert_completion(&repo, &old, Utc::now() - chrono::Duration::days(45)).await;
+        insert_completion(&repo, &recent, Utc::now() - chrono::Duration::days(5)).await;
+
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+        let dry = repo.prune(cutoff, true).await.unwrap();
+        assert_eq!(dry.beads, 1);
+        assert_eq!(dry.completions, 1);
+        assert!(BeadRepository::get(&repo, &old.id).await.unwrap().is_some());
+
+        let report = repo.prune(cutoff, false).await.unwrap();
+        assert_eq!(report, dry);
+        assert!(BeadRepository::get(&repo, &old.id).await.unwrap().is_none());
+        assert!(BeadRepository::get(&repo, &recent.id)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(BeadRepository::get(&repo, &running.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_prune_keeps_beads_of_live_convoys() {
+        let repo = repo().await;
+        insert_convoy(&repo, "live", "inprogress").await;
+        insert_convoy(&repo, "done", "completed").await;
+
+        let mut in_live = finished("Live", 45);
+        in_live.convoy_id = Some("live".into());
+        let mut in_done = finished("Done", 45);
+        in_done.convoy_id = Some("done".into());
+        for bead in [&in_live, &in_done] {
+            BeadRepository::create(&repo, bead).await.unwrap();
+            insert_completion(&repo, bead, Utc::now() - chrono::Duration::days(45)).await;
+        }
+
+        let report = repo
+            .prune(Utc::now() - chrono::Duration::days(30), false)
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            PruneReport {
+                beads: 1,
+                completions: 1,
+                protected: 1,
+            }
+        );
+        assert!(BeadRepository::get(&repo, &in_live.id)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(BeadRepository::get(&repo, &in_done.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
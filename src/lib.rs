@@ -0,0 +1,163 @@
+//! Rigs: rate-limit-aware multi-agent LLM orchestration
+//!
+//! The `rigs` binary is a thin CLI over this library. Embedders usually only
+//! need the [`Rigs`] facade, which owns a workspace database and exposes the
+//! bead and scheduler operations; the modules underneath stay public for
+//! anything the facade doesn't cover.
+//!
+//! ```
+//! use rigs::config::Config;
+//! use rigs::core::{Bead, TaskType};
+//! use rigs::Rigs;
+//!
+//! # tokio_test::block_on(async {
+//! let rigs = Rigs::in_memory(Config::default()).await?;
+//! let bead = rigs
+//!     .create_bead(Bead::new("Parser", "Write a TOML parser", TaskType::Implementation))
+//!     .await?;
+//! assert!(bead.estimated_tokens > 0);
+//! assert_eq!(rigs.get_bead(&bead.id).await?.unwrap().title, "Parser");
+//! # Ok::<(), rigs::core::RigsError>(())
+//! # }).unwrap();
+//! ```
+
+pub mod cli;
+pub mod config;
+pub mod core;
+pub mod db;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::core::assayer::Estimator;
+use crate::core::convoy::out_of_scope_dependencies;
+use crate::core::executor::CliExecutor;
+use crate::core::foreman::Foreman;
+use crate::core::planner::{GoalPlan, Planner};
+use crate::core::schedule::DispatchPlan;
+use crate::core::{Bead, BeadId, BeadStatus, Executor, Result, RigsError, Tank};
+use crate::db::repository::{BeadRepository, SqliteRepository};
+use crate::db::{init_memory_pool, open_workspace};
+
+/// Entry point for embedding Rigs
+pub struct Rigs {
+    config: Config,
+    repo: Arc<SqliteRepository>,
+    estimator: Estimator,
+}
+
+impl Rigs {
+    /// Open the workspace database named by `config`
+    ///
+    /// Fails with [`core::RigsError::WorkspaceNotInitialized`] if `rigs init`
+    /// hasn't been run for it.
+    pub async fn open(config: Config) -> Result<Self> {
+        let pool = open_workspace(&config).await?;
+        Ok(Self::with_repository(config, SqliteRepository::new(pool)))
+    }
+
+    /// Use a throwaway in-memory database (tests, one-off runs)
+    pub async fn in_memory(config: Config) -> Result<Self> {
+        let pool = init_memory_pool().await?;
+        Ok(Self::with_repository(config, SqliteRepository::new(pool)))
+    }
+
+    fn with_repository(config: Config, repo: SqliteRepository) -> Self {
+        Self {
+            config,
+            repo: Arc::new(repo),
+            estimator: Estimator::new(),
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Underlying repository, for operations the facade doesn't wrap
+    pub fn repository(&self) -> Arc<SqliteRepository> {
+        self.repo.clone()
+    }
+
+    /// Estimate (if not already estimated) and store a new bead
+    ///
+    /// The bead's retry budget is set from `foreman.bead_retries`, and the
+    /// returned bead carries its sequence number. Dependencies outside the
+    /// bead's convoy scope are logged, or rejected with
+    /// [`core::RigsError::DependencyOutOfScope`] under
+    /// `foreman.strict_dependencies`.
+    pub async fn create_bead(&self, mut bead: Bead) -> Result<Bead> {
+        bead.max_retries = self.config.foreman.bead_retries;
+        if bead.estimated_tokens == 0 {
+            bead.estimated_tokens = self.estimator.estimate(&bead).await?;
+        }
+        self.check_dependency_scope(&bead).await?;
+        bead.seq = Some(self.repo.create(&bead).await?);
+        Ok(bead)
+    }
+
+    async fn check_dependency_scope(&self, bead: &Bead) -> Result<()> {
+        if bead.convoy_id.is_none() {
+            return Ok(());
+        }
+        let mut dependency_convoys = HashMap::new();
+        for dep in &bead.dependencies {
+            if let Some(found) = self.repo.get(dep).await? {
+                dependency_convoys.insert(dep.clone(), found.convoy_id);
+            }
+        }
+        let convoy_deps = self.repo.convoy_dependencies().await?;
+        for dependency in out_of_scope_dependencies(bead, &dependency_convoys, &convoy_deps) {
+            let err = RigsError::DependencyOutOfScope {
+                bead_id: bead.id.clone(),
+                dependency,
+            };
+            if self.config.foreman.strict_dependencies {
+                return Err(err);
+            }
+            warn!("{}", err);
+        }
+        Ok(())
+    }
+
+    pub async fn get_bead(&self, id: &BeadId) -> Result<Option<Bead>> {
+        self.repo.get(id).await
+    }
+
+    pub async fn list_beads(&self, status: BeadStatus) -> Result<Vec<Bead>> {
+        self.repo.list_by_status(status).await
+    }
+
+    /// Split `goal` into beads without storing them
+    ///
+    /// Uses the decomposers the config allows and falls back to a heuristic
+    /// skeleton when none is reachable (see [`Planner::plan`]).
+    pub async fn plan_goal(&self, goal: &str) -> Result<GoalPlan> {
+        Planner::from_config(&self.config)?.plan(goal).await
+    }
+
+    /// Preview the order pending beads would be dispatched in
+    ///
+    /// Nothing is executed, so the CLI executor is never invoked.
+    pub async fn plan(&self, tanks: &[Tank]) -> Result<DispatchPlan> {
+        self.foreman(Arc::new(CliExecutor::default()))
+            .plan(tanks)
+            .await
+    }
+
+    /// Dispatch every ready bead through `executor` and wait for them
+    ///
+    /// Returns the number of beads processed.
+    pub async fn run_once(&self, executor: Arc<dyn Executor>) -> Result<usize> {
+        self.foreman(executor).run_once().await
+    }
+
+    fn foreman(&self, executor: Arc<dyn Executor>) -> Foreman {
+        Foreman::new(self.repo.clone(), executor, self.config.foreman.clone())
+            .with_history(self.repo.clone())
+            .with_local_only(self.config.general.local_only)
+            .with_prompt_template(self.config.prompts.bead_template())
+    }
+}
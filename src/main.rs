@@ -1,15 +1,10 @@
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
-use tracing::info;
+use tracing::{info, warn};
 
-mod cli;
-mod config;
-mod core;
-mod db;
-
-use crate::cli::{bead, convoy, foreman, goal, provider, tank};
-use crate::config::Config;
-use crate::core::error::Result;
+use rigs::cli::{self, bead, bench, convoy, foreman, goal, provider, prune, tank, OutputFormat};
+use rigs::config::Config;
+use rigs::core::error::Result;
 
 #[derive(Parser)]
 #[command(name = "rigs")]
@@ -23,26 +18,48 @@ struct Cli {
     #[arg(short, long, global = true, env = "RIGS_CONFIG")]
     config: Option<PathBuf>,
 
+    /// Path to the workspace database (overrides database.path)
+    #[arg(long, global = true, env = "RIGS_DB")]
+    db: Option<PathBuf>,
+
+    /// Workspace directory (overrides general.workspace; the database
+    /// defaults to <workspace>/db/rigs.db)
+    #[arg(long, global = true, env = "RIGS_WORKSPACE")]
+    workspace: Option<PathBuf>,
+
     /// Verbose output (-v, -vv, -vvv)
     #[arg(short, long, global = true, action = clap::ArgAction::Count)]
     verbose: u8,
 
-    /// Output format (text, json)
-    #[arg(long, global = true, default_value = "text")]
-    format: String,
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Never call remote APIs; run everything on Ollama (overrides general.local_only)
+    #[arg(long, global = true)]
+    local_only: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize a new Rigs workspace
     Init {
-        /// Path to workspace directory
-        #[arg(default_value = "~/.rigs")]
-        path: PathBuf,
+        /// Path to workspace directory (default: --workspace, else general.workspace)
+        path: Option<PathBuf>,
 
         /// Initialize git repository
-        #[arg(long)]
+        #[arg(long, conflicts_with = "minimal")]
         git: bool,
+
+        /// Only create and migrate the database (for CI and throwaway workspaces)
+        #[arg(long)]
+        minimal: bool,
+    },
+
+    /// Manage the configuration file
+    Config {
+        #[command(subcommand)]
+        action: cli::config::ConfigCommands,
     },
 
     /// Manage LLM providers (Claude, Codex, Gemini)
@@ -83,14 +100,50 @@ enum Commands {
 
     /// Show system status overview
     Status,
+
+    /// Aggregate metrics over beads, convoys and token usage (--format json for machines)
+    Stats,
+
+    /// Delete finished beads and history older than a retention window
+    Prune {
+        /// Retention window (e.g. 30d, 12h, 2w)
+        #[arg(long, default_value = "30d", value_parser = prune::parse_age)]
+        older_than: chrono::Duration,
+        /// Report what would be deleted without deleting it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Measure scheduler throughput with a zero-latency executor
+    #[command(hide = true)]
+    Bench {
+        /// Number of beads to seed
+        #[arg(long, default_value = "1000")]
+        beads: usize,
+        /// max_concurrent values to measure (repeatable)
+        #[arg(long = "concurrency", default_values_t = vec![1, 4, 16])]
+        concurrency: Vec<u32>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    // Config commands work on the file itself, so a broken one mustn't stop them
+    if let Commands::Config { action } = cli.command {
+        return cli::config::run(action, cli.config.as_deref()).await;
+    }
+
     // Load configuration
-    let config = Config::load(cli.config.as_deref())?;
+    let mut config = Config::load(cli.config.as_deref())?;
+    config.general.local_only |= cli.local_only;
+    if let Some(workspace) = &cli.workspace {
+        config.set_workspace(workspace);
+    }
+    if let Some(db) = &cli.db {
+        config.database.path = db.to_string_lossy().into_owned();
+    }
 
     // Initialize logging (CLI verbosity overrides config)
     let log_level = if cli.verbose > 0 {
@@ -106,32 +159,49 @@ async fn main() -> Result<()> {
 
     info!("Rigs v{} starting", env!("CARGO_PKG_VERSION"));
     info!("Workspace: {}", config.workspace_dir().display());
+    for warning in config.warnings() {
+        warn!("{}", warning);
+    }
 
     match cli.command {
-        Commands::Init { path, git } => {
-            cli::init::run(path, git).await?;
+        Commands::Init { path, git, minimal } => {
+            let path = path.unwrap_or_else(|| config.workspace_dir());
+            cli::init::run(path, git, minimal, cli.db).await?;
         }
+        Commands::Config { .. } => unreachable!("handled before loading the config"),
         Commands::Provider { action } => {
-            provider::run(action).await?;
+            provider::run(action, &config).await?;
         }
         Commands::Tank { action } => {
-            tank::run(action).await?;
+            tank::run(action, &config, cli.format).await?;
         }
         Commands::Bead { action } => {
-            bead::run(action).await?;
+            bead::run(action, &config, cli.format).await?;
         }
         Commands::Convoy { action } => {
-            convoy::run(action).await?;
+            convoy::run(action, &config, cli.format).await?;
         }
         Commands::Foreman { action } => {
-            foreman::run(action).await?;
+            foreman::run(action, &config).await?;
         }
         Commands::Goal { action } => {
-            goal::run(action).await?;
+            goal::run(action, &config).await?;
         }
         Commands::Status => {
             cli::status::run().await?;
         }
+        Commands::Stats => {
+            cli::stats::run(cli.format, &config).await?;
+        }
+        Commands::Prune {
+            older_than,
+            dry_run,
+        } => {
+            prune::run(older_than, dry_run, &config).await?;
+        }
+        Commands::Bench { beads, concurrency } => {
+            bench::run(beads, concurrency).await?;
+        }
     }
 
     Ok(())
@@ -142,8 +212,7 @@ fn init_logging(level: &str) {
 
     tracing_subscriber::fmt()
         .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| filter.into()),
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| filter.into()),
         )
         .with_target(false)
         .init();
@@ -0,0 +1,113 @@
+//! Embedding Rigs through the library API
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rigs::config::Config;
+use rigs::core::planner::PlanSource;
+use rigs::core::{
+    Bead, BeadStatus, ExecutionOutput, ExecutionRequest, Executor, Priority, Result, RigsError,
+    TaskType,
+};
+use rigs::Rigs;
+
+struct UppercaseExecutor;
+
+#[async_trait]
+impl Executor for UppercaseExecutor {
+    fn kind(&self) -> &'static str {
+        "uppercase"
+    }
+
+    async fn execute(&self, request: &ExecutionRequest) -> Result<ExecutionOutput> {
+        Ok(ExecutionOutput::estimated(
+            request,
+            request.prompt.to_uppercase(),
+            Duration::ZERO,
+        ))
+    }
+}
+
+#[tokio::test]
+async fn test_create_and_fetch_bead() {
+    let rigs = Rigs::in_memory(Config::default()).await.unwrap();
+    let bead = Bead::new("Docs", "Document the config file", TaskType::Documentation)
+        .with_priority(Priority::High);
+
+    let created = rigs.create_bead(bead).await.unwrap();
+    let fetched = rigs.get_bead(&created.id).await.unwrap().unwrap();
+
+    assert_eq!(fetched.title, "Docs");
+    assert_eq!(fetched.priority, Priority::High);
+    assert_eq!(fetched.estimated_tokens, created.estimated_tokens);
+    assert_eq!(rigs.list_beads(BeadStatus::Pending).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_plan_then_run() {
+    let rigs = Rigs::in_memory(Config::default()).await.unwrap();
+    let bead = rigs
+        .create_bead(Bead::new("Test", "write tests", TaskType::Test))
+        .await
+        .unwrap();
+
+    let plan = rigs.plan(&[]).await.unwrap();
+    assert_eq!(plan.order(), vec![bead.id.clone()]);
+
+    assert_eq!(rigs.run_once(Arc::new(UppercaseExecutor)).await.unwrap(), 1);
+    let done = rigs.get_bead(&bead.id).await.unwrap().unwrap();
+    assert_eq!(done.status, BeadStatus::Completed);
+    // Rendered through the default `[prompts] bead_template`
+    assert_eq!(
+        done.output.as_deref(),
+        Some("## TEST (TEST)\n\nWRITE TESTS")
+    );
+}
+
+#[tokio::test]
+async fn test_unrelated_convoy_dependency_warns_unless_strict() {
+    let in_convoy = |convoy: &str, title: &str| {
+        let mut bead = Bead::new(title, title, TaskType::Implementation);
+        bead.convoy_id = Some(convoy.into());
+        bead
+    };
+    let mut config = Config::default();
+    let rigs = Rigs::in_memory(config.clone()).await.unwrap();
+    let docs = rigs.create_bead(in_convoy("docs", "Docs")).await.unwrap();
+    let api = in_convoy("api", "Api").with_dependencies(vec![docs.id.clone()]);
+    assert!(rigs.create_bead(api).await.is_ok());
+
+    config.foreman.strict_dependencies = true;
+    let strict = Rigs::in_memory(config).await.unwrap();
+    let docs = strict.create_bead(in_convoy("docs", "Docs")).await.unwrap();
+    let sibling = strict
+        .create_bead(in_convoy("api", "Schema"))
+        .await
+        .unwrap();
+    let ok = in_convoy("api", "Api").with_dependencies(vec![sibling.id.clone()]);
+    assert!(strict.create_bead(ok).await.is_ok());
+    let api = in_convoy("api", "Api").with_dependencies(vec![docs.id.clone()]);
+    assert!(matches!(
+        strict.create_bead(api).await,
+        Err(RigsError::DependencyOutOfScope { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_plan_goal_without_a_decomposer() {
+    let mut config = Config::default();
+    config.assayer.use_ollama = false;
+    let rigs = Rigs::in_memory(config).await.unwrap();
+
+    let plan = rigs.plan_goal("Add OAuth2 login").await.unwrap();
+    assert_eq!(plan.source, PlanSource::Heuristic);
+    assert_eq!(plan.beads.len(), 4);
+    assert!(plan.total_tokens() > 0);
+    // Planning stores nothing
+    assert!(rigs
+        .list_beads(BeadStatus::Pending)
+        .await
+        .unwrap()
+        .is_empty());
+}